@@ -0,0 +1,3 @@
+ALTER TABLE warranties
+    ADD COLUMN receipt_key TEXT,
+    ADD COLUMN receipt_content_type VARCHAR(100);
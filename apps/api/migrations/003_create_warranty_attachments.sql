@@ -0,0 +1,22 @@
+CREATE TYPE attachment_kind AS ENUM (
+    'invoice',
+    'product_photo',
+    'warranty_card',
+    'manual',
+    'serial_plate',
+    'other'
+);
+
+CREATE TABLE warranty_attachments (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    warranty_id UUID NOT NULL REFERENCES warranties(id) ON DELETE CASCADE,
+    kind attachment_kind NOT NULL DEFAULT 'other',
+    file_name VARCHAR(255),
+    storage_key TEXT NOT NULL,
+    mime_type VARCHAR(100) NOT NULL,
+    size_bytes BIGINT NOT NULL,
+    checksum_sha256 CHAR(64) NOT NULL,
+    uploaded_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_warranty_attachments_warranty_id ON warranty_attachments(warranty_id);
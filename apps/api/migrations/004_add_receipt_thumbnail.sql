@@ -0,0 +1,3 @@
+ALTER TABLE warranties
+    ADD COLUMN receipt_thumbnail_url TEXT,
+    ADD COLUMN receipt_thumbnail_key TEXT;
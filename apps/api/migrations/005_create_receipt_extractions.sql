@@ -0,0 +1,32 @@
+ALTER TABLE warranties
+    ADD COLUMN price_cents BIGINT CHECK (price_cents >= 0),
+    ADD COLUMN invoice_number VARCHAR(100);
+
+CREATE TYPE extraction_status AS ENUM (
+    'pending',
+    'processing',
+    'completed',
+    'failed'
+);
+
+CREATE TABLE receipt_extractions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id VARCHAR(255) NOT NULL,
+    warranty_id UUID REFERENCES warranties(id) ON DELETE CASCADE,
+    status extraction_status NOT NULL DEFAULT 'pending',
+    storage_key TEXT NOT NULL,
+    content_type VARCHAR(100) NOT NULL,
+    raw_text TEXT,
+    suggestions JSONB NOT NULL DEFAULT '[]',
+    error TEXT,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    run_after TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_receipt_extractions_user_id ON receipt_extractions(user_id);
+CREATE INDEX idx_receipt_extractions_warranty_id ON receipt_extractions(warranty_id);
+CREATE INDEX idx_receipt_extractions_queue ON receipt_extractions(run_after)
+    WHERE status IN ('pending', 'processing');
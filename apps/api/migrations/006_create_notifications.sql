@@ -0,0 +1,18 @@
+CREATE TYPE notification_kind AS ENUM (
+    'expiry_reminder'
+);
+
+CREATE TABLE notifications (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id VARCHAR(255) NOT NULL,
+    warranty_id UUID NOT NULL REFERENCES warranties(id) ON DELETE CASCADE,
+    kind notification_kind NOT NULL,
+    -- Days before the end of the warranty that triggered this reminder
+    threshold_days INTEGER NOT NULL CHECK (threshold_days > 0),
+    warranty_end_date TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    read_at TIMESTAMPTZ,
+    UNIQUE (warranty_id, kind, threshold_days)
+);
+
+CREATE INDEX idx_notifications_user_id ON notifications(user_id, created_at DESC);
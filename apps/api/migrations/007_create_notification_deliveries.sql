@@ -0,0 +1,27 @@
+CREATE TYPE notification_channel AS ENUM (
+    'email'
+);
+
+CREATE TYPE delivery_status AS ENUM (
+    'pending',
+    'sending',
+    'sent',
+    'failed'
+);
+
+CREATE TABLE notification_deliveries (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    notification_id UUID NOT NULL REFERENCES notifications(id) ON DELETE CASCADE,
+    channel notification_channel NOT NULL,
+    status delivery_status NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    run_after TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    sent_at TIMESTAMPTZ,
+    UNIQUE (notification_id, channel)
+);
+
+CREATE INDEX idx_notification_deliveries_queue ON notification_deliveries(run_after)
+    WHERE status IN ('pending', 'sending');
@@ -0,0 +1,22 @@
+ALTER TYPE notification_channel ADD VALUE IF NOT EXISTS 'webhook';
+
+CREATE TYPE reminder_mode AS ENUM (
+    'individual',
+    'digest'
+);
+
+CREATE TABLE reminder_preferences (
+    user_id VARCHAR(255) PRIMARY KEY,
+    lead_days INTEGER[] NOT NULL,
+    channels notification_channel[] NOT NULL,
+    quiet_hours_start TIME,
+    quiet_hours_end TIME,
+    timezone VARCHAR(64) NOT NULL DEFAULT 'Europe/Paris',
+    mode reminder_mode NOT NULL DEFAULT 'individual',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((quiet_hours_start IS NULL) = (quiet_hours_end IS NULL))
+);
+
+-- Replaces the user's lead times for one warranty; an empty array mutes it.
+ALTER TABLE warranties ADD COLUMN reminder_lead_days INTEGER[];
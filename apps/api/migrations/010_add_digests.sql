@@ -0,0 +1,27 @@
+ALTER TYPE notification_kind ADD VALUE IF NOT EXISTS 'digest';
+
+CREATE TYPE digest_frequency AS ENUM (
+    'weekly',
+    'monthly'
+);
+
+-- A NULL frequency means no digest. The digest period runs from
+-- last_digest_at, set when the user opts in and after each digest.
+ALTER TABLE reminder_preferences
+    ADD COLUMN digest_frequency digest_frequency,
+    ADD COLUMN last_digest_at TIMESTAMPTZ,
+    ADD COLUMN digest_token UUID NOT NULL UNIQUE DEFAULT uuid_generate_v4(),
+    ADD CHECK ((digest_frequency IS NULL) = (last_digest_at IS NULL));
+
+-- Digests are about no warranty in particular; they carry a snapshot of the
+-- numbers they report instead.
+ALTER TABLE notifications
+    ALTER COLUMN warranty_id DROP NOT NULL,
+    ALTER COLUMN threshold_days DROP NOT NULL,
+    ALTER COLUMN warranty_end_date DROP NOT NULL,
+    ADD COLUMN digest JSONB,
+    ADD CHECK (
+        (warranty_id IS NOT NULL AND threshold_days IS NOT NULL
+            AND warranty_end_date IS NOT NULL)
+        OR digest IS NOT NULL
+    );
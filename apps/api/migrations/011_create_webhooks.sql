@@ -0,0 +1,60 @@
+ALTER TYPE notification_kind ADD VALUE IF NOT EXISTS 'expired';
+
+-- An expiry has no threshold: it is recorded once per end date, so that a
+-- warranty that was extended can expire again.
+ALTER TABLE notifications
+    DROP CONSTRAINT notifications_check,
+    ADD CONSTRAINT notifications_check CHECK (
+        (warranty_id IS NOT NULL AND warranty_end_date IS NOT NULL) OR digest IS NOT NULL
+    );
+
+CREATE UNIQUE INDEX idx_notifications_expired ON notifications(warranty_id, warranty_end_date)
+    WHERE kind = 'expired';
+
+CREATE TYPE webhook_event AS ENUM (
+    'warranty.created',
+    'warranty.updated',
+    'warranty.deleted',
+    'warranty.expiring',
+    'warranty.expired'
+);
+
+CREATE TABLE webhooks (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id VARCHAR(255) NOT NULL,
+    url TEXT NOT NULL,
+    description VARCHAR(200),
+    -- Key of the HMAC-SHA256 signature sent with every delivery
+    secret VARCHAR(100) NOT NULL,
+    events webhook_event[] NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_webhooks_user_id ON webhooks(user_id);
+
+CREATE TABLE webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    webhook_id UUID NOT NULL REFERENCES webhooks(id) ON DELETE CASCADE,
+    -- Shared by the deliveries of one event, redeliveries included
+    event_id UUID NOT NULL,
+    event webhook_event NOT NULL,
+    payload JSONB NOT NULL,
+    redelivery_of UUID REFERENCES webhook_deliveries(id) ON DELETE SET NULL,
+    status delivery_status NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    -- Outcome of the last attempt
+    response_status INTEGER,
+    response_body TEXT,
+    last_error TEXT,
+    duration_ms INTEGER,
+    run_after TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    delivered_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_webhook_deliveries_webhook_id ON webhook_deliveries(webhook_id, created_at DESC);
+CREATE INDEX idx_webhook_deliveries_queue ON webhook_deliveries(run_after)
+    WHERE status IN ('pending', 'sending');
@@ -0,0 +1,7 @@
+-- One secret feed URL per user. Regenerating replaces the token, which
+-- revokes the previous URL.
+CREATE TABLE calendar_feeds (
+    user_id VARCHAR(255) PRIMARY KEY,
+    token UUID NOT NULL UNIQUE DEFAULT uuid_generate_v4(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
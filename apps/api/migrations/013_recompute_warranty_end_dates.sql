@@ -0,0 +1,19 @@
+-- End dates used to be the purchase date plus 30 days per month. Recompute
+-- them in calendar months on the owner's local calendar, as the API now
+-- does: PostgreSQL also clamps to the end of shorter months.
+WITH recomputed AS (
+    SELECT w.id,
+           ((w.purchase_date AT TIME ZONE tz.name) + make_interval(months => w.warranty_months))
+               AT TIME ZONE tz.name AS warranty_end_date
+    FROM warranties w
+    CROSS JOIN LATERAL (
+        SELECT COALESCE(
+            (SELECT p.timezone FROM reminder_preferences p WHERE p.user_id = w.user_id),
+            'Europe/Paris'
+        ) AS name
+    ) tz
+)
+UPDATE warranties w
+SET warranty_end_date = r.warranty_end_date, updated_at = NOW()
+FROM recomputed r
+WHERE w.id = r.id AND w.warranty_end_date <> r.warranty_end_date;
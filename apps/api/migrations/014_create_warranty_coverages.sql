@@ -0,0 +1,31 @@
+CREATE TYPE coverage_kind AS ENUM (
+    'legal',
+    'manufacturer',
+    'extended'
+);
+
+-- Further coverages of an item on top of its own warranty: a manufacturer
+-- warranty, a paid extension from the retailer or a bank card...
+CREATE TABLE warranty_coverages (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    warranty_id UUID NOT NULL REFERENCES warranties(id) ON DELETE CASCADE,
+    kind coverage_kind NOT NULL,
+    provider VARCHAR(200),
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    contract_number VARCHAR(100),
+    cost_cents BIGINT CHECK (cost_cents >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (ends_at > starts_at)
+);
+
+CREATE INDEX idx_warranty_coverages_warranty_id ON warranty_coverages(warranty_id);
+
+-- Latest end date across the warranty and its coverages, kept up to date by
+-- the API so lists and stats can filter and sort on it.
+ALTER TABLE warranties ADD COLUMN covered_until TIMESTAMPTZ;
+UPDATE warranties SET covered_until = warranty_end_date;
+ALTER TABLE warranties ALTER COLUMN covered_until SET NOT NULL;
+
+CREATE INDEX idx_warranties_covered_until ON warranties(user_id, covered_until);
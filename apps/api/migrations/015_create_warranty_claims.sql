@@ -0,0 +1,46 @@
+CREATE TYPE claim_status AS ENUM (
+    'opened',
+    'shipped',
+    'in_repair',
+    'resolved',
+    'refused',
+    'replaced'
+);
+
+-- After-sales claims (SAV) on a warranty.
+CREATE TABLE warranty_claims (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    warranty_id UUID NOT NULL REFERENCES warranties(id) ON DELETE CASCADE,
+    status claim_status NOT NULL DEFAULT 'opened',
+    description TEXT NOT NULL,
+    rma_number VARCHAR(100),
+    -- When the store or brand was first contacted
+    contacted_at TIMESTAMPTZ,
+    dropped_off_at TIMESTAMPTZ,
+    returned_at TIMESTAMPTZ,
+    -- Months the repair added to the warranty under the legal guarantee
+    extension_months INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_warranty_claims_warranty_id ON warranty_claims(warranty_id);
+
+-- History of a claim: one entry per status it went through.
+CREATE TABLE claim_events (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    claim_id UUID NOT NULL REFERENCES warranty_claims(id) ON DELETE CASCADE,
+    status claim_status NOT NULL,
+    note TEXT,
+    occurred_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_claim_events_claim_id ON claim_events(claim_id, occurred_at);
+
+-- Attachments stay on the warranty; this ties them to a claim too.
+ALTER TABLE warranty_attachments
+    ADD COLUMN claim_id UUID REFERENCES warranty_claims(id) ON DELETE SET NULL;
+
+-- Sum of the extensions granted by repairs, on top of warranty_months.
+ALTER TABLE warranties ADD COLUMN repair_extension_months INTEGER NOT NULL DEFAULT 0;
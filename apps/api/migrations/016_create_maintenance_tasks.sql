@@ -0,0 +1,57 @@
+ALTER TYPE notification_kind ADD VALUE IF NOT EXISTS 'maintenance_due';
+ALTER TYPE webhook_event ADD VALUE IF NOT EXISTS 'maintenance.due';
+
+-- Recurring upkeep of an item, e.g. a filter every 6 months or a car service
+-- every 15,000 km or 12 months, whichever comes first.
+CREATE TABLE maintenance_tasks (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    warranty_id UUID NOT NULL REFERENCES warranties(id) ON DELETE CASCADE,
+    title VARCHAR(200) NOT NULL,
+    notes TEXT,
+    interval_months INTEGER CHECK (interval_months > 0),
+    interval_km INTEGER CHECK (interval_km > 0),
+    -- The schedule counts from here until the task is first done
+    starts_at TIMESTAMPTZ NOT NULL,
+    start_km INTEGER CHECK (start_km >= 0),
+    -- Days before each due date the reminder goes out
+    lead_days INTEGER NOT NULL DEFAULT 7 CHECK (lead_days BETWEEN 0 AND 365),
+    -- Next occurrence, from the last completion or the start
+    next_due_at TIMESTAMPTZ,
+    next_due_km INTEGER,
+    last_done_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (interval_months IS NOT NULL OR interval_km IS NOT NULL)
+);
+
+CREATE INDEX idx_maintenance_tasks_warranty_id ON maintenance_tasks(warranty_id);
+CREATE INDEX idx_maintenance_tasks_next_due_at ON maintenance_tasks(next_due_at);
+
+-- Completion log of a task.
+CREATE TABLE maintenance_completions (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    task_id UUID NOT NULL REFERENCES maintenance_tasks(id) ON DELETE CASCADE,
+    done_at TIMESTAMPTZ NOT NULL,
+    odometer_km INTEGER CHECK (odometer_km >= 0),
+    notes TEXT,
+    cost_cents BIGINT CHECK (cost_cents >= 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_maintenance_completions_task_id ON maintenance_completions(task_id, done_at DESC);
+
+-- A maintenance reminder is recorded once per due date of its task.
+ALTER TABLE notifications
+    ADD COLUMN maintenance_task_id UUID REFERENCES maintenance_tasks(id) ON DELETE CASCADE,
+    ADD COLUMN maintenance_due_at TIMESTAMPTZ,
+    DROP CONSTRAINT notifications_check,
+    ADD CONSTRAINT notifications_check CHECK (
+        (warranty_id IS NOT NULL AND warranty_end_date IS NOT NULL)
+        OR (warranty_id IS NOT NULL AND maintenance_task_id IS NOT NULL
+            AND maintenance_due_at IS NOT NULL)
+        OR digest IS NOT NULL
+    );
+
+CREATE UNIQUE INDEX idx_notifications_maintenance
+    ON notifications(maintenance_task_id, maintenance_due_at)
+    WHERE kind = 'maintenance_due';
@@ -0,0 +1,74 @@
+-- Full-text search over warranties. The french_unaccent configuration
+-- strips accents before French stemming, so "electromenager" matches
+-- "Électroménager".
+CREATE EXTENSION IF NOT EXISTS unaccent;
+
+CREATE TEXT SEARCH CONFIGURATION french_unaccent (COPY = french);
+ALTER TEXT SEARCH CONFIGURATION french_unaccent
+    ALTER MAPPING FOR hword, hword_part, word WITH unaccent, french_stem;
+
+-- Text read from the latest receipt of a warranty.
+CREATE FUNCTION warranty_receipt_text(p_warranty_id UUID) RETURNS TEXT
+LANGUAGE sql STABLE AS $$
+    SELECT raw_text FROM receipt_extractions
+    WHERE warranty_id = p_warranty_id AND status = 'completed' AND raw_text IS NOT NULL
+    ORDER BY completed_at DESC
+    LIMIT 1
+$$;
+
+CREATE FUNCTION warranty_search_vector(
+    product_name TEXT,
+    brand TEXT,
+    store TEXT,
+    notes TEXT,
+    receipt_text TEXT
+) RETURNS tsvector
+LANGUAGE sql IMMUTABLE AS $$
+    SELECT setweight(to_tsvector('french_unaccent', coalesce(product_name, '')), 'A')
+        || setweight(to_tsvector('french_unaccent', coalesce(brand, '')), 'A')
+        || setweight(to_tsvector('french_unaccent', coalesce(store, '')), 'B')
+        || setweight(to_tsvector('french_unaccent', coalesce(notes, '')), 'C')
+        || setweight(to_tsvector('french_unaccent', coalesce(receipt_text, '')), 'D')
+$$;
+
+ALTER TABLE warranties ADD COLUMN search_vector tsvector;
+
+CREATE FUNCTION warranties_search_vector_update() RETURNS trigger
+LANGUAGE plpgsql AS $$
+BEGIN
+    NEW.search_vector := warranty_search_vector(
+        NEW.product_name, NEW.brand, NEW.store, NEW.notes, warranty_receipt_text(NEW.id)
+    );
+    RETURN NEW;
+END
+$$;
+
+CREATE TRIGGER warranties_search_vector
+    BEFORE INSERT OR UPDATE OF product_name, brand, store, notes ON warranties
+    FOR EACH ROW EXECUTE FUNCTION warranties_search_vector_update();
+
+-- A receipt read after the warranty was saved adds its text to the index.
+CREATE FUNCTION receipt_extractions_search_vector_update() RETURNS trigger
+LANGUAGE plpgsql AS $$
+BEGIN
+    UPDATE warranties
+    SET search_vector = warranty_search_vector(
+        product_name, brand, store, notes, warranty_receipt_text(id)
+    )
+    WHERE id = NEW.warranty_id;
+    RETURN NULL;
+END
+$$;
+
+CREATE TRIGGER receipt_extractions_search_vector
+    AFTER INSERT OR UPDATE OF status, raw_text ON receipt_extractions
+    FOR EACH ROW
+    WHEN (NEW.warranty_id IS NOT NULL AND NEW.status = 'completed')
+    EXECUTE FUNCTION receipt_extractions_search_vector_update();
+
+UPDATE warranties
+SET search_vector = warranty_search_vector(
+    product_name, brand, store, notes, warranty_receipt_text(id)
+);
+
+CREATE INDEX idx_warranties_search_vector ON warranties USING GIN (search_vector);
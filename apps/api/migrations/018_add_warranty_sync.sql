@@ -0,0 +1,58 @@
+-- Delta sync for offline clients. Every insert or update of a warranty
+-- records the transaction that made it; a sync token is the oldest
+-- transaction still running when the client last synced, so the next sync
+-- sends everything changed from there on, including transactions that had
+-- not committed yet.
+ALTER TABLE warranties ADD COLUMN changed_xid xid8 NOT NULL DEFAULT pg_current_xact_id();
+
+CREATE INDEX idx_warranties_user_id_changed_xid ON warranties(user_id, changed_xid);
+
+CREATE FUNCTION warranties_changed_xid_update() RETURNS trigger
+LANGUAGE plpgsql AS $$
+BEGIN
+    NEW.changed_xid := pg_current_xact_id();
+    RETURN NEW;
+END
+$$;
+
+CREATE TRIGGER warranties_changed_xid
+    BEFORE UPDATE ON warranties
+    FOR EACH ROW EXECUTE FUNCTION warranties_changed_xid_update();
+
+-- Deleted warranties, so clients can drop their copy.
+CREATE TABLE warranty_tombstones (
+    warranty_id UUID PRIMARY KEY,
+    user_id VARCHAR(255) NOT NULL,
+    deleted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    changed_xid xid8 NOT NULL DEFAULT pg_current_xact_id()
+);
+
+CREATE INDEX idx_warranty_tombstones_user_id_changed_xid ON warranty_tombstones(user_id, changed_xid);
+
+CREATE FUNCTION warranties_tombstone_insert() RETURNS trigger
+LANGUAGE plpgsql AS $$
+BEGIN
+    INSERT INTO warranty_tombstones (warranty_id, user_id)
+    VALUES (OLD.id, OLD.user_id)
+    ON CONFLICT (warranty_id) DO UPDATE
+    SET user_id = EXCLUDED.user_id, deleted_at = NOW(), changed_xid = pg_current_xact_id();
+    RETURN NULL;
+END
+$$;
+
+CREATE TRIGGER warranties_tombstone
+    AFTER DELETE ON warranties
+    FOR EACH ROW EXECUTE FUNCTION warranties_tombstone_insert();
+
+-- A warranty created again under the same id is no longer deleted.
+CREATE FUNCTION warranties_tombstone_delete() RETURNS trigger
+LANGUAGE plpgsql AS $$
+BEGIN
+    DELETE FROM warranty_tombstones WHERE warranty_id = NEW.id;
+    RETURN NULL;
+END
+$$;
+
+CREATE TRIGGER warranties_tombstone_clear
+    AFTER INSERT ON warranties
+    FOR EACH ROW EXECUTE FUNCTION warranties_tombstone_delete();
@@ -0,0 +1,39 @@
+ALTER TYPE webhook_event ADD VALUE IF NOT EXISTS 'warranty.restored';
+
+-- Deleted warranties go to the trash, with their attachments and receipt,
+-- until they are restored or purged once the retention period is over.
+ALTER TABLE warranties ADD COLUMN deleted_at TIMESTAMPTZ;
+
+CREATE INDEX idx_warranties_deleted_at ON warranties(deleted_at) WHERE deleted_at IS NOT NULL;
+
+-- Sync reports trashed warranties as deleted; restored ones come back as
+-- changed.
+CREATE FUNCTION warranties_trash_tombstone() RETURNS trigger
+LANGUAGE plpgsql AS $$
+BEGIN
+    IF NEW.deleted_at IS NULL THEN
+        DELETE FROM warranty_tombstones WHERE warranty_id = NEW.id;
+    ELSE
+        INSERT INTO warranty_tombstones (warranty_id, user_id, deleted_at)
+        VALUES (NEW.id, NEW.user_id, NEW.deleted_at)
+        ON CONFLICT (warranty_id) DO UPDATE
+        SET user_id = EXCLUDED.user_id, deleted_at = EXCLUDED.deleted_at,
+            changed_xid = pg_current_xact_id();
+    END IF;
+    RETURN NULL;
+END
+$$;
+
+CREATE TRIGGER warranties_trash_tombstone
+    AFTER UPDATE OF deleted_at ON warranties
+    FOR EACH ROW
+    WHEN (OLD.deleted_at IS DISTINCT FROM NEW.deleted_at)
+    EXECUTE FUNCTION warranties_trash_tombstone();
+
+-- Purging a trashed warranty keeps the tombstone written when it was trashed.
+DROP TRIGGER warranties_tombstone ON warranties;
+CREATE TRIGGER warranties_tombstone
+    AFTER DELETE ON warranties
+    FOR EACH ROW
+    WHEN (OLD.deleted_at IS NULL)
+    EXECUTE FUNCTION warranties_tombstone_insert();
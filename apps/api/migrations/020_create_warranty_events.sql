@@ -0,0 +1,31 @@
+CREATE TYPE warranty_action AS ENUM ('created', 'updated', 'receipt_uploaded', 'trashed', 'restored');
+
+-- Append-only history of a warranty: who changed which fields, and in which
+-- request. Purged with the warranty.
+CREATE TABLE warranty_events (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    warranty_id UUID NOT NULL REFERENCES warranties(id) ON DELETE CASCADE,
+    action warranty_action NOT NULL,
+    -- [{"field", "old", "new"}, ...]
+    changes JSONB NOT NULL DEFAULT '[]',
+    actor_user_id VARCHAR(255) NOT NULL,
+    request_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_warranty_events_warranty_id ON warranty_events(warranty_id, created_at);
+
+CREATE FUNCTION warranty_events_append_only() RETURNS trigger
+LANGUAGE plpgsql AS $$
+BEGIN
+    RAISE EXCEPTION 'warranty_events is append-only';
+END
+$$;
+
+CREATE TRIGGER warranty_events_append_only
+    BEFORE UPDATE ON warranty_events
+    FOR EACH ROW EXECUTE FUNCTION warranty_events_append_only();
+
+-- Warranties created before the history started get their creation only.
+INSERT INTO warranty_events (warranty_id, action, actor_user_id, created_at)
+SELECT id, 'created', user_id, created_at FROM warranties;
@@ -0,0 +1,114 @@
+ALTER TYPE notification_kind ADD VALUE IF NOT EXISTS 'household_invitation';
+
+-- In increasing order of rights, so `role >= 'editor'` reads "editor or
+-- owner". Viewers read, editors also change warranties, owners also manage
+-- the household and its members.
+CREATE TYPE household_role AS ENUM (
+    'viewer',
+    'editor',
+    'owner'
+);
+
+CREATE TABLE households (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- joined_xid lets sync send a new member the warranties already there.
+CREATE TABLE household_members (
+    household_id UUID NOT NULL REFERENCES households(id) ON DELETE CASCADE,
+    user_id VARCHAR(255) NOT NULL,
+    role household_role NOT NULL,
+    joined_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    joined_xid xid8 NOT NULL DEFAULT pg_current_xact_id(),
+    PRIMARY KEY (household_id, user_id)
+);
+
+CREATE INDEX idx_household_members_user_id ON household_members(user_id);
+
+-- The token is sent by email; whoever accepts it joins with the role.
+CREATE TABLE household_invitations (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    household_id UUID NOT NULL REFERENCES households(id) ON DELETE CASCADE,
+    email VARCHAR(255) NOT NULL,
+    role household_role NOT NULL,
+    token UUID NOT NULL UNIQUE DEFAULT uuid_generate_v4(),
+    invited_by VARCHAR(255) NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    accepted_by VARCHAR(255),
+    accepted_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_household_invitations_household_id ON household_invitations(household_id);
+
+ALTER TABLE notifications
+    ADD COLUMN household_invitation_id UUID REFERENCES household_invitations(id) ON DELETE CASCADE,
+    DROP CONSTRAINT notifications_check,
+    ADD CONSTRAINT notifications_check CHECK (
+        (warranty_id IS NOT NULL AND warranty_end_date IS NOT NULL)
+        OR (warranty_id IS NOT NULL AND maintenance_task_id IS NOT NULL
+            AND maintenance_due_at IS NOT NULL)
+        OR digest IS NOT NULL
+        OR household_invitation_id IS NOT NULL
+    );
+
+-- Warranties belong to a household; user_id stays their creator, whose
+-- calendar their dates are counted on.
+ALTER TABLE warranties ADD COLUMN household_id UUID REFERENCES households(id);
+ALTER TABLE warranty_tombstones ADD COLUMN household_id UUID REFERENCES households(id) ON DELETE CASCADE;
+
+-- Every user gets a household of their own with their warranties in it.
+DO $$
+DECLARE
+    owner_id VARCHAR(255);
+    new_household_id UUID;
+BEGIN
+    FOR owner_id IN
+        SELECT user_id FROM warranties UNION SELECT user_id FROM warranty_tombstones
+    LOOP
+        INSERT INTO households (name) VALUES ('Mon foyer') RETURNING id INTO new_household_id;
+        INSERT INTO household_members (household_id, user_id, role)
+        VALUES (new_household_id, owner_id, 'owner');
+        UPDATE warranties SET household_id = new_household_id WHERE user_id = owner_id;
+        UPDATE warranty_tombstones SET household_id = new_household_id WHERE user_id = owner_id;
+    END LOOP;
+END
+$$;
+
+ALTER TABLE warranties ALTER COLUMN household_id SET NOT NULL;
+ALTER TABLE warranty_tombstones ALTER COLUMN household_id SET NOT NULL;
+
+CREATE INDEX idx_warranties_household_id_changed_xid ON warranties(household_id, changed_xid);
+CREATE INDEX idx_warranty_tombstones_household_id_changed_xid
+    ON warranty_tombstones(household_id, changed_xid);
+
+CREATE OR REPLACE FUNCTION warranties_tombstone_insert() RETURNS trigger
+LANGUAGE plpgsql AS $$
+BEGIN
+    INSERT INTO warranty_tombstones (warranty_id, user_id, household_id)
+    VALUES (OLD.id, OLD.user_id, OLD.household_id)
+    ON CONFLICT (warranty_id) DO UPDATE
+    SET user_id = EXCLUDED.user_id, household_id = EXCLUDED.household_id, deleted_at = NOW(),
+        changed_xid = pg_current_xact_id();
+    RETURN NULL;
+END
+$$;
+
+CREATE OR REPLACE FUNCTION warranties_trash_tombstone() RETURNS trigger
+LANGUAGE plpgsql AS $$
+BEGIN
+    IF NEW.deleted_at IS NULL THEN
+        DELETE FROM warranty_tombstones WHERE warranty_id = NEW.id;
+    ELSE
+        INSERT INTO warranty_tombstones (warranty_id, user_id, household_id, deleted_at)
+        VALUES (NEW.id, NEW.user_id, NEW.household_id, NEW.deleted_at)
+        ON CONFLICT (warranty_id) DO UPDATE
+        SET user_id = EXCLUDED.user_id, household_id = EXCLUDED.household_id,
+            deleted_at = EXCLUDED.deleted_at, changed_xid = pg_current_xact_id();
+    END IF;
+    RETURN NULL;
+END
+$$;
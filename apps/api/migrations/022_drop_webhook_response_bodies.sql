@@ -0,0 +1,3 @@
+-- Webhook URLs are chosen by users, so response bodies could show them what
+-- a URL reached inside the network. Only the status is kept.
+ALTER TABLE webhook_deliveries DROP COLUMN response_body;
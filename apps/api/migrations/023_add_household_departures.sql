@@ -0,0 +1,28 @@
+-- Members who left or were removed from a household, so sync can tell them
+-- to drop its warranties. A member who comes back is sent them again, as
+-- their new joined_xid is later.
+CREATE TABLE household_departures (
+    household_id UUID NOT NULL REFERENCES households(id) ON DELETE CASCADE,
+    user_id VARCHAR(255) NOT NULL,
+    left_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    changed_xid xid8 NOT NULL DEFAULT pg_current_xact_id(),
+    PRIMARY KEY (user_id, household_id)
+);
+
+CREATE FUNCTION household_members_departure() RETURNS trigger
+LANGUAGE plpgsql AS $$
+BEGIN
+    -- Nothing to record when the household itself goes.
+    IF EXISTS (SELECT 1 FROM households WHERE id = OLD.household_id) THEN
+        INSERT INTO household_departures (household_id, user_id)
+        VALUES (OLD.household_id, OLD.user_id)
+        ON CONFLICT (user_id, household_id) DO UPDATE
+        SET left_at = NOW(), changed_xid = pg_current_xact_id();
+    END IF;
+    RETURN NULL;
+END
+$$;
+
+CREATE TRIGGER household_members_departure
+    AFTER DELETE ON household_members
+    FOR EACH ROW EXECUTE FUNCTION household_members_departure();
@@ -0,0 +1,17 @@
+-- Reminders, expiries and maintenance reminders of a warranty go to every
+-- current member of its household, so each is recorded once per member.
+ALTER TABLE notifications DROP CONSTRAINT notifications_warranty_id_kind_threshold_days_key;
+
+CREATE UNIQUE INDEX idx_notifications_expiry_reminder
+    ON notifications(warranty_id, user_id, threshold_days)
+    WHERE kind = 'expiry_reminder';
+
+DROP INDEX idx_notifications_expired;
+CREATE UNIQUE INDEX idx_notifications_expired
+    ON notifications(warranty_id, user_id, warranty_end_date)
+    WHERE kind = 'expired';
+
+DROP INDEX idx_notifications_maintenance;
+CREATE UNIQUE INDEX idx_notifications_maintenance
+    ON notifications(maintenance_task_id, user_id, maintenance_due_at)
+    WHERE kind = 'maintenance_due';
@@ -0,0 +1,3 @@
+-- Language of the user's notifications; NULL follows the server's
+-- DEFAULT_LOCALE.
+ALTER TABLE reminder_preferences ADD COLUMN locale VARCHAR(10);
@@ -0,0 +1,7 @@
+-- An expiry reminder is recorded once per threshold and end date, like
+-- expiries, so moving a warranty's covered_until makes its thresholds fire
+-- again.
+DROP INDEX idx_notifications_expiry_reminder;
+CREATE UNIQUE INDEX idx_notifications_expiry_reminder
+    ON notifications(warranty_id, user_id, threshold_days, warranty_end_date)
+    WHERE kind = 'expiry_reminder';
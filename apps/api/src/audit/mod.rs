@@ -0,0 +1,82 @@
+//! Who changed a warranty, and in which request, for its change history.
+
+#[cfg(test)]
+mod tests;
+
+use axum::{
+    body::Body,
+    http::{HeaderName, HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use crate::models::{FieldChange, Warranty};
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Fields the history leaves out: they never change, or the action itself
+/// tells how they did.
+const UNTRACKED_FIELDS: [&str; 5] = ["id", "user_id", "created_at", "updated_at", "deleted_at"];
+
+/// Id of a request, shared by its tracing span, its `X-Request-Id` response
+/// header and the history entries it wrote.
+#[derive(Debug, Clone, Copy)]
+pub struct RequestId(pub Uuid);
+
+/// Who makes a change.
+#[derive(Debug, Clone)]
+pub struct Actor {
+    pub user_id: String,
+    pub request_id: Option<Uuid>,
+}
+
+impl Actor {
+    pub fn new<B>(user_id: &str, request: &Request<B>) -> Self {
+        Actor {
+            user_id: user_id.to_string(),
+            request_id: request
+                .extensions()
+                .get::<RequestId>()
+                .map(|request_id| request_id.0),
+        }
+    }
+}
+
+/// Gives each request an id. Runs outside the `TraceLayer` so its span can
+/// log the id.
+pub async fn assign_request_id(mut request: Request<Body>, next: Next) -> Response {
+    let request_id = Uuid::new_v4();
+    request.extensions_mut().insert(RequestId(request_id));
+
+    let mut response = next.run(request).await;
+    if let Ok(value) = HeaderValue::from_str(&request_id.to_string()) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// The fields that differ between two versions of a warranty, by name.
+/// Without `before`, every field `after` has set.
+pub fn diff(before: Option<&Warranty>, after: &Warranty) -> Vec<FieldChange> {
+    let old = before.map(tracked_fields).unwrap_or_default();
+
+    tracked_fields(after)
+        .into_iter()
+        .filter_map(|(field, new)| {
+            let old = old.get(&field).cloned().unwrap_or(Value::Null);
+            (old != new).then_some(FieldChange { field, old, new })
+        })
+        .collect()
+}
+
+fn tracked_fields(warranty: &Warranty) -> Map<String, Value> {
+    let Ok(Value::Object(mut fields)) = serde_json::to_value(warranty) else {
+        return Map::new();
+    };
+    for field in UNTRACKED_FIELDS {
+        fields.remove(field);
+    }
+    fields
+}
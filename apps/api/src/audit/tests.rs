@@ -0,0 +1,84 @@
+use chrono::{TimeZone, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::audit::diff;
+use crate::models::{FieldChange, Warranty, WarrantyCategory};
+
+fn warranty() -> Warranty {
+    let purchase_date = Utc.with_ymd_and_hms(2025, 6, 2, 0, 0, 0).unwrap();
+    Warranty {
+        id: Uuid::parse_str("3f2b8c1e-7d4a-4e9b-9c6f-1a2b3c4d5e6f").unwrap(),
+        user_id: "user-1".to_string(),
+        household_id: Uuid::nil(),
+        product_name: "Lave-vaisselle".to_string(),
+        brand: None,
+        category: WarrantyCategory::Appliances,
+        purchase_date,
+        warranty_end_date: Utc.with_ymd_and_hms(2027, 6, 2, 0, 0, 0).unwrap(),
+        warranty_months: 24,
+        repair_extension_months: 0,
+        covered_until: Utc.with_ymd_and_hms(2027, 6, 2, 0, 0, 0).unwrap(),
+        store: None,
+        price_cents: Some(49900),
+        invoice_number: None,
+        receipt_url: None,
+        receipt_key: None,
+        receipt_content_type: None,
+        receipt_thumbnail_url: None,
+        receipt_thumbnail_key: None,
+        notes: None,
+        reminder_lead_days: None,
+        created_at: purchase_date,
+        updated_at: purchase_date,
+        deleted_at: None,
+    }
+}
+
+#[test]
+fn test_diff_reports_changed_fields_only() {
+    let before = warranty();
+    let mut after = warranty();
+    after.brand = Some("Bosch".to_string());
+    after.price_cents = None;
+    after.updated_at = Utc::now();
+
+    assert_eq!(
+        diff(Some(&before), &after),
+        vec![
+            FieldChange {
+                field: "brand".to_string(),
+                old: json!(null),
+                new: json!("Bosch"),
+            },
+            FieldChange {
+                field: "price_cents".to_string(),
+                old: json!(49900),
+                new: json!(null),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_diff_of_new_warranty_lists_fields_set() {
+    let changes = diff(None, &warranty());
+    let fields: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+
+    assert!(fields.contains(&"product_name"));
+    assert!(fields.contains(&"price_cents"));
+    assert!(!fields.contains(&"brand"));
+    assert!(!fields.contains(&"id"));
+    assert!(!fields.contains(&"created_at"));
+    assert!(changes.iter().all(|c| c.old.is_null()));
+}
+
+#[test]
+fn test_diff_ignores_trashing() {
+    let before = warranty();
+    let mut after = warranty();
+    after.deleted_at = Some(Utc::now());
+    after.updated_at = Utc::now();
+
+    assert!(diff(Some(&before), &after).is_empty());
+}
@@ -0,0 +1,220 @@
+//! iCalendar (RFC 5545) feed of warranty end dates, for calendar apps that
+//! subscribe to a URL.
+
+#[cfg(test)]
+mod tests;
+
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+
+use crate::models::Warranty;
+use crate::notifications::templates::{format_date, product_label, Locale};
+
+/// Lines longer than this many octets are folded (RFC 5545, 3.1).
+const MAX_LINE_OCTETS: usize = 75;
+
+/// How often subscribers should refetch the feed.
+const REFRESH_INTERVAL: &str = "PT12H";
+
+/// Where the feed of `token` is served.
+pub fn feed_url(public_api_url: &str, token: uuid::Uuid) -> String {
+    format!(
+        "{}/api/v1/calendar/{}/warranties.ics",
+        public_api_url.trim_end_matches('/'),
+        token
+    )
+}
+
+/// `url` with its scheme replaced by `webcal`, which calendar apps open as a
+/// subscription.
+pub fn webcal_url(url: &str) -> String {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url);
+    format!("webcal://{}", rest)
+}
+
+/// A calendar with one all-day event on the day each warranty's coverage
+/// ends in `timezone`, and one alarm per reminder lead time: the warranty's
+/// own, or `lead_days`.
+pub fn warranty_calendar(
+    locale: Locale,
+    timezone: Tz,
+    warranties: &[Warranty],
+    lead_days: &[i32],
+    web_app_url: &str,
+    now: DateTime<Utc>,
+) -> String {
+    let mut calendar = Calendar::default();
+    calendar.line("BEGIN:VCALENDAR");
+    calendar.line("VERSION:2.0");
+    calendar.line("PRODID:-//Garry//Warranties//EN");
+    calendar.line("CALSCALE:GREGORIAN");
+    calendar.line("METHOD:PUBLISH");
+    calendar.property(
+        "X-WR-CALNAME",
+        match locale {
+            Locale::Fr => "Garanties Garry",
+            Locale::En => "Garry warranties",
+        },
+    );
+    calendar.line(&format!(
+        "REFRESH-INTERVAL;VALUE=DURATION:{}",
+        REFRESH_INTERVAL
+    ));
+    calendar.line(&format!("X-PUBLISHED-TTL:{}", REFRESH_INTERVAL));
+
+    for warranty in warranties {
+        let lead_days = warranty.reminder_lead_days.as_deref().unwrap_or(lead_days);
+        event(
+            &mut calendar,
+            locale,
+            timezone,
+            warranty,
+            lead_days,
+            web_app_url,
+            now,
+        );
+    }
+
+    calendar.line("END:VCALENDAR");
+    calendar.0
+}
+
+fn event(
+    calendar: &mut Calendar,
+    locale: Locale,
+    timezone: Tz,
+    warranty: &Warranty,
+    lead_days: &[i32],
+    web_app_url: &str,
+    now: DateTime<Utc>,
+) {
+    let product = product_label(&warranty.product_name, warranty.brand.as_deref());
+    let end_date = warranty.covered_until.with_timezone(&timezone).date_naive();
+    let link = format!(
+        "{}/warranty/{}",
+        web_app_url.trim_end_matches('/'),
+        warranty.id
+    );
+    let purchase = format_date(locale, warranty.purchase_date, timezone);
+    let store = warranty
+        .store
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+    let (summary, purchased) = match (locale, store) {
+        (Locale::Fr, Some(store)) => (
+            format!("Fin de garantie : {}", product),
+            format!("Acheté le {} chez {}", purchase, store),
+        ),
+        (Locale::Fr, None) => (
+            format!("Fin de garantie : {}", product),
+            format!("Acheté le {}", purchase),
+        ),
+        (Locale::En, Some(store)) => (
+            format!("Warranty ends: {}", product),
+            format!("Purchased on {} at {}", purchase, store),
+        ),
+        (Locale::En, None) => (
+            format!("Warranty ends: {}", product),
+            format!("Purchased on {}", purchase),
+        ),
+    };
+
+    calendar.line("BEGIN:VEVENT");
+    calendar.line(&format!("UID:warranty-{}@garry", warranty.id));
+    calendar.line(&format!("DTSTAMP:{}", timestamp(now)));
+    calendar.line(&format!("LAST-MODIFIED:{}", timestamp(warranty.updated_at)));
+    calendar.line(&format!("DTSTART;VALUE=DATE:{}", end_date.format("%Y%m%d")));
+    calendar.line(&format!(
+        "DTEND;VALUE=DATE:{}",
+        (end_date + Duration::days(1)).format("%Y%m%d")
+    ));
+    calendar.property("SUMMARY", &summary);
+    calendar.property("DESCRIPTION", &format!("{}\n{}", purchased, link));
+    calendar.line(&format!("URL:{}", link));
+    calendar.line("TRANSP:TRANSPARENT");
+
+    let mut lead_days = lead_days.to_vec();
+    lead_days.sort_unstable_by(|a, b| b.cmp(a));
+    lead_days.dedup();
+    for days in lead_days {
+        calendar.line("BEGIN:VALARM");
+        calendar.line("ACTION:DISPLAY");
+        calendar.line(&format!(
+            "TRIGGER:{}",
+            if days == 0 {
+                "PT0S".to_string()
+            } else {
+                format!("-P{}D", days)
+            }
+        ));
+        calendar.property("DESCRIPTION", &alarm_text(locale, &product, days));
+        calendar.line("END:VALARM");
+    }
+
+    calendar.line("END:VEVENT");
+}
+
+fn alarm_text(locale: Locale, product: &str, days: i32) -> String {
+    match (locale, days) {
+        (Locale::Fr, 0) => format!("La garantie {} expire aujourd'hui", product),
+        (Locale::Fr, 1) => format!("La garantie {} expire demain", product),
+        (Locale::Fr, days) => format!("La garantie {} expire dans {} jours", product, days),
+        (Locale::En, 0) => format!("The {} warranty expires today", product),
+        (Locale::En, 1) => format!("The {} warranty expires tomorrow", product),
+        (Locale::En, days) => format!("The {} warranty expires in {} days", product, days),
+    }
+}
+
+fn timestamp(date: DateTime<Utc>) -> String {
+    date.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escapes a TEXT value (RFC 5545, 3.3.11).
+pub fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Splits `line` into CRLF-terminated lines of at most 75 octets, each
+/// continuation starting with a space, without cutting a UTF-8 character.
+pub fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[derive(Default)]
+struct Calendar(String);
+
+impl Calendar {
+    fn line(&mut self, line: &str) {
+        self.0.push_str(&fold(line));
+    }
+
+    fn property(&mut self, name: &str, text: &str) {
+        self.line(&format!("{}:{}", name, escape_text(text)));
+    }
+}
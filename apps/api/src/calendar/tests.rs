@@ -0,0 +1,200 @@
+use chrono::{TimeZone, Utc};
+use chrono_tz::Tz;
+use uuid::Uuid;
+
+use crate::calendar::{escape_text, feed_url, fold, warranty_calendar, webcal_url};
+use crate::models::{Warranty, WarrantyCategory};
+use crate::notifications::templates::Locale;
+
+fn warranty() -> Warranty {
+    let purchase_date = Utc.with_ymd_and_hms(2024, 3, 15, 0, 0, 0).unwrap();
+    Warranty {
+        id: Uuid::parse_str("3f2b8c1e-7d4a-4e9b-9c6f-1a2b3c4d5e6f").unwrap(),
+        user_id: "user-1".to_string(),
+        household_id: Uuid::nil(),
+        product_name: "Lave-linge".to_string(),
+        brand: Some("Bosch".to_string()),
+        category: WarrantyCategory::Appliances,
+        purchase_date,
+        warranty_end_date: Utc.with_ymd_and_hms(2026, 3, 15, 0, 0, 0).unwrap(),
+        warranty_months: 24,
+        repair_extension_months: 0,
+        covered_until: Utc.with_ymd_and_hms(2026, 3, 15, 0, 0, 0).unwrap(),
+        store: Some("Darty, Lyon".to_string()),
+        price_cents: None,
+        invoice_number: None,
+        receipt_url: None,
+        receipt_key: None,
+        receipt_content_type: None,
+        receipt_thumbnail_url: None,
+        receipt_thumbnail_key: None,
+        notes: None,
+        reminder_lead_days: None,
+        created_at: purchase_date,
+        updated_at: Utc.with_ymd_and_hms(2024, 3, 16, 9, 30, 0).unwrap(),
+        deleted_at: None,
+    }
+}
+
+/// The feed with folded lines joined back, as a parser would read it.
+fn unfolded(ics: &str) -> Vec<String> {
+    ics.replace("\r\n ", "")
+        .split("\r\n")
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn test_warranty_calendar() {
+    let now = Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap();
+    let ics = warranty_calendar(
+        Locale::Fr,
+        Tz::Europe__Paris,
+        &[warranty()],
+        &[30, 7, 30],
+        "https://garry.app/",
+        now,
+    );
+
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    let lines = unfolded(&ics);
+    for expected in [
+        "X-WR-CALNAME:Garanties Garry",
+        "BEGIN:VEVENT",
+        "UID:warranty-3f2b8c1e-7d4a-4e9b-9c6f-1a2b3c4d5e6f@garry",
+        "DTSTAMP:20250601T120000Z",
+        "LAST-MODIFIED:20240316T093000Z",
+        "DTSTART;VALUE=DATE:20260315",
+        "DTEND;VALUE=DATE:20260316",
+        "SUMMARY:Fin de garantie : Lave-linge Bosch",
+        "DESCRIPTION:Acheté le 15/03/2024 chez Darty\\, Lyon\\nhttps://garry.app/warranty/3f2b8c1e-7d4a-4e9b-9c6f-1a2b3c4d5e6f",
+        "URL:https://garry.app/warranty/3f2b8c1e-7d4a-4e9b-9c6f-1a2b3c4d5e6f",
+        "TRIGGER:-P30D",
+        "DESCRIPTION:La garantie Lave-linge Bosch expire dans 30 jours",
+        "TRIGGER:-P7D",
+    ] {
+        assert!(lines.iter().any(|line| line == expected), "missing {}", expected);
+    }
+    // Duplicate lead times give one alarm.
+    assert_eq!(
+        lines.iter().filter(|line| *line == "BEGIN:VALARM").count(),
+        2
+    );
+}
+
+#[test]
+fn test_event_is_on_the_coverage_end_in_the_owners_timezone() {
+    // Covered until midnight on 15 September in Paris, after a repair
+    // extended the original end date.
+    let mut extended = warranty();
+    extended.repair_extension_months = 6;
+    extended.covered_until = Utc.with_ymd_and_hms(2026, 9, 14, 22, 0, 0).unwrap();
+    extended.purchase_date = Utc.with_ymd_and_hms(2024, 3, 14, 23, 0, 0).unwrap();
+
+    let lines = unfolded(&warranty_calendar(
+        Locale::Fr,
+        Tz::Europe__Paris,
+        &[extended.clone()],
+        &[30],
+        "https://garry.app",
+        Utc::now(),
+    ));
+    assert!(lines.contains(&"DTSTART;VALUE=DATE:20260915".to_string()));
+    assert!(lines.contains(&"DTEND;VALUE=DATE:20260916".to_string()));
+    assert!(lines
+        .iter()
+        .any(|line| line.starts_with("DESCRIPTION:Acheté le 15/03/2024 ")));
+
+    let lines = unfolded(&warranty_calendar(
+        Locale::Fr,
+        Tz::UTC,
+        &[extended],
+        &[30],
+        "https://garry.app",
+        Utc::now(),
+    ));
+    assert!(lines.contains(&"DTSTART;VALUE=DATE:20260914".to_string()));
+}
+
+#[test]
+fn test_warranty_lead_days_override() {
+    let mut muted = warranty();
+    muted.reminder_lead_days = Some(Vec::new());
+    let mut own = warranty();
+    own.reminder_lead_days = Some(vec![1, 0]);
+
+    let ics = warranty_calendar(
+        Locale::En,
+        Tz::Europe__Paris,
+        &[muted],
+        &[30],
+        "https://garry.app",
+        Utc::now(),
+    );
+    assert!(!ics.contains("BEGIN:VALARM"));
+
+    let lines = unfolded(&warranty_calendar(
+        Locale::En,
+        Tz::Europe__Paris,
+        &[own],
+        &[30],
+        "https://garry.app",
+        Utc::now(),
+    ));
+    let triggers: Vec<&String> = lines.iter().filter(|l| l.starts_with("TRIGGER:")).collect();
+    assert_eq!(triggers, ["TRIGGER:-P1D", "TRIGGER:PT0S"]);
+    assert!(
+        lines.contains(&"DESCRIPTION:The Lave-linge Bosch warranty expires tomorrow".to_string())
+    );
+}
+
+#[test]
+fn test_empty_calendar() {
+    let ics = warranty_calendar(
+        Locale::En,
+        Tz::Europe__Paris,
+        &[],
+        &[30],
+        "https://garry.app",
+        Utc::now(),
+    );
+    assert!(ics.contains("X-WR-CALNAME:Garry warranties\r\n"));
+    assert!(!ics.contains("BEGIN:VEVENT"));
+}
+
+#[test]
+fn test_escape_text() {
+    assert_eq!(escape_text("a\\b;c,d\r\ne"), "a\\\\b\\;c\\,d\\ne");
+}
+
+#[test]
+fn test_fold_keeps_lines_short_and_characters_whole() {
+    let line = format!("SUMMARY:{}", "é".repeat(60));
+    let folded = fold(&line);
+
+    assert!(folded.ends_with("\r\n"));
+    for part in folded.trim_end_matches("\r\n").split("\r\n") {
+        assert!(part.len() <= 75, "{} octets", part.len());
+    }
+    assert_eq!(folded.replace("\r\n ", "").trim_end(), line);
+    assert_eq!(fold("VERSION:2.0"), "VERSION:2.0\r\n");
+}
+
+#[test]
+fn test_feed_urls() {
+    let token = Uuid::parse_str("0b7a3c52-9d4e-4f61-8a2b-5c3d7e9f1a24").unwrap();
+    let url = feed_url("https://api.garry.app/", token);
+    assert_eq!(
+        url,
+        "https://api.garry.app/api/v1/calendar/0b7a3c52-9d4e-4f61-8a2b-5c3d7e9f1a24/warranties.ics"
+    );
+    assert_eq!(
+        webcal_url(&url),
+        "webcal://api.garry.app/api/v1/calendar/0b7a3c52-9d4e-4f61-8a2b-5c3d7e9f1a24/warranties.ics"
+    );
+    assert_eq!(
+        webcal_url("http://localhost:8080/feed.ics"),
+        "webcal://localhost:8080/feed.ics"
+    );
+}
@@ -0,0 +1,155 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::models::{Attachment, AttachmentKind, UpdateAttachmentRequest};
+
+pub struct NewAttachment<'a> {
+    pub id: Uuid,
+    pub warranty_id: Uuid,
+    pub claim_id: Option<Uuid>,
+    pub kind: AttachmentKind,
+    pub file_name: Option<&'a str>,
+    pub storage_key: &'a str,
+    pub mime_type: &'a str,
+    pub size_bytes: i64,
+    pub checksum_sha256: &'a str,
+}
+
+pub async fn list_attachments(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    user_id: &str,
+) -> Result<Vec<Attachment>> {
+    let attachments = sqlx::query_as::<_, Attachment>(
+        r#"
+        SELECT a.* FROM warranty_attachments a
+        JOIN warranties w ON w.id = a.warranty_id
+        WHERE a.warranty_id = $1
+          AND w.household_id IN (SELECT household_id FROM household_members WHERE user_id = $2)
+          AND w.deleted_at IS NULL
+        ORDER BY a.uploaded_at ASC
+        "#,
+    )
+    .bind(warranty_id)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(attachments)
+}
+
+/// Attachments filed under a claim the caller has checked belongs to the user.
+pub async fn list_claim_attachments(pool: &PgPool, claim_id: Uuid) -> Result<Vec<Attachment>> {
+    let attachments = sqlx::query_as::<_, Attachment>(
+        "SELECT * FROM warranty_attachments WHERE claim_id = $1 ORDER BY uploaded_at ASC",
+    )
+    .bind(claim_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(attachments)
+}
+
+pub async fn get_attachment(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    attachment_id: Uuid,
+    user_id: &str,
+) -> Result<Attachment> {
+    sqlx::query_as::<_, Attachment>(
+        r#"
+        SELECT a.* FROM warranty_attachments a
+        JOIN warranties w ON w.id = a.warranty_id
+        WHERE a.id = $1
+          AND a.warranty_id = $2
+          AND w.household_id IN (SELECT household_id FROM household_members WHERE user_id = $3)
+          AND w.deleted_at IS NULL
+        "#,
+    )
+    .bind(attachment_id)
+    .bind(warranty_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Attachment not found".to_string()))
+}
+
+pub async fn create_attachment(pool: &PgPool, new: NewAttachment<'_>) -> Result<Attachment> {
+    let attachment = sqlx::query_as::<_, Attachment>(
+        r#"
+        INSERT INTO warranty_attachments (id, warranty_id, claim_id, kind, file_name, storage_key, mime_type, size_bytes, checksum_sha256)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING *
+        "#,
+    )
+    .bind(new.id)
+    .bind(new.warranty_id)
+    .bind(new.claim_id)
+    .bind(new.kind)
+    .bind(new.file_name)
+    .bind(new.storage_key)
+    .bind(new.mime_type)
+    .bind(new.size_bytes)
+    .bind(new.checksum_sha256)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(attachment)
+}
+
+pub async fn update_attachment(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    attachment_id: Uuid,
+    user_id: &str,
+    req: UpdateAttachmentRequest,
+) -> Result<Attachment> {
+    let existing = get_attachment(pool, warranty_id, attachment_id, user_id).await?;
+
+    let kind = req.kind.unwrap_or(existing.kind);
+    let file_name = req.file_name.or(existing.file_name);
+
+    let attachment = sqlx::query_as::<_, Attachment>(
+        r#"
+        UPDATE warranty_attachments
+        SET kind = $1, file_name = $2
+        WHERE id = $3
+        RETURNING *
+        "#,
+    )
+    .bind(kind)
+    .bind(&file_name)
+    .bind(attachment_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(attachment)
+}
+
+/// Removes the attachment row and returns it so the caller can drop the blob.
+pub async fn delete_attachment(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    attachment_id: Uuid,
+    user_id: &str,
+) -> Result<Attachment> {
+    sqlx::query_as::<_, Attachment>(
+        r#"
+        DELETE FROM warranty_attachments a
+        USING warranties w
+        WHERE a.id = $1
+          AND a.warranty_id = $2
+          AND w.id = a.warranty_id
+          AND w.household_id IN (SELECT household_id FROM household_members WHERE user_id = $3)
+          AND w.deleted_at IS NULL
+        RETURNING a.*
+        "#,
+    )
+    .bind(attachment_id)
+    .bind(warranty_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Attachment not found".to_string()))
+}
@@ -0,0 +1,54 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::models::CalendarFeed;
+
+pub async fn get_calendar_feed(pool: &PgPool, user_id: &str) -> Result<Option<CalendarFeed>> {
+    let feed = sqlx::query_as::<_, CalendarFeed>("SELECT * FROM calendar_feeds WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(feed)
+}
+
+/// Gives the user a new feed token, revoking the previous one.
+pub async fn regenerate_calendar_feed(pool: &PgPool, user_id: &str) -> Result<CalendarFeed> {
+    let feed = sqlx::query_as::<_, CalendarFeed>(
+        r#"
+        INSERT INTO calendar_feeds (user_id)
+        VALUES ($1)
+        ON CONFLICT (user_id) DO UPDATE
+        SET token = uuid_generate_v4(), created_at = NOW()
+        RETURNING *
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(feed)
+}
+
+pub async fn delete_calendar_feed(pool: &PgPool, user_id: &str) -> Result<()> {
+    let result = sqlx::query("DELETE FROM calendar_feeds WHERE user_id = $1")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Calendar feed not found".to_string()));
+    }
+
+    Ok(())
+}
+
+/// The owner of the feed behind `token`.
+pub async fn get_calendar_feed_owner(pool: &PgPool, token: Uuid) -> Result<String> {
+    sqlx::query_scalar("SELECT user_id FROM calendar_feeds WHERE token = $1")
+        .bind(token)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Calendar feed not found".to_string()))
+}
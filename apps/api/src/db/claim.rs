@@ -0,0 +1,330 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use super::warranty::{end_date, lock_warranty};
+use super::warranty_event::record_warranty_event;
+use crate::audit::{self, Actor};
+use crate::error::{AppError, Result};
+use crate::legal::RepairExtension;
+use crate::models::{Claim, ClaimEvent, ClaimStatus, UpdateClaimRequest, Warranty, WarrantyAction};
+
+pub struct NewClaim<'a> {
+    pub description: &'a str,
+    pub rma_number: Option<&'a str>,
+    pub contacted_at: DateTime<Utc>,
+    pub note: Option<&'a str>,
+}
+
+pub struct ClaimTransition<'a> {
+    pub status: ClaimStatus,
+    pub note: Option<&'a str>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+pub async fn list_claims(pool: &PgPool, warranty_id: Uuid, user_id: &str) -> Result<Vec<Claim>> {
+    let claims = sqlx::query_as::<_, Claim>(
+        r#"
+        SELECT c.* FROM warranty_claims c
+        JOIN warranties w ON w.id = c.warranty_id
+        WHERE c.warranty_id = $1
+          AND w.household_id IN (SELECT household_id FROM household_members WHERE user_id = $2)
+          AND w.deleted_at IS NULL
+        ORDER BY c.created_at DESC
+        "#,
+    )
+    .bind(warranty_id)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(claims)
+}
+
+pub async fn count_claims(pool: &PgPool, warranty_id: Uuid) -> Result<i64> {
+    let count: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM warranty_claims WHERE warranty_id = $1")
+            .bind(warranty_id)
+            .fetch_one(pool)
+            .await?;
+
+    Ok(count)
+}
+
+pub async fn get_claim(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    claim_id: Uuid,
+    user_id: &str,
+) -> Result<Claim> {
+    sqlx::query_as::<_, Claim>(
+        r#"
+        SELECT c.* FROM warranty_claims c
+        JOIN warranties w ON w.id = c.warranty_id
+        WHERE c.id = $1
+          AND c.warranty_id = $2
+          AND w.household_id IN (SELECT household_id FROM household_members WHERE user_id = $3)
+          AND w.deleted_at IS NULL
+        "#,
+    )
+    .bind(claim_id)
+    .bind(warranty_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Claim not found".to_string()))
+}
+
+/// History of a claim the caller has checked belongs to the user, oldest first.
+pub async fn list_claim_events(pool: &PgPool, claim_id: Uuid) -> Result<Vec<ClaimEvent>> {
+    let events = sqlx::query_as::<_, ClaimEvent>(
+        "SELECT * FROM claim_events WHERE claim_id = $1 ORDER BY occurred_at ASC, created_at ASC",
+    )
+    .bind(claim_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(events)
+}
+
+/// Opens a claim on a warranty the caller has checked belongs to the user,
+/// with the first entry of its history.
+pub async fn create_claim(pool: &PgPool, warranty_id: Uuid, new: NewClaim<'_>) -> Result<Claim> {
+    let mut tx = pool.begin().await?;
+
+    let claim = sqlx::query_as::<_, Claim>(
+        r#"
+        INSERT INTO warranty_claims (warranty_id, description, rma_number, contacted_at)
+        VALUES ($1, $2, $3, $4)
+        RETURNING *
+        "#,
+    )
+    .bind(warranty_id)
+    .bind(new.description)
+    .bind(new.rma_number)
+    .bind(new.contacted_at)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    insert_event(
+        &mut tx,
+        claim.id,
+        ClaimTransition {
+            status: ClaimStatus::Opened,
+            note: new.note,
+            occurred_at: new.contacted_at,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(claim)
+}
+
+pub async fn update_claim(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    claim_id: Uuid,
+    user_id: &str,
+    req: UpdateClaimRequest,
+) -> Result<Claim> {
+    let existing = get_claim(pool, warranty_id, claim_id, user_id).await?;
+
+    let description = req.description.unwrap_or(existing.description);
+    let rma_number = req.rma_number.or(existing.rma_number);
+    let contacted_at = req.contacted_at.or(existing.contacted_at);
+    let dropped_off_at = req.dropped_off_at.or(existing.dropped_off_at);
+    let returned_at = req.returned_at.or(existing.returned_at);
+
+    let claim = sqlx::query_as::<_, Claim>(
+        r#"
+        UPDATE warranty_claims
+        SET description = $1, rma_number = $2, contacted_at = $3, dropped_off_at = $4,
+            returned_at = $5, updated_at = NOW()
+        WHERE id = $6
+        RETURNING *
+        "#,
+    )
+    .bind(&description)
+    .bind(&rma_number)
+    .bind(contacted_at)
+    .bind(dropped_off_at)
+    .bind(returned_at)
+    .bind(claim_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(claim)
+}
+
+/// Moves a claim forward and records the move in its history. A claim
+/// resolved by a repair asked for while the warranty still ran extends the
+/// warranty by `extension`, counted in calendar months in `timezone`.
+/// Returns the claim, and the warranty when its dates changed.
+pub async fn transition_claim(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    claim_id: Uuid,
+    transition: ClaimTransition<'_>,
+    extension: Option<&RepairExtension>,
+    timezone: Tz,
+    actor: &Actor,
+) -> Result<(Claim, Option<Warranty>)> {
+    let mut tx = pool.begin().await?;
+
+    // Lock the warranty first so two claims resolved at once both count.
+    let warranty = lock_warranty(&mut tx, warranty_id, &actor.user_id).await?;
+    let mut claim = sqlx::query_as::<_, Claim>(
+        "SELECT * FROM warranty_claims WHERE id = $1 AND warranty_id = $2 FOR UPDATE",
+    )
+    .bind(claim_id)
+    .bind(warranty_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Claim not found".to_string()))?;
+
+    if claim.status.is_closed() {
+        return Err(AppError::BadRequest(format!(
+            "Claim is already {}",
+            claim.status.as_str()
+        )));
+    }
+    if !claim.status.can_move_to(transition.status) {
+        return Err(AppError::BadRequest(format!(
+            "Claim is {} and cannot move to {}",
+            claim.status.as_str(),
+            transition.status.as_str()
+        )));
+    }
+    claim.stamp(transition.status, transition.occurred_at);
+
+    let mut extended = None;
+    if let (ClaimStatus::Resolved, Some(extension)) = (transition.status, extension) {
+        let repairs_counted: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM warranty_claims WHERE warranty_id = $1 AND extension_months > 0",
+        )
+        .bind(warranty_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if extension.extends(
+            claim.requested_at(),
+            warranty.warranty_end_date,
+            repairs_counted as usize,
+        ) {
+            claim.extension_months = extension.months;
+            let months = warranty.repair_extension_months + extension.months;
+            extended =
+                Some(set_repair_extension(&mut tx, &warranty, months, timezone, actor).await?);
+        }
+    }
+
+    let claim = sqlx::query_as::<_, Claim>(
+        r#"
+        UPDATE warranty_claims
+        SET status = $1, dropped_off_at = $2, returned_at = $3, extension_months = $4,
+            updated_at = NOW()
+        WHERE id = $5
+        RETURNING *
+        "#,
+    )
+    .bind(transition.status)
+    .bind(claim.dropped_off_at)
+    .bind(claim.returned_at)
+    .bind(claim.extension_months)
+    .bind(claim_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    insert_event(&mut tx, claim_id, transition).await?;
+    tx.commit().await?;
+
+    Ok((claim, extended))
+}
+
+/// Deletes a claim and takes back the months its repair added to the
+/// warranty. Returns the warranty when its dates changed.
+pub async fn delete_claim(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    claim_id: Uuid,
+    timezone: Tz,
+    actor: &Actor,
+) -> Result<Option<Warranty>> {
+    let mut tx = pool.begin().await?;
+
+    let warranty = lock_warranty(&mut tx, warranty_id, &actor.user_id).await?;
+    let claim = sqlx::query_as::<_, Claim>(
+        "DELETE FROM warranty_claims WHERE id = $1 AND warranty_id = $2 RETURNING *",
+    )
+    .bind(claim_id)
+    .bind(warranty_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Claim not found".to_string()))?;
+
+    let mut reverted = None;
+    if claim.extension_months > 0 {
+        let months = (warranty.repair_extension_months - claim.extension_months).max(0);
+        reverted = Some(set_repair_extension(&mut tx, &warranty, months, timezone, actor).await?);
+    }
+    tx.commit().await?;
+
+    Ok(reverted)
+}
+
+/// Sets the months repairs added to a warranty and moves its end date and
+/// `covered_until` to match.
+async fn set_repair_extension(
+    conn: &mut PgConnection,
+    warranty: &Warranty,
+    repair_extension_months: i32,
+    timezone: Tz,
+    actor: &Actor,
+) -> Result<Warranty> {
+    let warranty_end_date = end_date(
+        warranty.purchase_date,
+        warranty.warranty_months + repair_extension_months,
+        timezone,
+    )?;
+
+    let updated = sqlx::query_as::<_, Warranty>(
+        r#"
+        UPDATE warranties
+        SET repair_extension_months = $1, warranty_end_date = $2,
+            covered_until = GREATEST($2, (SELECT MAX(ends_at) FROM warranty_coverages WHERE warranty_id = $3)),
+            updated_at = NOW()
+        WHERE id = $3
+        RETURNING *
+        "#,
+    )
+    .bind(repair_extension_months)
+    .bind(warranty_end_date)
+    .bind(warranty.id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let changes = audit::diff(Some(warranty), &updated);
+    record_warranty_event(conn, warranty.id, WarrantyAction::Updated, changes, actor).await?;
+
+    Ok(updated)
+}
+
+async fn insert_event(
+    conn: &mut PgConnection,
+    claim_id: Uuid,
+    transition: ClaimTransition<'_>,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO claim_events (claim_id, status, note, occurred_at) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(claim_id)
+    .bind(transition.status)
+    .bind(transition.note)
+    .bind(transition.occurred_at)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
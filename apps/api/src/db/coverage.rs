@@ -0,0 +1,211 @@
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use super::warranty::lock_warranty;
+use super::warranty_event::record_warranty_event;
+use crate::audit::{self, Actor};
+use crate::error::{AppError, Result};
+use crate::models::{Coverage, CoverageKind, Warranty, WarrantyAction};
+
+pub struct CoverageFields<'a> {
+    pub kind: CoverageKind,
+    pub provider: Option<&'a str>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub contract_number: Option<&'a str>,
+    pub cost_cents: Option<i64>,
+}
+
+pub async fn list_coverages(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    user_id: &str,
+) -> Result<Vec<Coverage>> {
+    let coverages = sqlx::query_as::<_, Coverage>(
+        r#"
+        SELECT c.* FROM warranty_coverages c
+        JOIN warranties w ON w.id = c.warranty_id
+        WHERE c.warranty_id = $1
+          AND w.household_id IN (SELECT household_id FROM household_members WHERE user_id = $2)
+          AND w.deleted_at IS NULL
+        ORDER BY c.ends_at ASC
+        "#,
+    )
+    .bind(warranty_id)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(coverages)
+}
+
+pub async fn count_coverages(pool: &PgPool, warranty_id: Uuid) -> Result<i64> {
+    let count: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM warranty_coverages WHERE warranty_id = $1")
+            .bind(warranty_id)
+            .fetch_one(pool)
+            .await?;
+
+    Ok(count)
+}
+
+pub async fn get_coverage(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    coverage_id: Uuid,
+    user_id: &str,
+) -> Result<Coverage> {
+    sqlx::query_as::<_, Coverage>(
+        r#"
+        SELECT c.* FROM warranty_coverages c
+        JOIN warranties w ON w.id = c.warranty_id
+        WHERE c.id = $1
+          AND c.warranty_id = $2
+          AND w.household_id IN (SELECT household_id FROM household_members WHERE user_id = $3)
+          AND w.deleted_at IS NULL
+        "#,
+    )
+    .bind(coverage_id)
+    .bind(warranty_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Coverage not found".to_string()))
+}
+
+/// Adds a coverage to a warranty the caller has checked belongs to the user.
+pub async fn create_coverage(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    fields: CoverageFields<'_>,
+    actor: &Actor,
+) -> Result<Coverage> {
+    let mut tx = pool.begin().await?;
+
+    let coverage = sqlx::query_as::<_, Coverage>(
+        r#"
+        INSERT INTO warranty_coverages (warranty_id, kind, provider, starts_at, ends_at, contract_number, cost_cents)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING *
+        "#,
+    )
+    .bind(warranty_id)
+    .bind(fields.kind)
+    .bind(fields.provider)
+    .bind(fields.starts_at)
+    .bind(fields.ends_at)
+    .bind(fields.contract_number)
+    .bind(fields.cost_cents)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    refresh_covered_until(&mut tx, warranty_id, actor).await?;
+    tx.commit().await?;
+
+    Ok(coverage)
+}
+
+pub async fn update_coverage(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    coverage_id: Uuid,
+    fields: CoverageFields<'_>,
+    actor: &Actor,
+) -> Result<Coverage> {
+    let mut tx = pool.begin().await?;
+
+    let coverage = sqlx::query_as::<_, Coverage>(
+        r#"
+        UPDATE warranty_coverages c
+        SET kind = $1, provider = $2, starts_at = $3, ends_at = $4, contract_number = $5,
+            cost_cents = $6, updated_at = NOW()
+        FROM warranties w
+        WHERE c.id = $7
+          AND c.warranty_id = $8
+          AND w.id = c.warranty_id
+          AND w.deleted_at IS NULL
+        RETURNING c.*
+        "#,
+    )
+    .bind(fields.kind)
+    .bind(fields.provider)
+    .bind(fields.starts_at)
+    .bind(fields.ends_at)
+    .bind(fields.contract_number)
+    .bind(fields.cost_cents)
+    .bind(coverage_id)
+    .bind(warranty_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Coverage not found".to_string()))?;
+
+    refresh_covered_until(&mut tx, warranty_id, actor).await?;
+    tx.commit().await?;
+
+    Ok(coverage)
+}
+
+pub async fn delete_coverage(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    coverage_id: Uuid,
+    actor: &Actor,
+) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"
+        DELETE FROM warranty_coverages c
+        USING warranties w
+        WHERE c.id = $1
+          AND c.warranty_id = $2
+          AND w.id = c.warranty_id
+          AND w.household_id IN (SELECT household_id FROM household_members WHERE user_id = $3)
+          AND w.deleted_at IS NULL
+        "#,
+    )
+    .bind(coverage_id)
+    .bind(warranty_id)
+    .bind(&actor.user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Coverage not found".to_string()));
+    }
+
+    refresh_covered_until(&mut tx, warranty_id, actor).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Recomputes `covered_until` of a warranty from its own end date and its
+/// coverages, and records the change in its history. Run after either
+/// changes, in the same transaction.
+pub async fn refresh_covered_until(
+    conn: &mut PgConnection,
+    warranty_id: Uuid,
+    actor: &Actor,
+) -> Result<()> {
+    let warranty = lock_warranty(&mut *conn, warranty_id, &actor.user_id).await?;
+
+    let updated = sqlx::query_as::<_, Warranty>(
+        r#"
+        UPDATE warranties
+        SET covered_until = GREATEST(
+            warranty_end_date,
+            (SELECT MAX(ends_at) FROM warranty_coverages WHERE warranty_id = $1)
+        )
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(warranty_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let changes = audit::diff(Some(&warranty), &updated);
+    record_warranty_event(conn, warranty_id, WarrantyAction::Updated, changes, actor).await
+}
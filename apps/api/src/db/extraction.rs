@@ -0,0 +1,156 @@
+use chrono::Duration;
+use sqlx::types::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::models::{ReceiptExtraction, WarrantySuggestion};
+
+/// A job still `processing` after this long is assumed to belong to a worker
+/// that died, and is handed out again.
+const STALE_JOB_MINUTES: i64 = 10;
+
+pub struct NewExtraction<'a> {
+    pub id: Uuid,
+    pub user_id: &'a str,
+    pub warranty_id: Option<Uuid>,
+    pub storage_key: &'a str,
+    pub content_type: &'a str,
+}
+
+pub async fn create_extraction(
+    pool: &PgPool,
+    extraction: NewExtraction<'_>,
+) -> Result<ReceiptExtraction> {
+    let extraction = sqlx::query_as::<_, ReceiptExtraction>(
+        r#"
+        INSERT INTO receipt_extractions (id, user_id, warranty_id, storage_key, content_type)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(extraction.id)
+    .bind(extraction.user_id)
+    .bind(extraction.warranty_id)
+    .bind(extraction.storage_key)
+    .bind(extraction.content_type)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(extraction)
+}
+
+pub async fn get_extraction(pool: &PgPool, id: Uuid, user_id: &str) -> Result<ReceiptExtraction> {
+    sqlx::query_as::<_, ReceiptExtraction>(
+        "SELECT * FROM receipt_extractions WHERE id = $1 AND user_id = $2",
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Extraction not found".to_string()))
+}
+
+pub async fn get_latest_receipt_extraction(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    user_id: &str,
+) -> Result<ReceiptExtraction> {
+    sqlx::query_as::<_, ReceiptExtraction>(
+        r#"
+        SELECT e.* FROM receipt_extractions e
+        JOIN warranties w ON w.id = e.warranty_id
+        WHERE e.warranty_id = $1
+          AND w.household_id IN (SELECT household_id FROM household_members WHERE user_id = $2)
+        ORDER BY e.created_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(warranty_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Extraction not found".to_string()))
+}
+
+/// Hands the oldest runnable job to the calling worker. `SKIP LOCKED` lets
+/// several API replicas poll the same queue without handing out a job twice.
+pub async fn claim_extraction(pool: &PgPool) -> Result<Option<ReceiptExtraction>> {
+    let extraction = sqlx::query_as::<_, ReceiptExtraction>(
+        r#"
+        UPDATE receipt_extractions
+        SET status = 'processing', attempts = attempts + 1, started_at = NOW()
+        WHERE id = (
+            SELECT id FROM receipt_extractions
+            WHERE (status = 'pending' AND run_after <= NOW())
+               OR (status = 'processing' AND started_at < NOW() - make_interval(mins => $1))
+            ORDER BY run_after ASC
+            FOR UPDATE SKIP LOCKED
+            LIMIT 1
+        )
+        RETURNING *
+        "#,
+    )
+    .bind(STALE_JOB_MINUTES as i32)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(extraction)
+}
+
+pub async fn complete_extraction(
+    pool: &PgPool,
+    id: Uuid,
+    raw_text: &str,
+    suggestions: &[WarrantySuggestion],
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE receipt_extractions
+        SET status = 'completed', raw_text = $1, suggestions = $2, error = NULL,
+            completed_at = NOW()
+        WHERE id = $3
+        "#,
+    )
+    .bind(raw_text)
+    .bind(Json(suggestions))
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Puts a job back in the queue after a transient failure.
+pub async fn retry_extraction(pool: &PgPool, id: Uuid, error: &str, delay: Duration) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE receipt_extractions
+        SET status = 'pending', error = $1, run_after = NOW() + make_interval(secs => $2)
+        WHERE id = $3
+        "#,
+    )
+    .bind(error)
+    .bind(delay.num_seconds() as f64)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn fail_extraction(pool: &PgPool, id: Uuid, error: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE receipt_extractions
+        SET status = 'failed', error = $1, completed_at = NOW()
+        WHERE id = $2
+        "#,
+    )
+    .bind(error)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
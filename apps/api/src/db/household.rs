@@ -0,0 +1,462 @@
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use super::notification::{enqueue_deliveries, NewDelivery};
+use crate::error::{AppError, Result};
+use crate::models::{
+    Household, HouseholdInvitation, HouseholdMember, HouseholdRole, NotificationChannel,
+};
+
+/// Name of the household every user gets on their first warranty.
+pub const DEFAULT_HOUSEHOLD_NAME: &str = "Mon foyer";
+
+pub struct NewInvitation<'a> {
+    pub household_id: Uuid,
+    pub email: &'a str,
+    pub role: HouseholdRole,
+    pub invited_by: &'a str,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// The household new warranties of a user go to when they name none: the
+/// oldest one they own, else the oldest one they can edit. Users in none get
+/// a household of their own.
+pub async fn default_household(pool: &PgPool, user_id: &str) -> Result<Uuid> {
+    let mut tx = pool.begin().await?;
+    // Two first requests at once would create two households.
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1, 0))")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let existing: Option<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT household_id FROM household_members
+        WHERE user_id = $1 AND role >= 'editor'
+        ORDER BY role DESC, joined_at ASC, household_id ASC
+        LIMIT 1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let household_id = match existing {
+        Some(household_id) => household_id,
+        None => insert_household(&mut tx, user_id, DEFAULT_HOUSEHOLD_NAME).await?,
+    };
+    tx.commit().await?;
+
+    Ok(household_id)
+}
+
+/// The role of a user in the household of a warranty, trashed or not.
+/// `None` when the user is not a member.
+pub async fn get_warranty_role(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    user_id: &str,
+) -> Result<Option<HouseholdRole>> {
+    let role = sqlx::query_scalar(
+        r#"
+        SELECT m.role FROM warranties w
+        JOIN household_members m ON m.household_id = w.household_id
+        WHERE w.id = $1 AND m.user_id = $2
+        "#,
+    )
+    .bind(warranty_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(role)
+}
+
+/// The role of a user in a household; `None` when not a member.
+pub async fn get_household_role(
+    pool: &PgPool,
+    household_id: Uuid,
+    user_id: &str,
+) -> Result<Option<HouseholdRole>> {
+    let role = sqlx::query_scalar(
+        "SELECT role FROM household_members WHERE household_id = $1 AND user_id = $2",
+    )
+    .bind(household_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(role)
+}
+
+/// The households of a user, in the order they joined them.
+pub async fn list_households(pool: &PgPool, user_id: &str) -> Result<Vec<Household>> {
+    let households = sqlx::query_as::<_, Household>(
+        r#"
+        SELECT h.id, h.name, m.role, h.created_at, h.updated_at
+        FROM households h
+        JOIN household_members m ON m.household_id = h.id
+        WHERE m.user_id = $1
+        ORDER BY m.joined_at ASC, h.id ASC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(households)
+}
+
+pub async fn get_household(pool: &PgPool, household_id: Uuid, user_id: &str) -> Result<Household> {
+    sqlx::query_as::<_, Household>(
+        r#"
+        SELECT h.id, h.name, m.role, h.created_at, h.updated_at
+        FROM households h
+        JOIN household_members m ON m.household_id = h.id
+        WHERE h.id = $1 AND m.user_id = $2
+        "#,
+    )
+    .bind(household_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Household not found".to_string()))
+}
+
+/// Members of a household the caller has checked the user belongs to,
+/// owners first.
+pub async fn list_household_members(
+    pool: &PgPool,
+    household_id: Uuid,
+) -> Result<Vec<HouseholdMember>> {
+    let members = sqlx::query_as::<_, HouseholdMember>(
+        r#"
+        SELECT user_id, role, joined_at FROM household_members
+        WHERE household_id = $1
+        ORDER BY role DESC, joined_at ASC
+        "#,
+    )
+    .bind(household_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(members)
+}
+
+/// Ids of the current members of a household, whatever their role.
+pub async fn household_member_ids(
+    conn: &mut PgConnection,
+    household_id: Uuid,
+) -> Result<Vec<String>> {
+    let ids = sqlx::query_scalar(
+        "SELECT user_id FROM household_members WHERE household_id = $1 ORDER BY joined_at",
+    )
+    .bind(household_id)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(ids)
+}
+
+/// Creates a household with the user as its owner.
+pub async fn create_household(pool: &PgPool, user_id: &str, name: &str) -> Result<Household> {
+    let mut tx = pool.begin().await?;
+    let household_id = insert_household(&mut tx, user_id, name).await?;
+    tx.commit().await?;
+
+    get_household(pool, household_id, user_id).await
+}
+
+async fn insert_household(conn: &mut PgConnection, user_id: &str, name: &str) -> Result<Uuid> {
+    let household_id: Uuid =
+        sqlx::query_scalar("INSERT INTO households (name) VALUES ($1) RETURNING id")
+            .bind(name)
+            .fetch_one(&mut *conn)
+            .await?;
+
+    sqlx::query(
+        "INSERT INTO household_members (household_id, user_id, role) VALUES ($1, $2, 'owner')",
+    )
+    .bind(household_id)
+    .bind(user_id)
+    .execute(conn)
+    .await?;
+
+    Ok(household_id)
+}
+
+/// Renames a household the caller has checked the user owns.
+pub async fn rename_household(
+    pool: &PgPool,
+    household_id: Uuid,
+    user_id: &str,
+    name: &str,
+) -> Result<Household> {
+    sqlx::query("UPDATE households SET name = $1, updated_at = NOW() WHERE id = $2")
+        .bind(name)
+        .bind(household_id)
+        .execute(pool)
+        .await?;
+
+    get_household(pool, household_id, user_id).await
+}
+
+/// Deletes a household the caller has checked the user owns, with its
+/// members and invitations. Warranties, even trashed ones, must be moved or
+/// purged first.
+pub async fn delete_household(pool: &PgPool, household_id: Uuid) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    lock_household(&mut tx, household_id).await?;
+
+    let has_warranties: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM warranties WHERE household_id = $1)")
+            .bind(household_id)
+            .fetch_one(&mut *tx)
+            .await?;
+    if has_warranties {
+        return Err(AppError::Conflict(
+            "Household still has warranties".to_string(),
+        ));
+    }
+
+    sqlx::query("DELETE FROM households WHERE id = $1")
+        .bind(household_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Changes the role of a member. A household keeps at least one owner.
+pub async fn update_member_role(
+    pool: &PgPool,
+    household_id: Uuid,
+    member_id: &str,
+    role: HouseholdRole,
+) -> Result<HouseholdMember> {
+    let mut tx = pool.begin().await?;
+    lock_household(&mut tx, household_id).await?;
+
+    let current = member_role(&mut tx, household_id, member_id).await?;
+    if current == HouseholdRole::Owner && role != HouseholdRole::Owner {
+        ensure_other_owner(&mut tx, household_id).await?;
+    }
+
+    let member = sqlx::query_as::<_, HouseholdMember>(
+        r#"
+        UPDATE household_members SET role = $1
+        WHERE household_id = $2 AND user_id = $3
+        RETURNING user_id, role, joined_at
+        "#,
+    )
+    .bind(role)
+    .bind(household_id)
+    .bind(member_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(member)
+}
+
+/// Takes a member out of a household. A household keeps at least one owner.
+pub async fn remove_member(pool: &PgPool, household_id: Uuid, member_id: &str) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    lock_household(&mut tx, household_id).await?;
+
+    if member_role(&mut tx, household_id, member_id).await? == HouseholdRole::Owner {
+        ensure_other_owner(&mut tx, household_id).await?;
+    }
+
+    sqlx::query("DELETE FROM household_members WHERE household_id = $1 AND user_id = $2")
+        .bind(household_id)
+        .bind(member_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Locks a household until the end of the transaction, so two changes to
+/// its owners cannot both see another owner left.
+async fn lock_household(conn: &mut PgConnection, household_id: Uuid) -> Result<()> {
+    sqlx::query_scalar::<_, Uuid>("SELECT id FROM households WHERE id = $1 FOR UPDATE")
+        .bind(household_id)
+        .fetch_optional(conn)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Household not found".to_string()))?;
+
+    Ok(())
+}
+
+async fn member_role(
+    conn: &mut PgConnection,
+    household_id: Uuid,
+    member_id: &str,
+) -> Result<HouseholdRole> {
+    sqlx::query_scalar(
+        "SELECT role FROM household_members WHERE household_id = $1 AND user_id = $2",
+    )
+    .bind(household_id)
+    .bind(member_id)
+    .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Member not found".to_string()))
+}
+
+/// Fails unless the household has more than one owner.
+async fn ensure_other_owner(conn: &mut PgConnection, household_id: Uuid) -> Result<()> {
+    let owners: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM household_members WHERE household_id = $1 AND role = 'owner'",
+    )
+    .bind(household_id)
+    .fetch_one(conn)
+    .await?;
+
+    if owners < 2 {
+        return Err(AppError::Conflict("A household needs an owner".to_string()));
+    }
+    Ok(())
+}
+
+/// Records an invitation and, with `send_email`, queues the email carrying
+/// its token.
+pub async fn create_invitation(
+    pool: &PgPool,
+    new: NewInvitation<'_>,
+    send_email: bool,
+) -> Result<HouseholdInvitation> {
+    let mut tx = pool.begin().await?;
+    let invitation = sqlx::query_as::<_, HouseholdInvitation>(
+        r#"
+        INSERT INTO household_invitations (household_id, email, role, invited_by, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(new.household_id)
+    .bind(new.email)
+    .bind(new.role)
+    .bind(new.invited_by)
+    .bind(new.expires_at)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if send_email {
+        let notification_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO notifications (user_id, kind, household_invitation_id)
+            VALUES ($1, 'household_invitation', $2)
+            RETURNING id
+            "#,
+        )
+        .bind(new.invited_by)
+        .bind(invitation.id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        enqueue_deliveries(
+            &mut tx,
+            &[NewDelivery {
+                notification_id,
+                channel: NotificationChannel::Email,
+                run_after: Utc::now(),
+            }],
+        )
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(invitation)
+}
+
+/// Invitations of a household neither accepted nor expired, newest first.
+pub async fn list_pending_invitations(
+    pool: &PgPool,
+    household_id: Uuid,
+) -> Result<Vec<HouseholdInvitation>> {
+    let invitations = sqlx::query_as::<_, HouseholdInvitation>(
+        r#"
+        SELECT * FROM household_invitations
+        WHERE household_id = $1 AND accepted_at IS NULL AND expires_at > NOW()
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(household_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(invitations)
+}
+
+/// Revokes an invitation; its email is dropped if not sent yet.
+pub async fn delete_invitation(
+    pool: &PgPool,
+    household_id: Uuid,
+    invitation_id: Uuid,
+) -> Result<()> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM household_invitations
+        WHERE id = $1 AND household_id = $2 AND accepted_at IS NULL
+        "#,
+    )
+    .bind(invitation_id)
+    .bind(household_id)
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Invitation not found".to_string()));
+    }
+    Ok(())
+}
+
+/// Makes the user a member of the household an invitation is for, with its
+/// role. An invitation works once, until it expires.
+pub async fn accept_invitation(pool: &PgPool, token: Uuid, user_id: &str) -> Result<Household> {
+    let mut tx = pool.begin().await?;
+    let invitation = sqlx::query_as::<_, HouseholdInvitation>(
+        r#"
+        SELECT * FROM household_invitations
+        WHERE token = $1 AND accepted_at IS NULL AND expires_at > NOW()
+        FOR UPDATE
+        "#,
+    )
+    .bind(token)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Invitation not found or expired".to_string()))?;
+
+    let joined = sqlx::query(
+        r#"
+        INSERT INTO household_members (household_id, user_id, role)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (household_id, user_id) DO NOTHING
+        "#,
+    )
+    .bind(invitation.household_id)
+    .bind(user_id)
+    .bind(invitation.role)
+    .execute(&mut *tx)
+    .await?;
+    if joined.rows_affected() == 0 {
+        return Err(AppError::Conflict(
+            "Already a member of this household".to_string(),
+        ));
+    }
+
+    sqlx::query(
+        "UPDATE household_invitations SET accepted_by = $1, accepted_at = NOW() WHERE id = $2",
+    )
+    .bind(user_id)
+    .bind(invitation.id)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    get_household(pool, invitation.household_id, user_id).await
+}
@@ -0,0 +1,413 @@
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use sqlx::{FromRow, PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::maintenance::{self, LastDone};
+use crate::models::{MaintenanceCompletion, MaintenanceTask, Notification, NotificationChannel};
+
+pub struct MaintenanceTaskFields<'a> {
+    pub title: &'a str,
+    pub notes: Option<&'a str>,
+    pub interval_months: Option<i32>,
+    pub interval_km: Option<i32>,
+    pub starts_at: DateTime<Utc>,
+    pub start_km: Option<i32>,
+    pub lead_days: i32,
+}
+
+pub struct NewMaintenanceCompletion<'a> {
+    pub done_at: DateTime<Utc>,
+    pub odometer_km: Option<i32>,
+    pub notes: Option<&'a str>,
+    pub cost_cents: Option<i64>,
+}
+
+/// A task whose reminder is due, with the settings of one member of the
+/// warranty's household. The settings are `None` for users who never saved
+/// preferences.
+#[derive(Debug, Clone, FromRow)]
+pub struct MaintenanceCandidate {
+    pub task_id: Uuid,
+    pub warranty_id: Uuid,
+    pub user_id: String,
+    pub next_due_at: DateTime<Utc>,
+    pub channels: Option<Vec<NotificationChannel>>,
+    pub quiet_hours_start: Option<NaiveTime>,
+    pub quiet_hours_end: Option<NaiveTime>,
+    pub timezone: Option<String>,
+}
+
+/// Tasks of a warranty, the next due first.
+pub async fn list_maintenance_tasks(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    user_id: &str,
+) -> Result<Vec<MaintenanceTask>> {
+    let tasks = sqlx::query_as::<_, MaintenanceTask>(
+        r#"
+        SELECT m.* FROM maintenance_tasks m
+        JOIN warranties w ON w.id = m.warranty_id
+        WHERE m.warranty_id = $1
+          AND w.household_id IN (SELECT household_id FROM household_members WHERE user_id = $2)
+          AND w.deleted_at IS NULL
+        ORDER BY m.next_due_at ASC NULLS LAST, m.created_at ASC
+        "#,
+    )
+    .bind(warranty_id)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tasks)
+}
+
+pub async fn count_maintenance_tasks(pool: &PgPool, warranty_id: Uuid) -> Result<i64> {
+    let count: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM maintenance_tasks WHERE warranty_id = $1")
+            .bind(warranty_id)
+            .fetch_one(pool)
+            .await?;
+
+    Ok(count)
+}
+
+pub async fn get_maintenance_task(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    task_id: Uuid,
+    user_id: &str,
+) -> Result<MaintenanceTask> {
+    sqlx::query_as::<_, MaintenanceTask>(
+        r#"
+        SELECT m.* FROM maintenance_tasks m
+        JOIN warranties w ON w.id = m.warranty_id
+        WHERE m.id = $1
+          AND m.warranty_id = $2
+          AND w.household_id IN (SELECT household_id FROM household_members WHERE user_id = $3)
+          AND w.deleted_at IS NULL
+        "#,
+    )
+    .bind(task_id)
+    .bind(warranty_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Maintenance task not found".to_string()))
+}
+
+pub async fn get_maintenance_tasks_by_ids(
+    conn: &mut PgConnection,
+    ids: &[Uuid],
+) -> Result<Vec<MaintenanceTask>> {
+    let tasks =
+        sqlx::query_as::<_, MaintenanceTask>("SELECT * FROM maintenance_tasks WHERE id = ANY($1)")
+            .bind(ids)
+            .fetch_all(conn)
+            .await?;
+
+    Ok(tasks)
+}
+
+/// Adds a task to a warranty the caller has checked belongs to the user, due
+/// first one interval after it starts.
+pub async fn create_maintenance_task(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    fields: MaintenanceTaskFields<'_>,
+    timezone: Tz,
+) -> Result<MaintenanceTask> {
+    let mut tx = pool.begin().await?;
+
+    let task_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO maintenance_tasks (warranty_id, title, notes, interval_months, interval_km,
+                                       starts_at, start_km, lead_days)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id
+        "#,
+    )
+    .bind(warranty_id)
+    .bind(fields.title)
+    .bind(fields.notes)
+    .bind(fields.interval_months)
+    .bind(fields.interval_km)
+    .bind(fields.starts_at)
+    .bind(fields.start_km)
+    .bind(fields.lead_days)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let task = reschedule(&mut tx, task_id, timezone).await?;
+    tx.commit().await?;
+
+    Ok(task)
+}
+
+pub async fn update_maintenance_task(
+    pool: &PgPool,
+    task_id: Uuid,
+    fields: MaintenanceTaskFields<'_>,
+    timezone: Tz,
+) -> Result<MaintenanceTask> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        UPDATE maintenance_tasks
+        SET title = $1, notes = $2, interval_months = $3, interval_km = $4, starts_at = $5,
+            start_km = $6, lead_days = $7, updated_at = NOW()
+        WHERE id = $8
+        "#,
+    )
+    .bind(fields.title)
+    .bind(fields.notes)
+    .bind(fields.interval_months)
+    .bind(fields.interval_km)
+    .bind(fields.starts_at)
+    .bind(fields.start_km)
+    .bind(fields.lead_days)
+    .bind(task_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let task = reschedule(&mut tx, task_id, timezone).await?;
+    tx.commit().await?;
+
+    Ok(task)
+}
+
+pub async fn delete_maintenance_task(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    task_id: Uuid,
+    user_id: &str,
+) -> Result<()> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM maintenance_tasks m
+        USING warranties w
+        WHERE m.id = $1
+          AND m.warranty_id = $2
+          AND w.id = m.warranty_id
+          AND w.household_id IN (SELECT household_id FROM household_members WHERE user_id = $3)
+          AND w.deleted_at IS NULL
+        "#,
+    )
+    .bind(task_id)
+    .bind(warranty_id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Maintenance task not found".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Completion log of a task the caller has checked belongs to the user,
+/// newest first.
+pub async fn list_maintenance_completions(
+    pool: &PgPool,
+    task_id: Uuid,
+) -> Result<Vec<MaintenanceCompletion>> {
+    let completions = sqlx::query_as::<_, MaintenanceCompletion>(
+        r#"
+        SELECT * FROM maintenance_completions
+        WHERE task_id = $1
+        ORDER BY done_at DESC, created_at DESC
+        "#,
+    )
+    .bind(task_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(completions)
+}
+
+/// Logs that a task was done and moves its next due date. Returns the entry
+/// and the task as rescheduled.
+pub async fn create_maintenance_completion(
+    pool: &PgPool,
+    task_id: Uuid,
+    new: NewMaintenanceCompletion<'_>,
+    timezone: Tz,
+) -> Result<(MaintenanceCompletion, MaintenanceTask)> {
+    let mut tx = pool.begin().await?;
+
+    let completion = sqlx::query_as::<_, MaintenanceCompletion>(
+        r#"
+        INSERT INTO maintenance_completions (task_id, done_at, odometer_km, notes, cost_cents)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(task_id)
+    .bind(new.done_at)
+    .bind(new.odometer_km)
+    .bind(new.notes)
+    .bind(new.cost_cents)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let task = reschedule(&mut tx, task_id, timezone).await?;
+    tx.commit().await?;
+
+    Ok((completion, task))
+}
+
+/// Removes an entry from the log of a task the caller has checked belongs to
+/// the user, and returns the task as rescheduled.
+pub async fn delete_maintenance_completion(
+    pool: &PgPool,
+    task_id: Uuid,
+    completion_id: Uuid,
+    timezone: Tz,
+) -> Result<MaintenanceTask> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query("DELETE FROM maintenance_completions WHERE id = $1 AND task_id = $2")
+        .bind(completion_id)
+        .bind(task_id)
+        .execute(&mut *tx)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Completion not found".to_string()));
+    }
+
+    let task = reschedule(&mut tx, task_id, timezone).await?;
+    tx.commit().await?;
+
+    Ok(task)
+}
+
+/// Recomputes when a task is next due, from its latest completion or else
+/// its start. Run after either changes.
+async fn reschedule(
+    conn: &mut PgConnection,
+    task_id: Uuid,
+    timezone: Tz,
+) -> Result<MaintenanceTask> {
+    let task = sqlx::query_as::<_, MaintenanceTask>(
+        "SELECT * FROM maintenance_tasks WHERE id = $1 FOR UPDATE",
+    )
+    .bind(task_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Maintenance task not found".to_string()))?;
+
+    let latest: Option<(DateTime<Utc>, Option<i32>)> = sqlx::query_as(
+        r#"
+        SELECT done_at, odometer_km FROM maintenance_completions
+        WHERE task_id = $1
+        ORDER BY done_at DESC, created_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(task_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let last_done = match latest {
+        Some((at, km)) => LastDone { at, km },
+        None => LastDone {
+            at: task.starts_at,
+            km: task.start_km,
+        },
+    };
+    let next_due =
+        maintenance::next_due(task.interval_months, task.interval_km, last_done, timezone);
+
+    let task = sqlx::query_as::<_, MaintenanceTask>(
+        r#"
+        UPDATE maintenance_tasks
+        SET next_due_at = $1, next_due_km = $2, last_done_at = $3
+        WHERE id = $4
+        RETURNING *
+        "#,
+    )
+    .bind(next_due.at)
+    .bind(next_due.km)
+    .bind(latest.map(|(at, _)| at))
+    .bind(task_id)
+    .fetch_one(conn)
+    .await?;
+
+    Ok(task)
+}
+
+/// Tasks whose next due date is within their lead time of `now`, or past,
+/// once per current member of the warranty's household it was not announced
+/// to yet.
+pub async fn get_maintenance_candidates(
+    conn: &mut PgConnection,
+    now: DateTime<Utc>,
+) -> Result<Vec<MaintenanceCandidate>> {
+    let candidates = sqlx::query_as::<_, MaintenanceCandidate>(
+        r#"
+        SELECT m.id AS task_id, m.warranty_id, hm.user_id, m.next_due_at,
+               p.channels, p.quiet_hours_start, p.quiet_hours_end, p.timezone
+        FROM maintenance_tasks m
+        JOIN warranties w ON w.id = m.warranty_id
+        JOIN household_members hm ON hm.household_id = w.household_id
+        LEFT JOIN reminder_preferences p ON p.user_id = hm.user_id
+        WHERE w.deleted_at IS NULL
+          AND m.next_due_at <= $1 + make_interval(days => m.lead_days)
+          AND NOT EXISTS (
+              SELECT 1 FROM notifications n
+              WHERE n.kind = 'maintenance_due'
+                AND n.maintenance_task_id = m.id
+                AND n.user_id = hm.user_id
+                AND n.maintenance_due_at = m.next_due_at
+          )
+        ORDER BY m.next_due_at ASC
+        "#,
+    )
+    .bind(now)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(candidates)
+}
+
+/// Records one maintenance reminder per candidate, once per due date and
+/// member.
+/// Returns only the notifications created by this call.
+pub async fn create_maintenance_notifications(
+    conn: &mut PgConnection,
+    candidates: &[MaintenanceCandidate],
+) -> Result<Vec<Notification>> {
+    if candidates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let user_ids: Vec<&str> = candidates.iter().map(|c| c.user_id.as_str()).collect();
+    let warranty_ids: Vec<Uuid> = candidates.iter().map(|c| c.warranty_id).collect();
+    let task_ids: Vec<Uuid> = candidates.iter().map(|c| c.task_id).collect();
+    let due_dates: Vec<DateTime<Utc>> = candidates.iter().map(|c| c.next_due_at).collect();
+
+    let notifications = sqlx::query_as::<_, Notification>(
+        r#"
+        INSERT INTO notifications (user_id, warranty_id, kind, maintenance_task_id, maintenance_due_at)
+        SELECT user_id, warranty_id, 'maintenance_due', task_id, due_at
+        FROM UNNEST($1::varchar[], $2::uuid[], $3::uuid[], $4::timestamptz[])
+            AS m(user_id, warranty_id, task_id, due_at)
+        ON CONFLICT (maintenance_task_id, user_id, maintenance_due_at)
+            WHERE kind = 'maintenance_due'
+            DO NOTHING
+        RETURNING *
+        "#,
+    )
+    .bind(&user_ids)
+    .bind(&warranty_ids)
+    .bind(&task_ids)
+    .bind(&due_dates)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(notifications)
+}
@@ -1,6 +1,37 @@
+#[cfg(test)]
+mod testing;
+#[cfg(test)]
+mod tests;
+
+mod attachment;
+mod calendar_feed;
+mod claim;
+mod coverage;
+mod extraction;
+mod household;
+mod maintenance;
+mod notification;
+mod push_subscription;
+mod reminder_preferences;
+mod sync;
 mod warranty;
+mod warranty_event;
+mod webhook;
 
+pub use attachment::*;
+pub use calendar_feed::*;
+pub use claim::*;
+pub use coverage::*;
+pub use extraction::*;
+pub use household::*;
+pub use maintenance::*;
+pub use notification::*;
+pub use push_subscription::*;
+pub use reminder_preferences::*;
+pub use sync::*;
 pub use warranty::*;
+pub use warranty_event::*;
+pub use webhook::*;
 
 use sqlx::postgres::PgPoolOptions;
 use sqlx::PgPool;
@@ -0,0 +1,444 @@
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use sqlx::types::Json;
+use sqlx::{FromRow, PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::error::Result;
+use crate::models::{
+    DigestFrequency, DigestItem, DigestSummary, Notification, NotificationChannel, PendingDelivery,
+    ReminderMode,
+};
+
+/// Advisory lock key held by the replica running the reminder scheduler.
+const REMINDER_LOCK_KEY: i64 = 0x6761_7272_7972_656d;
+
+/// A delivery still `sending` after this long is assumed to belong to a
+/// dispatcher that died, and is handed out again.
+const STALE_DELIVERY_MINUTES: i32 = 10;
+
+/// Warranties a digest lists by name; the rest are only counted.
+const DIGEST_ITEMS: i64 = 10;
+
+pub struct NewReminder {
+    pub user_id: String,
+    pub warranty_id: Uuid,
+    pub threshold_days: i32,
+    pub warranty_end_date: DateTime<Utc>,
+}
+
+pub struct NewDelivery {
+    pub notification_id: Uuid,
+    pub channel: NotificationChannel,
+    /// Held back until then, e.g. past the user's quiet hours
+    pub run_after: DateTime<Utc>,
+}
+
+/// A warranty inside the widest reminder window of one member of its
+/// household, with that member's settings. The settings are `None` for users
+/// who never saved preferences.
+#[derive(Debug, Clone, FromRow)]
+pub struct ReminderCandidate {
+    pub warranty_id: Uuid,
+    pub user_id: String,
+    /// When the warranty stops covering the product, extensions included
+    pub warranty_end_date: DateTime<Utc>,
+    /// The warranty's own lead days, else the user's, else the default
+    pub lead_days: Vec<i32>,
+    pub channels: Option<Vec<NotificationChannel>>,
+    pub quiet_hours_start: Option<NaiveTime>,
+    pub quiet_hours_end: Option<NaiveTime>,
+    pub timezone: Option<String>,
+    pub mode: Option<ReminderMode>,
+}
+
+/// A user whose digest period is over, with the numbers for the digest.
+#[derive(Debug, Clone, FromRow)]
+pub struct DigestCandidate {
+    pub user_id: String,
+    pub channels: Vec<NotificationChannel>,
+    pub quiet_hours_start: Option<NaiveTime>,
+    pub quiet_hours_end: Option<NaiveTime>,
+    pub timezone: String,
+    pub frequency: DigestFrequency,
+    pub since: DateTime<Utc>,
+    pub expiring_within_30_days: i64,
+    pub expiring_within_90_days: i64,
+    pub expired_since_last_digest: i64,
+    pub missing_receipts: i64,
+    pub expiring: Json<Vec<DigestItem>>,
+}
+
+impl DigestCandidate {
+    pub fn summary(&self) -> DigestSummary {
+        DigestSummary {
+            frequency: self.frequency,
+            since: self.since,
+            expiring_within_30_days: self.expiring_within_30_days,
+            expiring_within_90_days: self.expiring_within_90_days,
+            expired_since_last_digest: self.expired_since_last_digest,
+            missing_receipts: self.missing_receipts,
+            expiring: self.expiring.0.clone(),
+        }
+    }
+}
+
+/// Takes the scheduler lock for the rest of the transaction. Returns `false`
+/// when another replica already holds it.
+pub async fn try_lock_reminders(conn: &mut PgConnection) -> Result<bool> {
+    let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+        .bind(REMINDER_LOCK_KEY)
+        .fetch_one(conn)
+        .await?;
+
+    Ok(locked)
+}
+
+/// Warranties whose coverage, extensions included, ends after `now` and
+/// within the widest lead time of a member of their household, once per such
+/// member, with the reminder settings that apply to them. Every member gets
+/// reminders, viewers included, since all of them read the warranty.
+pub async fn get_reminder_candidates(
+    conn: &mut PgConnection,
+    now: DateTime<Utc>,
+    default_lead_days: &[i32],
+) -> Result<Vec<ReminderCandidate>> {
+    let candidates = sqlx::query_as::<_, ReminderCandidate>(
+        r#"
+        SELECT w.id AS warranty_id, m.user_id, w.covered_until AS warranty_end_date, l.lead_days,
+               p.channels, p.quiet_hours_start, p.quiet_hours_end, p.timezone, p.mode
+        FROM warranties w
+        JOIN household_members m ON m.household_id = w.household_id
+        LEFT JOIN reminder_preferences p ON p.user_id = m.user_id
+        CROSS JOIN LATERAL (
+            SELECT COALESCE(w.reminder_lead_days, p.lead_days, $2::int[]) AS lead_days
+        ) l
+        WHERE w.deleted_at IS NULL
+          AND w.covered_until > $1
+          AND w.covered_until <= $1 + make_interval(
+                days => COALESCE((SELECT MAX(d) FROM UNNEST(l.lead_days) AS d), 0))
+        ORDER BY w.covered_until ASC
+        "#,
+    )
+    .bind(now)
+    .bind(default_lead_days)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(candidates)
+}
+
+/// Records expiry reminders, skipping thresholds that already fired for a
+/// warranty, member and end date: once the coverage moves, they fire again.
+/// Returns only the notifications created by this call.
+pub async fn create_expiry_notifications(
+    conn: &mut PgConnection,
+    reminders: &[NewReminder],
+) -> Result<Vec<Notification>> {
+    if reminders.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let user_ids: Vec<&str> = reminders.iter().map(|r| r.user_id.as_str()).collect();
+    let warranty_ids: Vec<Uuid> = reminders.iter().map(|r| r.warranty_id).collect();
+    let thresholds: Vec<i32> = reminders.iter().map(|r| r.threshold_days).collect();
+    let end_dates: Vec<DateTime<Utc>> = reminders.iter().map(|r| r.warranty_end_date).collect();
+
+    let notifications = sqlx::query_as::<_, Notification>(
+        r#"
+        INSERT INTO notifications (user_id, warranty_id, kind, threshold_days, warranty_end_date)
+        SELECT user_id, warranty_id, 'expiry_reminder', threshold_days, warranty_end_date
+        FROM UNNEST($1::varchar[], $2::uuid[], $3::int[], $4::timestamptz[])
+            AS r(user_id, warranty_id, threshold_days, warranty_end_date)
+        ON CONFLICT (warranty_id, user_id, threshold_days, warranty_end_date)
+            WHERE kind = 'expiry_reminder' DO NOTHING
+        RETURNING *
+        "#,
+    )
+    .bind(&user_ids)
+    .bind(&warranty_ids)
+    .bind(&thresholds)
+    .bind(&end_dates)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(notifications)
+}
+
+/// Records the expiry of warranties whose coverage, extensions included,
+/// ended in the `lookback_days` before `now`, once per end date for each
+/// current member of their household. Returns only the notifications created
+/// by this call.
+pub async fn create_expired_notifications(
+    conn: &mut PgConnection,
+    now: DateTime<Utc>,
+    lookback_days: i32,
+) -> Result<Vec<Notification>> {
+    let notifications = sqlx::query_as::<_, Notification>(
+        r#"
+        INSERT INTO notifications (user_id, warranty_id, kind, warranty_end_date)
+        SELECT m.user_id, w.id, 'expired', w.covered_until
+        FROM warranties w
+        JOIN household_members m ON m.household_id = w.household_id
+        WHERE w.deleted_at IS NULL
+          AND w.covered_until <= $1
+          AND w.covered_until > $1 - make_interval(days => $2)
+        ON CONFLICT (warranty_id, user_id, warranty_end_date) WHERE kind = 'expired' DO NOTHING
+        RETURNING *
+        "#,
+    )
+    .bind(now)
+    .bind(lookback_days)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(notifications)
+}
+
+/// Users whose digest is due at `now`, a week or a month after the last one,
+/// covering the warranties of every household they belong to.
+/// The windows match `get_warranty_stats`: a warranty expiring within 30
+/// days ends after `now` and at most 30 days after it.
+pub async fn get_digest_candidates(
+    conn: &mut PgConnection,
+    now: DateTime<Utc>,
+) -> Result<Vec<DigestCandidate>> {
+    let candidates = sqlx::query_as::<_, DigestCandidate>(
+        r#"
+        SELECT p.user_id, p.channels, p.quiet_hours_start, p.quiet_hours_end, p.timezone,
+               p.digest_frequency AS frequency, p.last_digest_at AS since,
+               COUNT(w.id) FILTER (
+                   WHERE w.covered_until > $1
+                     AND w.covered_until <= $1 + INTERVAL '30 days'
+               ) AS expiring_within_30_days,
+               COUNT(w.id) FILTER (
+                   WHERE w.covered_until > $1
+                     AND w.covered_until <= $1 + INTERVAL '90 days'
+               ) AS expiring_within_90_days,
+               COUNT(w.id) FILTER (
+                   WHERE w.covered_until > p.last_digest_at AND w.covered_until <= $1
+               ) AS expired_since_last_digest,
+               COUNT(w.id) FILTER (
+                   WHERE w.covered_until > $1 AND w.receipt_key IS NULL
+               ) AS missing_receipts,
+               COALESCE((
+                   SELECT jsonb_agg(jsonb_build_object(
+                       'warranty_id', e.id, 'product_name', e.product_name,
+                       'brand', e.brand, 'warranty_end_date', e.covered_until
+                   ) ORDER BY e.covered_until)
+                   FROM (
+                       SELECT id, product_name, brand, covered_until FROM warranties
+                       WHERE household_id IN (
+                               SELECT household_id FROM household_members WHERE user_id = p.user_id
+                           )
+                         AND deleted_at IS NULL
+                         AND covered_until > $1
+                         AND covered_until <= $1 + INTERVAL '30 days'
+                       ORDER BY covered_until ASC
+                       LIMIT $2
+                   ) e
+               ), '[]'::jsonb) AS expiring
+        FROM reminder_preferences p
+        LEFT JOIN warranties w
+            ON w.household_id IN (SELECT household_id FROM household_members WHERE user_id = p.user_id)
+           AND w.deleted_at IS NULL
+        WHERE p.digest_frequency IS NOT NULL
+          AND p.last_digest_at <= $1 - CASE p.digest_frequency
+                WHEN 'weekly' THEN INTERVAL '7 days'
+                ELSE INTERVAL '1 month'
+              END
+        GROUP BY p.user_id
+        "#,
+    )
+    .bind(now)
+    .bind(DIGEST_ITEMS)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(candidates)
+}
+
+/// Records one digest notification per entry of `digests`.
+pub async fn create_digest_notifications(
+    conn: &mut PgConnection,
+    digests: &[(String, DigestSummary)],
+) -> Result<Vec<Notification>> {
+    if digests.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let user_ids: Vec<&str> = digests
+        .iter()
+        .map(|(user_id, _)| user_id.as_str())
+        .collect();
+    let summaries: Vec<Json<&DigestSummary>> =
+        digests.iter().map(|(_, summary)| Json(summary)).collect();
+
+    let notifications = sqlx::query_as::<_, Notification>(
+        r#"
+        INSERT INTO notifications (user_id, kind, digest)
+        SELECT user_id, 'digest', digest
+        FROM UNNEST($1::varchar[], $2::jsonb[]) AS d(user_id, digest)
+        RETURNING *
+        "#,
+    )
+    .bind(&user_ids)
+    .bind(&summaries)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(notifications)
+}
+
+/// Starts the next digest period of `user_ids` at `now`.
+pub async fn start_digest_periods(
+    conn: &mut PgConnection,
+    user_ids: &[String],
+    now: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query("UPDATE reminder_preferences SET last_digest_at = $1 WHERE user_id = ANY($2)")
+        .bind(now)
+        .bind(user_ids)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Queues deliveries. Push deliveries are only queued for users with a
+/// subscribed browser.
+pub async fn enqueue_deliveries(conn: &mut PgConnection, deliveries: &[NewDelivery]) -> Result<()> {
+    if deliveries.is_empty() {
+        return Ok(());
+    }
+
+    let notification_ids: Vec<Uuid> = deliveries.iter().map(|d| d.notification_id).collect();
+    let channels: Vec<NotificationChannel> = deliveries.iter().map(|d| d.channel).collect();
+    let run_after: Vec<DateTime<Utc>> = deliveries.iter().map(|d| d.run_after).collect();
+
+    sqlx::query(
+        r#"
+        INSERT INTO notification_deliveries (notification_id, channel, run_after)
+        SELECT d.notification_id, d.channel, d.run_after
+        FROM UNNEST($1::uuid[], $2::notification_channel[], $3::timestamptz[])
+            AS d(notification_id, channel, run_after)
+        JOIN notifications n ON n.id = d.notification_id
+        WHERE d.channel <> 'push'
+           OR EXISTS (SELECT 1 FROM push_subscriptions s WHERE s.user_id = n.user_id)
+        ON CONFLICT (notification_id, channel) DO NOTHING
+        "#,
+    )
+    .bind(&notification_ids)
+    .bind(&channels)
+    .bind(&run_after)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Hands the oldest due delivery on one of `channels` to the calling
+/// dispatcher, with `SKIP LOCKED` so replicas never send it twice. Deliveries
+/// about trashed warranties wait until they are restored or purged.
+pub async fn claim_delivery(
+    pool: &PgPool,
+    channels: &[NotificationChannel],
+) -> Result<Option<PendingDelivery>> {
+    let delivery = sqlx::query_as::<_, PendingDelivery>(
+        r#"
+        WITH claimed AS (
+            UPDATE notification_deliveries
+            SET status = 'sending', attempts = attempts + 1, started_at = NOW()
+            WHERE id = (
+                SELECT id FROM notification_deliveries
+                WHERE channel = ANY($1)
+                  AND ((status = 'pending' AND run_after <= NOW())
+                    OR (status = 'sending' AND started_at < NOW() - make_interval(mins => $2)))
+                  -- Held while the warranty is in the trash
+                  AND NOT EXISTS (
+                      SELECT 1 FROM notifications n
+                      JOIN warranties w ON w.id = n.warranty_id
+                      WHERE n.id = notification_deliveries.notification_id
+                        AND w.deleted_at IS NOT NULL
+                  )
+                ORDER BY run_after ASC
+                FOR UPDATE SKIP LOCKED
+                LIMIT 1
+            )
+            RETURNING *
+        )
+        SELECT c.id, c.channel, c.attempts, n.user_id, n.kind, w.id AS warranty_id,
+               w.product_name, w.brand, w.covered_until AS warranty_end_date, n.digest,
+               m.id AS maintenance_task_id, m.title AS maintenance_title,
+               n.maintenance_due_at, m.next_due_km AS maintenance_due_km, p.digest_token,
+               h.name AS household_name, i.token AS invitation_token,
+               -- Invitations go to the invited address, not the inviter
+               COALESCE(i.email, u.email) AS recipient_email,
+               CASE WHEN i.id IS NULL THEN u.name END AS recipient_name,
+               CASE WHEN i.id IS NULL THEN p.timezone END AS recipient_timezone,
+               CASE WHEN i.id IS NULL THEN p.locale END AS recipient_locale
+        FROM claimed c
+        JOIN notifications n ON n.id = c.notification_id
+        LEFT JOIN warranties w ON w.id = n.warranty_id
+        LEFT JOIN maintenance_tasks m ON m.id = n.maintenance_task_id
+        LEFT JOIN household_invitations i ON i.id = n.household_invitation_id
+        LEFT JOIN households h ON h.id = i.household_id
+        LEFT JOIN reminder_preferences p ON p.user_id = n.user_id
+        LEFT JOIN users u ON u.id::text = n.user_id
+        "#,
+    )
+    .bind(channels)
+    .bind(STALE_DELIVERY_MINUTES)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(delivery)
+}
+
+pub async fn complete_delivery(pool: &PgPool, id: Uuid) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE notification_deliveries
+        SET status = 'sent', last_error = NULL, sent_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Puts a delivery back in the queue after a transient failure.
+pub async fn retry_delivery(pool: &PgPool, id: Uuid, error: &str, delay: Duration) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE notification_deliveries
+        SET status = 'pending', last_error = $1, run_after = NOW() + make_interval(secs => $2)
+        WHERE id = $3
+        "#,
+    )
+    .bind(error)
+    .bind(delay.num_seconds() as f64)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn fail_delivery(pool: &PgPool, id: Uuid, error: &str) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE notification_deliveries
+        SET status = 'failed', last_error = $1
+        WHERE id = $2
+        "#,
+    )
+    .bind(error)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
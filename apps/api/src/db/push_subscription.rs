@@ -0,0 +1,74 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::models::PushSubscription;
+
+/// Registers a browser for `user_id`. An endpoint belongs to one browser, so
+/// subscribing it again moves it to the new user and replaces its keys.
+pub async fn upsert_push_subscription(
+    pool: &PgPool,
+    user_id: &str,
+    endpoint: &str,
+    p256dh: &str,
+    auth: &str,
+) -> Result<PushSubscription> {
+    let subscription = sqlx::query_as::<_, PushSubscription>(
+        r#"
+        INSERT INTO push_subscriptions (user_id, endpoint, p256dh, auth)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (endpoint) DO UPDATE
+        SET user_id = EXCLUDED.user_id, p256dh = EXCLUDED.p256dh, auth = EXCLUDED.auth,
+            updated_at = NOW()
+        RETURNING *
+        "#,
+    )
+    .bind(user_id)
+    .bind(endpoint)
+    .bind(p256dh)
+    .bind(auth)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(subscription)
+}
+
+pub async fn delete_push_subscription(pool: &PgPool, user_id: &str, endpoint: &str) -> Result<()> {
+    let result = sqlx::query("DELETE FROM push_subscriptions WHERE endpoint = $1 AND user_id = $2")
+        .bind(endpoint)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(
+            "Push subscription not found".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn list_push_subscriptions(
+    pool: &PgPool,
+    user_id: &str,
+) -> Result<Vec<PushSubscription>> {
+    let subscriptions = sqlx::query_as::<_, PushSubscription>(
+        "SELECT * FROM push_subscriptions WHERE user_id = $1 ORDER BY created_at ASC",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(subscriptions)
+}
+
+/// Drops a subscription the push service reported as gone.
+pub async fn prune_push_subscription(pool: &PgPool, id: Uuid) -> Result<()> {
+    sqlx::query("DELETE FROM push_subscriptions WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use chrono_tz::Tz;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::models::{ReminderPreferences, DEFAULT_TIMEZONE};
+
+pub async fn get_reminder_preferences(
+    pool: &PgPool,
+    user_id: &str,
+) -> Result<Option<ReminderPreferences>> {
+    let preferences = sqlx::query_as::<_, ReminderPreferences>(
+        "SELECT * FROM reminder_preferences WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(preferences)
+}
+
+/// The timezone of the user's preferences, or the default one.
+pub async fn get_user_timezone(pool: &PgPool, user_id: &str) -> Result<Tz> {
+    let timezone: Option<String> =
+        sqlx::query_scalar("SELECT timezone FROM reminder_preferences WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
+
+    Ok(parse_timezone(timezone))
+}
+
+/// The timezone the dates of a warranty are counted in: its creator's, so
+/// an edit gives the same dates whoever in the household makes it.
+pub async fn get_warranty_timezone(pool: &PgPool, warranty_id: Uuid) -> Result<Tz> {
+    let timezone: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT p.timezone FROM warranties w
+        JOIN reminder_preferences p ON p.user_id = w.user_id
+        WHERE w.id = $1
+        "#,
+    )
+    .bind(warranty_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(parse_timezone(timezone))
+}
+
+fn parse_timezone(timezone: Option<String>) -> Tz {
+    timezone
+        .as_deref()
+        .unwrap_or(DEFAULT_TIMEZONE)
+        .parse()
+        .unwrap_or(Tz::UTC)
+}
+
+/// Saves preferences. Turning the digest on starts its first period now;
+/// changing its frequency keeps the current period.
+pub async fn save_reminder_preferences(
+    pool: &PgPool,
+    user_id: &str,
+    preferences: &ReminderPreferences,
+) -> Result<ReminderPreferences> {
+    let saved = sqlx::query_as::<_, ReminderPreferences>(
+        r#"
+        INSERT INTO reminder_preferences
+            (user_id, lead_days, channels, quiet_hours_start, quiet_hours_end, timezone, locale,
+             mode, digest_frequency, last_digest_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9,
+                CASE WHEN $9::digest_frequency IS NULL THEN NULL ELSE NOW() END)
+        ON CONFLICT (user_id) DO UPDATE
+        SET lead_days = EXCLUDED.lead_days, channels = EXCLUDED.channels,
+            quiet_hours_start = EXCLUDED.quiet_hours_start,
+            quiet_hours_end = EXCLUDED.quiet_hours_end,
+            timezone = EXCLUDED.timezone, locale = EXCLUDED.locale, mode = EXCLUDED.mode,
+            digest_frequency = EXCLUDED.digest_frequency,
+            last_digest_at = CASE
+                WHEN reminder_preferences.digest_frequency IS NULL
+                  OR EXCLUDED.digest_frequency IS NULL THEN EXCLUDED.last_digest_at
+                ELSE reminder_preferences.last_digest_at
+            END,
+            updated_at = NOW()
+        RETURNING *
+        "#,
+    )
+    .bind(user_id)
+    .bind(&preferences.lead_days)
+    .bind(&preferences.channels)
+    .bind(preferences.quiet_hours_start)
+    .bind(preferences.quiet_hours_end)
+    .bind(&preferences.timezone)
+    .bind(&preferences.locale)
+    .bind(preferences.mode)
+    .bind(preferences.digest_frequency)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(saved)
+}
+
+/// The language picked by the user `token` was issued to, if any.
+pub async fn get_digest_locale(pool: &PgPool, token: Uuid) -> Result<Option<String>> {
+    let locale: Option<Option<String>> =
+        sqlx::query_scalar("SELECT locale FROM reminder_preferences WHERE digest_token = $1")
+            .bind(token)
+            .fetch_optional(pool)
+            .await?;
+
+    Ok(locale.flatten())
+}
+
+/// Turns off the digest of the user `token` was issued to. Unsubscribing
+/// twice is not an error.
+pub async fn unsubscribe_digest(pool: &PgPool, token: Uuid) -> Result<()> {
+    let result = sqlx::query(
+        r#"
+        UPDATE reminder_preferences
+        SET digest_frequency = NULL, last_digest_at = NULL, updated_at = NOW()
+        WHERE digest_token = $1
+        "#,
+    )
+    .bind(token)
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Unsubscribe link not found".to_string()));
+    }
+
+    Ok(())
+}
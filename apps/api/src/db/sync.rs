@@ -0,0 +1,111 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::Result;
+use crate::models::{Tombstone, Warranty};
+
+/// What changed for a user, read from one snapshot.
+#[derive(Debug, Clone)]
+pub struct SyncChanges {
+    pub warranties: Vec<Warranty>,
+    pub deleted: Vec<Tombstone>,
+    /// Oldest transaction still running at the snapshot
+    pub xmin: i64,
+}
+
+/// The warranties of the households of a user changed and deleted by
+/// transaction `since` and later ones, or all of them without it. A
+/// household the user joined since comes whole; one the user left since is
+/// reported deleted whole.
+pub async fn get_sync_changes(
+    pool: &PgPool,
+    user_id: &str,
+    since: Option<i64>,
+) -> Result<SyncChanges> {
+    let mut tx = pool.begin().await?;
+    // One snapshot for the token and the rows, so no change falls between.
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .execute(&mut *tx)
+        .await?;
+
+    let xmin: i64 =
+        sqlx::query_scalar("SELECT pg_snapshot_xmin(pg_current_snapshot())::text::bigint")
+            .fetch_one(&mut *tx)
+            .await?;
+
+    let warranties = sqlx::query_as::<_, Warranty>(
+        r#"
+        SELECT w.* FROM warranties w
+        JOIN household_members m ON m.household_id = w.household_id AND m.user_id = $1
+        WHERE w.deleted_at IS NULL
+          AND ($2::bigint IS NULL
+               OR w.changed_xid >= $2::bigint::text::xid8
+               OR m.joined_xid >= $2::bigint::text::xid8)
+        ORDER BY w.covered_until ASC, w.id ASC
+        "#,
+    )
+    .bind(user_id)
+    .bind(since)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let deleted = match since {
+        None => Vec::new(),
+        Some(since) => {
+            sqlx::query_as::<_, Tombstone>(
+                r#"
+                SELECT warranty_id AS id, deleted_at FROM warranty_tombstones
+                WHERE household_id IN
+                      (SELECT household_id FROM household_members WHERE user_id = $1)
+                  AND changed_xid >= $2::bigint::text::xid8
+                UNION
+                -- Everything in a household the user has left since
+                SELECT g.id, d.left_at AS deleted_at
+                FROM household_departures d
+                CROSS JOIN LATERAL (
+                    SELECT id FROM warranties WHERE household_id = d.household_id
+                    UNION
+                    SELECT warranty_id FROM warranty_tombstones
+                    WHERE household_id = d.household_id
+                      AND changed_xid >= $2::bigint::text::xid8
+                ) g
+                WHERE d.user_id = $1
+                  AND d.changed_xid >= $2::bigint::text::xid8
+                  AND NOT EXISTS (
+                      SELECT 1 FROM household_members m
+                      WHERE m.household_id = d.household_id AND m.user_id = $1
+                  )
+                ORDER BY deleted_at ASC
+                "#,
+            )
+            .bind(user_id)
+            .bind(since)
+            .fetch_all(&mut *tx)
+            .await?
+        }
+    };
+
+    tx.commit().await?;
+
+    Ok(SyncChanges {
+        warranties,
+        deleted,
+        xmin,
+    })
+}
+
+pub async fn get_tombstone(pool: &PgPool, id: Uuid, user_id: &str) -> Result<Option<Tombstone>> {
+    let tombstone = sqlx::query_as::<_, Tombstone>(
+        r#"
+        SELECT warranty_id AS id, deleted_at FROM warranty_tombstones
+        WHERE warranty_id = $1
+          AND household_id IN (SELECT household_id FROM household_members WHERE user_id = $2)
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(tombstone)
+}
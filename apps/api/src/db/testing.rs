@@ -0,0 +1,138 @@
+//! A PostgreSQL database for the tests of the queries, migrated like the
+//! docker-compose one. Set `TEST_DATABASE_URL` to a server where the user
+//! may create databases; without it, these tests pass without running.
+
+use std::path::{Path, PathBuf};
+
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::{Connection, PgConnection, PgPool};
+use tokio::sync::OnceCell;
+use uuid::Uuid;
+
+/// Databases made by earlier runs are dropped once nothing uses them.
+const DATABASE_PREFIX: &str = "garry_test_";
+
+static DATABASE: OnceCell<PgConnectOptions> = OnceCell::const_new();
+
+/// A pool on the database of this test run, created and migrated by the
+/// first test to ask, or `None` without `TEST_DATABASE_URL`. Tests share it
+/// and keep to their own data by using fresh user ids.
+pub async fn pool() -> Option<PgPool> {
+    let url = std::env::var("TEST_DATABASE_URL").ok()?;
+    let options = DATABASE.get_or_init(|| create_database(url)).await;
+    let pool = PgPoolOptions::new()
+        .max_connections(2)
+        .connect_with(options.clone())
+        .await
+        .expect("connect to the test database");
+    Some(pool)
+}
+
+/// A user id no other test uses.
+pub fn user() -> String {
+    Uuid::new_v4().to_string()
+}
+
+async fn create_database(url: String) -> PgConnectOptions {
+    let server: PgConnectOptions = url.parse().expect("TEST_DATABASE_URL");
+    let mut conn = PgConnection::connect_with(&server)
+        .await
+        .expect("connect to TEST_DATABASE_URL");
+
+    let stale: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT datname::text FROM pg_database d
+        WHERE datname LIKE $1 || '%'
+          AND NOT EXISTS (SELECT 1 FROM pg_stat_activity a WHERE a.datname = d.datname)
+        "#,
+    )
+    .bind(DATABASE_PREFIX)
+    .fetch_all(&mut conn)
+    .await
+    .expect("list stale test databases");
+    for name in stale {
+        let _ = sqlx::raw_sql(&format!("DROP DATABASE IF EXISTS \"{}\"", name))
+            .execute(&mut conn)
+            .await;
+    }
+
+    // The C locale accepts any encoding, whatever the server's default.
+    let name = format!("{}{}", DATABASE_PREFIX, Uuid::new_v4().simple());
+    sqlx::raw_sql(&format!(
+        "CREATE DATABASE \"{}\" TEMPLATE template0 ENCODING 'UTF8' LC_COLLATE 'C' LC_CTYPE 'C'",
+        name
+    ))
+    .execute(&mut conn)
+    .await
+    .expect("create the test database");
+    conn.close().await.ok();
+
+    let options = server.database(&name);
+    let mut conn = PgConnection::connect_with(&options)
+        .await
+        .expect("connect to the test database");
+    for migration in migrations() {
+        let sql = std::fs::read_to_string(&migration).expect("read migration");
+        for statement in statements(&sql) {
+            sqlx::raw_sql(statement)
+                .execute(&mut conn)
+                .await
+                .unwrap_or_else(|e| panic!("{}: {}", migration.display(), e));
+        }
+    }
+    conn.close().await.ok();
+
+    options
+}
+
+/// The migrations in the order docker-compose runs them: the users table of
+/// the auth service comes right after the first one.
+fn migrations() -> Vec<PathBuf> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut migrations: Vec<PathBuf> = std::fs::read_dir(root.join("migrations"))
+        .expect("migrations directory")
+        .map(|entry| entry.expect("migration").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "sql"))
+        .collect();
+    migrations.sort();
+    migrations.insert(1, root.join("../auth/migrations/001_create_users.sql"));
+    migrations
+}
+
+/// The statements of a migration, run one at a time as psql does: sent
+/// together, they would share a transaction, and an enum value added by one
+/// could not be used by the next. Semicolons inside strings, comments and
+/// `$$` bodies do not end a statement.
+fn statements(sql: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let bytes = sql.as_bytes();
+    let (mut start, mut i) = (0, 0);
+    while i < bytes.len() {
+        let rest = &sql[i..];
+        let skip_to = |end: &str, from: usize| {
+            rest[from..]
+                .find(end)
+                .map_or(bytes.len(), |at| i + from + at + end.len())
+        };
+        i = if rest.starts_with("--") {
+            skip_to("\n", 2)
+        } else if rest.starts_with("$$") {
+            skip_to("$$", 2)
+        } else if rest.starts_with('\'') {
+            skip_to("'", 1)
+        } else if rest.starts_with(';') {
+            statements.push(&sql[start..i]);
+            start = i + 1;
+            i + 1
+        } else {
+            i + rest.chars().next().map_or(1, char::len_utf8)
+        };
+    }
+    statements.push(&sql[start..]);
+    statements.retain(|statement| {
+        statement
+            .lines()
+            .any(|line| !line.trim().is_empty() && !line.trim_start().starts_with("--"))
+    });
+    statements
+}
@@ -0,0 +1,667 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::audit::Actor;
+use crate::db::{self, testing, PaginatedWarranties};
+use crate::listing::WarrantyListing;
+use crate::models::{
+    CoverageKind, CreateWarrantyRequest, FieldChange, HouseholdRole, ReminderPreferences,
+    UpdateWarrantyRequest, Warranty, WarrantyAction, WarrantyEvent,
+};
+use crate::warranty_period::warranty_end_date;
+
+fn actor(user_id: &str) -> Actor {
+    Actor {
+        user_id: user_id.to_string(),
+        request_id: None,
+    }
+}
+
+async fn set_timezone(pool: &PgPool, user_id: &str, timezone: Tz) {
+    let preferences = ReminderPreferences {
+        timezone: timezone.name().to_string(),
+        ..ReminderPreferences::defaults(&[30])
+    };
+    db::save_reminder_preferences(pool, user_id, &preferences)
+        .await
+        .unwrap();
+}
+
+async fn join(pool: &PgPool, household_id: Uuid, user_id: &str, role: HouseholdRole) {
+    sqlx::query("INSERT INTO household_members (household_id, user_id, role) VALUES ($1, $2, $3)")
+        .bind(household_id)
+        .bind(user_id)
+        .bind(role)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+fn new_warranty(purchase_date: DateTime<Utc>, months: i32) -> CreateWarrantyRequest {
+    serde_json::from_value(json!({
+        "product_name": "Four",
+        "category": "appliances",
+        "purchase_date": purchase_date,
+        "warranty_months": months,
+    }))
+    .unwrap()
+}
+
+async fn create(pool: &PgPool, user_id: &str, req: CreateWarrantyRequest) -> Warranty {
+    let household_id = db::default_household(pool, user_id).await.unwrap();
+    db::create_warranty(pool, user_id, household_id, None, req, &actor(user_id))
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_edits_keep_the_owners_calendar() {
+    let Some(pool) = testing::pool().await else {
+        return;
+    };
+    let owner = testing::user();
+    let editor = testing::user();
+    set_timezone(&pool, &owner, Tz::Europe__Paris).await;
+    set_timezone(&pool, &editor, Tz::America__New_York).await;
+
+    // January 31 in Paris, still January 30 in New York.
+    let purchase_date = Utc.with_ymd_and_hms(2025, 1, 30, 23, 30, 0).unwrap();
+    let expected = warranty_end_date(purchase_date, 1, Tz::Europe__Paris).unwrap();
+    assert_ne!(
+        warranty_end_date(purchase_date, 1, Tz::America__New_York),
+        Some(expected)
+    );
+
+    let warranty = create(&pool, &owner, new_warranty(purchase_date, 1)).await;
+    assert_eq!(warranty.warranty_end_date, expected);
+    join(&pool, warranty.household_id, &editor, HouseholdRole::Editor).await;
+
+    let edit: UpdateWarrantyRequest = serde_json::from_value(json!({
+        "notes": "Edited in New York",
+        "purchase_date": purchase_date,
+        "warranty_months": 1,
+    }))
+    .unwrap();
+    let edited = db::update_warranty(&pool, warranty.id, &editor, edit, &actor(&editor))
+        .await
+        .unwrap();
+    assert_eq!(edited.warranty_end_date, expected);
+    assert_eq!(edited.covered_until, expected);
+
+    let replaced = db::replace_warranty(
+        &pool,
+        warranty.id,
+        &editor,
+        edited.updated_at,
+        new_warranty(purchase_date, 1),
+        &actor(&editor),
+    )
+    .await
+    .unwrap();
+    assert_eq!(replaced.warranty_end_date, expected);
+}
+
+#[tokio::test]
+async fn test_end_date_recompute_counts_on_the_owners_calendar() {
+    let Some(pool) = testing::pool().await else {
+        return;
+    };
+    let owner = testing::user();
+    let member = testing::user();
+    set_timezone(&pool, &owner, Tz::America__New_York).await;
+    set_timezone(&pool, &member, Tz::Europe__Paris).await;
+
+    // January 30 in New York, January 31 in Paris.
+    let purchase_date = Utc.with_ymd_and_hms(2025, 1, 31, 3, 0, 0).unwrap();
+    let warranty = create(&pool, &owner, new_warranty(purchase_date, 1)).await;
+    join(&pool, warranty.household_id, &member, HouseholdRole::Owner).await;
+
+    // Run the migration that moved end dates to calendar months over an end
+    // date still counted in days, then leave everything as it was.
+    let mut tx = pool.begin().await.unwrap();
+    sqlx::query("UPDATE warranties SET warranty_end_date = purchase_date + INTERVAL '30 days' WHERE id = $1")
+        .bind(warranty.id)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    let migration = include_str!("../../migrations/013_recompute_warranty_end_dates.sql");
+    sqlx::raw_sql(migration).execute(&mut *tx).await.unwrap();
+    let recomputed: DateTime<Utc> =
+        sqlx::query_scalar("SELECT warranty_end_date FROM warranties WHERE id = $1")
+            .bind(warranty.id)
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+    tx.rollback().await.unwrap();
+
+    assert_eq!(recomputed, warranty.warranty_end_date);
+    assert_eq!(
+        Some(recomputed),
+        warranty_end_date(purchase_date, 1, Tz::America__New_York)
+    );
+}
+
+/// Creates a warranty of `user_id` bought in 2024, with `fields` on top of
+/// the defaults.
+async fn create_with(pool: &PgPool, user_id: &str, fields: serde_json::Value) -> Warranty {
+    let mut warranty = json!({
+        "product_name": "Four",
+        "category": "appliances",
+        "purchase_date": "2024-06-01T10:00:00Z",
+        "warranty_months": 24,
+    });
+    for (key, value) in fields.as_object().unwrap() {
+        warranty[key] = value.clone();
+    }
+    create(pool, user_id, serde_json::from_value(warranty).unwrap()).await
+}
+
+/// The warranties of `user_id` the listing asked for by `filters` returns,
+/// with `status` read on June 1, 2025.
+async fn list(pool: &PgPool, user_id: &str, filters: serde_json::Value) -> PaginatedWarranties {
+    let now = Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap();
+    let listing = WarrantyListing::parse(serde_json::from_value(filters).unwrap(), now).unwrap();
+    db::list_warranties(pool, user_id, &listing).await.unwrap()
+}
+
+fn names(page: &PaginatedWarranties) -> Vec<&str> {
+    page.warranties
+        .iter()
+        .map(|warranty| warranty.product_name.as_str())
+        .collect()
+}
+
+/// Every page of the listing, following `next_cursor` as clients do.
+async fn list_by_cursor(pool: &PgPool, user_id: &str, filters: serde_json::Value) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut filters = filters;
+    loop {
+        let page = list(pool, user_id, filters.clone()).await;
+        assert_eq!(page.total.is_some(), filters.get("cursor").is_none());
+        names.extend(page.warranties.iter().map(|w| w.product_name.clone()));
+        match page.next_cursor {
+            Some(cursor) => filters["cursor"] = json!(cursor.encode()),
+            None => return names,
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_listing_filters() {
+    let Some(pool) = testing::pool().await else {
+        return;
+    };
+    let user = testing::user();
+    let lave_linge = create_with(
+        &pool,
+        &user,
+        json!({
+            "product_name": "Lave-linge",
+            "brand": "Bosch",
+            "store": "Darty",
+            "price_cents": 59900,
+            "purchase_date": "2024-01-15T10:00:00Z",
+        }),
+    )
+    .await;
+    create_with(
+        &pool,
+        &user,
+        json!({
+            "product_name": "Télévision",
+            "category": "electronics",
+            "brand": "BOSCH",
+            "store": "Fnac",
+            "price_cents": 89900,
+            "purchase_date": "2024-03-10T10:00:00Z",
+            "warranty_months": 36,
+        }),
+    )
+    .await;
+    create_with(
+        &pool,
+        &user,
+        json!({
+            "product_name": "Canapé",
+            "category": "furniture",
+            "store": "darty",
+            "purchase_date": "2024-09-01T10:00:00Z",
+        }),
+    )
+    .await;
+    create_with(
+        &pool,
+        &user,
+        json!({
+            "product_name": "Vélo",
+            "category": "sports",
+            "price_cents": 25000,
+            "purchase_date": "2023-05-01T10:00:00Z",
+            "warranty_months": 12,
+        }),
+    )
+    .await;
+    let trashed = create_with(&pool, &user, json!({ "product_name": "Grille-pain" })).await;
+    sqlx::query("UPDATE warranties SET deleted_at = NOW() WHERE id = $1")
+        .bind(trashed.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE warranties SET receipt_key = 'receipts/lave-linge.pdf' WHERE id = $1")
+        .bind(lave_linge.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    // Someone else's warranty matches every filter below but never shows.
+    create_with(
+        &pool,
+        &testing::user(),
+        json!({ "product_name": "Lave-linge", "brand": "Bosch", "store": "Darty" }),
+    )
+    .await;
+
+    let sorted_by_name = json!({ "sort": "product_name" });
+    let all = list(&pool, &user, sorted_by_name.clone()).await;
+    assert_eq!(names(&all), ["Canapé", "Lave-linge", "Télévision", "Vélo"]);
+    assert_eq!(all.total, Some(4));
+
+    let cases = [
+        (
+            json!({ "category": "appliances,electronics" }),
+            vec!["Lave-linge", "Télévision"],
+        ),
+        (
+            json!({ "brand": "bosch" }),
+            vec!["Lave-linge", "Télévision"],
+        ),
+        (json!({ "store": "DARTY" }), vec!["Canapé", "Lave-linge"]),
+        (
+            json!({
+                "purchased_from": "2024-01-15T10:00:00Z",
+                "purchased_to": "2024-03-10T10:00:00Z",
+            }),
+            vec!["Lave-linge", "Télévision"],
+        ),
+        (
+            json!({ "min_price_cents": 25000, "max_price_cents": 59900 }),
+            vec!["Lave-linge", "Vélo"],
+        ),
+        (json!({ "has_receipt": true }), vec!["Lave-linge"]),
+        (
+            json!({ "has_receipt": false }),
+            vec!["Canapé", "Télévision", "Vélo"],
+        ),
+        (json!({ "status": "expired" }), vec!["Vélo"]),
+        (
+            json!({ "expires_from": "2026-01-01T00:00:00Z", "expires_to": "2026-12-31T00:00:00Z" }),
+            vec!["Lave-linge", "Canapé"],
+        ),
+        (
+            json!({ "category": "appliances,furniture", "store": "darty", "has_receipt": false }),
+            vec!["Canapé"],
+        ),
+        (json!({ "brand": "Bosch", "category": "sports" }), vec![]),
+    ];
+    for (filters, expected) in cases {
+        let mut query = sorted_by_name.clone();
+        for (key, value) in filters.as_object().unwrap() {
+            query[key] = value.clone();
+        }
+        let mut expected = expected;
+        expected.sort_by_key(|name| name.to_lowercase());
+        let page = list(&pool, &user, query).await;
+        assert_eq!(names(&page), expected, "{}", filters);
+        assert_eq!(page.total, Some(expected.len() as i64), "{}", filters);
+    }
+
+    let household_id = lave_linge.household_id;
+    let other_household = db::create_household(&pool, &user, "Maison de campagne")
+        .await
+        .unwrap();
+    let hidden = list(&pool, &user, json!({ "household_id": other_household.id })).await;
+    assert!(hidden.warranties.is_empty());
+    let shown = list(&pool, &user, json!({ "household_id": household_id })).await;
+    assert_eq!(shown.total, Some(4));
+}
+
+#[tokio::test]
+async fn test_listing_sort_orders() {
+    let Some(pool) = testing::pool().await else {
+        return;
+    };
+    let user = testing::user();
+    for (name, category, price, purchase_date) in [
+        (
+            "bouilloire",
+            "appliances",
+            Some(3000),
+            "2024-02-01T10:00:00Z",
+        ),
+        ("Aspirateur", "appliances", None, "2024-04-01T10:00:00Z"),
+        ("Casque", "electronics", Some(15000), "2024-01-01T10:00:00Z"),
+        ("Table", "furniture", Some(3000), "2024-03-01T10:00:00Z"),
+    ] {
+        create_with(
+            &pool,
+            &user,
+            json!({
+                "product_name": name,
+                "category": category,
+                "price_cents": price,
+                "purchase_date": purchase_date,
+            }),
+        )
+        .await;
+    }
+
+    let cases = [
+        // Names compare without regard to case.
+        (
+            json!({ "sort": "product_name" }),
+            ["Aspirateur", "bouilloire", "Casque", "Table"],
+        ),
+        (
+            json!({ "sort": "product_name", "order": "desc" }),
+            ["Table", "Casque", "bouilloire", "Aspirateur"],
+        ),
+        (
+            json!({ "sort": "purchase_date", "order": "desc" }),
+            ["Aspirateur", "Table", "bouilloire", "Casque"],
+        ),
+        // Every warranty lasts 24 months: end dates sort like purchase dates.
+        (json!({}), ["Casque", "bouilloire", "Table", "Aspirateur"]),
+    ];
+    for (filters, expected) in cases {
+        let page = list(&pool, &user, filters.clone()).await;
+        assert_eq!(names(&page), expected, "{}", filters);
+    }
+
+    // Warranties without a price come last either way.
+    for order in ["asc", "desc"] {
+        let page = list(&pool, &user, json!({ "sort": "price", "order": order })).await;
+        let prices: Vec<Option<i64>> = page.warranties.iter().map(|w| w.price_cents).collect();
+        let expected = match order {
+            "asc" => [Some(3000), Some(3000), Some(15000), None],
+            _ => [Some(15000), Some(3000), Some(3000), None],
+        };
+        assert_eq!(prices, expected, "{}", order);
+    }
+
+    let page = list(&pool, &user, json!({ "sort": "category", "order": "desc" })).await;
+    let categories: Vec<&str> = page
+        .warranties
+        .iter()
+        .map(|w| w.category.as_str())
+        .collect();
+    assert_eq!(
+        categories,
+        ["furniture", "electronics", "appliances", "appliances"]
+    );
+}
+
+#[tokio::test]
+async fn test_cursor_pages_match_the_whole_listing() {
+    let Some(pool) = testing::pool().await else {
+        return;
+    };
+    let user = testing::user();
+    // Ties and missing prices, so pages split inside equal values.
+    for (i, price) in [
+        Some(1000),
+        None,
+        Some(2000),
+        Some(1000),
+        None,
+        Some(1000),
+        Some(3000),
+        Some(2000),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let category = if i % 3 == 0 {
+            "electronics"
+        } else {
+            "appliances"
+        };
+        create_with(
+            &pool,
+            &user,
+            json!({
+                "product_name": format!("Article {}", i),
+                "category": category,
+                "price_cents": price,
+                "store": "Darty",
+            }),
+        )
+        .await;
+    }
+    create_with(
+        &pool,
+        &user,
+        json!({ "product_name": "Ailleurs", "store": "Fnac" }),
+    )
+    .await;
+
+    for filters in [
+        json!({ "sort": "price" }),
+        json!({ "sort": "price", "order": "desc" }),
+        json!({ "sort": "price", "store": "darty" }),
+        json!({ "sort": "price", "order": "desc", "category": "appliances" }),
+        json!({ "sort": "product_name", "order": "desc", "max_price_cents": 2000 }),
+        json!({ "sort": "end_date", "category": "electronics" }),
+    ] {
+        let mut whole = filters.clone();
+        whole["per_page"] = json!(100);
+        let expected: Vec<String> = list(&pool, &user, whole)
+            .await
+            .warranties
+            .into_iter()
+            .map(|w| w.product_name)
+            .collect();
+
+        for per_page in [1, 2, 3] {
+            let mut paged = filters.clone();
+            paged["per_page"] = json!(per_page);
+            let names = list_by_cursor(&pool, &user, paged).await;
+            assert_eq!(names, expected, "{} by {}", filters, per_page);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_cursor_pages_do_not_shift_when_warranties_are_added() {
+    let Some(pool) = testing::pool().await else {
+        return;
+    };
+    let user = testing::user();
+    for name in ["B", "D", "F", "H"] {
+        create_with(&pool, &user, json!({ "product_name": name })).await;
+    }
+
+    let first = list(
+        &pool,
+        &user,
+        json!({ "sort": "product_name", "per_page": 2 }),
+    )
+    .await;
+    assert_eq!(names(&first), ["B", "D"]);
+    // One before the cursor, one after.
+    create_with(&pool, &user, json!({ "product_name": "A" })).await;
+    create_with(&pool, &user, json!({ "product_name": "E" })).await;
+
+    let cursor = first.next_cursor.unwrap().encode();
+    let rest = list_by_cursor(
+        &pool,
+        &user,
+        json!({ "sort": "product_name", "per_page": 2, "cursor": cursor }),
+    )
+    .await;
+    assert_eq!(rest, ["E", "F", "H"]);
+}
+
+#[tokio::test]
+async fn test_reminders_go_to_current_household_members() {
+    let Some(pool) = testing::pool().await else {
+        return;
+    };
+    let creator = testing::user();
+    let viewer = testing::user();
+    let now = Utc::now();
+    let expiring = create_with(
+        &pool,
+        &creator,
+        json!({ "purchase_date": now - Duration::days(720), "warranty_months": 24 }),
+    )
+    .await;
+    let expired = create_with(
+        &pool,
+        &creator,
+        json!({ "purchase_date": now - Duration::days(735), "warranty_months": 24 }),
+    )
+    .await;
+    let household_id = expiring.household_id;
+    join(&pool, household_id, &viewer, HouseholdRole::Viewer).await;
+    let mut conn = pool.acquire().await.unwrap();
+    let members = db::household_member_ids(&mut conn, household_id)
+        .await
+        .unwrap();
+    assert_eq!(members, [creator.clone(), viewer.clone()]);
+
+    // The creator hands the household over and leaves.
+    db::update_member_role(&pool, household_id, &viewer, HouseholdRole::Owner)
+        .await
+        .unwrap();
+    db::remove_member(&pool, household_id, &creator)
+        .await
+        .unwrap();
+
+    let mut conn = pool.acquire().await.unwrap();
+    let candidates = db::get_reminder_candidates(&mut conn, now, &[30])
+        .await
+        .unwrap();
+    let recipients: Vec<&str> = candidates
+        .iter()
+        .filter(|candidate| candidate.warranty_id == expiring.id)
+        .map(|candidate| candidate.user_id.as_str())
+        .collect();
+    assert_eq!(recipients, [viewer.as_str()]);
+
+    let notifications = db::create_expired_notifications(&mut conn, now, 30)
+        .await
+        .unwrap();
+    let recipients: Vec<&str> = notifications
+        .iter()
+        .filter(|notification| notification.warranty_id == Some(expired.id))
+        .map(|notification| notification.user_id.as_str())
+        .collect();
+    assert_eq!(recipients, [viewer.as_str()]);
+}
+
+#[tokio::test]
+async fn test_expiry_reminders_fire_again_once_the_end_date_moves() {
+    let Some(pool) = testing::pool().await else {
+        return;
+    };
+    let user = testing::user();
+    let now = Utc::now();
+    let warranty = create_with(
+        &pool,
+        &user,
+        json!({ "purchase_date": now - Duration::days(720), "warranty_months": 24 }),
+    )
+    .await;
+    let reminder = |warranty_end_date| db::NewReminder {
+        user_id: user.clone(),
+        warranty_id: warranty.id,
+        threshold_days: 30,
+        warranty_end_date,
+    };
+    let mut conn = pool.acquire().await.unwrap();
+
+    let created = db::create_expiry_notifications(&mut conn, &[reminder(warranty.covered_until)])
+        .await
+        .unwrap();
+    assert_eq!(created.len(), 1);
+    let created = db::create_expiry_notifications(&mut conn, &[reminder(warranty.covered_until)])
+        .await
+        .unwrap();
+    assert!(created.is_empty());
+
+    // A repair pushed the end of coverage back.
+    let extended = warranty.covered_until + Duration::days(182);
+    let created = db::create_expiry_notifications(&mut conn, &[reminder(extended)])
+        .await
+        .unwrap();
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0].warranty_end_date, Some(extended));
+}
+
+#[tokio::test]
+async fn test_coverage_changes_record_covered_until_in_the_history() {
+    let Some(pool) = testing::pool().await else {
+        return;
+    };
+    let user = testing::user();
+    let warranty = create(&pool, &user, new_warranty(Utc::now(), 24)).await;
+    let fields = |ends_at| db::CoverageFields {
+        kind: CoverageKind::Extended,
+        provider: None,
+        starts_at: warranty.purchase_date,
+        ends_at,
+        contract_number: None,
+        cost_cents: None,
+    };
+    let covered_until_changes = |events: &[WarrantyEvent]| -> Vec<FieldChange> {
+        events
+            .iter()
+            .flat_map(|event| &event.changes.0)
+            .filter(|change| change.field == "covered_until")
+            .cloned()
+            .collect()
+    };
+
+    // Shorter than the warranty itself: covered_until stays.
+    db::create_coverage(
+        &pool,
+        warranty.id,
+        fields(warranty.warranty_end_date - Duration::days(30)),
+        &actor(&user),
+    )
+    .await
+    .unwrap();
+    let events = db::list_warranty_events(&pool, warranty.id).await.unwrap();
+    assert_eq!(events.len(), 1);
+
+    let extended_until = warranty.warranty_end_date + Duration::days(365);
+    let coverage = db::create_coverage(&pool, warranty.id, fields(extended_until), &actor(&user))
+        .await
+        .unwrap();
+    db::delete_coverage(&pool, warranty.id, coverage.id, &actor(&user))
+        .await
+        .unwrap();
+
+    let events = db::list_warranty_events(&pool, warranty.id).await.unwrap();
+    assert_eq!(events.len(), 3);
+    assert!(events[1..]
+        .iter()
+        .all(|event| event.action == WarrantyAction::Updated && event.actor_user_id == user));
+    assert_eq!(
+        covered_until_changes(&events[1..]),
+        [
+            FieldChange {
+                field: "covered_until".to_string(),
+                old: json!(warranty.warranty_end_date),
+                new: json!(extended_until),
+            },
+            FieldChange {
+                field: "covered_until".to_string(),
+                old: json!(extended_until),
+                new: json!(warranty.warranty_end_date),
+            },
+        ]
+    );
+}
@@ -1,31 +1,63 @@
-use chrono::{Duration, Utc};
-use sqlx::PgPool;
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use sqlx::{Encode, FromRow, PgConnection, PgPool, Postgres, QueryBuilder, Type};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+use super::warranty_event::record_warranty_event;
+use crate::audit::{self, Actor};
+use crate::db::{get_user_timezone, get_warranty_timezone};
 use crate::error::{AppError, Result};
-use crate::models::{CreateWarrantyRequest, UpdateWarrantyRequest, Warranty, WarrantyFilters};
+use crate::listing::{Cursor, Range, SortField, SortOrder, SortValue, WarrantyListing};
+use crate::models::{
+    CreateWarrantyRequest, SearchMatch, UpdateWarrantyRequest, Warranty, WarrantyAction,
+};
+use crate::search;
+use crate::storage::{self, Storage};
+use crate::warranty_period;
 
 #[derive(Debug, Clone)]
 pub struct PaginatedWarranties {
     pub warranties: Vec<Warranty>,
-    pub total: i64,
+    /// Across all pages; not counted when paging by cursor
+    pub total: Option<i64>,
+    /// Filled when the listing was a search
+    pub matches: Vec<SearchMatch>,
+    /// Where the next page starts, unless this one is the last
+    pub next_cursor: Option<Cursor>,
+}
+
+#[derive(FromRow)]
+struct ListingRow {
+    #[sqlx(flatten)]
+    warranty: Warranty,
+    search_rank: Option<f32>,
+    search_snippet: Option<String>,
 }
 
+/// Creates a warranty in a household the caller has checked the user may
+/// edit, under `id` when the client chose one, as offline clients do, or
+/// under a new one.
 pub async fn create_warranty(
     pool: &PgPool,
     user_id: &str,
+    household_id: Uuid,
+    id: Option<Uuid>,
     req: CreateWarrantyRequest,
+    actor: &Actor,
 ) -> Result<Warranty> {
     let warranty_months = req
         .warranty_months
-        .unwrap_or_else(|| req.category.default_warranty_months());
-    let warranty_end_date = req.purchase_date + Duration::days(warranty_months as i64 * 30);
+        .ok_or_else(|| AppError::BadRequest("Warranty months is required".to_string()))?;
+    let timezone = get_user_timezone(pool, user_id).await?;
+    let warranty_end_date = end_date(req.purchase_date, warranty_months, timezone)?;
 
+    let mut tx = pool.begin().await?;
     let warranty = sqlx::query_as::<_, Warranty>(
         r#"
-        INSERT INTO warranties (user_id, product_name, brand, category, purchase_date, warranty_end_date, warranty_months, store, notes)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        INSERT INTO warranties (id, user_id, household_id, product_name, brand, category, purchase_date, warranty_end_date, covered_until, warranty_months, store, price_cents, invoice_number, notes, reminder_lead_days)
+        VALUES (COALESCE($14, uuid_generate_v4()), $1, $13, $2, $3, $4, $5, $6, $6, $7, $8, $9, $10, $11, $12)
+        ON CONFLICT (id) DO NOTHING
         RETURNING *
         "#,
     )
@@ -37,144 +69,257 @@ pub async fn create_warranty(
     .bind(warranty_end_date)
     .bind(warranty_months)
     .bind(&req.store)
+    .bind(req.price_cents)
+    .bind(&req.invoice_number)
     .bind(&req.notes)
-    .fetch_one(pool)
+    .bind(&req.reminder_lead_days)
+    .bind(household_id)
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::Conflict("Warranty already exists".to_string()))?;
+
+    let changes = audit::diff(None, &warranty);
+    record_warranty_event(
+        &mut tx,
+        warranty.id,
+        WarrantyAction::Created,
+        changes,
+        actor,
+    )
     .await?;
+    tx.commit().await?;
 
     Ok(warranty)
 }
 
+pub(super) fn end_date(
+    purchase_date: DateTime<Utc>,
+    months: i32,
+    timezone: Tz,
+) -> Result<DateTime<Utc>> {
+    warranty_period::warranty_end_date(purchase_date, months, timezone)
+        .ok_or_else(|| AppError::BadRequest("Purchase date is out of range".to_string()))
+}
+
 pub async fn get_warranty_by_id(pool: &PgPool, id: Uuid, user_id: &str) -> Result<Warranty> {
-    sqlx::query_as::<_, Warranty>("SELECT * FROM warranties WHERE id = $1 AND user_id = $2")
-        .bind(id)
-        .bind(user_id)
-        .fetch_optional(pool)
-        .await?
-        .ok_or_else(|| AppError::NotFound("Warranty not found".to_string()))
+    sqlx::query_as::<_, Warranty>(
+        r#"
+        SELECT * FROM warranties
+        WHERE id = $1
+          AND household_id IN (SELECT household_id FROM household_members WHERE user_id = $2)
+          AND deleted_at IS NULL
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Warranty not found".to_string()))
 }
 
+/// One page of the warranties a user can see, filtered and sorted as `listing`
+/// asks, with the total across pages when paging by number and the cursor
+/// of the next page. Searches also get how each warranty matched.
 pub async fn list_warranties(
     pool: &PgPool,
     user_id: &str,
-    filters: WarrantyFilters,
+    listing: &WarrantyListing,
 ) -> Result<PaginatedWarranties> {
-    let per_page = filters.per_page.unwrap_or(20).clamp(1, 100);
-    let page = filters.page.unwrap_or(1).max(1);
-    let offset = (page - 1) * per_page;
-
-    let (warranties, total) = match (&filters.category, &filters.status) {
-        (Some(category), Some(status)) => {
-            let (start_date, end_date) = get_status_date_range(status);
-            let warranties = sqlx::query_as::<_, Warranty>(
-                r#"
-                SELECT * FROM warranties 
-                WHERE user_id = $1 AND category = $2 AND warranty_end_date >= $3 AND warranty_end_date <= $4
-                ORDER BY warranty_end_date ASC
-                LIMIT $5 OFFSET $6
-                "#,
-            )
-            .bind(user_id)
-            .bind(category)
-            .bind(start_date)
-            .bind(end_date)
-            .bind(per_page)
-            .bind(offset)
-            .fetch_all(pool)
-            .await?;
-
-            let count: (i64,) = sqlx::query_as(
-                "SELECT COUNT(*) FROM warranties WHERE user_id = $1 AND category = $2 AND warranty_end_date >= $3 AND warranty_end_date <= $4"
+    let mut query = QueryBuilder::new("SELECT w.*");
+    if listing.q.is_some() {
+        query
+            .push(", ts_rank_cd(w.search_vector, query) AS search_rank, ts_headline(")
+            .push_bind(search::TEXT_SEARCH_CONFIG)
+            .push(
+                "::regconfig, concat_ws(' — ', w.product_name, w.brand, w.store, w.notes, \
+                 warranty_receipt_text(w.id)), query, ",
             )
-            .bind(user_id)
-            .bind(category)
-            .bind(start_date)
-            .bind(end_date)
-            .fetch_one(pool)
-            .await?;
-
-            (warranties, count.0)
+            .push_bind(search::headline_options())
+            .push(") AS search_snippet");
+    } else {
+        query.push(", NULL::real AS search_rank, NULL::text AS search_snippet");
+    }
+    push_listing_filters(&mut query, user_id, listing);
+    if let Some(cursor) = &listing.cursor {
+        push_after_cursor(&mut query, listing, cursor);
+    }
+    // One row more than the page tells whether another page follows.
+    query
+        .push(" ORDER BY ")
+        .push(listing.sort.column())
+        .push(" ")
+        .push(listing.order.as_sql())
+        .push(" NULLS LAST, w.id ")
+        .push(listing.order.as_sql())
+        .push(" LIMIT ")
+        .push_bind(listing.per_page + 1)
+        .push(" OFFSET ")
+        .push_bind(listing.offset());
+
+    let total = match listing.cursor {
+        Some(_) => None,
+        None => {
+            let mut count = QueryBuilder::new("SELECT COUNT(*)");
+            push_listing_filters(&mut count, user_id, listing);
+            Some(count.build_query_scalar().fetch_one(pool).await?)
         }
-        (Some(category), None) => {
-            let warranties = sqlx::query_as::<_, Warranty>(
-                r#"
-                SELECT * FROM warranties 
-                WHERE user_id = $1 AND category = $2
-                ORDER BY warranty_end_date ASC
-                LIMIT $3 OFFSET $4
-                "#,
-            )
-            .bind(user_id)
-            .bind(category)
-            .bind(per_page)
-            .bind(offset)
-            .fetch_all(pool)
-            .await?;
-
-            let count: (i64,) = sqlx::query_as(
-                "SELECT COUNT(*) FROM warranties WHERE user_id = $1 AND category = $2",
-            )
-            .bind(user_id)
-            .bind(category)
-            .fetch_one(pool)
-            .await?;
+    };
+
+    let mut rows = query.build_query_as::<ListingRow>().fetch_all(pool).await?;
+    let next_cursor = if rows.len() as i64 > listing.per_page {
+        rows.truncate(listing.per_page as usize);
+        rows.last()
+            .map(|row| Cursor::after(listing, &row.warranty, row.search_rank))
+    } else {
+        None
+    };
 
-            (warranties, count.0)
+    let matches = rows
+        .iter()
+        .filter_map(|row| {
+            Some(SearchMatch {
+                warranty_id: row.warranty.id,
+                rank: row.search_rank?,
+                snippet: search::highlight(row.search_snippet.as_deref()?),
+            })
+        })
+        .collect();
+    let warranties = rows.into_iter().map(|row| row.warranty).collect();
+
+    Ok(PaginatedWarranties {
+        warranties,
+        total,
+        matches,
+        next_cursor,
+    })
+}
+
+/// Keeps the rows sorted after the cursor: a later value, or the same one
+/// and a later id. Rows without a value sort last.
+fn push_after_cursor<'a>(
+    query: &mut QueryBuilder<'a, Postgres>,
+    listing: &WarrantyListing,
+    cursor: &'a Cursor,
+) {
+    let column = listing.sort.column();
+    let after = match listing.order {
+        SortOrder::Asc => ">",
+        SortOrder::Desc => "<",
+    };
+
+    let Some(value) = &cursor.value else {
+        query
+            .push(format!(" AND {} IS NULL AND w.id {} ", column, after))
+            .push_bind(cursor.id);
+        return;
+    };
+    query.push(format!(" AND ({} {} ", column, after));
+    push_sort_value(query, listing.sort, value);
+    query.push(format!(" OR ({} = ", column));
+    push_sort_value(query, listing.sort, value);
+    query
+        .push(format!(" AND w.id {} ", after))
+        .push_bind(cursor.id)
+        .push(format!(") OR {} IS NULL)", column));
+}
+
+/// Binds a sort value as its column compares it.
+fn push_sort_value<'a>(
+    query: &mut QueryBuilder<'a, Postgres>,
+    sort: SortField,
+    value: &'a SortValue,
+) {
+    match value {
+        SortValue::Time(at) => {
+            query.push_bind(at);
         }
-        (None, Some(status)) => {
-            let (start_date, end_date) = get_status_date_range(status);
-            let warranties = sqlx::query_as::<_, Warranty>(
-                r#"
-                SELECT * FROM warranties 
-                WHERE user_id = $1 AND warranty_end_date >= $2 AND warranty_end_date <= $3
-                ORDER BY warranty_end_date ASC
-                LIMIT $4 OFFSET $5
-                "#,
-            )
-            .bind(user_id)
-            .bind(start_date)
-            .bind(end_date)
-            .bind(per_page)
-            .bind(offset)
-            .fetch_all(pool)
-            .await?;
-
-            let count: (i64,) = sqlx::query_as(
-                "SELECT COUNT(*) FROM warranties WHERE user_id = $1 AND warranty_end_date >= $2 AND warranty_end_date <= $3"
-            )
-            .bind(user_id)
-            .bind(start_date)
-            .bind(end_date)
-            .fetch_one(pool)
-            .await?;
+        SortValue::Text(text) if sort == SortField::ProductName => {
+            query.push("lower(").push_bind(text).push(")");
+        }
+        SortValue::Text(text) => {
+            query.push_bind(text);
+        }
+        SortValue::Cents(cents) => {
+            query.push_bind(cents);
+        }
+        SortValue::Rank(rank) => {
+            query.push_bind(rank).push("::real");
+        }
+    }
+}
+
+/// `FROM` and `WHERE` of a listing, shared by its page and its count.
+fn push_listing_filters<'a>(
+    query: &mut QueryBuilder<'a, Postgres>,
+    user_id: &'a str,
+    listing: &'a WarrantyListing,
+) {
+    query.push(" FROM warranties w");
+    if let Some(q) = &listing.q {
+        query
+            .push(", websearch_to_tsquery(")
+            .push_bind(search::TEXT_SEARCH_CONFIG)
+            .push("::regconfig, ")
+            .push_bind(q)
+            .push(") AS query");
+    }
 
-            (warranties, count.0)
+    query
+        .push(
+            " WHERE w.household_id IN \
+             (SELECT household_id FROM household_members WHERE user_id = ",
+        )
+        .push_bind(user_id)
+        .push(") AND w.deleted_at IS NULL");
+    if let Some(household_id) = listing.household_id {
+        query.push(" AND w.household_id = ").push_bind(household_id);
+    }
+    if listing.q.is_some() {
+        query.push(" AND w.search_vector @@ query");
+    }
+    if !listing.categories.is_empty() {
+        query
+            .push(" AND w.category = ANY(")
+            .push_bind(&listing.categories)
+            .push(")");
+    }
+    if let Some(brand) = &listing.brand {
+        query
+            .push(" AND lower(w.brand) = lower(")
+            .push_bind(brand)
+            .push(")");
+    }
+    if let Some(store) = &listing.store {
+        query
+            .push(" AND lower(w.store) = lower(")
+            .push_bind(store)
+            .push(")");
+    }
+    push_range(query, "w.purchase_date", listing.purchased);
+    push_range(query, "w.covered_until", listing.covered);
+    push_range(query, "w.price_cents", listing.price_cents);
+    match listing.has_receipt {
+        Some(true) => {
+            query.push(" AND w.receipt_key IS NOT NULL");
         }
-        (None, None) => {
-            let warranties = sqlx::query_as::<_, Warranty>(
-                r#"
-                SELECT * FROM warranties 
-                WHERE user_id = $1
-                ORDER BY warranty_end_date ASC
-                LIMIT $2 OFFSET $3
-                "#,
-            )
-            .bind(user_id)
-            .bind(per_page)
-            .bind(offset)
-            .fetch_all(pool)
-            .await?;
-
-            let count: (i64,) =
-                sqlx::query_as("SELECT COUNT(*) FROM warranties WHERE user_id = $1")
-                    .bind(user_id)
-                    .fetch_one(pool)
-                    .await?;
-
-            (warranties, count.0)
+        Some(false) => {
+            query.push(" AND w.receipt_key IS NULL");
         }
-    };
+        None => {}
+    }
+}
 
-    Ok(PaginatedWarranties { warranties, total })
+fn push_range<'a, T>(query: &mut QueryBuilder<'a, Postgres>, column: &str, range: Range<T>)
+where
+    T: 'a + Encode<'a, Postgres> + Type<Postgres> + Send,
+{
+    if let Some(from) = range.from {
+        query.push(format!(" AND {} >= ", column)).push_bind(from);
+    }
+    if let Some(to) = range.to {
+        query.push(format!(" AND {} <= ", column)).push_bind(to);
+    }
 }
 
 pub async fn update_warranty(
@@ -182,8 +327,12 @@ pub async fn update_warranty(
     id: Uuid,
     user_id: &str,
     req: UpdateWarrantyRequest,
+    actor: &Actor,
 ) -> Result<Warranty> {
-    let existing = get_warranty_by_id(pool, id, user_id).await?;
+    let timezone = get_warranty_timezone(pool, id).await?;
+    let mut tx = pool.begin().await?;
+    let existing = lock_warranty(&mut tx, id, user_id).await?;
+    let before = existing.clone();
 
     let product_name = req.product_name.unwrap_or(existing.product_name);
     let brand = req.brand.or(existing.brand);
@@ -191,15 +340,29 @@ pub async fn update_warranty(
     let purchase_date = req.purchase_date.unwrap_or(existing.purchase_date);
     let warranty_months = req.warranty_months.unwrap_or(existing.warranty_months);
     let store = req.store.or(existing.store);
+    let price_cents = req.price_cents.or(existing.price_cents);
+    let invoice_number = req.invoice_number.or(existing.invoice_number);
     let notes = req.notes.or(existing.notes);
-    let warranty_end_date = purchase_date + Duration::days(warranty_months as i64 * 30);
+    let reminder_lead_days = req
+        .reminder_lead_days
+        .unwrap_or(existing.reminder_lead_days);
+    let warranty_end_date = end_date(
+        purchase_date,
+        warranty_months + existing.repair_extension_months,
+        timezone,
+    )?;
 
     let warranty = sqlx::query_as::<_, Warranty>(
         r#"
         UPDATE warranties 
         SET product_name = $1, brand = $2, category = $3, purchase_date = $4, 
-            warranty_end_date = $5, warranty_months = $6, store = $7, notes = $8, updated_at = NOW()
-        WHERE id = $9 AND user_id = $10
+            warranty_end_date = $5,
+            covered_until = GREATEST($5, (SELECT MAX(ends_at) FROM warranty_coverages WHERE warranty_id = $12)),
+            warranty_months = $6, store = $7, price_cents = $8,
+            invoice_number = $9, notes = $10, reminder_lead_days = $11, updated_at = NOW()
+        WHERE id = $12
+          AND household_id IN (SELECT household_id FROM household_members WHERE user_id = $13)
+          AND deleted_at IS NULL
         RETURNING *
         "#,
     )
@@ -210,27 +373,279 @@ pub async fn update_warranty(
     .bind(warranty_end_date)
     .bind(warranty_months)
     .bind(&store)
+    .bind(price_cents)
+    .bind(&invoice_number)
     .bind(&notes)
+    .bind(&reminder_lead_days)
     .bind(id)
     .bind(user_id)
-    .fetch_one(pool)
+    .fetch_one(&mut *tx)
     .await?;
 
+    let changes = audit::diff(Some(&before), &warranty);
+    record_warranty_event(&mut tx, id, WarrantyAction::Updated, changes, actor).await?;
+    tx.commit().await?;
+
     Ok(warranty)
 }
 
-pub async fn delete_warranty(pool: &PgPool, id: Uuid, user_id: &str) -> Result<()> {
-    let result = sqlx::query("DELETE FROM warranties WHERE id = $1 AND user_id = $2")
-        .bind(id)
-        .bind(user_id)
-        .execute(pool)
-        .await?;
+/// Replaces every field of a warranty a client can edit, as long as it was
+/// last updated at `updated_at`. Offline clients send whole warranties, so
+/// fields left out are cleared rather than kept.
+pub async fn replace_warranty(
+    pool: &PgPool,
+    id: Uuid,
+    user_id: &str,
+    updated_at: DateTime<Utc>,
+    req: CreateWarrantyRequest,
+    actor: &Actor,
+) -> Result<Warranty> {
+    let warranty_months = req
+        .warranty_months
+        .ok_or_else(|| AppError::BadRequest("Warranty months is required".to_string()))?;
+    let timezone = get_warranty_timezone(pool, id).await?;
+
+    // The lock keeps a concurrent edit from being lost.
+    let mut tx = pool.begin().await?;
+    let existing = lock_warranty(&mut tx, id, user_id).await?;
+    if existing.updated_at != updated_at {
+        return Err(changed_since());
+    }
 
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound("Warranty not found".to_string()));
+    let warranty_end_date = end_date(
+        req.purchase_date,
+        warranty_months + existing.repair_extension_months,
+        timezone,
+    )?;
+
+    let warranty = sqlx::query_as::<_, Warranty>(
+        r#"
+        UPDATE warranties
+        SET product_name = $1, brand = $2, category = $3, purchase_date = $4,
+            warranty_end_date = $5,
+            covered_until = GREATEST($5, (SELECT MAX(ends_at) FROM warranty_coverages WHERE warranty_id = $12)),
+            warranty_months = $6, store = $7, price_cents = $8,
+            invoice_number = $9, notes = $10, reminder_lead_days = $11, updated_at = NOW()
+        WHERE id = $12
+          AND household_id IN (SELECT household_id FROM household_members WHERE user_id = $13)
+        RETURNING *
+        "#,
+    )
+    .bind(&req.product_name)
+    .bind(&req.brand)
+    .bind(&req.category)
+    .bind(req.purchase_date)
+    .bind(warranty_end_date)
+    .bind(warranty_months)
+    .bind(&req.store)
+    .bind(req.price_cents)
+    .bind(&req.invoice_number)
+    .bind(&req.notes)
+    .bind(&req.reminder_lead_days)
+    .bind(id)
+    .bind(user_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let changes = audit::diff(Some(&existing), &warranty);
+    record_warranty_event(&mut tx, id, WarrantyAction::Updated, changes, actor).await?;
+    tx.commit().await?;
+
+    Ok(warranty)
+}
+
+fn changed_since() -> AppError {
+    AppError::Conflict("Warranty was changed since".to_string())
+}
+
+/// Locks a warranty not in the trash until the end of the transaction.
+pub(super) async fn lock_warranty(
+    conn: &mut PgConnection,
+    id: Uuid,
+    user_id: &str,
+) -> Result<Warranty> {
+    sqlx::query_as::<_, Warranty>(
+        r#"
+        SELECT * FROM warranties
+        WHERE id = $1
+          AND household_id IN (SELECT household_id FROM household_members WHERE user_id = $2)
+          AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(conn)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Warranty not found".to_string()))
+}
+
+/// Moves a warranty to the trash, keeping its attachments and receipt until
+/// it is purged. Returns the warranty as trashed. With `updated_at`, only
+/// trashes the warranty if it was last updated then.
+pub async fn trash_warranty(
+    pool: &PgPool,
+    id: Uuid,
+    user_id: &str,
+    updated_at: Option<DateTime<Utc>>,
+    actor: &Actor,
+) -> Result<Warranty> {
+    let mut tx = pool.begin().await?;
+    let trashed = sqlx::query_as::<_, Warranty>(
+        r#"
+        UPDATE warranties SET deleted_at = NOW()
+        WHERE id = $1
+          AND household_id IN (SELECT household_id FROM household_members WHERE user_id = $2)
+          AND deleted_at IS NULL
+          AND ($3::timestamptz IS NULL OR updated_at = $3)
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(updated_at)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    match trashed {
+        Some(warranty) => {
+            record_warranty_event(&mut tx, id, WarrantyAction::Trashed, Vec::new(), actor).await?;
+            tx.commit().await?;
+            Ok(warranty)
+        }
+        None => {
+            // Tell a missing warranty from one changed since `updated_at`.
+            get_warranty_by_id(pool, id, user_id).await?;
+            Err(changed_since())
+        }
     }
+}
+
+/// The warranties in the trash of the households of a user, most recently deleted first.
+pub async fn list_trashed_warranties(pool: &PgPool, user_id: &str) -> Result<Vec<Warranty>> {
+    let warranties = sqlx::query_as::<_, Warranty>(
+        r#"
+        SELECT * FROM warranties
+        WHERE household_id IN (SELECT household_id FROM household_members WHERE user_id = $1)
+          AND deleted_at IS NOT NULL
+        ORDER BY deleted_at DESC, id ASC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
 
-    Ok(())
+    Ok(warranties)
+}
+
+pub async fn restore_warranty(
+    pool: &PgPool,
+    id: Uuid,
+    user_id: &str,
+    actor: &Actor,
+) -> Result<Warranty> {
+    let mut tx = pool.begin().await?;
+    let warranty = sqlx::query_as::<_, Warranty>(
+        r#"
+        UPDATE warranties SET deleted_at = NULL
+        WHERE id = $1
+          AND household_id IN (SELECT household_id FROM household_members WHERE user_id = $2)
+          AND deleted_at IS NOT NULL
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Warranty not found in trash".to_string()))?;
+
+    record_warranty_event(&mut tx, id, WarrantyAction::Restored, Vec::new(), actor).await?;
+    tx.commit().await?;
+
+    Ok(warranty)
+}
+
+/// Deletes up to `limit` warranties trashed before `deleted_before`, with
+/// their attachments, then removes their receipt and attachment blobs from
+/// storage. Returns how many were purged.
+pub async fn purge_trashed_warranties(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    deleted_before: DateTime<Utc>,
+    limit: i64,
+) -> Result<usize> {
+    let mut tx = pool.begin().await?;
+
+    // Skip rows another replica is purging, or that are being restored.
+    let ids: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT id FROM warranties
+        WHERE deleted_at < $1
+        ORDER BY deleted_at ASC
+        LIMIT $2
+        FOR UPDATE SKIP LOCKED
+        "#,
+    )
+    .bind(deleted_before)
+    .bind(limit)
+    .fetch_all(&mut *tx)
+    .await?;
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    let keys: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT storage_key FROM warranty_attachments WHERE warranty_id = ANY($1)
+        UNION ALL
+        SELECT key FROM warranties, UNNEST(ARRAY[receipt_key, receipt_thumbnail_key]) AS key
+        WHERE id = ANY($1) AND key IS NOT NULL
+        "#,
+    )
+    .bind(&ids)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    sqlx::query("DELETE FROM warranties WHERE id = ANY($1)")
+        .bind(&ids)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    storage::delete_blobs(storage, keys).await;
+
+    Ok(ids.len())
+}
+
+/// The warranties with the given ids, whoever owns them, unless trashed.
+pub async fn get_warranties_by_ids(conn: &mut PgConnection, ids: &[Uuid]) -> Result<Vec<Warranty>> {
+    let warranties = sqlx::query_as::<_, Warranty>(
+        "SELECT * FROM warranties WHERE id = ANY($1) AND deleted_at IS NULL",
+    )
+    .bind(ids)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(warranties)
+}
+
+/// Every warranty the user can see, in the order of `list_warranties`.
+pub async fn get_all_warranties(pool: &PgPool, user_id: &str) -> Result<Vec<Warranty>> {
+    let warranties = sqlx::query_as::<_, Warranty>(
+        r#"
+        SELECT * FROM warranties
+        WHERE household_id IN (SELECT household_id FROM household_members WHERE user_id = $1)
+          AND deleted_at IS NULL
+        ORDER BY covered_until ASC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(warranties)
 }
 
 pub async fn get_expiring_warranties(
@@ -244,8 +659,11 @@ pub async fn get_expiring_warranties(
     let warranties = sqlx::query_as::<_, Warranty>(
         r#"
         SELECT * FROM warranties 
-        WHERE user_id = $1 AND warranty_end_date > $2 AND warranty_end_date <= $3
-        ORDER BY warranty_end_date ASC
+        WHERE household_id IN (SELECT household_id FROM household_members WHERE user_id = $1)
+          AND deleted_at IS NULL
+          AND covered_until > $2
+          AND covered_until <= $3
+        ORDER BY covered_until ASC
         "#,
     )
     .bind(user_id)
@@ -257,26 +675,44 @@ pub async fn get_expiring_warranties(
     Ok(warranties)
 }
 
-pub async fn update_receipt_url(
+pub struct StoredReceipt<'a> {
+    pub url: &'a str,
+    pub key: &'a str,
+    pub content_type: &'a str,
+    pub thumbnail_url: Option<&'a str>,
+    pub thumbnail_key: Option<&'a str>,
+}
+
+pub async fn update_receipt(
     pool: &PgPool,
     id: Uuid,
     user_id: &str,
-    receipt_url: &str,
+    receipt: StoredReceipt<'_>,
+    actor: &Actor,
 ) -> Result<Warranty> {
+    let mut tx = pool.begin().await?;
+    let existing = lock_warranty(&mut tx, id, user_id).await?;
     let warranty = sqlx::query_as::<_, Warranty>(
         r#"
         UPDATE warranties 
-        SET receipt_url = $1, updated_at = NOW()
-        WHERE id = $2 AND user_id = $3
+        SET receipt_url = $1, receipt_key = $2, receipt_content_type = $3,
+            receipt_thumbnail_url = $4, receipt_thumbnail_key = $5, updated_at = NOW()
+        WHERE id = $6
         RETURNING *
         "#,
     )
-    .bind(receipt_url)
+    .bind(receipt.url)
+    .bind(receipt.key)
+    .bind(receipt.content_type)
+    .bind(receipt.thumbnail_url)
+    .bind(receipt.thumbnail_key)
     .bind(id)
-    .bind(user_id)
-    .fetch_optional(pool)
-    .await?
-    .ok_or_else(|| AppError::NotFound("Warranty not found".to_string()))?;
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let changes = audit::diff(Some(&existing), &warranty);
+    record_warranty_event(&mut tx, id, WarrantyAction::ReceiptUploaded, changes, actor).await?;
+    tx.commit().await?;
 
     Ok(warranty)
 }
@@ -285,21 +721,16 @@ pub async fn get_warranty_stats(pool: &PgPool, user_id: &str) -> Result<Warranty
     let now = Utc::now();
     let thirty_days = now + Duration::days(30);
 
-    let total: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM warranties WHERE user_id = $1")
-        .bind(user_id)
-        .fetch_one(pool)
-        .await?;
-
-    let active: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM warranties WHERE user_id = $1 AND warranty_end_date > $2",
-    )
-    .bind(user_id)
-    .bind(now)
-    .fetch_one(pool)
-    .await?;
-
-    let expiring_soon: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM warranties WHERE user_id = $1 AND warranty_end_date > $2 AND warranty_end_date <= $3",
+    let (total, active, expiring_soon, expired): (i64, i64, i64, i64) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*),
+               COUNT(*) FILTER (WHERE covered_until > $2),
+               COUNT(*) FILTER (WHERE covered_until > $2 AND covered_until <= $3),
+               COUNT(*) FILTER (WHERE covered_until <= $2)
+        FROM warranties
+        WHERE household_id IN (SELECT household_id FROM household_members WHERE user_id = $1)
+          AND deleted_at IS NULL
+        "#,
     )
     .bind(user_id)
     .bind(now)
@@ -307,19 +738,11 @@ pub async fn get_warranty_stats(pool: &PgPool, user_id: &str) -> Result<Warranty
     .fetch_one(pool)
     .await?;
 
-    let expired: (i64,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM warranties WHERE user_id = $1 AND warranty_end_date <= $2",
-    )
-    .bind(user_id)
-    .bind(now)
-    .fetch_one(pool)
-    .await?;
-
     Ok(WarrantyStats {
-        total: total.0,
-        active: active.0,
-        expiring_soon: expiring_soon.0,
-        expired: expired.0,
+        total,
+        active,
+        expiring_soon,
+        expired,
     })
 }
 
@@ -330,12 +753,3 @@ pub struct WarrantyStats {
     pub expiring_soon: i64,
     pub expired: i64,
 }
-
-fn get_status_date_range(status: &str) -> (chrono::DateTime<Utc>, chrono::DateTime<Utc>) {
-    let now = Utc::now();
-    match status {
-        "expiring_soon" => (now, now + Duration::days(30)),
-        "expired" => (chrono::DateTime::<Utc>::MIN_UTC, now),
-        _ => (now, chrono::DateTime::<Utc>::MAX_UTC),
-    }
-}
@@ -0,0 +1,49 @@
+use sqlx::types::Json;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::audit::Actor;
+use crate::error::Result;
+use crate::models::{FieldChange, WarrantyAction, WarrantyEvent};
+
+/// Records a change of a warranty in its history. Updates that changed no
+/// field are left out.
+pub async fn record_warranty_event(
+    conn: &mut PgConnection,
+    warranty_id: Uuid,
+    action: WarrantyAction,
+    changes: Vec<FieldChange>,
+    actor: &Actor,
+) -> Result<()> {
+    if action == WarrantyAction::Updated && changes.is_empty() {
+        return Ok(());
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO warranty_events (warranty_id, action, changes, actor_user_id, request_id)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(warranty_id)
+    .bind(action)
+    .bind(Json(changes))
+    .bind(&actor.user_id)
+    .bind(actor.request_id)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// The history of a warranty, oldest first.
+pub async fn list_warranty_events(pool: &PgPool, warranty_id: Uuid) -> Result<Vec<WarrantyEvent>> {
+    let events = sqlx::query_as::<_, WarrantyEvent>(
+        "SELECT * FROM warranty_events WHERE warranty_id = $1 ORDER BY created_at ASC, id ASC",
+    )
+    .bind(warranty_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(events)
+}
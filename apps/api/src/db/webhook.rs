@@ -0,0 +1,268 @@
+use chrono::Duration;
+use sqlx::types::Json;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::models::{
+    DeliveryStatus, PendingWebhookDelivery, Webhook, WebhookDelivery, WebhookEvent,
+};
+
+/// A delivery still `sending` after this long is assumed to belong to a
+/// dispatcher that died, and is handed out again.
+const STALE_DELIVERY_MINUTES: i32 = 10;
+
+/// An event to send to every active webhook of `user_id` subscribed to it.
+#[derive(Debug, Clone)]
+pub struct NewWebhookEvent {
+    pub user_id: String,
+    pub event_id: Uuid,
+    pub event: WebhookEvent,
+    pub payload: serde_json::Value,
+}
+
+/// What came of one attempt at a delivery.
+pub struct WebhookAttempt {
+    pub status: DeliveryStatus,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub duration_ms: i32,
+    /// Before the next attempt, when `status` is back to pending
+    pub retry_in: Option<Duration>,
+}
+
+pub async fn create_webhook(
+    pool: &PgPool,
+    user_id: &str,
+    url: &str,
+    description: Option<&str>,
+    secret: &str,
+    events: &[WebhookEvent],
+) -> Result<Webhook> {
+    let webhook = sqlx::query_as::<_, Webhook>(
+        r#"
+        INSERT INTO webhooks (user_id, url, description, secret, events)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(user_id)
+    .bind(url)
+    .bind(description)
+    .bind(secret)
+    .bind(events)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(webhook)
+}
+
+pub async fn count_webhooks(pool: &PgPool, user_id: &str) -> Result<i64> {
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM webhooks WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(count)
+}
+
+pub async fn list_webhooks(pool: &PgPool, user_id: &str) -> Result<Vec<Webhook>> {
+    let webhooks = sqlx::query_as::<_, Webhook>(
+        "SELECT * FROM webhooks WHERE user_id = $1 ORDER BY created_at ASC",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(webhooks)
+}
+
+pub async fn get_webhook(pool: &PgPool, id: Uuid, user_id: &str) -> Result<Webhook> {
+    sqlx::query_as::<_, Webhook>("SELECT * FROM webhooks WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook not found".to_string()))
+}
+
+/// Saves the editable fields of `webhook`.
+pub async fn update_webhook(pool: &PgPool, user_id: &str, webhook: &Webhook) -> Result<Webhook> {
+    sqlx::query_as::<_, Webhook>(
+        r#"
+        UPDATE webhooks
+        SET url = $1, description = $2, events = $3, active = $4, updated_at = NOW()
+        WHERE id = $5 AND user_id = $6
+        RETURNING *
+        "#,
+    )
+    .bind(&webhook.url)
+    .bind(&webhook.description)
+    .bind(&webhook.events)
+    .bind(webhook.active)
+    .bind(webhook.id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Webhook not found".to_string()))
+}
+
+pub async fn delete_webhook(pool: &PgPool, id: Uuid, user_id: &str) -> Result<()> {
+    let result = sqlx::query("DELETE FROM webhooks WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Webhook not found".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Queues one delivery per event and subscribed webhook. Expiry and
+/// maintenance events are reminders, so they also need the webhook channel
+/// in the user's reminder preferences.
+pub async fn enqueue_webhook_events(
+    conn: &mut PgConnection,
+    events: &[NewWebhookEvent],
+) -> Result<()> {
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let user_ids: Vec<&str> = events.iter().map(|e| e.user_id.as_str()).collect();
+    let event_ids: Vec<Uuid> = events.iter().map(|e| e.event_id).collect();
+    let kinds: Vec<WebhookEvent> = events.iter().map(|e| e.event).collect();
+    let payloads: Vec<Json<&serde_json::Value>> = events.iter().map(|e| Json(&e.payload)).collect();
+
+    sqlx::query(
+        r#"
+        INSERT INTO webhook_deliveries (webhook_id, event_id, event, payload)
+        SELECT w.id, e.event_id, e.event, e.payload
+        FROM UNNEST($1::varchar[], $2::uuid[], $3::webhook_event[], $4::jsonb[])
+            AS e(user_id, event_id, event, payload)
+        JOIN webhooks w ON w.user_id = e.user_id AND w.active AND e.event = ANY(w.events)
+        LEFT JOIN reminder_preferences p ON p.user_id = e.user_id
+        WHERE e.event NOT IN ('warranty.expiring', 'warranty.expired', 'maintenance.due')
+           OR p.channels IS NULL
+           OR 'webhook' = ANY(p.channels)
+        "#,
+    )
+    .bind(&user_ids)
+    .bind(&event_ids)
+    .bind(&kinds)
+    .bind(&payloads)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// The latest deliveries of a webhook, newest first.
+pub async fn list_webhook_deliveries(
+    pool: &PgPool,
+    webhook_id: Uuid,
+    limit: i64,
+) -> Result<Vec<WebhookDelivery>> {
+    let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        SELECT * FROM webhook_deliveries
+        WHERE webhook_id = $1
+        ORDER BY created_at DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(webhook_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(deliveries)
+}
+
+/// Queues the event of a past delivery again, as a new delivery.
+pub async fn redeliver_webhook_delivery(
+    pool: &PgPool,
+    webhook_id: Uuid,
+    delivery_id: Uuid,
+) -> Result<WebhookDelivery> {
+    sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        INSERT INTO webhook_deliveries (webhook_id, event_id, event, payload, redelivery_of)
+        SELECT webhook_id, event_id, event, payload, id
+        FROM webhook_deliveries
+        WHERE id = $1 AND webhook_id = $2
+        RETURNING *
+        "#,
+    )
+    .bind(delivery_id)
+    .bind(webhook_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Webhook delivery not found".to_string()))
+}
+
+/// Hands the oldest due delivery of an active webhook to the calling
+/// dispatcher, with `SKIP LOCKED` so replicas never send it twice.
+pub async fn claim_webhook_delivery(pool: &PgPool) -> Result<Option<PendingWebhookDelivery>> {
+    let delivery = sqlx::query_as::<_, PendingWebhookDelivery>(
+        r#"
+        WITH claimed AS (
+            UPDATE webhook_deliveries
+            SET status = 'sending', attempts = attempts + 1, started_at = NOW()
+            WHERE id = (
+                SELECT d.id FROM webhook_deliveries d
+                JOIN webhooks w ON w.id = d.webhook_id
+                WHERE w.active
+                  AND ((d.status = 'pending' AND d.run_after <= NOW())
+                    OR (d.status = 'sending'
+                        AND d.started_at < NOW() - make_interval(mins => $1)))
+                ORDER BY d.run_after ASC
+                FOR UPDATE OF d SKIP LOCKED
+                LIMIT 1
+            )
+            RETURNING *
+        )
+        SELECT c.id, c.webhook_id, w.url, w.secret, c.event_id, c.event, c.payload, c.attempts
+        FROM claimed c
+        JOIN webhooks w ON w.id = c.webhook_id
+        "#,
+    )
+    .bind(STALE_DELIVERY_MINUTES)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(delivery)
+}
+
+pub async fn record_webhook_attempt(
+    pool: &PgPool,
+    id: Uuid,
+    attempt: &WebhookAttempt,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE webhook_deliveries
+        SET status = $1, response_status = $2, last_error = $3, duration_ms = $4,
+            run_after = NOW() + make_interval(secs => $5),
+            delivered_at = CASE WHEN $1 = 'sent'::delivery_status THEN NOW() END
+        WHERE id = $6
+        "#,
+    )
+    .bind(attempt.status)
+    .bind(attempt.response_status)
+    .bind(&attempt.error)
+    .bind(attempt.duration_ms)
+    .bind(
+        attempt
+            .retry_in
+            .map_or(0.0, |delay| delay.num_seconds() as f64),
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
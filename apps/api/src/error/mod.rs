@@ -14,12 +14,30 @@ pub enum AppError {
     #[error("Unauthorized")]
     Unauthorized,
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
     #[allow(dead_code)]
     #[error("Too many requests")]
     TooManyRequests,
 
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("Unsupported media type: {0}")]
+    UnsupportedMediaType(String),
+
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
+
+    #[error("Storage error: {0}")]
+    Storage(String),
+
+    #[error("Internal error: {0}")]
+    Internal(String),
 }
 
 #[derive(Serialize, ToSchema)]
@@ -38,11 +56,23 @@ impl IntoResponse for AppError {
                 "unauthorized",
                 "Invalid or missing authentication".to_string(),
             ),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, "forbidden", msg.clone()),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, "conflict", msg.clone()),
             AppError::TooManyRequests => (
                 StatusCode::TOO_MANY_REQUESTS,
                 "too_many_requests",
                 "Rate limit exceeded. Please try again later.".to_string(),
             ),
+            AppError::PayloadTooLarge(msg) => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "payload_too_large",
+                msg.clone(),
+            ),
+            AppError::UnsupportedMediaType(msg) => (
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "unsupported_media_type",
+                msg.clone(),
+            ),
             AppError::Database(e) => {
                 tracing::error!("Database error: {:?}", e);
                 (
@@ -51,6 +81,22 @@ impl IntoResponse for AppError {
                     "Database operation failed".to_string(),
                 )
             }
+            AppError::Storage(e) => {
+                tracing::error!("Storage error: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "storage_error",
+                    "File storage operation failed".to_string(),
+                )
+            }
+            AppError::Internal(e) => {
+                tracing::error!("Internal error: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "internal_error",
+                    "An unexpected error occurred".to_string(),
+                )
+            }
         };
 
         let body = Json(ErrorResponse {
@@ -0,0 +1,253 @@
+//! Reads the PDF invoices that e-commerce sites generate.
+//!
+//! Their text layer keeps the item table intact, so unlike a photographed
+//! receipt every line item can be read on its own: each one becomes a
+//! separate suggestion sharing the store, date and invoice number.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::extraction::receipt::{
+    self, amounts, apply_product, clean_description, contains_words, fold, letter_count, Line,
+};
+use crate::models::WarrantySuggestion;
+
+/// Where a merchant's item table sits and how its columns are titled. All
+/// words are accent-folded.
+struct Template {
+    /// As returned in `WarrantySuggestion::store`.
+    store: &'static str,
+    /// Titles of the product name column.
+    description_column: &'static [&'static str],
+    /// Titles of the quantity column.
+    quantity_column: &'static [&'static str],
+    /// Lines starting with these close the table.
+    table_end: &'static [&'static str],
+    /// Lines with these words are notes about the item above, never part of
+    /// its name.
+    notes: &'static [&'static str],
+}
+
+const TEMPLATES: &[Template] = &[
+    Template {
+        store: "Amazon",
+        description_column: &["description"],
+        quantity_column: &["qte"],
+        table_end: &["frais de livraison", "total"],
+        notes: &["asin", "vendu par", "expedie par"],
+    },
+    Template {
+        store: "Fnac",
+        description_column: &["designation"],
+        quantity_column: &["qte"],
+        table_end: &["total"],
+        notes: &["ref", "vendu par", "expedie par"],
+    },
+    Template {
+        store: "Darty",
+        description_column: &["designation"],
+        quantity_column: &["qte"],
+        table_end: &["total"],
+        notes: &["ref"],
+    },
+    Template {
+        store: "Cdiscount",
+        description_column: &["produit"],
+        quantity_column: &["quantite"],
+        table_end: &["frais de port", "total"],
+        notes: &["vendu par", "vendu et expedie par", "expedie par"],
+    },
+    Template {
+        store: "Apple",
+        description_column: &["description"],
+        quantity_column: &["qte"],
+        table_end: &["total", "sous total"],
+        notes: &[],
+    },
+];
+
+/// Any other invoice with a titled item table.
+const GENERIC: Template = Template {
+    store: "",
+    description_column: &[
+        "description",
+        "designation",
+        "article",
+        "produit",
+        "libelle",
+    ],
+    quantity_column: &["qte", "quantite", "qty", "quantity"],
+    table_end: &[
+        "total",
+        "sous total",
+        "montant total",
+        "net a payer",
+        "frais de port",
+        "frais de livraison",
+    ],
+    notes: &["ref", "ean", "asin", "vendu par", "expedie par"],
+};
+
+/// Rows with any of these words are charges or services, not something to
+/// keep a warranty for.
+const NON_ITEM_WORDS: &[&str] = &[
+    "eco",
+    "ecotaxe",
+    "ecoparticipation",
+    "participation",
+    "garantie",
+    "extension",
+    "assurance",
+    "applecare",
+    "remise",
+    "reduction",
+    "livraison",
+    "frais",
+    "port",
+    "avoir",
+    "coupon",
+];
+
+static COLUMN_GAP: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s{2,}").unwrap());
+
+struct Item {
+    description: String,
+    cents: i64,
+    confidence: f32,
+}
+
+/// Reads one suggestion per line item. Falls back to the receipt heuristics,
+/// and a single suggestion, when no item table is found.
+pub fn parse_invoice(text: &str) -> Vec<WarrantySuggestion> {
+    let lines = receipt::lines(text);
+    let shared = receipt::document_fields(&lines);
+
+    let template = shared
+        .store
+        .as_deref()
+        .and_then(|store| TEMPLATES.iter().find(|t| t.store == store));
+    let mut items = template
+        .map(|template| read_items(template, &lines))
+        .unwrap_or_default();
+    if items.is_empty() {
+        items = read_items(&GENERIC, &lines);
+    }
+    if items.is_empty() {
+        return vec![receipt::parse_receipt(text)];
+    }
+
+    items
+        .into_iter()
+        .map(|item| {
+            let mut suggestion = shared.clone();
+            suggestion.price_cents = Some(item.cents);
+            suggestion.confidence.insert("price_cents".to_string(), 0.9);
+            apply_product(&mut suggestion, &item.description, item.confidence);
+            suggestion
+        })
+        .collect()
+}
+
+fn read_items(template: &Template, lines: &[Line]) -> Vec<Item> {
+    let Some(header) = lines.iter().position(|line| {
+        let columns = cells(&line.raw);
+        column(&columns, template.description_column).is_some()
+            && column(&columns, template.quantity_column).is_some()
+    }) else {
+        return Vec::new();
+    };
+    let columns = cells(&lines[header].raw);
+    let description_column = column(&columns, template.description_column);
+    let quantity_column = column(&columns, template.quantity_column);
+
+    let mut items: Vec<Item> = Vec::new();
+    // Text lines since the last priced row: either the end of a wrapped name
+    // or a name printed above its price.
+    let mut pending: Vec<String> = Vec::new();
+    // Whether the last priced row became an item that a wrapped name may
+    // continue.
+    let mut continues = false;
+
+    for line in &lines[header + 1..] {
+        if template
+            .table_end
+            .iter()
+            .any(|end| line.folded.starts_with(end))
+        {
+            break;
+        }
+        if template.notes.iter().any(|note| line.has_word(note)) {
+            continue;
+        }
+
+        let line_amounts = amounts(&line.raw);
+        let (Some(&(first, _)), Some(&(_, total))) = (line_amounts.first(), line_amounts.last())
+        else {
+            let text = clean_description(&line.raw);
+            if letter_count(&text) > 0 && !is_non_item(&text) {
+                pending.push(text);
+            }
+            continue;
+        };
+
+        let row = cells(&line.raw);
+        let (mut description, quantity, confidence) = match description_column {
+            // A cell holds the name alone, trailing model numbers included.
+            Some(index) if row.len() == columns.len() => (
+                row[index].split_whitespace().collect::<Vec<_>>().join(" "),
+                quantity_column.and_then(|index| row[index].trim().parse::<i64>().ok()),
+                0.9,
+            ),
+            // Cells are missing, so the columns cannot be trusted.
+            _ => (clean_description(&line.raw[..first]), None, 0.75),
+        };
+
+        if letter_count(&description) < 3 {
+            description = pending.join(" ");
+        } else if continues && !pending.is_empty() {
+            if let Some(previous) = items.last_mut() {
+                previous.description = format!("{} {}", previous.description, pending.join(" "));
+            }
+        }
+        pending.clear();
+
+        continues = total > 0 && letter_count(&description) >= 3 && !is_non_item(&description);
+        if !continues {
+            continue;
+        }
+        let quantity = quantity.filter(|q| *q > 0).unwrap_or(1);
+        items.push(Item {
+            description,
+            cents: (total + quantity / 2) / quantity,
+            confidence,
+        });
+    }
+
+    if let Some(last) = items
+        .last_mut()
+        .filter(|_| continues && !pending.is_empty())
+    {
+        last.description = format!("{} {}", last.description, pending.join(" "));
+    }
+
+    items
+}
+
+fn cells(raw: &str) -> Vec<&str> {
+    COLUMN_GAP.split(raw.trim()).collect()
+}
+
+fn column(columns: &[&str], titles: &[&str]) -> Option<usize> {
+    columns.iter().position(|cell| {
+        let folded = fold(cell);
+        titles.iter().any(|title| folded.starts_with(title))
+    })
+}
+
+fn is_non_item(description: &str) -> bool {
+    let folded = fold(description);
+    NON_ITEM_WORDS
+        .iter()
+        .any(|word| contains_words(&folded, word))
+}
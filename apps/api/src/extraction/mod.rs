@@ -0,0 +1,135 @@
+mod invoice;
+mod ocr;
+mod pdf;
+mod receipt;
+#[cfg(test)]
+mod tests;
+
+pub use invoice::parse_invoice;
+pub use ocr::{TesseractRecognizer, TextRecognizer};
+pub use pdf::text_layer;
+pub use receipt::parse_receipt;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use sqlx::PgPool;
+
+use crate::db;
+use crate::error::{AppError, Result};
+use crate::models::{ReceiptExtraction, WarrantySuggestion};
+use crate::storage::{self, Storage};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const MAX_ATTEMPTS: i32 = 3;
+
+/// Reads the text of a receipt and turns it into warranty suggestions.
+/// Generated PDFs are read from their text layer, one suggestion per line
+/// item; scans and photos go through OCR.
+pub async fn extract(
+    recognizer: &dyn TextRecognizer,
+    content_type: &str,
+    data: &[u8],
+) -> Result<(String, Vec<WarrantySuggestion>)> {
+    if content_type == "application/pdf" {
+        if let Some(text) = text_layer(data.to_vec()).await {
+            let suggestions = parse_invoice(&text);
+            return Ok((text, suggestions));
+        }
+    }
+
+    let text = recognizer.recognize(content_type, data).await?;
+    let suggestion = parse_receipt(&text);
+    Ok((text, vec![suggestion]))
+}
+
+/// Starts the background task that drains the `receipt_extractions` queue.
+/// Every replica can run one: jobs are claimed with `SKIP LOCKED`.
+pub fn spawn_worker(
+    pool: PgPool,
+    storage: Arc<dyn Storage>,
+    recognizer: Arc<dyn TextRecognizer>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            match db::claim_extraction(&pool).await {
+                Ok(Some(job)) => run_job(&pool, storage.as_ref(), recognizer.as_ref(), job).await,
+                Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+                Err(e) => {
+                    tracing::warn!(error = %e, "failed to claim extraction job");
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
+        }
+    })
+}
+
+async fn run_job(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    recognizer: &dyn TextRecognizer,
+    job: ReceiptExtraction,
+) {
+    let result = async {
+        let data = storage.get(&job.storage_key).await?.into_bytes().await?;
+        extract(recognizer, &job.content_type, &data).await
+    }
+    .await;
+
+    let recorded = match result {
+        Ok((text, suggestions)) => {
+            tracing::info!(extraction_id = %job.id, "receipt extraction completed");
+            db::complete_extraction(pool, job.id, &text, &suggestions)
+                .await
+                .map(|_| true)
+        }
+        Err(e) if is_transient(&e) && job.attempts < MAX_ATTEMPTS => {
+            tracing::warn!(
+                extraction_id = %job.id,
+                attempt = job.attempts,
+                error = %e,
+                "receipt extraction failed, will retry"
+            );
+            let delay = chrono::Duration::seconds(30 * 2i64.pow(job.attempts as u32));
+            db::retry_extraction(pool, job.id, &failure_message(&e), delay)
+                .await
+                .map(|_| false)
+        }
+        Err(e) => {
+            tracing::warn!(extraction_id = %job.id, error = %e, "receipt extraction failed");
+            db::fail_extraction(pool, job.id, &failure_message(&e))
+                .await
+                .map(|_| true)
+        }
+    };
+
+    match recorded {
+        // Standalone uploads are only kept until they have been read.
+        Ok(true) if job.warranty_id.is_none() => {
+            storage::delete_blobs(storage, [job.storage_key]).await;
+        }
+        Ok(_) => {}
+        Err(e) => {
+            tracing::error!(extraction_id = %job.id, error = %e, "failed to record extraction result");
+        }
+    }
+}
+
+/// Missing tools, storage hiccups and database errors may go away on their
+/// own; a file we cannot read will not.
+fn is_transient(error: &AppError) -> bool {
+    matches!(
+        error,
+        AppError::Internal(_) | AppError::Storage(_) | AppError::Database(_)
+    )
+}
+
+/// What the user gets to see: client errors carry their own message, the
+/// rest stays in the logs.
+fn failure_message(error: &AppError) -> String {
+    match error {
+        AppError::NotFound(_) => "Receipt file not found".to_string(),
+        AppError::BadRequest(msg) | AppError::UnsupportedMediaType(msg) => msg.clone(),
+        _ => "Text recognition failed".to_string(),
+    }
+}
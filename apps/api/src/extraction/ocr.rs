@@ -0,0 +1,136 @@
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+
+/// Turns a stored receipt (image or PDF) into plain text.
+#[async_trait]
+pub trait TextRecognizer: Send + Sync {
+    async fn recognize(&self, content_type: &str, data: &[u8]) -> Result<String>;
+}
+
+/// Runs Tesseract locally, so receipts never leave the server. PDFs are first
+/// rasterized with Poppler's `pdftoppm`.
+pub struct TesseractRecognizer {
+    pub command: String,
+    pub languages: String,
+    pub pdf_rasterize_command: String,
+}
+
+/// Only the first pages of a PDF are read; receipts rarely go further and
+/// OCR time grows linearly with the page count.
+const MAX_PDF_PAGES: u32 = 3;
+
+#[async_trait]
+impl TextRecognizer for TesseractRecognizer {
+    async fn recognize(&self, content_type: &str, data: &[u8]) -> Result<String> {
+        let dir = std::env::temp_dir().join(format!("garry-ocr-{}", Uuid::new_v4()));
+        tokio::fs::create_dir(&dir)
+            .await
+            .map_err(|e| AppError::Internal(format!("create {}: {}", dir.display(), e)))?;
+
+        let result = self.recognize_in(&dir, content_type, data).await;
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        result
+    }
+}
+
+impl TesseractRecognizer {
+    async fn recognize_in(&self, dir: &Path, content_type: &str, data: &[u8]) -> Result<String> {
+        let input = dir.join(format!(
+            "input.{}",
+            crate::storage::extension_for(content_type)
+        ));
+        tokio::fs::write(&input, data)
+            .await
+            .map_err(|e| AppError::Internal(format!("write {}: {}", input.display(), e)))?;
+
+        let pages = match content_type {
+            "application/pdf" => self.rasterize_pdf(dir, &input).await?,
+            "image/jpeg" | "image/png" | "image/webp" => vec![input],
+            other => {
+                return Err(AppError::UnsupportedMediaType(format!(
+                    "Text cannot be read from {}",
+                    other
+                )))
+            }
+        };
+
+        let mut text = String::new();
+        for page in pages {
+            let output = run(
+                tokio::process::Command::new(&self.command)
+                    .arg(&page)
+                    .arg("stdout")
+                    .args(["-l", &self.languages])
+                    // A single column of text of variable sizes, which suits
+                    // till receipts and invoices alike.
+                    .args(["--psm", "4"]),
+                &self.command,
+            )
+            .await?;
+            text.push_str(&String::from_utf8_lossy(&output));
+            text.push('\n');
+        }
+
+        Ok(text)
+    }
+
+    async fn rasterize_pdf(&self, dir: &Path, input: &Path) -> Result<Vec<PathBuf>> {
+        run(
+            tokio::process::Command::new(&self.pdf_rasterize_command)
+                .args(["-r", "300", "-png", "-l", &MAX_PDF_PAGES.to_string()])
+                .arg(input)
+                .arg(dir.join("page")),
+            &self.pdf_rasterize_command,
+        )
+        .await?;
+
+        let mut pages = Vec::new();
+        let mut entries = tokio::fs::read_dir(dir)
+            .await
+            .map_err(|e| AppError::Internal(format!("read {}: {}", dir.display(), e)))?;
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            let is_page = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("page") && name.ends_with(".png"));
+            if is_page {
+                pages.push(path);
+            }
+        }
+        // pdftoppm zero-pads page numbers, so name order is page order.
+        pages.sort();
+
+        if pages.is_empty() {
+            return Err(AppError::UnsupportedMediaType(
+                "PDF has no readable pages".to_string(),
+            ));
+        }
+        Ok(pages)
+    }
+}
+
+async fn run(command: &mut tokio::process::Command, name: &str) -> Result<Vec<u8>> {
+    let output = command
+        .stdin(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .map_err(|e| AppError::Internal(format!("failed to run {}: {}", name, e)))?;
+
+    if !output.status.success() {
+        return Err(AppError::Internal(format!(
+            "{} exited with {}: {}",
+            name,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(output.stdout)
+}
@@ -0,0 +1,107 @@
+use pdf_extract::{Document, MediaBox, OutputDev, OutputError, Transform};
+
+/// Below this many letters and digits a PDF is taken for a scan and sent to
+/// OCR instead.
+const MIN_TEXT_CHARS: usize = 40;
+
+/// Reads the text layer of a generated PDF, laid out line by line with wide
+/// gaps between table columns. Returns `None` for scans and unreadable files.
+pub async fn text_layer(data: Vec<u8>) -> Option<String> {
+    // The parser can panic on malformed files; a panic in a blocking task is
+    // caught by the join handle.
+    let result = tokio::task::spawn_blocking(move || {
+        let mut doc = Document::load_mem(&data)?;
+        if doc.is_encrypted() {
+            doc.decrypt("")?;
+        }
+        let mut output = LayoutText::default();
+        pdf_extract::output_doc(&doc, &mut output)?;
+        Ok::<_, OutputError>(output.text)
+    })
+    .await;
+
+    let text = match result {
+        Ok(Ok(text)) => text,
+        Ok(Err(e)) => {
+            tracing::debug!(error = %e, "PDF text layer could not be read");
+            return None;
+        }
+        Err(e) => {
+            tracing::debug!(error = %e, "PDF text layer extraction panicked");
+            return None;
+        }
+    };
+
+    let readable = text.chars().filter(|c| c.is_alphanumeric()).count();
+    (readable >= MIN_TEXT_CHARS).then_some(text)
+}
+
+/// Writes glyphs in reading order like `pdf_extract::PlainTextOutput`, but
+/// keeps a column gap as several spaces so table cells stay apart.
+#[derive(Default)]
+struct LayoutText {
+    text: String,
+    last_end: f64,
+    last_y: f64,
+    started: bool,
+}
+
+impl OutputDev for LayoutText {
+    fn begin_page(
+        &mut self,
+        _page_num: u32,
+        _media_box: &MediaBox,
+        _art_box: Option<(f64, f64, f64, f64)>,
+    ) -> Result<(), OutputError> {
+        if self.started {
+            self.text.push('\n');
+        }
+        self.started = false;
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn output_character(
+        &mut self,
+        trm: &Transform,
+        width: f64,
+        _spacing: f64,
+        font_size: f64,
+        char: &str,
+    ) -> Result<(), OutputError> {
+        let size = font_size * (trm.m11 * trm.m22 - trm.m12 * trm.m21).abs().sqrt();
+        let (x, y) = (trm.m31, trm.m32);
+
+        if self.started {
+            if (y - self.last_y).abs() > size * 0.5 {
+                self.text.push('\n');
+            } else if x > self.last_end + size * 0.6 {
+                // Wider than a word space: the next table cell.
+                self.text.push_str("    ");
+            } else if x > self.last_end + size * 0.1 {
+                self.text.push(' ');
+            }
+        }
+
+        self.text.push_str(char);
+        self.started = true;
+        self.last_y = y;
+        self.last_end = x + width * size;
+        Ok(())
+    }
+
+    fn begin_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_line(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,758 @@
+//! Heuristics that read warranty fields out of the text of a French receipt.
+//!
+//! The text usually comes from OCR, so nothing here relies on exact layout:
+//! each field is found from keywords on the same line and scored by how
+//! specific that keyword is.
+
+use std::sync::LazyLock;
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use regex::Regex;
+
+use crate::models::{WarrantyCategory, WarrantySuggestion};
+
+/// Known retailers, matched on whole words of the accent-folded text.
+const MERCHANTS: &[(&str, &str)] = &[
+    ("amazon", "Amazon"),
+    ("fnac", "Fnac"),
+    ("darty", "Darty"),
+    ("boulanger", "Boulanger"),
+    ("cdiscount", "Cdiscount"),
+    ("apple", "Apple"),
+    ("leroy merlin", "Leroy Merlin"),
+    ("castorama", "Castorama"),
+    ("ikea", "IKEA"),
+    ("decathlon", "Decathlon"),
+    ("intersport", "Intersport"),
+    ("go sport", "Go Sport"),
+    ("conforama", "Conforama"),
+    ("maisons du monde", "Maisons du Monde"),
+    ("carrefour", "Carrefour"),
+    ("auchan", "Auchan"),
+    ("leclerc", "E.Leclerc"),
+    ("intermarche", "Intermarché"),
+    ("lidl", "Lidl"),
+    ("ldlc", "LDLC"),
+    ("materiel net", "Materiel.net"),
+    ("rue du commerce", "Rue du Commerce"),
+    ("electro depot", "Electro Dépôt"),
+    ("norauto", "Norauto"),
+    ("feu vert", "Feu Vert"),
+];
+
+/// Brands as they should be written, matched on whole words of the product.
+const BRANDS: &[&str] = &[
+    "Apple",
+    "Samsung",
+    "LG",
+    "Sony",
+    "Philips",
+    "Panasonic",
+    "Toshiba",
+    "Sharp",
+    "Hisense",
+    "TCL",
+    "Thomson",
+    "Bosch",
+    "Siemens",
+    "Miele",
+    "Whirlpool",
+    "Electrolux",
+    "AEG",
+    "Beko",
+    "Brandt",
+    "Candy",
+    "Hoover",
+    "Indesit",
+    "Haier",
+    "Smeg",
+    "Dyson",
+    "Seb",
+    "Moulinex",
+    "Tefal",
+    "Rowenta",
+    "Krups",
+    "Calor",
+    "Nespresso",
+    "DeLonghi",
+    "Kenwood",
+    "KitchenAid",
+    "Babyliss",
+    "Braun",
+    "iRobot",
+    "Roborock",
+    "Karcher",
+    "Xiaomi",
+    "Huawei",
+    "Google",
+    "Microsoft",
+    "OnePlus",
+    "Oppo",
+    "Motorola",
+    "Nokia",
+    "HP",
+    "Lenovo",
+    "Asus",
+    "Acer",
+    "Dell",
+    "MSI",
+    "Nintendo",
+    "Canon",
+    "Nikon",
+    "Fujifilm",
+    "GoPro",
+    "Epson",
+    "Brother",
+    "Bose",
+    "JBL",
+    "Sonos",
+    "Marshall",
+    "Garmin",
+    "Fitbit",
+    "Logitech",
+];
+
+/// Product lines that give away their brand.
+const MODEL_BRANDS: &[(&str, &str)] = &[
+    ("iphone", "Apple"),
+    ("ipad", "Apple"),
+    ("macbook", "Apple"),
+    ("imac", "Apple"),
+    ("airpods", "Apple"),
+    ("galaxy", "Samsung"),
+    ("pixel", "Google"),
+    ("playstation", "Sony"),
+    ("ps5", "Sony"),
+    ("xbox", "Microsoft"),
+    ("surface", "Microsoft"),
+    ("thermomix", "Vorwerk"),
+];
+
+/// Keywords of the accent-folded product name, checked in order.
+const CATEGORY_KEYWORDS: &[(WarrantyCategory, &[&str])] = &[
+    (
+        WarrantyCategory::Appliances,
+        &[
+            "lave linge",
+            "lave vaisselle",
+            "seche linge",
+            "refrigerateur",
+            "frigo",
+            "congelateur",
+            "four",
+            "micro ondes",
+            "aspirateur",
+            "cafetiere",
+            "expresso",
+            "machine a cafe",
+            "bouilloire",
+            "grille pain",
+            "robot",
+            "plaque",
+            "hotte",
+            "cuisiniere",
+            "climatiseur",
+            "fer a repasser",
+            "centrale vapeur",
+            "friteuse",
+            "blender",
+            "mixeur",
+            "seche cheveux",
+        ],
+    ),
+    (
+        WarrantyCategory::Electronics,
+        &[
+            "televiseur",
+            "tv",
+            "smartphone",
+            "telephone",
+            "iphone",
+            "ordinateur",
+            "pc",
+            "macbook",
+            "tablette",
+            "ipad",
+            "casque",
+            "ecouteurs",
+            "airpods",
+            "enceinte",
+            "barre de son",
+            "console",
+            "appareil photo",
+            "objectif",
+            "camera",
+            "montre connectee",
+            "imprimante",
+            "ecran",
+            "moniteur",
+            "videoprojecteur",
+        ],
+    ),
+    (
+        WarrantyCategory::Furniture,
+        &[
+            "canape", "fauteuil", "table", "chaise", "lit", "matelas", "armoire", "commode",
+            "bureau", "etagere", "buffet",
+        ],
+    ),
+    (
+        WarrantyCategory::Sports,
+        &[
+            "velo",
+            "trottinette",
+            "tapis de course",
+            "rameur",
+            "raquette",
+            "ski",
+            "snowboard",
+        ],
+    ),
+    (
+        WarrantyCategory::Clothing,
+        &[
+            "veste",
+            "manteau",
+            "blouson",
+            "chaussures",
+            "baskets",
+            "pantalon",
+            "jean",
+            "robe",
+        ],
+    ),
+    (
+        WarrantyCategory::Automotive,
+        &["pneu", "pneus", "autoradio", "batterie auto", "dashcam"],
+    ),
+];
+
+/// Lines with any of these words are never the purchased product.
+const NON_PRODUCT_WORDS: &[&str] = &[
+    "total",
+    "tva",
+    "ht",
+    "remise",
+    "reduction",
+    "livraison",
+    "frais",
+    "eco",
+    "ecotaxe",
+    "ecoparticipation",
+    "garantie",
+    "extension",
+    "adherent",
+    "carte",
+    "cb",
+    "visa",
+    "mastercard",
+    "paiement",
+    "reglement",
+    "paye",
+    "rendu",
+    "especes",
+    "avoir",
+    "acompte",
+    "fidelite",
+    "points",
+    "dont",
+];
+
+const HEADER_LINES: usize = 6;
+
+static AMOUNT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(\d{1,3}(?:[ \u{a0}.]\d{3})+|\d+)[,.](\d{2})").unwrap());
+static NUMERIC_DATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(\d{1,2})[/.\-](\d{1,2})[/.\-](\d{4}|\d{2})\b").unwrap());
+static ISO_DATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(\d{4})-(\d{2})-(\d{2})\b").unwrap());
+static TEXT_DATE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\b(\d{1,2})(?:er)? (janv(?:ier)?|fevr?(?:ier)?|mars|avr(?:il)?|mai|juin|juil(?:let)?|aout|sept?(?:embre)?|oct(?:obre)?|nov(?:embre)?|dec(?:embre)?)\.? (\d{4})\b",
+    )
+    .unwrap()
+});
+static INVOICE_NUMBER: LazyLock<[Regex; 2]> = LazyLock::new(|| {
+    [
+        Regex::new(
+            r"(?i)\b(?:n[°º]|no\.?|num[ée]ro|number)\s*(?:de\s+(?:la\s+)?)?(?:facture|invoice)\s*:?\s*([A-Z0-9][A-Z0-9/-]{3,})",
+        )
+        .unwrap(),
+        Regex::new(
+            r"(?i)\b(?:facture|invoice)\s*(?:n[°º]|no\.?|num[ée]ro|number|#)\s*:?\s*([A-Z0-9][A-Z0-9/-]{3,})",
+        )
+        .unwrap(),
+    ]
+});
+static ORDER_NUMBER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b(?:n[°º]|no\.?|num[ée]ro)\s*(?:de\s+(?:la\s+)?)?commande\s*:?\s*([A-Z0-9][A-Z0-9/-]{3,})",
+    )
+    .unwrap()
+});
+static WARRANTY_DURATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"garantie[^0-9]{0,40}?(\d{1,2}) ?(ans?|mois)\b").unwrap());
+static REFERENCE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:r[ée]f\b\.?|ean\b|sku\b|asin\b|code\b)\s*:?\s*\S+").unwrap()
+});
+static QUANTITY_PREFIX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\d{1,3}\s*[xX×]\s+").unwrap());
+static QUANTITY_SUFFIX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:\s+\d{1,3})+\s*$").unwrap());
+
+pub(crate) struct Line {
+    /// The line as read, keeping column spacing so a quantity is not taken
+    /// for the thousands of the price next to it.
+    pub(crate) raw: String,
+    /// Whitespace collapsed.
+    pub(crate) text: String,
+    /// Lowercase, without accents, punctuation collapsed to single spaces.
+    pub(crate) folded: String,
+}
+
+impl Line {
+    pub(crate) fn new(raw: &str) -> Self {
+        let text = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+        let folded = fold(&text);
+        Self {
+            raw: raw.trim().to_string(),
+            text,
+            folded,
+        }
+    }
+
+    pub(crate) fn has_word(&self, word: &str) -> bool {
+        contains_words(&self.folded, word)
+    }
+}
+
+/// Splits text into its non-blank lines.
+pub(crate) fn lines(text: &str) -> Vec<Line> {
+    text.lines()
+        .map(Line::new)
+        .filter(|line| !line.text.is_empty())
+        .collect()
+}
+
+/// Reads the fields of a `CreateWarrantyRequest` out of receipt text.
+pub fn parse_receipt(text: &str) -> WarrantySuggestion {
+    let lines = lines(text);
+    let mut suggestion = document_fields(&lines);
+
+    let total = find_total(&lines);
+    if let Some((_, cents, confidence)) = total {
+        suggestion.price_cents = Some(cents);
+        suggestion
+            .confidence
+            .insert("price_cents".to_string(), confidence);
+    }
+
+    let items_end = total.map(|(index, _, _)| index).unwrap_or(lines.len());
+    if let Some((product, confidence)) = find_product(&lines[..items_end]) {
+        apply_product(&mut suggestion, &product, confidence);
+    }
+
+    suggestion
+}
+
+/// The fields printed once per document, whatever was bought: store, date,
+/// invoice number and warranty length.
+pub(crate) fn document_fields(lines: &[Line]) -> WarrantySuggestion {
+    let mut suggestion = WarrantySuggestion::default();
+
+    if let Some((store, confidence)) = find_store(lines) {
+        suggestion.store = Some(store);
+        suggestion
+            .confidence
+            .insert("store".to_string(), confidence);
+    }
+    if let Some((date, confidence)) = find_purchase_date(lines) {
+        suggestion.purchase_date = Some(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap()));
+        suggestion
+            .confidence
+            .insert("purchase_date".to_string(), confidence);
+    }
+    if let Some((number, confidence)) = find_invoice_number(lines) {
+        suggestion.invoice_number = Some(number);
+        suggestion
+            .confidence
+            .insert("invoice_number".to_string(), confidence);
+    }
+    if let Some((months, confidence)) = find_warranty_months(lines) {
+        suggestion.warranty_months = Some(months);
+        suggestion
+            .confidence
+            .insert("warranty_months".to_string(), confidence);
+    }
+
+    suggestion
+}
+
+/// Fills in the product name, and the brand and category it gives away.
+pub(crate) fn apply_product(suggestion: &mut WarrantySuggestion, product: &str, confidence: f32) {
+    let folded = fold(product);
+
+    if let Some((brand, brand_confidence)) = find_brand(&folded) {
+        suggestion.brand = Some(brand.to_string());
+        suggestion
+            .confidence
+            .insert("brand".to_string(), brand_confidence);
+    }
+    if let Some(category) = find_category(&folded) {
+        suggestion.category = Some(category);
+        suggestion.confidence.insert("category".to_string(), 0.7);
+    }
+
+    suggestion.product_name = Some(tidy_case(product));
+    suggestion
+        .confidence
+        .insert("product_name".to_string(), confidence);
+}
+
+fn find_store(lines: &[Line]) -> Option<(String, f32)> {
+    let find_in = |lines: &[Line]| {
+        MERCHANTS.iter().find_map(|(needle, name)| {
+            lines
+                .iter()
+                .any(|line| line.has_word(needle))
+                .then(|| name.to_string())
+        })
+    };
+
+    let header = &lines[..lines.len().min(HEADER_LINES)];
+    if let Some(store) = find_in(header) {
+        return Some((store, 0.95));
+    }
+    if let Some(store) = find_in(lines) {
+        return Some((store, 0.6));
+    }
+
+    // Receipts start with the shop name more often than not.
+    header
+        .iter()
+        .find(|line| {
+            line.text.chars().filter(|c| c.is_alphabetic()).count() >= 3
+                && !line.text.chars().any(|c| c.is_ascii_digit())
+                && !line.has_word("facture")
+                && !line.has_word("ticket")
+        })
+        .map(|line| (line.text.clone(), 0.4))
+}
+
+fn find_purchase_date(lines: &[Line]) -> Option<(NaiveDate, f32)> {
+    lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let date = parse_date(line)?;
+            // The legal guarantee runs from delivery, so prefer that date.
+            let (rank, confidence) = if line.has_word("livraison") || line.has_word("livre le") {
+                (0, 0.9)
+            } else if line.has_word("achat") {
+                (1, 0.9)
+            } else if line.has_word("facture") {
+                (2, 0.85)
+            } else if line.has_word("date") || line.folded.starts_with("le ") {
+                (3, 0.8)
+            } else if line.has_word("commande") {
+                (4, 0.75)
+            } else {
+                (5, 0.6)
+            };
+            Some((rank, index, date, confidence))
+        })
+        .min_by_key(|(rank, index, _, _)| (*rank, *index))
+        .map(|(_, _, date, confidence)| (date, confidence))
+}
+
+fn parse_date(line: &Line) -> Option<NaiveDate> {
+    let valid = |year: i32, month: u32, day: u32| {
+        (2000..2100)
+            .contains(&year)
+            .then(|| NaiveDate::from_ymd_opt(year, month, day))
+            .flatten()
+    };
+
+    if let Some(c) = TEXT_DATE.captures(&line.folded) {
+        let month = match &c[2][..3] {
+            "jan" => 1,
+            "fev" => 2,
+            "mar" => 3,
+            "avr" => 4,
+            "mai" => 5,
+            "jui" if c[2].starts_with("juil") => 7,
+            "jui" => 6,
+            "aou" => 8,
+            "sep" => 9,
+            "oct" => 10,
+            "nov" => 11,
+            _ => 12,
+        };
+        if let Some(date) = valid(c[3].parse().ok()?, month, c[1].parse().ok()?) {
+            return Some(date);
+        }
+    }
+    if let Some(c) = ISO_DATE.captures(&line.text) {
+        if let Some(date) = valid(c[1].parse().ok()?, c[2].parse().ok()?, c[3].parse().ok()?) {
+            return Some(date);
+        }
+    }
+    NUMERIC_DATE.captures_iter(&line.text).find_map(|c| {
+        let year: i32 = c[3].parse().ok()?;
+        let year = if year < 100 { 2000 + year } else { year };
+        valid(year, c[2].parse().ok()?, c[1].parse().ok()?)
+    })
+}
+
+/// Returns the line index, the amount in cents and the confidence.
+fn find_total(lines: &[Line]) -> Option<(usize, i64, f32)> {
+    const EXPLICIT: &[&str] = &[
+        "net a payer",
+        "total a payer",
+        "montant a payer",
+        "reste a payer",
+        "total ttc",
+        "montant ttc",
+        "total eur",
+    ];
+
+    let total_on = |tier: fn(&Line) -> bool| {
+        lines
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, line)| tier(line))
+            .find_map(|(index, line)| {
+                let (_, cents) = *amounts(&line.raw).last()?;
+                (cents > 0).then_some((index, cents))
+            })
+    };
+
+    if let Some((index, cents)) = total_on(|line| EXPLICIT.iter().any(|kw| line.has_word(kw))) {
+        return Some((index, cents, 0.95));
+    }
+    if let Some((index, cents)) = total_on(|line| {
+        line.folded.starts_with("total")
+            && !["ht", "hors", "tva", "sous"]
+                .iter()
+                .any(|kw| line.has_word(kw))
+    }) {
+        return Some((index, cents, 0.8));
+    }
+
+    // Without a total line, the largest amount is the best guess.
+    lines
+        .iter()
+        .enumerate()
+        .flat_map(|(index, line)| {
+            amounts(&line.raw)
+                .into_iter()
+                .map(move |(_, cents)| (index, cents))
+        })
+        .max_by_key(|(_, cents)| *cents)
+        .filter(|(_, cents)| *cents > 0)
+        .map(|(index, cents)| (index, cents, 0.4))
+}
+
+fn find_invoice_number(lines: &[Line]) -> Option<(String, f32)> {
+    let number_in = |regex: &Regex| {
+        lines.iter().find_map(|line| {
+            regex
+                .captures(&line.text)
+                .map(|c| c[1].to_string())
+                .filter(|number| number.chars().any(|c| c.is_ascii_digit()))
+        })
+    };
+
+    INVOICE_NUMBER
+        .iter()
+        .find_map(number_in)
+        .map(|number| (number, 0.9))
+        .or_else(|| number_in(&ORDER_NUMBER).map(|number| (number, 0.6)))
+}
+
+fn find_warranty_months(lines: &[Line]) -> Option<(i32, f32)> {
+    lines.iter().find_map(|line| {
+        let c = WARRANTY_DURATION.captures(&line.folded)?;
+        let count: i32 = c[1].parse().ok()?;
+        let months = if c[2].starts_with("an") {
+            count * 12
+        } else {
+            count
+        };
+        (1..=120).contains(&months).then_some((months, 0.8))
+    })
+}
+
+/// Picks the most expensive item line; that is what a warranty is usually
+/// wanted for.
+fn find_product(lines: &[Line]) -> Option<(String, f32)> {
+    let mut candidates = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let line_amounts = amounts(&line.raw);
+        let (Some(&(first, _)), Some(&(_, cents))) = (line_amounts.first(), line_amounts.last())
+        else {
+            continue;
+        };
+        if cents <= 0 || is_non_product(line) {
+            continue;
+        }
+
+        let mut description = clean_description(&line.raw[..first]);
+        // Receipts often print the reference and price under the name.
+        if letter_count(&description) < 3 && index > 0 {
+            let previous = &lines[index - 1];
+            if amounts(&previous.raw).is_empty() && !is_non_product(previous) {
+                description = clean_description(&previous.raw);
+            }
+        }
+        if letter_count(&description) >= 3 && parse_date(&Line::new(&description)).is_none() {
+            candidates.push((cents, description));
+        }
+    }
+
+    let confidence = if candidates.len() == 1 { 0.75 } else { 0.65 };
+    candidates
+        .into_iter()
+        .max_by_key(|(cents, _)| *cents)
+        .map(|(_, description)| (description, confidence))
+}
+
+fn is_non_product(line: &Line) -> bool {
+    NON_PRODUCT_WORDS.iter().any(|word| line.has_word(word))
+}
+
+pub(crate) fn clean_description(text: &str) -> String {
+    let text = text.replace('€', " ");
+    let text = REFERENCE.replace_all(&text, " ");
+    let text = QUANTITY_PREFIX.replace(text.trim(), "");
+    let text = QUANTITY_SUFFIX.replace(&text, "");
+    let text = text
+        .trim_matches(|c: char| c.is_whitespace() || matches!(c, '-' | ':' | '*' | '|'))
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    text.chars().take(200).collect()
+}
+
+fn find_brand(folded_product: &str) -> Option<(&'static str, f32)> {
+    BRANDS
+        .iter()
+        .find(|brand| contains_words(folded_product, &fold(brand)))
+        .map(|brand| (*brand, 0.85))
+        .or_else(|| {
+            MODEL_BRANDS
+                .iter()
+                .find(|(model, _)| contains_words(folded_product, model))
+                .map(|(_, brand)| (*brand, 0.7))
+        })
+}
+
+fn find_category(folded_product: &str) -> Option<WarrantyCategory> {
+    CATEGORY_KEYWORDS
+        .iter()
+        .find(|(_, keywords)| keywords.iter().any(|kw| contains_words(folded_product, kw)))
+        .map(|(category, _)| category.clone())
+}
+
+/// Amounts on a line as (byte offset, cents). Fragments of longer numbers,
+/// such as the `14.03` of `14.03.2024`, are skipped.
+pub(crate) fn amounts(text: &str) -> Vec<(usize, i64)> {
+    let mut found = Vec::new();
+    let mut position = 0;
+
+    while let Some(c) = AMOUNT.captures_at(text, position) {
+        let whole = c.get(0).unwrap();
+        let before = text[..whole.start()].chars().next_back();
+        let mut after = text[whole.end()..].chars();
+        let next = after.next();
+        let is_fragment = before.is_some_and(|ch| ch.is_ascii_digit() || ch == '/')
+            || next.is_some_and(|ch| ch.is_ascii_digit())
+            || (matches!(next, Some('.' | ',' | '/'))
+                && after.next().is_some_and(|ch| ch.is_ascii_digit()));
+        if is_fragment {
+            // A shorter amount may still start further in, e.g. after a
+            // reference number glued to the price.
+            position = whole.start() + 1;
+            continue;
+        }
+        position = whole.end();
+
+        let units: i64 = c[1]
+            .chars()
+            .filter(char::is_ascii_digit)
+            .collect::<String>()
+            .parse()
+            .unwrap_or(0);
+        let cents = units * 100 + c[2].parse::<i64>().unwrap_or(0);
+        let negative = text[..whole.start()].trim_end().ends_with('-');
+        found.push((whole.start(), if negative { -cents } else { cents }));
+    }
+
+    found
+}
+
+/// Rewrites an all-caps OCR line as a sentence, keeping model numbers and
+/// brand spellings intact.
+fn tidy_case(text: &str) -> String {
+    let is_all_caps = text
+        .split(' ')
+        .filter(|word| !word.chars().any(|c| c.is_ascii_digit()))
+        .all(|word| !word.chars().any(|c| c.is_lowercase()));
+    if !is_all_caps {
+        return text.to_string();
+    }
+
+    let words: Vec<String> = text
+        .split(' ')
+        .enumerate()
+        .map(|(index, word)| {
+            if word.chars().any(|c| c.is_ascii_digit()) {
+                return word.to_string();
+            }
+            if let Some(brand) = BRANDS.iter().find(|brand| fold(brand) == fold(word)) {
+                return brand.to_string();
+            }
+            let lower = word.to_lowercase();
+            if index == 0 {
+                let mut chars = lower.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            } else {
+                lower
+            }
+        })
+        .collect();
+    words.join(" ")
+}
+
+pub(crate) fn letter_count(text: &str) -> usize {
+    text.chars().filter(|c| c.is_alphabetic()).count()
+}
+
+/// Lowercases, strips French accents and turns punctuation into spaces.
+pub(crate) fn fold(text: &str) -> String {
+    let folded: String = text
+        .chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| match c {
+            'à' | 'â' | 'ä' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'î' | 'ï' => 'i',
+            'ô' | 'ö' => 'o',
+            'ù' | 'û' | 'ü' => 'u',
+            'ç' => 'c',
+            c if c.is_alphanumeric() => c,
+            _ => ' ',
+        })
+        .collect();
+    folded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Whether `needle` (already folded) appears in `folded` on word boundaries.
+pub(crate) fn contains_words(folded: &str, needle: &str) -> bool {
+    format!(" {} ", folded).contains(&format!(" {} ", needle))
+}
@@ -0,0 +1,426 @@
+use async_trait::async_trait;
+use chrono::{TimeZone, Utc};
+
+use crate::error::{AppError, Result};
+use crate::extraction::receipt::{amounts, clean_description, fold};
+use crate::extraction::{
+    extract, parse_invoice, parse_receipt, text_layer, TesseractRecognizer, TextRecognizer,
+};
+use crate::models::{WarrantyCategory, WarrantySuggestion};
+
+const DARTY: &str = include_str!("../../tests/fixtures/receipts/darty.txt");
+const FNAC: &str = include_str!("../../tests/fixtures/receipts/fnac.txt");
+const BOULANGER: &str = include_str!("../../tests/fixtures/receipts/boulanger.txt");
+const AMAZON_FR: &str = include_str!("../../tests/fixtures/receipts/amazon_fr.txt");
+
+const AMAZON_FR_PDF: &[u8] = include_bytes!("../../tests/fixtures/invoices/amazon_fr.pdf");
+const FNAC_PDF: &[u8] = include_bytes!("../../tests/fixtures/invoices/fnac.pdf");
+const DARTY_PDF: &[u8] = include_bytes!("../../tests/fixtures/invoices/darty.pdf");
+const CDISCOUNT_PDF: &[u8] = include_bytes!("../../tests/fixtures/invoices/cdiscount.pdf");
+const APPLE_PDF: &[u8] = include_bytes!("../../tests/fixtures/invoices/apple.pdf");
+const GENERIC_PDF: &[u8] = include_bytes!("../../tests/fixtures/invoices/generic.pdf");
+const SCANNED_PDF: &[u8] = include_bytes!("../../tests/fixtures/invoices/scanned.pdf");
+
+fn confidence(suggestion: &WarrantySuggestion, field: &str) -> f32 {
+    suggestion.confidence.get(field).copied().unwrap_or(0.0)
+}
+
+async fn parse_pdf(data: &[u8]) -> Vec<WarrantySuggestion> {
+    let text = text_layer(data.to_vec())
+        .await
+        .expect("fixture has a text layer");
+    parse_invoice(&text)
+}
+
+fn products(suggestions: &[WarrantySuggestion]) -> Vec<(&str, i64)> {
+    suggestions
+        .iter()
+        .map(|s| {
+            (
+                s.product_name.as_deref().unwrap_or(""),
+                s.price_cents.unwrap_or(0),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_parse_darty_receipt() {
+    let suggestion = parse_receipt(DARTY);
+
+    assert_eq!(suggestion.store.as_deref(), Some("Darty"));
+    assert_eq!(
+        suggestion.purchase_date,
+        Some(Utc.with_ymd_and_hms(2024, 3, 14, 0, 0, 0).unwrap())
+    );
+    assert_eq!(suggestion.price_cents, Some(64_899));
+    assert_eq!(
+        suggestion.invoice_number.as_deref(),
+        Some("0142-2024-118734")
+    );
+    assert_eq!(
+        suggestion.product_name.as_deref(),
+        Some("Lave-linge Bosch WAN28209FF")
+    );
+    assert_eq!(suggestion.brand.as_deref(), Some("Bosch"));
+    assert_eq!(suggestion.category, Some(WarrantyCategory::Appliances));
+    assert_eq!(suggestion.warranty_months, Some(36));
+    assert!(confidence(&suggestion, "price_cents") >= 0.9);
+}
+
+#[test]
+fn test_parse_fnac_receipt() {
+    let suggestion = parse_receipt(FNAC);
+
+    assert_eq!(suggestion.store.as_deref(), Some("Fnac"));
+    assert_eq!(
+        suggestion.purchase_date,
+        Some(Utc.with_ymd_and_hms(2023, 11, 2, 0, 0, 0).unwrap())
+    );
+    assert_eq!(suggestion.price_cents, Some(37_998));
+    assert_eq!(
+        suggestion.invoice_number.as_deref(),
+        Some("FA23-0117-55821")
+    );
+    // The name sits on the line above the reference and price.
+    assert_eq!(
+        suggestion.product_name.as_deref(),
+        Some("Casque Sony WH-1000XM5 noir")
+    );
+    assert_eq!(suggestion.brand.as_deref(), Some("Sony"));
+    assert_eq!(suggestion.category, Some(WarrantyCategory::Electronics));
+    assert_eq!(suggestion.warranty_months, Some(24));
+}
+
+#[test]
+fn test_parse_boulanger_receipt() {
+    let suggestion = parse_receipt(BOULANGER);
+
+    assert_eq!(suggestion.store.as_deref(), Some("Boulanger"));
+    assert_eq!(
+        suggestion.purchase_date,
+        Some(Utc.with_ymd_and_hms(2024, 1, 21, 0, 0, 0).unwrap())
+    );
+    // Thousands separated by a space, and the HT total is not the price paid.
+    assert_eq!(suggestion.price_cents, Some(136_090));
+    assert_eq!(suggestion.invoice_number.as_deref(), Some("B59-7781204"));
+    assert_eq!(
+        suggestion.product_name.as_deref(),
+        Some("Televiseur LG OLED55C3 139cm")
+    );
+    assert_eq!(suggestion.brand.as_deref(), Some("LG"));
+    assert_eq!(suggestion.category, Some(WarrantyCategory::Electronics));
+    assert_eq!(suggestion.warranty_months, None);
+}
+
+#[test]
+fn test_parse_amazon_fr_invoice() {
+    let suggestion = parse_receipt(AMAZON_FR);
+
+    assert_eq!(suggestion.store.as_deref(), Some("Amazon"));
+    // Delivery date wins over the order date: the legal guarantee starts there.
+    assert_eq!(
+        suggestion.purchase_date,
+        Some(Utc.with_ymd_and_hms(2024, 7, 8, 0, 0, 0).unwrap())
+    );
+    assert_eq!(suggestion.price_cents, Some(58_999));
+    assert_eq!(
+        suggestion.invoice_number.as_deref(),
+        Some("FR4002ABCD123456")
+    );
+    assert_eq!(
+        suggestion.product_name.as_deref(),
+        Some("Aspirateur balai sans fil Dyson V15 Detect")
+    );
+    assert_eq!(suggestion.brand.as_deref(), Some("Dyson"));
+    assert_eq!(suggestion.category, Some(WarrantyCategory::Appliances));
+}
+
+#[test]
+fn test_every_filled_field_has_a_confidence() {
+    for text in [DARTY, FNAC, BOULANGER, AMAZON_FR] {
+        let suggestion = parse_receipt(text);
+        let json = serde_json::to_value(&suggestion).unwrap();
+        for (field, value) in json.as_object().unwrap() {
+            if field == "confidence" || value.is_null() {
+                continue;
+            }
+            let score = confidence(&suggestion, field);
+            assert!(
+                score > 0.0 && score <= 1.0,
+                "{} has confidence {}",
+                field,
+                score
+            );
+        }
+    }
+}
+
+#[test]
+fn test_parse_receipt_without_keywords_falls_back() {
+    let suggestion = parse_receipt("BRICO CENTRE\nPerceuse visseuse 18V   89,90\n");
+
+    assert_eq!(suggestion.store.as_deref(), Some("BRICO CENTRE"));
+    assert_eq!(suggestion.price_cents, Some(8_990));
+    assert!(confidence(&suggestion, "store") < 0.5);
+    assert!(confidence(&suggestion, "price_cents") < 0.5);
+    assert_eq!(suggestion.purchase_date, None);
+    assert_eq!(suggestion.invoice_number, None);
+}
+
+#[test]
+fn test_parse_empty_text() {
+    let suggestion = parse_receipt("");
+    assert_eq!(suggestion, WarrantySuggestion::default());
+}
+
+#[test]
+fn test_amounts() {
+    let cents = |text: &str| {
+        amounts(text)
+            .into_iter()
+            .map(|(_, c)| c)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(cents("TOTAL TTC  648,99 €"), [64_899]);
+    assert_eq!(cents("Total  1 360,90 €"), [136_090]);
+    assert_eq!(cents("Total 1.360,90"), [136_090]);
+    assert_eq!(cents("Remise  -15,00 €"), [-1_500]);
+    assert_eq!(cents("Réf 4548736132610 379,99 €"), [37_999]);
+    assert!(cents("Date : 14.03.2024").is_empty());
+    assert!(cents("Tel : 01 42 60 00 00").is_empty());
+}
+
+#[test]
+fn test_clean_description() {
+    assert_eq!(clean_description("1 x CASQUE SONY  "), "CASQUE SONY");
+    assert_eq!(
+        clean_description("TV LG OLED55C3    1    "),
+        "TV LG OLED55C3"
+    );
+    assert_eq!(clean_description("Réf 4548736132610 "), "");
+    assert_eq!(
+        clean_description("REFRIGERATEUR SAMSUNG  1  "),
+        "REFRIGERATEUR SAMSUNG"
+    );
+}
+
+#[test]
+fn test_fold() {
+    assert_eq!(fold("Électroménager"), "electromenager");
+    assert_eq!(fold("LAVE-LINGE  à 549€"), "lave linge a 549");
+}
+
+struct StaticText(&'static str);
+
+#[async_trait]
+impl TextRecognizer for StaticText {
+    async fn recognize(&self, _content_type: &str, _data: &[u8]) -> Result<String> {
+        Ok(self.0.to_string())
+    }
+}
+
+#[tokio::test]
+async fn test_extract_runs_recognizer_then_heuristics() {
+    let (text, suggestions) = extract(&StaticText(FNAC), "image/jpeg", b"").await.unwrap();
+
+    assert_eq!(text, FNAC);
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0].store.as_deref(), Some("Fnac"));
+}
+
+#[tokio::test]
+async fn test_tesseract_recognizer_reports_missing_binary() {
+    let recognizer = TesseractRecognizer {
+        command: "garry-missing-tesseract".to_string(),
+        languages: "fra".to_string(),
+        pdf_rasterize_command: "garry-missing-pdftoppm".to_string(),
+    };
+
+    // A missing tool is an operator problem, retried rather than blamed on the file.
+    assert!(matches!(
+        recognizer.recognize("image/jpeg", b"\xFF\xD8\xFF").await,
+        Err(AppError::Internal(_))
+    ));
+    assert!(matches!(
+        recognizer.recognize("application/pdf", b"%PDF-1.7").await,
+        Err(AppError::Internal(_))
+    ));
+    assert!(matches!(
+        recognizer.recognize("image/heic", b"").await,
+        Err(AppError::UnsupportedMediaType(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_parse_amazon_fr_pdf_invoice() {
+    let suggestions = parse_pdf(AMAZON_FR_PDF).await;
+
+    // The second line of a wrapped name joins the first; ASIN lines do not.
+    assert_eq!(
+        products(&suggestions),
+        [
+            (
+                "Apple AirPods Pro (2e génération) avec boîtier de charge MagSafe (USB-C)",
+                24_899
+            ),
+            // Two chargers at 34,99 € each.
+            ("Anker Chargeur USB C 65W", 3_499),
+        ]
+    );
+    for suggestion in &suggestions {
+        assert_eq!(suggestion.store.as_deref(), Some("Amazon"));
+        assert_eq!(
+            suggestion.purchase_date,
+            Some(Utc.with_ymd_and_hms(2024, 9, 12, 0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            suggestion.invoice_number.as_deref(),
+            Some("FR5001EFGH987654")
+        );
+    }
+    assert_eq!(suggestions[0].brand.as_deref(), Some("Apple"));
+    assert_eq!(suggestions[0].category, Some(WarrantyCategory::Electronics));
+    assert!(confidence(&suggestions[0], "product_name") >= 0.9);
+}
+
+#[tokio::test]
+async fn test_parse_fnac_pdf_invoice() {
+    let suggestions = parse_pdf(FNAC_PDF).await;
+
+    assert_eq!(
+        products(&suggestions),
+        [
+            ("Appareil photo hybride Fujifilm X-T5 Boîtier Noir", 189_999),
+            ("Carte mémoire SanDisk Extreme PRO SDXC 128 Go", 4_599),
+        ]
+    );
+    assert_eq!(suggestions[0].store.as_deref(), Some("Fnac"));
+    assert_eq!(
+        suggestions[0].purchase_date,
+        Some(Utc.with_ymd_and_hms(2024, 4, 17, 0, 0, 0).unwrap())
+    );
+    assert_eq!(
+        suggestions[0].invoice_number.as_deref(),
+        Some("FD24-0412-88310")
+    );
+    assert_eq!(suggestions[0].warranty_months, Some(24));
+    assert_eq!(suggestions[0].brand.as_deref(), Some("Fujifilm"));
+}
+
+#[tokio::test]
+async fn test_parse_darty_pdf_invoice() {
+    let suggestions = parse_pdf(DARTY_PDF).await;
+
+    // The eco-participation and the extended warranty are not products.
+    assert_eq!(
+        products(&suggestions),
+        [
+            ("Refrigerateur americain Samsung RS68A8840S9", 129_900),
+            ("Micro-ondes Whirlpool mwp 303 w", 14_999),
+        ]
+    );
+    assert_eq!(suggestions[0].store.as_deref(), Some("Darty"));
+    assert_eq!(
+        suggestions[0].invoice_number.as_deref(),
+        Some("0317-2024-204518")
+    );
+    assert_eq!(suggestions[0].warranty_months, Some(36));
+    assert_eq!(suggestions[0].category, Some(WarrantyCategory::Appliances));
+    assert_eq!(suggestions[1].brand.as_deref(), Some("Whirlpool"));
+}
+
+#[tokio::test]
+async fn test_parse_cdiscount_pdf_invoice() {
+    let suggestions = parse_pdf(CDISCOUNT_PDF).await;
+
+    assert_eq!(
+        products(&suggestions),
+        [
+            ("Trottinette électrique Xiaomi Electric Scooter 4", 39_999),
+            ("Casque vélo urbain Decathlon Elops 500", 2_499),
+        ]
+    );
+    assert_eq!(suggestions[0].store.as_deref(), Some("Cdiscount"));
+    assert_eq!(
+        suggestions[0].purchase_date,
+        Some(Utc.with_ymd_and_hms(2024, 8, 12, 0, 0, 0).unwrap())
+    );
+    assert_eq!(
+        suggestions[0].invoice_number.as_deref(),
+        Some("FCD2408123456")
+    );
+    assert_eq!(suggestions[0].category, Some(WarrantyCategory::Sports));
+}
+
+#[tokio::test]
+async fn test_parse_apple_pdf_invoice() {
+    let suggestions = parse_pdf(APPLE_PDF).await;
+
+    // Part numbers sit in their own column; AppleCare+ is not a product.
+    assert_eq!(
+        products(&suggestions),
+        [
+            ("iPhone 16 128 Go Noir", 96_900),
+            ("AirTag (paquet de 4)", 11_900),
+        ]
+    );
+    assert_eq!(suggestions[0].store.as_deref(), Some("Apple"));
+    assert_eq!(
+        suggestions[0].purchase_date,
+        Some(Utc.with_ymd_and_hms(2024, 10, 22, 0, 0, 0).unwrap())
+    );
+    assert_eq!(suggestions[0].invoice_number.as_deref(), Some("MA71234567"));
+    assert_eq!(suggestions[0].brand.as_deref(), Some("Apple"));
+    assert_eq!(suggestions[0].warranty_months, Some(24));
+}
+
+#[tokio::test]
+async fn test_parse_unknown_merchant_pdf_invoice() {
+    let suggestions = parse_pdf(GENERIC_PDF).await;
+
+    assert_eq!(
+        products(&suggestions),
+        [
+            ("Objectif Canon RF 24-105mm f/4 L IS USM", 114_900),
+            ("Filtre UV Hoya 77mm", 5_990),
+        ]
+    );
+    assert_eq!(
+        suggestions[0].store.as_deref(),
+        Some("PHOTO STUDIO CONFLUENCE")
+    );
+    assert_eq!(
+        suggestions[0].invoice_number.as_deref(),
+        Some("PSC-2024-0098")
+    );
+    assert_eq!(suggestions[0].brand.as_deref(), Some("Canon"));
+}
+
+#[test]
+fn test_parse_invoice_without_item_table_falls_back_to_receipt() {
+    let text = "BRICO CENTRE\nPerceuse visseuse 18V   89,90\n";
+    assert_eq!(parse_invoice(text), vec![parse_receipt(text)]);
+}
+
+#[tokio::test]
+async fn test_text_layer_skips_scans_and_garbage() {
+    assert_eq!(text_layer(SCANNED_PDF.to_vec()).await, None);
+    assert_eq!(text_layer(b"%PDF-1.7 truncated".to_vec()).await, None);
+}
+
+#[tokio::test]
+async fn test_extract_prefers_pdf_text_layer() {
+    // The recognizer would read a Fnac receipt; the text layer wins.
+    let (_, suggestions) = extract(&StaticText(FNAC), "application/pdf", AMAZON_FR_PDF)
+        .await
+        .unwrap();
+    assert_eq!(suggestions.len(), 2);
+    assert_eq!(suggestions[0].store.as_deref(), Some("Amazon"));
+
+    let (text, _) = extract(&StaticText(FNAC), "application/pdf", SCANNED_PDF)
+        .await
+        .unwrap();
+    assert_eq!(text, FNAC);
+}
@@ -0,0 +1,351 @@
+use axum::{
+    extract::{FromRequest, Multipart, Path, State},
+    http::{Request, StatusCode},
+    response::Response,
+    Json,
+};
+use sha2::{Digest, Sha256};
+use tracing::info;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db;
+use crate::error::{AppError, Result};
+use crate::handlers::authorize_warranty;
+use crate::models::{Attachment, AttachmentKind, HouseholdRole, UpdateAttachmentRequest};
+use crate::storage;
+use crate::{AppState, AuthUser};
+
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct AttachmentUploadForm {
+    /// JPEG, PNG, WebP, HEIC or PDF file
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+    /// Defaults to `other`
+    kind: Option<AttachmentKind>,
+    /// Files the attachment under one of the warranty's claims
+    claim_id: Option<Uuid>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/warranties/{id}/attachments",
+    tag = "attachments",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID")
+    ),
+    responses(
+        (status = 200, description = "Attachments of the warranty", body = Vec<Attachment>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Warranty not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_attachments(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Vec<Attachment>>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    db::get_warranty_by_id(&state.pool, id, &user.user_id).await?;
+    let attachments = db::list_attachments(&state.pool, id, &user.user_id).await?;
+    Ok(Json(attachments))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/warranties/{id}/attachments",
+    tag = "attachments",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID")
+    ),
+    request_body(content = AttachmentUploadForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "Attachment uploaded successfully", body = Attachment),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Warranty or claim not found", body = ErrorResponse),
+        (status = 413, description = "File too large", body = ErrorResponse),
+        (status = 415, description = "Unsupported file type", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_attachment(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<(StatusCode, Json<Attachment>)> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+
+    let warranty = db::get_warranty_by_id(&state.pool, id, &user.user_id).await?;
+
+    let mut multipart = Multipart::from_request(request, &state)
+        .await
+        .map_err(|e| AppError::BadRequest(e.body_text()))?;
+    let upload = super::read_upload(&mut multipart, state.config.max_upload_bytes).await?;
+
+    let kind = match upload.fields.get("kind") {
+        Some(kind) => kind
+            .parse::<AttachmentKind>()
+            .map_err(AppError::BadRequest)?,
+        None => AttachmentKind::Other,
+    };
+    let claim_id = match upload.fields.get("claim_id") {
+        Some(claim_id) => {
+            let claim_id = claim_id
+                .parse::<Uuid>()
+                .map_err(|_| AppError::BadRequest("Invalid claim_id".to_string()))?;
+            db::get_claim(&state.pool, id, claim_id, &user.user_id).await?;
+            Some(claim_id)
+        }
+        None => None,
+    };
+    let file_name = upload
+        .file
+        .file_name
+        .as_deref()
+        .map(|name| name.chars().take(255).collect::<String>());
+
+    let attachment_id = Uuid::new_v4();
+    let storage_key = storage::attachment_key(
+        &warranty.user_id,
+        id,
+        attachment_id,
+        upload.file.content_type,
+    );
+    let checksum = hex::encode(Sha256::digest(&upload.file.data));
+    let size_bytes = upload.file.data.len() as i64;
+
+    state
+        .storage
+        .put(&storage_key, upload.file.content_type, upload.file.data)
+        .await?;
+
+    let created = db::create_attachment(
+        &state.pool,
+        db::NewAttachment {
+            id: attachment_id,
+            warranty_id: id,
+            claim_id,
+            kind,
+            file_name: file_name.as_deref(),
+            storage_key: &storage_key,
+            mime_type: upload.file.content_type,
+            size_bytes,
+            checksum_sha256: &checksum,
+        },
+    )
+    .await;
+
+    let attachment = match created {
+        Ok(attachment) => attachment,
+        Err(e) => {
+            // The warranty may have been deleted while the file was uploading.
+            storage::delete_blobs(state.storage.as_ref(), [storage_key]).await;
+            return Err(e);
+        }
+    };
+
+    info!(
+        warranty_id = %id,
+        attachment_id = %attachment.id,
+        user_id = %user.user_id,
+        kind = ?attachment.kind,
+        size = attachment.size_bytes,
+        "attachment uploaded"
+    );
+
+    Ok((StatusCode::CREATED, Json(attachment)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/warranties/{id}/attachments/{attachment_id}",
+    tag = "attachments",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("attachment_id" = Uuid, Path, description = "Attachment ID")
+    ),
+    responses(
+        (status = 200, description = "Attachment details", body = Attachment),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Attachment not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_attachment(
+    State(state): State<AppState>,
+    Path((id, attachment_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Attachment>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let attachment = db::get_attachment(&state.pool, id, attachment_id, &user.user_id).await?;
+    Ok(Json(attachment))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/warranties/{id}/attachments/{attachment_id}/content",
+    tag = "attachments",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("attachment_id" = Uuid, Path, description = "Attachment ID")
+    ),
+    responses(
+        (status = 200, description = "Attachment file", content_type = "application/octet-stream"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Attachment not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn download_attachment(
+    State(state): State<AppState>,
+    Path((id, attachment_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<Response> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let attachment = db::get_attachment(&state.pool, id, attachment_id, &user.user_id).await?;
+    let file_name = attachment.file_name.clone().unwrap_or_else(|| {
+        format!(
+            "{}.{}",
+            attachment.id,
+            storage::extension_for(&attachment.mime_type)
+        )
+    });
+
+    super::file_response(
+        state.storage.as_ref(),
+        &attachment.storage_key,
+        &attachment.mime_type,
+        &file_name,
+    )
+    .await
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/warranties/{id}/attachments/{attachment_id}",
+    tag = "attachments",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("attachment_id" = Uuid, Path, description = "Attachment ID")
+    ),
+    request_body = UpdateAttachmentRequest,
+    responses(
+        (status = 200, description = "Attachment updated successfully", body = Attachment),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Attachment not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_attachment(
+    State(state): State<AppState>,
+    Path((id, attachment_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Attachment>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+
+    let body = axum::body::to_bytes(request.into_body(), 1024 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+
+    let payload: UpdateAttachmentRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    if let Some(ref file_name) = payload.file_name {
+        if file_name.trim().is_empty() || file_name.len() > 255 {
+            return Err(AppError::BadRequest(
+                "File name must be between 1 and 255 characters".to_string(),
+            ));
+        }
+    }
+
+    let attachment =
+        db::update_attachment(&state.pool, id, attachment_id, &user.user_id, payload).await?;
+
+    info!(attachment_id = %attachment.id, user_id = %user.user_id, "attachment updated");
+
+    Ok(Json(attachment))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/warranties/{id}/attachments/{attachment_id}",
+    tag = "attachments",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("attachment_id" = Uuid, Path, description = "Attachment ID")
+    ),
+    responses(
+        (status = 204, description = "Attachment deleted successfully"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Attachment not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_attachment(
+    State(state): State<AppState>,
+    Path((id, attachment_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<StatusCode> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+
+    let attachment = db::delete_attachment(&state.pool, id, attachment_id, &user.user_id).await?;
+    storage::delete_blobs(state.storage.as_ref(), [attachment.storage_key]).await;
+
+    info!(attachment_id = %attachment_id, user_id = %user.user_id, "attachment deleted");
+
+    Ok(StatusCode::NO_CONTENT)
+}
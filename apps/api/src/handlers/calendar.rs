@@ -0,0 +1,167 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, Request, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::Utc;
+use chrono_tz::Tz;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::calendar;
+use crate::db;
+use crate::error::{AppError, Result};
+use crate::models::{CalendarFeed, CalendarFeedResponse, ReminderPreferences};
+use crate::notifications::templates::Locale;
+use crate::{AppState, AuthUser};
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/calendar-feed",
+    tag = "calendar",
+    responses(
+        (status = 200, description = "Address of the user's calendar feed", body = CalendarFeedResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "No calendar feed yet", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_calendar_feed(
+    State(state): State<AppState>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<CalendarFeedResponse>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let feed = db::get_calendar_feed(&state.pool, &user.user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Calendar feed not found".to_string()))?;
+    Ok(Json(feed_response(&state, feed)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/calendar-feed",
+    tag = "calendar",
+    responses(
+        (status = 201, description = "New feed address; any previous one stops working", body = CalendarFeedResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn regenerate_calendar_feed(
+    State(state): State<AppState>,
+    request: Request<axum::body::Body>,
+) -> Result<(StatusCode, Json<CalendarFeedResponse>)> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let feed = db::regenerate_calendar_feed(&state.pool, &user.user_id).await?;
+
+    info!(user_id = %user.user_id, "calendar feed token generated");
+
+    Ok((StatusCode::CREATED, Json(feed_response(&state, feed))))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/calendar-feed",
+    tag = "calendar",
+    responses(
+        (status = 204, description = "Feed revoked"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "No calendar feed", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_calendar_feed(
+    State(state): State<AppState>,
+    request: Request<axum::body::Body>,
+) -> Result<StatusCode> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    db::delete_calendar_feed(&state.pool, &user.user_id).await?;
+
+    info!(user_id = %user.user_id, "calendar feed revoked");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/calendar/{token}/warranties.ics",
+    tag = "calendar",
+    params(
+        ("token" = Uuid, Path, description = "Secret token of the feed")
+    ),
+    responses(
+        (status = 200, description = "iCalendar feed of warranty end dates", content_type = "text/calendar"),
+        (status = 404, description = "Unknown or revoked token", body = ErrorResponse)
+    )
+)]
+pub async fn warranty_calendar(
+    State(state): State<AppState>,
+    Path(token): Path<Uuid>,
+) -> Result<Response> {
+    let user_id = db::get_calendar_feed_owner(&state.pool, token).await?;
+    let warranties = db::get_all_warranties(&state.pool, &user_id).await?;
+    // Dates and words are the feed owner's, as in their reminders.
+    let preferences = db::get_reminder_preferences(&state.pool, &user_id)
+        .await?
+        .unwrap_or_else(|| ReminderPreferences::defaults(&state.config.reminder_thresholds_days));
+    let timezone = preferences.timezone.parse().unwrap_or(Tz::UTC);
+    let locale = Locale::parse(
+        preferences
+            .locale
+            .as_deref()
+            .unwrap_or(&state.config.default_locale),
+    );
+
+    let body = calendar::warranty_calendar(
+        locale,
+        timezone,
+        &warranties,
+        &preferences.lead_days,
+        &state.config.web_app_url,
+        Utc::now(),
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "inline; filename=\"garry.ics\"",
+            ),
+            (header::CACHE_CONTROL, "private, max-age=300"),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+fn feed_response(state: &AppState, feed: CalendarFeed) -> CalendarFeedResponse {
+    let url = calendar::feed_url(&state.config.public_api_url, feed.token);
+    CalendarFeedResponse {
+        webcal_url: calendar::webcal_url(&url),
+        url,
+        created_at: feed.created_at,
+    }
+}
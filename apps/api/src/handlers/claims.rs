@@ -0,0 +1,389 @@
+use axum::{
+    extract::{Path, State},
+    http::{Request, StatusCode},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::audit::Actor;
+use crate::db::{self, ClaimTransition, NewClaim};
+use crate::error::{AppError, Result};
+use crate::handlers::authorize_warranty;
+use crate::legal;
+use crate::models::{
+    Claim, ClaimDetail, ClaimTransitionRequest, CreateClaimRequest, HouseholdRole,
+    UpdateClaimRequest, WebhookEvent,
+};
+use crate::webhooks;
+use crate::{AppState, AuthUser};
+
+use super::legal_guarantee::rules_for;
+
+const MAX_CLAIMS_PER_WARRANTY: i64 = 50;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/warranties/{id}/claims",
+    tag = "claims",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID")
+    ),
+    responses(
+        (status = 200, description = "Claims of the warranty, newest first", body = Vec<Claim>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Warranty not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_claims(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Vec<Claim>>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    db::get_warranty_by_id(&state.pool, id, &user.user_id).await?;
+    let claims = db::list_claims(&state.pool, id, &user.user_id).await?;
+    Ok(Json(claims))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/warranties/{id}/claims",
+    tag = "claims",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID")
+    ),
+    request_body = CreateClaimRequest,
+    responses(
+        (status = 201, description = "Claim opened", body = Claim),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Warranty not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_claim(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<(StatusCode, Json<Claim>)> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+
+    let body = axum::body::to_bytes(request.into_body(), 64 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+    let payload: CreateClaimRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    validate_description(&payload.description)?;
+    validate_rma_number(payload.rma_number.as_deref())?;
+    validate_note(payload.note.as_deref())?;
+    let contacted_at = payload.contacted_at.unwrap_or_else(Utc::now);
+    validate_not_future(contacted_at)?;
+
+    db::get_warranty_by_id(&state.pool, id, &user.user_id).await?;
+    if db::count_claims(&state.pool, id).await? >= MAX_CLAIMS_PER_WARRANTY {
+        return Err(AppError::BadRequest(format!(
+            "At most {} claims are allowed per warranty",
+            MAX_CLAIMS_PER_WARRANTY
+        )));
+    }
+
+    let claim = db::create_claim(
+        &state.pool,
+        id,
+        NewClaim {
+            description: payload.description.trim(),
+            rma_number: payload.rma_number.as_deref(),
+            contacted_at,
+            note: payload.note.as_deref(),
+        },
+    )
+    .await?;
+
+    info!(claim_id = %claim.id, warranty_id = %id, user_id = %user.user_id, "claim created");
+
+    Ok((StatusCode::CREATED, Json(claim)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/warranties/{id}/claims/{claim_id}",
+    tag = "claims",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("claim_id" = Uuid, Path, description = "Claim ID")
+    ),
+    responses(
+        (status = 200, description = "Claim with its history and attachments", body = ClaimDetail),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Claim not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_claim(
+    State(state): State<AppState>,
+    Path((id, claim_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<ClaimDetail>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let claim = db::get_claim(&state.pool, id, claim_id, &user.user_id).await?;
+    let events = db::list_claim_events(&state.pool, claim_id).await?;
+    let attachments = db::list_claim_attachments(&state.pool, claim_id).await?;
+
+    Ok(Json(ClaimDetail {
+        claim,
+        events,
+        attachments,
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/warranties/{id}/claims/{claim_id}",
+    tag = "claims",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("claim_id" = Uuid, Path, description = "Claim ID")
+    ),
+    request_body = UpdateClaimRequest,
+    responses(
+        (status = 200, description = "Claim updated", body = Claim),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Claim not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_claim(
+    State(state): State<AppState>,
+    Path((id, claim_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Claim>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+
+    let body = axum::body::to_bytes(request.into_body(), 64 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+    let mut payload: UpdateClaimRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    if let Some(description) = payload.description.take() {
+        validate_description(&description)?;
+        payload.description = Some(description.trim().to_string());
+    }
+    validate_rma_number(payload.rma_number.as_deref())?;
+    for at in [
+        payload.contacted_at,
+        payload.dropped_off_at,
+        payload.returned_at,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        validate_not_future(at)?;
+    }
+
+    let claim = db::update_claim(&state.pool, id, claim_id, &user.user_id, payload).await?;
+
+    info!(claim_id = %claim.id, user_id = %user.user_id, "claim updated");
+
+    Ok(Json(claim))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/warranties/{id}/claims/{claim_id}",
+    tag = "claims",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("claim_id" = Uuid, Path, description = "Claim ID")
+    ),
+    responses(
+        (status = 204, description = "Claim deleted and any extension it granted taken back"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Claim not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_claim(
+    State(state): State<AppState>,
+    Path((id, claim_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<StatusCode> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+
+    let actor = Actor::new(&user.user_id, &request);
+    let timezone = db::get_warranty_timezone(&state.pool, id).await?;
+    let reverted = db::delete_claim(&state.pool, id, claim_id, timezone, &actor).await?;
+    if let Some(warranty) = reverted {
+        webhooks::emit(&state.pool, WebhookEvent::Updated, &warranty).await;
+    }
+
+    info!(claim_id = %claim_id, user_id = %user.user_id, "claim deleted");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/warranties/{id}/claims/{claim_id}/transitions",
+    tag = "claims",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("claim_id" = Uuid, Path, description = "Claim ID")
+    ),
+    request_body = ClaimTransitionRequest,
+    responses(
+        (status = 200, description = "Claim moved to the new status; a repair under the legal guarantee extends the warranty", body = Claim),
+        (status = 400, description = "Invalid request or status change", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Claim not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn transition_claim(
+    State(state): State<AppState>,
+    Path((id, claim_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Claim>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+    let actor = Actor::new(&user.user_id, &request);
+
+    let body = axum::body::to_bytes(request.into_body(), 64 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+    let payload: ClaimTransitionRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    validate_note(payload.note.as_deref())?;
+    let occurred_at = payload.occurred_at.unwrap_or_else(Utc::now);
+    validate_not_future(occurred_at)?;
+
+    let warranty = db::get_warranty_by_id(&state.pool, id, &user.user_id).await?;
+    let timezone = db::get_warranty_timezone(&state.pool, id).await?;
+    let rules = rules_for(&state.config.legal_country)?;
+    let extension = legal::repair_extension(rules, warranty.purchase_date, timezone);
+
+    let (claim, extended) = db::transition_claim(
+        &state.pool,
+        id,
+        claim_id,
+        ClaimTransition {
+            status: payload.status,
+            note: payload.note.as_deref(),
+            occurred_at,
+        },
+        extension,
+        timezone,
+        &actor,
+    )
+    .await?;
+    if let Some(warranty) = extended {
+        webhooks::emit(&state.pool, WebhookEvent::Updated, &warranty).await;
+    }
+
+    info!(
+        claim_id = %claim.id,
+        user_id = %user.user_id,
+        status = claim.status.as_str(),
+        extension_months = claim.extension_months,
+        "claim moved"
+    );
+
+    Ok(Json(claim))
+}
+
+fn validate_description(description: &str) -> Result<()> {
+    if description.trim().is_empty() {
+        return Err(AppError::BadRequest("Description is required".to_string()));
+    }
+    if description.len() > 2000 {
+        return Err(AppError::BadRequest(
+            "Description must be less than 2000 characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_rma_number(rma_number: Option<&str>) -> Result<()> {
+    if rma_number.is_some_and(|number| number.len() > 100) {
+        return Err(AppError::BadRequest(
+            "RMA number must be less than 100 characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_note(note: Option<&str>) -> Result<()> {
+    if note.is_some_and(|note| note.len() > 2000) {
+        return Err(AppError::BadRequest(
+            "Note must be less than 2000 characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_not_future(at: DateTime<Utc>) -> Result<()> {
+    if at > Utc::now() {
+        return Err(AppError::BadRequest(
+            "Claim dates cannot be in the future".to_string(),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,340 @@
+use axum::{
+    extract::{Path, State},
+    http::{Request, StatusCode},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::audit::Actor;
+use crate::db::{self, CoverageFields};
+use crate::error::{AppError, Result};
+use crate::handlers::authorize_warranty;
+use crate::models::{Coverage, CreateCoverageRequest, HouseholdRole, UpdateCoverageRequest};
+use crate::warranty_period;
+use crate::{AppState, AuthUser};
+
+const MAX_COVERAGES_PER_WARRANTY: i64 = 20;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/warranties/{id}/coverages",
+    tag = "coverages",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID")
+    ),
+    responses(
+        (status = 200, description = "Coverages of the warranty, ending soonest first", body = Vec<Coverage>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Warranty not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_coverages(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Vec<Coverage>>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    db::get_warranty_by_id(&state.pool, id, &user.user_id).await?;
+    let coverages = db::list_coverages(&state.pool, id, &user.user_id).await?;
+    Ok(Json(coverages))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/warranties/{id}/coverages",
+    tag = "coverages",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID")
+    ),
+    request_body = CreateCoverageRequest,
+    responses(
+        (status = 201, description = "Coverage added", body = Coverage),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Warranty not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_coverage(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<(StatusCode, Json<Coverage>)> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    let actor = Actor::new(&user.user_id, &request);
+    authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+
+    let body = axum::body::to_bytes(request.into_body(), 64 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+    let payload: CreateCoverageRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    validate_details(
+        payload.provider.as_deref(),
+        payload.contract_number.as_deref(),
+        payload.cost_cents,
+    )?;
+
+    let warranty = db::get_warranty_by_id(&state.pool, id, &user.user_id).await?;
+    if db::count_coverages(&state.pool, id).await? >= MAX_COVERAGES_PER_WARRANTY {
+        return Err(AppError::BadRequest(format!(
+            "At most {} coverages are allowed per warranty",
+            MAX_COVERAGES_PER_WARRANTY
+        )));
+    }
+
+    let starts_at = payload.starts_at.unwrap_or(warranty.purchase_date);
+    let ends_at = match (payload.ends_at, payload.months) {
+        (Some(_), Some(_)) => return Err(both_ends_error()),
+        (Some(ends_at), None) => ends_at,
+        (None, Some(months)) => end_after(&state, id, starts_at, months).await?,
+        (None, None) => {
+            return Err(AppError::BadRequest(
+                "Either ends_at or months is required".to_string(),
+            ))
+        }
+    };
+    validate_period(starts_at, ends_at)?;
+
+    let coverage = db::create_coverage(
+        &state.pool,
+        id,
+        CoverageFields {
+            kind: payload.kind,
+            provider: payload.provider.as_deref(),
+            starts_at,
+            ends_at,
+            contract_number: payload.contract_number.as_deref(),
+            cost_cents: payload.cost_cents,
+        },
+        &actor,
+    )
+    .await?;
+
+    info!(coverage_id = %coverage.id, warranty_id = %id, user_id = %user.user_id, "coverage created");
+
+    Ok((StatusCode::CREATED, Json(coverage)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/warranties/{id}/coverages/{coverage_id}",
+    tag = "coverages",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("coverage_id" = Uuid, Path, description = "Coverage ID")
+    ),
+    responses(
+        (status = 200, description = "Coverage found", body = Coverage),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Coverage not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_coverage(
+    State(state): State<AppState>,
+    Path((id, coverage_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Coverage>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let coverage = db::get_coverage(&state.pool, id, coverage_id, &user.user_id).await?;
+    Ok(Json(coverage))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/warranties/{id}/coverages/{coverage_id}",
+    tag = "coverages",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("coverage_id" = Uuid, Path, description = "Coverage ID")
+    ),
+    request_body = UpdateCoverageRequest,
+    responses(
+        (status = 200, description = "Coverage updated", body = Coverage),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Coverage not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_coverage(
+    State(state): State<AppState>,
+    Path((id, coverage_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Coverage>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    let actor = Actor::new(&user.user_id, &request);
+    authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+
+    let body = axum::body::to_bytes(request.into_body(), 64 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+    let payload: UpdateCoverageRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    validate_details(
+        payload.provider.as_deref(),
+        payload.contract_number.as_deref(),
+        payload.cost_cents,
+    )?;
+
+    let existing = db::get_coverage(&state.pool, id, coverage_id, &user.user_id).await?;
+    let starts_at = payload.starts_at.unwrap_or(existing.starts_at);
+    let ends_at = match (payload.ends_at, payload.months) {
+        (Some(_), Some(_)) => return Err(both_ends_error()),
+        (Some(ends_at), None) => ends_at,
+        (None, Some(months)) => end_after(&state, id, starts_at, months).await?,
+        (None, None) => existing.ends_at,
+    };
+    validate_period(starts_at, ends_at)?;
+
+    let provider = payload.provider.or(existing.provider);
+    let contract_number = payload.contract_number.or(existing.contract_number);
+    let coverage = db::update_coverage(
+        &state.pool,
+        id,
+        coverage_id,
+        CoverageFields {
+            kind: payload.kind.unwrap_or(existing.kind),
+            provider: provider.as_deref(),
+            starts_at,
+            ends_at,
+            contract_number: contract_number.as_deref(),
+            cost_cents: payload.cost_cents.or(existing.cost_cents),
+        },
+        &actor,
+    )
+    .await?;
+
+    info!(coverage_id = %coverage.id, user_id = %user.user_id, "coverage updated");
+
+    Ok(Json(coverage))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/warranties/{id}/coverages/{coverage_id}",
+    tag = "coverages",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("coverage_id" = Uuid, Path, description = "Coverage ID")
+    ),
+    responses(
+        (status = 204, description = "Coverage deleted"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Coverage not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_coverage(
+    State(state): State<AppState>,
+    Path((id, coverage_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<StatusCode> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let actor = Actor::new(&user.user_id, &request);
+    authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+
+    db::delete_coverage(&state.pool, id, coverage_id, &actor).await?;
+
+    info!(coverage_id = %coverage_id, user_id = %user.user_id, "coverage deleted");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn validate_details(
+    provider: Option<&str>,
+    contract_number: Option<&str>,
+    cost_cents: Option<i64>,
+) -> Result<()> {
+    if provider.is_some_and(|provider| provider.len() > 200) {
+        return Err(AppError::BadRequest(
+            "Provider must be less than 200 characters".to_string(),
+        ));
+    }
+    if contract_number.is_some_and(|number| number.len() > 100) {
+        return Err(AppError::BadRequest(
+            "Contract number must be less than 100 characters".to_string(),
+        ));
+    }
+    if cost_cents.is_some_and(|cost| cost < 0) {
+        return Err(AppError::BadRequest("Cost cannot be negative".to_string()));
+    }
+    Ok(())
+}
+
+fn validate_period(starts_at: DateTime<Utc>, ends_at: DateTime<Utc>) -> Result<()> {
+    if ends_at <= starts_at {
+        return Err(AppError::BadRequest(
+            "Coverage must end after it starts".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn both_ends_error() -> AppError {
+    AppError::BadRequest("Give either ends_at or months, not both".to_string())
+}
+
+/// `months` calendar months after `starts_at`, on the calendar of the
+/// warranty's creator.
+async fn end_after(
+    state: &AppState,
+    warranty_id: Uuid,
+    starts_at: DateTime<Utc>,
+    months: i32,
+) -> Result<DateTime<Utc>> {
+    if !(1..=120).contains(&months) {
+        return Err(AppError::BadRequest(
+            "Months must be between 1 and 120".to_string(),
+        ));
+    }
+    let timezone = db::get_warranty_timezone(&state.pool, warranty_id).await?;
+    warranty_period::warranty_end_date(starts_at, months, timezone)
+        .ok_or_else(|| AppError::BadRequest("Start date is out of range".to_string()))
+}
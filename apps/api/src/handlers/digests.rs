@@ -0,0 +1,63 @@
+use axum::{
+    extract::{Path, State},
+    response::Html,
+};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::db;
+use crate::error::Result;
+use crate::notifications::templates::{unsubscribe_page, unsubscribe_url, Locale};
+use crate::AppState;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/digest/unsubscribe/{token}",
+    tag = "reminders",
+    params(
+        ("token" = Uuid, Path, description = "Unsubscribe token from the digest email")
+    ),
+    responses(
+        (status = 200, description = "Page asking to confirm", content_type = "text/html")
+    )
+)]
+pub async fn confirm_digest_unsubscribe(
+    State(state): State<AppState>,
+    Path(token): Path<Uuid>,
+) -> Result<Html<String>> {
+    let locale = page_locale(&state, token).await?;
+    let action = unsubscribe_url(&state.config.public_api_url, token);
+    Ok(Html(unsubscribe_page(locale, Some(&action))))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/digest/unsubscribe/{token}",
+    tag = "reminders",
+    params(
+        ("token" = Uuid, Path, description = "Unsubscribe token from the digest email")
+    ),
+    responses(
+        (status = 200, description = "Digest turned off", content_type = "text/html"),
+        (status = 404, description = "Unknown token", body = ErrorResponse)
+    )
+)]
+pub async fn unsubscribe_digest(
+    State(state): State<AppState>,
+    Path(token): Path<Uuid>,
+) -> Result<Html<String>> {
+    let locale = page_locale(&state, token).await?;
+    db::unsubscribe_digest(&state.pool, token).await?;
+
+    info!("digest unsubscribed by link");
+
+    Ok(Html(unsubscribe_page(locale, None)))
+}
+
+/// The language of the digest the link came from.
+async fn page_locale(state: &AppState, token: Uuid) -> Result<Locale> {
+    let locale = db::get_digest_locale(&state.pool, token).await?;
+    Ok(Locale::parse(
+        locale.as_deref().unwrap_or(&state.config.default_locale),
+    ))
+}
@@ -0,0 +1,159 @@
+use axum::{
+    extract::{FromRequest, Multipart, Path, State},
+    http::{Request, StatusCode},
+    Json,
+};
+use tracing::info;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db;
+use crate::error::{AppError, Result};
+use crate::imaging;
+use crate::models::ReceiptExtraction;
+use crate::storage;
+use crate::{AppState, AuthUser};
+
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct ExtractionUploadForm {
+    /// JPEG, PNG, WebP, HEIC or PDF receipt
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/extractions",
+    tag = "extractions",
+    request_body(content = ExtractionUploadForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 202, description = "Receipt queued for extraction", body = ReceiptExtraction),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 413, description = "File too large", body = ErrorResponse),
+        (status = 415, description = "Unsupported file type", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_extraction(
+    State(state): State<AppState>,
+    request: Request<axum::body::Body>,
+) -> Result<(StatusCode, Json<ReceiptExtraction>)> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    let mut multipart = Multipart::from_request(request, &state)
+        .await
+        .map_err(|e| AppError::BadRequest(e.body_text()))?;
+    let upload = super::read_upload(&mut multipart, state.config.max_upload_bytes)
+        .await?
+        .file;
+
+    // Same treatment as stored receipts: upright, no EXIF, HEIC converted.
+    let receipt = imaging::process_receipt(
+        upload.content_type,
+        upload.data,
+        &state.config.heic_convert_command,
+    )
+    .await?;
+
+    let extraction_id = Uuid::new_v4();
+    let storage_key = storage::extraction_key(&user.user_id, extraction_id, receipt.content_type);
+    state
+        .storage
+        .put(&storage_key, receipt.content_type, receipt.data)
+        .await?;
+
+    let created = db::create_extraction(
+        &state.pool,
+        db::NewExtraction {
+            id: extraction_id,
+            user_id: &user.user_id,
+            warranty_id: None,
+            storage_key: &storage_key,
+            content_type: receipt.content_type,
+        },
+    )
+    .await;
+
+    let extraction = match created {
+        Ok(extraction) => extraction,
+        Err(e) => {
+            storage::delete_blobs(state.storage.as_ref(), [storage_key]).await;
+            return Err(e);
+        }
+    };
+
+    info!(extraction_id = %extraction.id, user_id = %user.user_id, "receipt extraction queued");
+
+    Ok((StatusCode::ACCEPTED, Json(extraction)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/extractions/{id}",
+    tag = "extractions",
+    params(
+        ("id" = Uuid, Path, description = "Extraction ID")
+    ),
+    responses(
+        (status = 200, description = "Extraction status and suggestions", body = ReceiptExtraction),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Extraction not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_extraction(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<ReceiptExtraction>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let extraction = db::get_extraction(&state.pool, id, &user.user_id).await?;
+    Ok(Json(extraction))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/warranties/{id}/receipt/extraction",
+    tag = "extractions",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID")
+    ),
+    responses(
+        (status = 200, description = "Latest extraction of the warranty's receipt", body = ReceiptExtraction),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "No extraction for this warranty", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_receipt_extraction(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<ReceiptExtraction>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let extraction = db::get_latest_receipt_extraction(&state.pool, id, &user.user_id).await?;
+    Ok(Json(extraction))
+}
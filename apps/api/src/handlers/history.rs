@@ -0,0 +1,44 @@
+use axum::{
+    extract::{Path, State},
+    http::Request,
+    Json,
+};
+use uuid::Uuid;
+
+use crate::db;
+use crate::error::{AppError, Result};
+use crate::models::WarrantyEvent;
+use crate::{AppState, AuthUser};
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/warranties/{id}/history",
+    tag = "history",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID")
+    ),
+    responses(
+        (status = 200, description = "Changes made to the warranty, oldest first", body = Vec<WarrantyEvent>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Warranty not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_warranty_history(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Vec<WarrantyEvent>>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    db::get_warranty_by_id(&state.pool, id, &user.user_id).await?;
+    let events = db::list_warranty_events(&state.pool, id).await?;
+
+    Ok(Json(events))
+}
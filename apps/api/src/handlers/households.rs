@@ -0,0 +1,500 @@
+use axum::{
+    extract::{Path, State},
+    http::{Request, StatusCode},
+    Json,
+};
+use chrono::Utc;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::db::{self, NewInvitation};
+use crate::error::{AppError, Result};
+use crate::handlers::authorize_household;
+use crate::households;
+use crate::models::{
+    AcceptInvitationRequest, CreateHouseholdRequest, CreateInvitationRequest, CreatedInvitation,
+    Household, HouseholdDetail, HouseholdInvitation, HouseholdMember, HouseholdRole,
+    UpdateHouseholdRequest, UpdateMemberRequest,
+};
+use crate::{AppState, AuthUser};
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/households",
+    tag = "households",
+    responses(
+        (status = 200, description = "Households of the user, with their role", body = Vec<Household>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_households(
+    State(state): State<AppState>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Vec<Household>>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    // New users get their own household on first sight.
+    db::default_household(&state.pool, &user.user_id).await?;
+    let households = db::list_households(&state.pool, &user.user_id).await?;
+    Ok(Json(households))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/households",
+    tag = "households",
+    request_body = CreateHouseholdRequest,
+    responses(
+        (status = 201, description = "Household created, with the user as its owner", body = Household),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_household(
+    State(state): State<AppState>,
+    request: Request<axum::body::Body>,
+) -> Result<(StatusCode, Json<Household>)> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    let body = axum::body::to_bytes(request.into_body(), 64 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+    let payload: CreateHouseholdRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    let name = households::validate_name(&payload.name).map_err(AppError::BadRequest)?;
+    let household = db::create_household(&state.pool, &user.user_id, &name).await?;
+
+    info!(household_id = %household.id, user_id = %user.user_id, "household created");
+
+    Ok((StatusCode::CREATED, Json(household)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/households/{id}",
+    tag = "households",
+    params(
+        ("id" = Uuid, Path, description = "Household ID")
+    ),
+    responses(
+        (status = 200, description = "Household with its members", body = HouseholdDetail),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Household not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_household(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<HouseholdDetail>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let household = db::get_household(&state.pool, id, &user.user_id).await?;
+    let members = db::list_household_members(&state.pool, id).await?;
+
+    Ok(Json(HouseholdDetail { household, members }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/households/{id}",
+    tag = "households",
+    params(
+        ("id" = Uuid, Path, description = "Household ID")
+    ),
+    request_body = UpdateHouseholdRequest,
+    responses(
+        (status = 200, description = "Household renamed", body = Household),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Only owners manage the household", body = ErrorResponse),
+        (status = 404, description = "Household not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_household(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Household>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    authorize_household(&state.pool, id, &user.user_id, HouseholdRole::Owner).await?;
+
+    let body = axum::body::to_bytes(request.into_body(), 64 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+    let payload: UpdateHouseholdRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    let name = households::validate_name(&payload.name).map_err(AppError::BadRequest)?;
+    let household = db::rename_household(&state.pool, id, &user.user_id, &name).await?;
+
+    info!(household_id = %id, user_id = %user.user_id, "household renamed");
+
+    Ok(Json(household))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/households/{id}",
+    tag = "households",
+    params(
+        ("id" = Uuid, Path, description = "Household ID")
+    ),
+    responses(
+        (status = 204, description = "Household deleted"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Only owners manage the household", body = ErrorResponse),
+        (status = 404, description = "Household not found", body = ErrorResponse),
+        (status = 409, description = "Household still has warranties, trashed ones included", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_household(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<StatusCode> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    authorize_household(&state.pool, id, &user.user_id, HouseholdRole::Owner).await?;
+    db::delete_household(&state.pool, id).await?;
+
+    info!(household_id = %id, user_id = %user.user_id, "household deleted");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/households/{id}/members/{user_id}",
+    tag = "households",
+    params(
+        ("id" = Uuid, Path, description = "Household ID"),
+        ("user_id" = String, Path, description = "User ID of the member")
+    ),
+    request_body = UpdateMemberRequest,
+    responses(
+        (status = 200, description = "Role changed", body = HouseholdMember),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Only owners manage the household", body = ErrorResponse),
+        (status = 404, description = "Household or member not found", body = ErrorResponse),
+        (status = 409, description = "The last owner cannot step down", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_member(
+    State(state): State<AppState>,
+    Path((id, member_id)): Path<(Uuid, String)>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<HouseholdMember>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    authorize_household(&state.pool, id, &user.user_id, HouseholdRole::Owner).await?;
+
+    let body = axum::body::to_bytes(request.into_body(), 64 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+    let payload: UpdateMemberRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    let member = db::update_member_role(&state.pool, id, &member_id, payload.role).await?;
+
+    info!(
+        household_id = %id,
+        member_id = %member_id,
+        role = member.role.as_str(),
+        user_id = %user.user_id,
+        "household member role changed"
+    );
+
+    Ok(Json(member))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/households/{id}/members/{user_id}",
+    tag = "households",
+    params(
+        ("id" = Uuid, Path, description = "Household ID"),
+        ("user_id" = String, Path, description = "User ID of the member; the caller's own to leave")
+    ),
+    responses(
+        (status = 204, description = "Member removed"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Only owners remove other members", body = ErrorResponse),
+        (status = 404, description = "Household or member not found", body = ErrorResponse),
+        (status = 409, description = "The last owner cannot leave", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn remove_member(
+    State(state): State<AppState>,
+    Path((id, member_id)): Path<(Uuid, String)>,
+    request: Request<axum::body::Body>,
+) -> Result<StatusCode> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    // Anyone may leave; only owners show others out.
+    let needed = if member_id == user.user_id {
+        HouseholdRole::Viewer
+    } else {
+        HouseholdRole::Owner
+    };
+    authorize_household(&state.pool, id, &user.user_id, needed).await?;
+    db::remove_member(&state.pool, id, &member_id).await?;
+
+    info!(
+        household_id = %id,
+        member_id = %member_id,
+        user_id = %user.user_id,
+        "household member removed"
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/households/{id}/invitations",
+    tag = "households",
+    params(
+        ("id" = Uuid, Path, description = "Household ID")
+    ),
+    responses(
+        (status = 200, description = "Invitations neither accepted nor expired", body = Vec<HouseholdInvitation>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Only owners manage the household", body = ErrorResponse),
+        (status = 404, description = "Household not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_invitations(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Vec<HouseholdInvitation>>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    authorize_household(&state.pool, id, &user.user_id, HouseholdRole::Owner).await?;
+    let invitations = db::list_pending_invitations(&state.pool, id).await?;
+    Ok(Json(invitations))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/households/{id}/invitations",
+    tag = "households",
+    params(
+        ("id" = Uuid, Path, description = "Household ID")
+    ),
+    request_body = CreateInvitationRequest,
+    responses(
+        (status = 201, description = "Invitation created and emailed when SMTP is set up; the token is only shown here", body = CreatedInvitation),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Only owners manage the household", body = ErrorResponse),
+        (status = 404, description = "Household not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_invitation(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<(StatusCode, Json<CreatedInvitation>)> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    authorize_household(&state.pool, id, &user.user_id, HouseholdRole::Owner).await?;
+
+    let body = axum::body::to_bytes(request.into_body(), 64 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+    let payload: CreateInvitationRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    let email = households::normalize_email(&payload.email).map_err(AppError::BadRequest)?;
+    if payload.role == HouseholdRole::Owner {
+        return Err(AppError::BadRequest(
+            "An invitation cannot make an owner".to_string(),
+        ));
+    }
+
+    let invitation = db::create_invitation(
+        &state.pool,
+        NewInvitation {
+            household_id: id,
+            email: &email,
+            role: payload.role,
+            invited_by: &user.user_id,
+            expires_at: households::invitation_expires_at(Utc::now()),
+        },
+        state.config.smtp.is_some(),
+    )
+    .await?;
+
+    info!(
+        household_id = %id,
+        invitation_id = %invitation.id,
+        role = invitation.role.as_str(),
+        user_id = %user.user_id,
+        "household invitation created"
+    );
+
+    let token = invitation.token;
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedInvitation { invitation, token }),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/households/{id}/invitations/{invitation_id}",
+    tag = "households",
+    params(
+        ("id" = Uuid, Path, description = "Household ID"),
+        ("invitation_id" = Uuid, Path, description = "Invitation ID")
+    ),
+    responses(
+        (status = 204, description = "Invitation revoked"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Only owners manage the household", body = ErrorResponse),
+        (status = 404, description = "Household or pending invitation not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_invitation(
+    State(state): State<AppState>,
+    Path((id, invitation_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<StatusCode> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    authorize_household(&state.pool, id, &user.user_id, HouseholdRole::Owner).await?;
+    db::delete_invitation(&state.pool, id, invitation_id).await?;
+
+    info!(
+        household_id = %id,
+        invitation_id = %invitation_id,
+        user_id = %user.user_id,
+        "household invitation revoked"
+    );
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/household-invitations/accept",
+    tag = "households",
+    request_body = AcceptInvitationRequest,
+    responses(
+        (status = 200, description = "Joined the household", body = Household),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Invitation not found, used or expired", body = ErrorResponse),
+        (status = 409, description = "Already a member of the household", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn accept_invitation(
+    State(state): State<AppState>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Household>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    let body = axum::body::to_bytes(request.into_body(), 64 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+    let payload: AcceptInvitationRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    let household = db::accept_invitation(&state.pool, payload.token, &user.user_id).await?;
+
+    info!(
+        household_id = %household.id,
+        role = household.role.as_str(),
+        user_id = %user.user_id,
+        "household invitation accepted"
+    );
+
+    Ok(Json(household))
+}
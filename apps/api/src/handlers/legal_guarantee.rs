@@ -0,0 +1,87 @@
+use axum::{extract::State, http::Request, Json};
+
+use crate::db;
+use crate::error::{AppError, Result};
+use crate::legal::{self, CountryRules, Purchase};
+use crate::models::{LegalGuarantee, LegalGuaranteeRequest};
+use crate::{AppState, AuthUser};
+
+const MAX_REPAIRS: usize = 20;
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/legal-guarantee",
+    tag = "legal",
+    request_body = LegalGuaranteeRequest,
+    responses(
+        (status = 200, description = "Legal guarantee of the purchase and when it ends", body = LegalGuarantee),
+        (status = 400, description = "Invalid request or unsupported country", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn evaluate_legal_guarantee(
+    State(state): State<AppState>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<LegalGuarantee>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    let body = axum::body::to_bytes(request.into_body(), 64 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+
+    let payload: LegalGuaranteeRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    let rules = rules_for(
+        payload
+            .country
+            .as_deref()
+            .unwrap_or(&state.config.legal_country),
+    )?;
+    if payload.repairs.len() > MAX_REPAIRS {
+        return Err(AppError::BadRequest(format!(
+            "At most {} repairs are allowed",
+            MAX_REPAIRS
+        )));
+    }
+    if payload
+        .repairs
+        .iter()
+        .any(|repair| *repair < payload.purchase_date)
+    {
+        return Err(AppError::BadRequest(
+            "Repairs cannot predate the purchase".to_string(),
+        ));
+    }
+
+    let timezone = db::get_user_timezone(&state.pool, &user.user_id).await?;
+    let purchase = Purchase {
+        category: &payload.category,
+        purchase_date: payload.purchase_date,
+        condition: payload.condition,
+        channel: payload.purchase_channel,
+        repairs: &payload.repairs,
+    };
+    let guarantee = legal::legal_guarantee(rules, &purchase, timezone)
+        .ok_or_else(|| AppError::BadRequest("Purchase date is out of range".to_string()))?;
+
+    Ok(Json(guarantee))
+}
+
+pub fn rules_for(country: &str) -> Result<&'static CountryRules> {
+    legal::country_rules(country).ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "Unsupported country {}; expected one of {}",
+            country,
+            legal::supported_countries().join(", ")
+        ))
+    })
+}
@@ -0,0 +1,444 @@
+use axum::{
+    extract::{Path, State},
+    http::{Request, StatusCode},
+    Json,
+};
+use chrono::Utc;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::db::{self, MaintenanceTaskFields, NewMaintenanceCompletion};
+use crate::error::{AppError, Result};
+use crate::handlers::authorize_warranty;
+use crate::maintenance::{self, DEFAULT_LEAD_DAYS, MAX_LEAD_DAYS};
+use crate::models::{
+    CreateMaintenanceCompletionRequest, CreateMaintenanceTaskRequest, HouseholdRole,
+    MaintenanceCompletion, MaintenanceTask, UpdateMaintenanceTaskRequest,
+};
+use crate::{AppState, AuthUser};
+
+const MAX_TASKS_PER_WARRANTY: i64 = 20;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/warranties/{id}/maintenance-tasks",
+    tag = "maintenance",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID")
+    ),
+    responses(
+        (status = 200, description = "Maintenance tasks of the warranty, the next due first", body = Vec<MaintenanceTask>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Warranty not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_maintenance_tasks(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Vec<MaintenanceTask>>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    db::get_warranty_by_id(&state.pool, id, &user.user_id).await?;
+    let tasks = db::list_maintenance_tasks(&state.pool, id, &user.user_id).await?;
+    Ok(Json(tasks))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/warranties/{id}/maintenance-tasks",
+    tag = "maintenance",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID")
+    ),
+    request_body = CreateMaintenanceTaskRequest,
+    responses(
+        (status = 201, description = "Maintenance task created", body = MaintenanceTask),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Warranty not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_maintenance_task(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<(StatusCode, Json<MaintenanceTask>)> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+
+    let body = axum::body::to_bytes(request.into_body(), 64 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+    let payload: CreateMaintenanceTaskRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    let warranty = db::get_warranty_by_id(&state.pool, id, &user.user_id).await?;
+    let fields = MaintenanceTaskFields {
+        title: payload.title.trim(),
+        notes: payload.notes.as_deref(),
+        interval_months: payload.interval_months,
+        interval_km: payload.interval_km,
+        starts_at: payload.starts_at.unwrap_or(warranty.purchase_date),
+        start_km: payload.start_km,
+        lead_days: payload.lead_days.unwrap_or(DEFAULT_LEAD_DAYS),
+    };
+    validate_fields(&fields)?;
+
+    if db::count_maintenance_tasks(&state.pool, id).await? >= MAX_TASKS_PER_WARRANTY {
+        return Err(AppError::BadRequest(format!(
+            "At most {} maintenance tasks are allowed per warranty",
+            MAX_TASKS_PER_WARRANTY
+        )));
+    }
+
+    let timezone = db::get_warranty_timezone(&state.pool, id).await?;
+    let task = db::create_maintenance_task(&state.pool, id, fields, timezone).await?;
+
+    info!(task_id = %task.id, warranty_id = %id, user_id = %user.user_id, "maintenance task created");
+
+    Ok((StatusCode::CREATED, Json(task)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/warranties/{id}/maintenance-tasks/{task_id}",
+    tag = "maintenance",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("task_id" = Uuid, Path, description = "Maintenance task ID")
+    ),
+    responses(
+        (status = 200, description = "Maintenance task", body = MaintenanceTask),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Maintenance task not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_maintenance_task(
+    State(state): State<AppState>,
+    Path((id, task_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<MaintenanceTask>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let task = db::get_maintenance_task(&state.pool, id, task_id, &user.user_id).await?;
+    Ok(Json(task))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/warranties/{id}/maintenance-tasks/{task_id}",
+    tag = "maintenance",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("task_id" = Uuid, Path, description = "Maintenance task ID")
+    ),
+    request_body = UpdateMaintenanceTaskRequest,
+    responses(
+        (status = 200, description = "Maintenance task updated and rescheduled", body = MaintenanceTask),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Maintenance task not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_maintenance_task(
+    State(state): State<AppState>,
+    Path((id, task_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<MaintenanceTask>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+
+    let body = axum::body::to_bytes(request.into_body(), 64 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+    let payload: UpdateMaintenanceTaskRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    let existing = db::get_maintenance_task(&state.pool, id, task_id, &user.user_id).await?;
+    let title = payload.title.unwrap_or(existing.title);
+    let notes = payload.notes.or(existing.notes);
+    let fields = MaintenanceTaskFields {
+        title: title.trim(),
+        notes: notes.as_deref(),
+        interval_months: payload.interval_months.unwrap_or(existing.interval_months),
+        interval_km: payload.interval_km.unwrap_or(existing.interval_km),
+        starts_at: payload.starts_at.unwrap_or(existing.starts_at),
+        start_km: payload.start_km.unwrap_or(existing.start_km),
+        lead_days: payload.lead_days.unwrap_or(existing.lead_days),
+    };
+    validate_fields(&fields)?;
+
+    let timezone = db::get_warranty_timezone(&state.pool, id).await?;
+    let task = db::update_maintenance_task(&state.pool, task_id, fields, timezone).await?;
+
+    info!(task_id = %task.id, user_id = %user.user_id, "maintenance task updated");
+
+    Ok(Json(task))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/warranties/{id}/maintenance-tasks/{task_id}",
+    tag = "maintenance",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("task_id" = Uuid, Path, description = "Maintenance task ID")
+    ),
+    responses(
+        (status = 204, description = "Maintenance task and its completion log deleted"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Maintenance task not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_maintenance_task(
+    State(state): State<AppState>,
+    Path((id, task_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<StatusCode> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+
+    db::delete_maintenance_task(&state.pool, id, task_id, &user.user_id).await?;
+
+    info!(task_id = %task_id, user_id = %user.user_id, "maintenance task deleted");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/warranties/{id}/maintenance-tasks/{task_id}/completions",
+    tag = "maintenance",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("task_id" = Uuid, Path, description = "Maintenance task ID")
+    ),
+    responses(
+        (status = 200, description = "Completion log of the task, newest first", body = Vec<MaintenanceCompletion>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Maintenance task not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_maintenance_completions(
+    State(state): State<AppState>,
+    Path((id, task_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Vec<MaintenanceCompletion>>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    db::get_maintenance_task(&state.pool, id, task_id, &user.user_id).await?;
+    let completions = db::list_maintenance_completions(&state.pool, task_id).await?;
+    Ok(Json(completions))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/warranties/{id}/maintenance-tasks/{task_id}/completions",
+    tag = "maintenance",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("task_id" = Uuid, Path, description = "Maintenance task ID")
+    ),
+    request_body = CreateMaintenanceCompletionRequest,
+    responses(
+        (status = 201, description = "Completion logged; the task is rescheduled from it", body = MaintenanceCompletion),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Maintenance task not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_maintenance_completion(
+    State(state): State<AppState>,
+    Path((id, task_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<(StatusCode, Json<MaintenanceCompletion>)> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+
+    let body = axum::body::to_bytes(request.into_body(), 64 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+    let payload: CreateMaintenanceCompletionRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    let done_at = payload.done_at.unwrap_or_else(Utc::now);
+    if done_at > Utc::now() {
+        return Err(AppError::BadRequest(
+            "Completion date cannot be in the future".to_string(),
+        ));
+    }
+    validate_km("Odometer", payload.odometer_km)?;
+    validate_notes(payload.notes.as_deref())?;
+    if payload.cost_cents.is_some_and(|cost| cost < 0) {
+        return Err(AppError::BadRequest("Cost cannot be negative".to_string()));
+    }
+
+    db::get_maintenance_task(&state.pool, id, task_id, &user.user_id).await?;
+    let timezone = db::get_warranty_timezone(&state.pool, id).await?;
+    let (completion, task) = db::create_maintenance_completion(
+        &state.pool,
+        task_id,
+        NewMaintenanceCompletion {
+            done_at,
+            odometer_km: payload.odometer_km,
+            notes: payload.notes.as_deref(),
+            cost_cents: payload.cost_cents,
+        },
+        timezone,
+    )
+    .await?;
+
+    info!(
+        completion_id = %completion.id,
+        task_id = %task_id,
+        user_id = %user.user_id,
+        next_due_at = ?task.next_due_at,
+        next_due_km = ?task.next_due_km,
+        "maintenance completion created"
+    );
+
+    Ok((StatusCode::CREATED, Json(completion)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/warranties/{id}/maintenance-tasks/{task_id}/completions/{completion_id}",
+    tag = "maintenance",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("task_id" = Uuid, Path, description = "Maintenance task ID"),
+        ("completion_id" = Uuid, Path, description = "Completion ID")
+    ),
+    responses(
+        (status = 204, description = "Completion deleted; the task is rescheduled from the one before"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Completion not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_maintenance_completion(
+    State(state): State<AppState>,
+    Path((id, task_id, completion_id)): Path<(Uuid, Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<StatusCode> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+
+    db::get_maintenance_task(&state.pool, id, task_id, &user.user_id).await?;
+    let timezone = db::get_warranty_timezone(&state.pool, id).await?;
+    db::delete_maintenance_completion(&state.pool, task_id, completion_id, timezone).await?;
+
+    info!(completion_id = %completion_id, task_id = %task_id, user_id = %user.user_id, "maintenance completion deleted");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn validate_fields(fields: &MaintenanceTaskFields<'_>) -> Result<()> {
+    if fields.title.is_empty() {
+        return Err(AppError::BadRequest("Title is required".to_string()));
+    }
+    if fields.title.len() > 200 {
+        return Err(AppError::BadRequest(
+            "Title must be less than 200 characters".to_string(),
+        ));
+    }
+    validate_notes(fields.notes)?;
+    maintenance::validate_schedule(fields.interval_months, fields.interval_km)
+        .map_err(AppError::BadRequest)?;
+    validate_km("Start km", fields.start_km)?;
+    if !(0..=MAX_LEAD_DAYS).contains(&fields.lead_days) {
+        return Err(AppError::BadRequest(format!(
+            "Lead days must be between 0 and {}",
+            MAX_LEAD_DAYS
+        )));
+    }
+    Ok(())
+}
+
+fn validate_notes(notes: Option<&str>) -> Result<()> {
+    if notes.is_some_and(|notes| notes.len() > 2000) {
+        return Err(AppError::BadRequest(
+            "Notes must be less than 2000 characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_km(field: &str, km: Option<i32>) -> Result<()> {
+    if km.is_some_and(|km| km < 0) {
+        return Err(AppError::BadRequest(format!(
+            "{} cannot be negative",
+            field
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,194 @@
+pub mod attachments;
+pub mod calendar;
+pub mod claims;
+pub mod coverages;
+pub mod digests;
+pub mod extractions;
+pub mod history;
+pub mod households;
+pub mod legal_guarantee;
+pub mod maintenance;
+pub mod push_subscriptions;
+pub mod reminder_preferences;
+pub mod sync;
+pub mod trash;
+pub mod webhooks;
+
+use std::collections::HashMap;
+
+use axum::{
+    body::Body,
+    extract::{multipart::MultipartError, Multipart},
+    http::{header, StatusCode},
+    response::Response,
+};
+use bytes::{Bytes, BytesMut};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db;
+use crate::error::{AppError, Result};
+use crate::models::HouseholdRole;
+use crate::storage::{self, Storage};
+
+const MAX_TEXT_FIELD_BYTES: usize = 1024;
+
+pub struct FileUpload {
+    pub content_type: &'static str,
+    pub file_name: Option<String>,
+    pub data: Bytes,
+}
+
+pub struct MultipartUpload {
+    pub file: FileUpload,
+    pub fields: HashMap<String, String>,
+}
+
+/// Reads a multipart body made of one `file` part and optional short text
+/// fields. The file is read chunk by chunk and rejected as soon as it grows
+/// past `max_bytes`.
+pub async fn read_upload(multipart: &mut Multipart, max_bytes: usize) -> Result<MultipartUpload> {
+    let mut file = None;
+    let mut fields = HashMap::new();
+
+    while let Some(mut field) = multipart.next_field().await.map_err(multipart_error)? {
+        let Some(name) = field.name().map(str::to_string) else {
+            continue;
+        };
+
+        if name != "file" {
+            let value = field.text().await.map_err(multipart_error)?;
+            if value.len() > MAX_TEXT_FIELD_BYTES {
+                return Err(AppError::BadRequest(format!("Field {} is too long", name)));
+            }
+            fields.insert(name, value);
+            continue;
+        }
+
+        let declared_type = field.content_type().map(str::to_string);
+        let file_name = field.file_name().map(str::to_string);
+        let mut data = BytesMut::new();
+        while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+            if data.len() + chunk.len() > max_bytes {
+                return Err(AppError::PayloadTooLarge(format!(
+                    "File must be at most {} bytes",
+                    max_bytes
+                )));
+            }
+            data.extend_from_slice(&chunk);
+        }
+
+        if data.is_empty() {
+            return Err(AppError::BadRequest("File is empty".to_string()));
+        }
+
+        let content_type = storage::validate_content_type(declared_type.as_deref(), &data)?;
+        file = Some(FileUpload {
+            content_type,
+            file_name,
+            data: data.freeze(),
+        });
+    }
+
+    let file = file.ok_or_else(|| AppError::BadRequest("Missing file field".to_string()))?;
+    Ok(MultipartUpload { file, fields })
+}
+
+/// Checks the user has at least `needed` in the household of a warranty,
+/// trashed or not. Non-members get a 404, as if the warranty did not exist.
+pub async fn authorize_warranty(
+    pool: &PgPool,
+    warranty_id: Uuid,
+    user_id: &str,
+    needed: HouseholdRole,
+) -> Result<HouseholdRole> {
+    let role = db::get_warranty_role(pool, warranty_id, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Warranty not found".to_string()))?;
+    check_role(role, needed)
+}
+
+/// Checks the user has at least `needed` in a household. Non-members get a
+/// 404.
+pub async fn authorize_household(
+    pool: &PgPool,
+    household_id: Uuid,
+    user_id: &str,
+    needed: HouseholdRole,
+) -> Result<HouseholdRole> {
+    let role = db::get_household_role(pool, household_id, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Household not found".to_string()))?;
+    check_role(role, needed)
+}
+
+/// The household a new warranty goes to: the one asked for, if the user may
+/// edit it, else their default one.
+pub async fn household_for_new_warranty(
+    pool: &PgPool,
+    user_id: &str,
+    requested: Option<Uuid>,
+) -> Result<Uuid> {
+    match requested {
+        Some(household_id) => {
+            authorize_household(pool, household_id, user_id, HouseholdRole::Editor).await?;
+            Ok(household_id)
+        }
+        None => db::default_household(pool, user_id).await,
+    }
+}
+
+fn check_role(role: HouseholdRole, needed: HouseholdRole) -> Result<HouseholdRole> {
+    if role < needed {
+        return Err(AppError::Forbidden(format!(
+            "Needs the {} role in the household",
+            needed.as_str()
+        )));
+    }
+    Ok(role)
+}
+
+fn multipart_error(e: MultipartError) -> AppError {
+    if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        AppError::PayloadTooLarge("Request body is too large".to_string())
+    } else {
+        AppError::BadRequest(e.body_text())
+    }
+}
+
+/// Streams a stored blob back to the client.
+pub async fn file_response(
+    storage: &dyn Storage,
+    key: &str,
+    content_type: &str,
+    file_name: &str,
+) -> Result<Response> {
+    let object = storage.get(key).await?;
+
+    let mut response = Response::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("inline; filename=\"{}\"", header_safe_file_name(file_name)),
+        )
+        .header(header::CACHE_CONTROL, "private, max-age=300")
+        .header(header::X_CONTENT_TYPE_OPTIONS, "nosniff");
+    if let Some(length) = object.content_length {
+        response = response.header(header::CONTENT_LENGTH, length);
+    }
+
+    response
+        .body(Body::from_stream(object.body))
+        .map_err(|e| AppError::Storage(e.to_string()))
+}
+
+fn header_safe_file_name(file_name: &str) -> String {
+    file_name
+        .chars()
+        .map(|c| match c {
+            '"' | '\\' => '_',
+            c if c == ' ' || c.is_ascii_graphic() => c,
+            _ => '_',
+        })
+        .collect()
+}
@@ -0,0 +1,134 @@
+use axum::{
+    extract::State,
+    http::{Request, StatusCode},
+    Json,
+};
+use serde::de::DeserializeOwned;
+use tracing::info;
+
+use crate::db;
+use crate::error::{AppError, Result};
+use crate::models::{
+    CreatePushSubscriptionRequest, DeletePushSubscriptionRequest, PushSubscription,
+    VapidPublicKeyResponse,
+};
+use crate::notifications::{self, VapidKey};
+use crate::{AppState, AuthUser};
+
+const MAX_BODY_BYTES: usize = 16 * 1024;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/push-subscriptions/vapid-public-key",
+    tag = "push",
+    responses(
+        (status = 200, description = "Key to subscribe browsers with", body = VapidPublicKeyResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Web Push is not configured", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_vapid_public_key(
+    State(state): State<AppState>,
+) -> Result<Json<VapidPublicKeyResponse>> {
+    let config = state
+        .config
+        .vapid
+        .as_ref()
+        .ok_or_else(|| AppError::NotFound("Web Push is not configured".to_string()))?;
+    let key = VapidKey::new(config).map_err(AppError::Internal)?;
+
+    Ok(Json(VapidPublicKeyResponse {
+        public_key: key.public_key().to_string(),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/push-subscriptions",
+    tag = "push",
+    request_body = CreatePushSubscriptionRequest,
+    responses(
+        (status = 201, description = "Browser subscribed", body = PushSubscription),
+        (status = 400, description = "Invalid subscription", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_push_subscription(
+    State(state): State<AppState>,
+    request: Request<axum::body::Body>,
+) -> Result<(StatusCode, Json<PushSubscription>)> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+    let payload: CreatePushSubscriptionRequest = read_json(request).await?;
+
+    notifications::validate_endpoint(&payload.endpoint, !state.config.is_production())
+        .map_err(AppError::BadRequest)?;
+    notifications::validate_subscription_keys(&payload.keys.p256dh, &payload.keys.auth)
+        .map_err(AppError::BadRequest)?;
+
+    let subscription = db::upsert_push_subscription(
+        &state.pool,
+        &user.user_id,
+        &payload.endpoint,
+        &payload.keys.p256dh,
+        &payload.keys.auth,
+    )
+    .await?;
+
+    info!(subscription_id = %subscription.id, user_id = %user.user_id, "push subscription saved");
+
+    Ok((StatusCode::CREATED, Json(subscription)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/push-subscriptions",
+    tag = "push",
+    request_body = DeletePushSubscriptionRequest,
+    responses(
+        (status = 204, description = "Browser unsubscribed"),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Subscription not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_push_subscription(
+    State(state): State<AppState>,
+    request: Request<axum::body::Body>,
+) -> Result<StatusCode> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+    let payload: DeletePushSubscriptionRequest = read_json(request).await?;
+
+    db::delete_push_subscription(&state.pool, &user.user_id, &payload.endpoint).await?;
+
+    info!(user_id = %user.user_id, "push subscription deleted");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn read_json<T: DeserializeOwned>(request: Request<axum::body::Body>) -> Result<T> {
+    let body = axum::body::to_bytes(request.into_body(), MAX_BODY_BYTES)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+
+    serde_json::from_slice(&body).map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))
+}
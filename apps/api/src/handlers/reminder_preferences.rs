@@ -0,0 +1,142 @@
+use axum::{extract::State, http::Request, Json};
+use chrono_tz::Tz;
+use tracing::info;
+
+use crate::db;
+use crate::error::{AppError, Result};
+use crate::models::{ReminderPreferences, UpdateReminderPreferencesRequest};
+use crate::reminders;
+use crate::{AppState, AuthUser};
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/reminder-preferences",
+    tag = "reminders",
+    responses(
+        (status = 200, description = "Saved preferences, or the defaults", body = ReminderPreferences),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_reminder_preferences(
+    State(state): State<AppState>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<ReminderPreferences>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let preferences = current_preferences(&state, &user.user_id).await?;
+    Ok(Json(preferences))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/reminder-preferences",
+    tag = "reminders",
+    request_body = UpdateReminderPreferencesRequest,
+    responses(
+        (status = 200, description = "Preferences saved", body = ReminderPreferences),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_reminder_preferences(
+    State(state): State<AppState>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<ReminderPreferences>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    let body = axum::body::to_bytes(request.into_body(), 64 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+    let payload: UpdateReminderPreferencesRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    let mut preferences = current_preferences(&state, &user.user_id).await?;
+    apply_update(&mut preferences, payload)?;
+    let preferences =
+        db::save_reminder_preferences(&state.pool, &user.user_id, &preferences).await?;
+
+    info!(user_id = %user.user_id, "reminder preferences saved");
+
+    Ok(Json(preferences))
+}
+
+async fn current_preferences(state: &AppState, user_id: &str) -> Result<ReminderPreferences> {
+    let saved = db::get_reminder_preferences(&state.pool, user_id).await?;
+    Ok(saved
+        .unwrap_or_else(|| ReminderPreferences::defaults(&state.config.reminder_thresholds_days)))
+}
+
+const SUPPORTED_LOCALES: [&str; 2] = ["fr", "en"];
+
+/// Validates `update` and merges it into `preferences`.
+fn apply_update(
+    preferences: &mut ReminderPreferences,
+    update: UpdateReminderPreferencesRequest,
+) -> Result<()> {
+    if let Some(mut lead_days) = update.lead_days {
+        reminders::validate_lead_days(&lead_days).map_err(AppError::BadRequest)?;
+        lead_days.sort_unstable_by(|a, b| b.cmp(a));
+        lead_days.dedup();
+        preferences.lead_days = lead_days;
+    }
+    if let Some(channels) = update.channels {
+        preferences.channels.clear();
+        for channel in channels {
+            if !preferences.channels.contains(&channel) {
+                preferences.channels.push(channel);
+            }
+        }
+    }
+    if let Some(quiet_hours) = update.quiet_hours {
+        if quiet_hours.is_some_and(|hours| hours.start == hours.end) {
+            return Err(AppError::BadRequest(
+                "Quiet hours must start and end at different times".to_string(),
+            ));
+        }
+        preferences.quiet_hours_start = quiet_hours.map(|hours| hours.start);
+        preferences.quiet_hours_end = quiet_hours.map(|hours| hours.end);
+    }
+    if let Some(timezone) = update.timezone {
+        if timezone.parse::<Tz>().is_err() {
+            return Err(AppError::BadRequest(format!(
+                "Unknown timezone: {}",
+                timezone
+            )));
+        }
+        preferences.timezone = timezone;
+    }
+    if let Some(locale) = update.locale {
+        if locale
+            .as_deref()
+            .is_some_and(|locale| !SUPPORTED_LOCALES.contains(&locale))
+        {
+            return Err(AppError::BadRequest(format!(
+                "Locale must be one of: {}",
+                SUPPORTED_LOCALES.join(", ")
+            )));
+        }
+        preferences.locale = locale;
+    }
+    if let Some(mode) = update.mode {
+        preferences.mode = mode;
+    }
+    if let Some(frequency) = update.digest_frequency {
+        preferences.digest_frequency = frequency;
+    }
+    Ok(())
+}
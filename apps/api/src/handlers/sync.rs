@@ -0,0 +1,267 @@
+use axum::{
+    extract::{Query, State},
+    http::Request,
+    Json,
+};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::audit::Actor;
+use crate::db;
+use crate::error::{AppError, Result};
+use crate::handlers::{authorize_warranty, household_for_new_warranty};
+use crate::models::{
+    HouseholdRole, SyncChange, SyncPushRequest, SyncPushResponse, SyncQuery, SyncResponse,
+    SyncResult, SyncStatus, Warranty, WebhookEvent,
+};
+use crate::sync::{SyncToken, MAX_PUSH_CHANGES};
+use crate::webhooks;
+use crate::{fill_warranty_months, validate_create_warranty, AppState, AuthUser};
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/sync",
+    tag = "sync",
+    params(
+        ("since" = Option<String>, Query, description = "`token` of the previous sync; without it, every warranty is sent")
+    ),
+    responses(
+        (status = 200, description = "Warranties changed and deleted since the token", body = SyncResponse),
+        (status = 400, description = "Invalid sync token", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_changes(
+    State(state): State<AppState>,
+    Query(query): Query<SyncQuery>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<SyncResponse>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let since = query
+        .since
+        .as_deref()
+        .map(SyncToken::decode)
+        .transpose()
+        .map_err(AppError::BadRequest)?;
+
+    let changes =
+        db::get_sync_changes(&state.pool, &user.user_id, since.map(|token| token.xmin)).await?;
+
+    Ok(Json(SyncResponse {
+        warranties: changes.warranties,
+        deleted: changes.deleted,
+        token: SyncToken { xmin: changes.xmin }.encode(),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/sync",
+    tag = "sync",
+    request_body = SyncPushRequest,
+    responses(
+        (status = 200, description = "Outcome of each change", body = SyncPushResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn push_changes(
+    State(state): State<AppState>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<SyncPushResponse>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+    let actor = Actor::new(&user.user_id, &request);
+
+    let body = axum::body::to_bytes(request.into_body(), 1024 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+    let payload: SyncPushRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    if payload.changes.len() > MAX_PUSH_CHANGES {
+        return Err(AppError::BadRequest(format!(
+            "At most {} changes can be pushed at once",
+            MAX_PUSH_CHANGES
+        )));
+    }
+
+    let mut results = Vec::with_capacity(payload.changes.len());
+    for change in payload.changes {
+        let id = change.id();
+        let result = match apply_change(&state, &actor, change).await {
+            Err(AppError::BadRequest(message) | AppError::Forbidden(message)) => {
+                rejected(id, message)
+            }
+            result => result?,
+        };
+        results.push(result);
+    }
+
+    let applied = results
+        .iter()
+        .filter(|result| result.status == SyncStatus::Applied)
+        .count();
+    info!(user_id = %user.user_id, changes = results.len(), applied, "sync changes pushed");
+
+    Ok(Json(SyncPushResponse { results }))
+}
+
+async fn apply_change(state: &AppState, actor: &Actor, change: SyncChange) -> Result<SyncResult> {
+    let user_id = actor.user_id.as_str();
+    match change {
+        SyncChange::Upsert {
+            id,
+            base_updated_at: None,
+            mut warranty,
+        } => {
+            // Pushed again after another client deleted it
+            if db::get_tombstone(&state.pool, id, user_id).await?.is_some() {
+                return Ok(conflict(id, None, "Warranty was deleted"));
+            }
+            validate_create_warranty(&warranty)?;
+            let household_id =
+                match household_for_new_warranty(&state.pool, user_id, warranty.household_id).await
+                {
+                    Err(AppError::NotFound(message)) => return Ok(rejected(id, message)),
+                    result => result?,
+                };
+            fill_warranty_months(state, user_id, None, &mut warranty).await?;
+
+            match db::create_warranty(
+                &state.pool,
+                user_id,
+                household_id,
+                Some(id),
+                warranty,
+                actor,
+            )
+            .await
+            {
+                Ok(warranty) => {
+                    webhooks::emit(&state.pool, WebhookEvent::Created, &warranty).await;
+                    Ok(applied(id, Some(warranty)))
+                }
+                Err(AppError::Conflict(_)) => {
+                    match db::get_warranty_by_id(&state.pool, id, user_id).await {
+                        Ok(warranty) => Ok(conflict(id, Some(warranty), "Warranty already exists")),
+                        Err(AppError::NotFound(_)) => Ok(rejected(id, "Warranty id is taken")),
+                        Err(e) => Err(e),
+                    }
+                }
+                Err(e) => Err(e),
+            }
+        }
+        SyncChange::Upsert {
+            id,
+            base_updated_at: Some(base_updated_at),
+            mut warranty,
+        } => {
+            validate_create_warranty(&warranty)?;
+            authorize_edit(state, user_id, id).await?;
+            fill_warranty_months(state, user_id, Some(id), &mut warranty).await?;
+
+            match db::replace_warranty(&state.pool, id, user_id, base_updated_at, warranty, actor)
+                .await
+            {
+                Ok(warranty) => {
+                    webhooks::emit(&state.pool, WebhookEvent::Updated, &warranty).await;
+                    Ok(applied(id, Some(warranty)))
+                }
+                Err(AppError::Conflict(_) | AppError::NotFound(_)) => {
+                    server_copy(state, user_id, id).await
+                }
+                Err(e) => Err(e),
+            }
+        }
+        SyncChange::Delete {
+            id,
+            base_updated_at,
+        } => {
+            authorize_edit(state, user_id, id).await?;
+            match db::trash_warranty(&state.pool, id, user_id, Some(base_updated_at), actor).await {
+                Ok(warranty) => {
+                    webhooks::emit(&state.pool, WebhookEvent::Deleted, &warranty).await;
+                    Ok(applied(id, None))
+                }
+                // Deleting twice is no conflict.
+                Err(AppError::NotFound(_))
+                    if db::get_tombstone(&state.pool, id, user_id).await?.is_some() =>
+                {
+                    Ok(applied(id, None))
+                }
+                Err(AppError::Conflict(_) | AppError::NotFound(_)) => {
+                    server_copy(state, user_id, id).await
+                }
+                Err(e) => Err(e),
+            }
+        }
+    }
+}
+
+/// Checks the user may change a warranty. Warranties the user cannot see
+/// are left for the change itself to report.
+async fn authorize_edit(state: &AppState, user_id: &str, id: Uuid) -> Result<()> {
+    match authorize_warranty(&state.pool, id, user_id, HouseholdRole::Editor).await {
+        Ok(_) | Err(AppError::NotFound(_)) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// A conflict with the warranty as the server has it, or a rejection when
+/// the user never had it.
+async fn server_copy(state: &AppState, user_id: &str, id: Uuid) -> Result<SyncResult> {
+    match db::get_warranty_by_id(&state.pool, id, user_id).await {
+        Ok(warranty) => Ok(conflict(id, Some(warranty), "Warranty was changed since")),
+        Err(AppError::NotFound(_)) => {
+            if db::get_tombstone(&state.pool, id, user_id).await?.is_some() {
+                Ok(conflict(id, None, "Warranty was deleted"))
+            } else {
+                Ok(rejected(id, "Warranty not found"))
+            }
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn applied(id: Uuid, warranty: Option<Warranty>) -> SyncResult {
+    SyncResult {
+        id,
+        status: SyncStatus::Applied,
+        warranty,
+        message: None,
+    }
+}
+
+fn conflict(id: Uuid, warranty: Option<Warranty>, message: &str) -> SyncResult {
+    SyncResult {
+        id,
+        status: SyncStatus::Conflict,
+        warranty,
+        message: Some(message.to_string()),
+    }
+}
+
+fn rejected(id: Uuid, message: impl Into<String>) -> SyncResult {
+    SyncResult {
+        id,
+        status: SyncStatus::Rejected,
+        warranty: None,
+        message: Some(message.into()),
+    }
+}
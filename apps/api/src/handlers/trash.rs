@@ -0,0 +1,92 @@
+use axum::{
+    extract::{Path, State},
+    http::Request,
+    Json,
+};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::audit::Actor;
+use crate::db;
+use crate::error::{AppError, Result};
+use crate::handlers::authorize_warranty;
+use crate::models::{HouseholdRole, TrashListResponse, TrashedWarranty, Warranty, WebhookEvent};
+use crate::trash;
+use crate::webhooks;
+use crate::{AppState, AuthUser};
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/trash",
+    tag = "trash",
+    responses(
+        (status = 200, description = "Deleted warranties that can still be restored", body = TrashListResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_trash(
+    State(state): State<AppState>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<TrashListResponse>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let retention_days = state.config.trash_retention_days;
+    let warranties = db::list_trashed_warranties(&state.pool, &user.user_id)
+        .await?
+        .into_iter()
+        .filter_map(|warranty| {
+            let purge_at = trash::purge_at(warranty.deleted_at?, retention_days);
+            Some(TrashedWarranty { warranty, purge_at })
+        })
+        .collect();
+
+    Ok(Json(TrashListResponse {
+        warranties,
+        retention_days,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/warranties/{id}/restore",
+    tag = "trash",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID")
+    ),
+    responses(
+        (status = 200, description = "Warranty restored from the trash", body = Warranty),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Warranty not found in trash", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn restore_warranty(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Warranty>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+    let actor = Actor::new(&user.user_id, &request);
+    let warranty = db::restore_warranty(&state.pool, id, &user.user_id, &actor).await?;
+    webhooks::emit(&state.pool, WebhookEvent::Restored, &warranty).await;
+
+    info!(warranty_id = %id, user_id = %user.user_id, "warranty restored from trash");
+
+    Ok(Json(warranty))
+}
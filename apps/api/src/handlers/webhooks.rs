@@ -0,0 +1,355 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{Request, StatusCode},
+    Json,
+};
+use serde::Deserialize;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::db;
+use crate::error::{AppError, Result};
+use crate::models::{
+    CreateWebhookRequest, CreatedWebhook, UpdateWebhookRequest, Webhook, WebhookDelivery,
+    WebhookEvent,
+};
+use crate::webhooks;
+use crate::{AppState, AuthUser};
+
+const MAX_WEBHOOKS_PER_USER: i64 = 10;
+const MAX_DESCRIPTION_LENGTH: usize = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct DeliveriesQuery {
+    limit: Option<i64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks",
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "The user's webhooks", body = Vec<Webhook>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Vec<Webhook>>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let webhooks = db::list_webhooks(&state.pool, &user.user_id).await?;
+    Ok(Json(webhooks))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks",
+    tag = "webhooks",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 201, description = "Webhook created; the secret is only shown here", body = CreatedWebhook),
+        (status = 400, description = "Invalid request or too many webhooks", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    request: Request<axum::body::Body>,
+) -> Result<(StatusCode, Json<CreatedWebhook>)> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    let body = axum::body::to_bytes(request.into_body(), 64 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+    let payload: CreateWebhookRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    validate_url(&state, &payload.url)?;
+    let description = validate_description(payload.description)?;
+    let events = validate_events(payload.events.unwrap_or_else(|| WebhookEvent::ALL.to_vec()))?;
+
+    if db::count_webhooks(&state.pool, &user.user_id).await? >= MAX_WEBHOOKS_PER_USER {
+        return Err(AppError::BadRequest(format!(
+            "At most {} webhooks are allowed",
+            MAX_WEBHOOKS_PER_USER
+        )));
+    }
+
+    let secret = webhooks::generate_secret();
+    let webhook = db::create_webhook(
+        &state.pool,
+        &user.user_id,
+        &payload.url,
+        description.as_deref(),
+        &secret,
+        &events,
+    )
+    .await?;
+
+    info!(webhook_id = %webhook.id, user_id = %user.user_id, "webhook created");
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedWebhook { webhook, secret }),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks/{id}",
+    tag = "webhooks",
+    params(
+        ("id" = Uuid, Path, description = "Webhook ID")
+    ),
+    responses(
+        (status = 200, description = "Webhook found", body = Webhook),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn get_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Webhook>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let webhook = db::get_webhook(&state.pool, id, &user.user_id).await?;
+    Ok(Json(webhook))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/webhooks/{id}",
+    tag = "webhooks",
+    params(
+        ("id" = Uuid, Path, description = "Webhook ID")
+    ),
+    request_body = UpdateWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook updated", body = Webhook),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn update_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Webhook>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?
+        .clone();
+
+    let body = axum::body::to_bytes(request.into_body(), 64 * 1024)
+        .await
+        .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
+    let payload: UpdateWebhookRequest = serde_json::from_slice(&body)
+        .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
+
+    let mut webhook = db::get_webhook(&state.pool, id, &user.user_id).await?;
+    if let Some(url) = payload.url {
+        validate_url(&state, &url)?;
+        webhook.url = url;
+    }
+    if let Some(description) = payload.description {
+        webhook.description = validate_description(description)?;
+    }
+    if let Some(events) = payload.events {
+        webhook.events = validate_events(events)?;
+    }
+    if let Some(active) = payload.active {
+        webhook.active = active;
+    }
+    let webhook = db::update_webhook(&state.pool, &user.user_id, &webhook).await?;
+
+    info!(webhook_id = %webhook.id, user_id = %user.user_id, "webhook updated");
+
+    Ok(Json(webhook))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/webhooks/{id}",
+    tag = "webhooks",
+    params(
+        ("id" = Uuid, Path, description = "Webhook ID")
+    ),
+    responses(
+        (status = 204, description = "Webhook and its delivery log deleted"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    request: Request<axum::body::Body>,
+) -> Result<StatusCode> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    db::delete_webhook(&state.pool, id, &user.user_id).await?;
+
+    info!(webhook_id = %id, user_id = %user.user_id, "webhook deleted");
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks/{id}/deliveries",
+    tag = "webhooks",
+    params(
+        ("id" = Uuid, Path, description = "Webhook ID"),
+        ("limit" = Option<i64>, Query, description = "Number of deliveries to return (default: 50, max: 100)")
+    ),
+    responses(
+        (status = 200, description = "Latest deliveries, newest first", body = Vec<WebhookDelivery>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn list_webhook_deliveries(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<DeliveriesQuery>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<Vec<WebhookDelivery>>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+    let limit = query.limit.unwrap_or(50).clamp(1, 100);
+
+    let webhook = db::get_webhook(&state.pool, id, &user.user_id).await?;
+    let deliveries = db::list_webhook_deliveries(&state.pool, webhook.id, limit).await?;
+    Ok(Json(deliveries))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks/{id}/deliveries/{delivery_id}/redeliver",
+    tag = "webhooks",
+    params(
+        ("id" = Uuid, Path, description = "Webhook ID"),
+        ("delivery_id" = Uuid, Path, description = "Delivery to send again")
+    ),
+    responses(
+        (status = 202, description = "Event queued again as a new delivery", body = WebhookDelivery),
+        (status = 400, description = "Webhook is inactive", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Webhook or delivery not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+pub async fn redeliver_webhook_delivery(
+    State(state): State<AppState>,
+    Path((id, delivery_id)): Path<(Uuid, Uuid)>,
+    request: Request<axum::body::Body>,
+) -> Result<(StatusCode, Json<WebhookDelivery>)> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let webhook = db::get_webhook(&state.pool, id, &user.user_id).await?;
+    if !webhook.active {
+        return Err(AppError::BadRequest(
+            "Webhook is inactive; activate it before redelivering".to_string(),
+        ));
+    }
+    let delivery = db::redeliver_webhook_delivery(&state.pool, webhook.id, delivery_id).await?;
+
+    info!(
+        webhook_id = %webhook.id,
+        delivery_id = %delivery.id,
+        redelivery_of = %delivery_id,
+        user_id = %user.user_id,
+        "webhook delivery queued again"
+    );
+
+    Ok((StatusCode::ACCEPTED, Json(delivery)))
+}
+
+fn validate_url(state: &AppState, url: &str) -> Result<()> {
+    webhooks::validate_url(url, !state.config.is_production())
+        .map(|_| ())
+        .map_err(AppError::BadRequest)
+}
+
+fn validate_description(description: Option<String>) -> Result<Option<String>> {
+    let description = description
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty());
+    if description
+        .as_ref()
+        .is_some_and(|d| d.chars().count() > MAX_DESCRIPTION_LENGTH)
+    {
+        return Err(AppError::BadRequest(format!(
+            "Description must be at most {} characters",
+            MAX_DESCRIPTION_LENGTH
+        )));
+    }
+    Ok(description)
+}
+
+/// Drops duplicates, keeping the order given.
+fn validate_events(events: Vec<WebhookEvent>) -> Result<Vec<WebhookEvent>> {
+    let mut unique = Vec::with_capacity(events.len());
+    for event in events {
+        if !unique.contains(&event) {
+            unique.push(event);
+        }
+    }
+    if unique.is_empty() {
+        return Err(AppError::BadRequest(
+            "At least one event is required".to_string(),
+        ));
+    }
+    Ok(unique)
+}
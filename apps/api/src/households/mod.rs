@@ -0,0 +1,46 @@
+//! Households: warranties shared by their members, each with a role.
+
+#[cfg(test)]
+mod tests;
+
+use chrono::{DateTime, Duration, Utc};
+
+pub const MAX_NAME_LENGTH: usize = 100;
+
+/// How long an invitation can be accepted.
+pub const INVITATION_DAYS: i64 = 7;
+
+/// Trims a household name and checks its length.
+pub fn validate_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Household name is required".to_string());
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!(
+            "Household name must be at most {} characters",
+            MAX_NAME_LENGTH
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Trims and lowercases an email address, after a sanity check: the mail
+/// relay has the final word.
+pub fn normalize_email(email: &str) -> Result<String, String> {
+    let email = email.trim().to_lowercase();
+    let valid = email.len() <= 255
+        && !email.chars().any(char::is_whitespace)
+        && email.split_once('@').is_some_and(|(local, domain)| {
+            !local.is_empty() && !domain.contains('@') && domain.contains('.')
+        });
+    if !valid {
+        return Err("Invalid email address".to_string());
+    }
+    Ok(email)
+}
+
+/// When an invitation sent at `now` expires.
+pub fn invitation_expires_at(now: DateTime<Utc>) -> DateTime<Utc> {
+    now + Duration::days(INVITATION_DAYS)
+}
@@ -0,0 +1,44 @@
+use chrono::{TimeZone, Utc};
+
+use crate::households::{invitation_expires_at, normalize_email, validate_name};
+use crate::models::HouseholdRole;
+
+#[test]
+fn test_validate_name_trims() {
+    assert_eq!(validate_name("  Les Martin ").unwrap(), "Les Martin");
+    assert!(validate_name("   ").is_err());
+    assert!(validate_name(&"é".repeat(100)).is_ok());
+    assert!(validate_name(&"é".repeat(101)).is_err());
+}
+
+#[test]
+fn test_normalize_email() {
+    assert_eq!(
+        normalize_email(" Marie.Martin@Example.com ").unwrap(),
+        "marie.martin@example.com"
+    );
+    assert!(normalize_email("marie").is_err());
+    assert!(normalize_email("@example.com").is_err());
+    assert!(normalize_email("marie@localhost").is_err());
+    assert!(normalize_email("marie martin@example.com").is_err());
+    assert!(normalize_email("marie@a@example.com").is_err());
+}
+
+#[test]
+fn test_invitation_expires_after_a_week() {
+    let now = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+    assert_eq!(
+        invitation_expires_at(now),
+        Utc.with_ymd_and_hms(2026, 3, 8, 12, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn test_roles_are_ordered_by_rights() {
+    assert!(HouseholdRole::Viewer < HouseholdRole::Editor);
+    assert!(HouseholdRole::Editor < HouseholdRole::Owner);
+    assert_eq!(
+        serde_json::to_string(&HouseholdRole::Editor).unwrap(),
+        "\"editor\""
+    );
+}
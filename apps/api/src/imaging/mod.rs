@@ -0,0 +1,175 @@
+#[cfg(test)]
+mod tests;
+
+use std::io::Cursor;
+
+use bytes::Bytes;
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, ImageDecoder, ImageReader, Limits, RgbImage};
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+
+/// Longest edge of the stored receipt image.
+pub const MAX_DIMENSION: u32 = 2560;
+/// Longest edge of the receipt thumbnail.
+pub const THUMBNAIL_DIMENSION: u32 = 320;
+
+const JPEG_QUALITY: u8 = 85;
+const THUMBNAIL_JPEG_QUALITY: u8 = 75;
+/// Refuse anything that would decode to more than this many pixels.
+const MAX_DECODED_PIXELS: u64 = 50_000_000;
+
+/// A receipt ready to be stored, plus its thumbnail when it is an image.
+#[derive(Debug)]
+pub struct ProcessedReceipt {
+    pub content_type: &'static str,
+    pub data: Bytes,
+    pub thumbnail: Option<Bytes>,
+}
+
+#[derive(Debug)]
+pub struct NormalizedImage {
+    pub jpeg: Vec<u8>,
+    pub thumbnail: Vec<u8>,
+}
+
+/// Turns an uploaded receipt into the variants we keep. Images are rotated
+/// upright, re-encoded as JPEG without any metadata (EXIF, GPS, ...) and
+/// capped to [`MAX_DIMENSION`]; PDFs are stored untouched.
+pub async fn process_receipt(
+    content_type: &'static str,
+    data: Bytes,
+    heic_convert_command: &str,
+) -> Result<ProcessedReceipt> {
+    let data = match content_type {
+        "application/pdf" => {
+            return Ok(ProcessedReceipt {
+                content_type,
+                data,
+                thumbnail: None,
+            })
+        }
+        "image/heic" => Bytes::from(convert_heic(&data, heic_convert_command).await?),
+        _ => data,
+    };
+
+    let normalized = tokio::task::spawn_blocking(move || normalize(&data))
+        .await
+        .map_err(|e| AppError::Internal(format!("image processing task failed: {}", e)))??;
+
+    Ok(ProcessedReceipt {
+        content_type: "image/jpeg",
+        data: Bytes::from(normalized.jpeg),
+        thumbnail: Some(Bytes::from(normalized.thumbnail)),
+    })
+}
+
+/// Decodes a JPEG, PNG or WebP image, applies its EXIF orientation and
+/// re-encodes a capped JPEG and a thumbnail. CPU-bound: call it from a
+/// blocking task.
+pub fn normalize(data: &[u8]) -> Result<NormalizedImage> {
+    let image = decode_upright(data).map_err(|e| {
+        tracing::debug!(error = %e, "failed to decode image");
+        AppError::UnsupportedMediaType("Image could not be decoded".to_string())
+    })?;
+
+    let image = if image.width() > MAX_DIMENSION || image.height() > MAX_DIMENSION {
+        image.resize(
+            MAX_DIMENSION,
+            MAX_DIMENSION,
+            image::imageops::FilterType::Lanczos3,
+        )
+    } else {
+        image
+    };
+    let flattened = flatten_on_white(&image);
+    let thumbnail = if image.width() > THUMBNAIL_DIMENSION || image.height() > THUMBNAIL_DIMENSION {
+        flatten_on_white(&image.thumbnail(THUMBNAIL_DIMENSION, THUMBNAIL_DIMENSION))
+    } else {
+        flattened.clone()
+    };
+
+    Ok(NormalizedImage {
+        jpeg: encode_jpeg(&flattened, JPEG_QUALITY)?,
+        thumbnail: encode_jpeg(&thumbnail, THUMBNAIL_JPEG_QUALITY)?,
+    })
+}
+
+fn decode_upright(data: &[u8]) -> image::ImageResult<DynamicImage> {
+    let mut reader = ImageReader::new(Cursor::new(data)).with_guessed_format()?;
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(20_000);
+    limits.max_image_height = Some(20_000);
+    limits.max_alloc = Some(MAX_DECODED_PIXELS * 4);
+    reader.limits(limits);
+
+    let mut decoder = reader.into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+/// Drops the alpha channel the way a viewer would show it: over white.
+fn flatten_on_white(image: &DynamicImage) -> RgbImage {
+    if !image.color().has_alpha() {
+        return image.to_rgb8();
+    }
+
+    let rgba = image.to_rgba8();
+    RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let blend = |c: u8| ((c as u16 * a as u16 + 255 * (255 - a as u16)) / 255) as u8;
+        image::Rgb([blend(r), blend(g), blend(b)])
+    })
+}
+
+fn encode_jpeg(image: &RgbImage, quality: u8) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    JpegEncoder::new_with_quality(&mut out, quality)
+        .encode_image(image)
+        .map_err(|e| AppError::Internal(format!("JPEG encoding failed: {}", e)))?;
+    Ok(out)
+}
+
+/// HEIC cannot be decoded in-process, so it goes through libheif's
+/// `heif-convert` (or whatever `HEIC_CONVERT_COMMAND` points to), which applies
+/// the HEIF rotation and writes a JPEG we then normalize like any other.
+async fn convert_heic(data: &[u8], command: &str) -> Result<Vec<u8>> {
+    let dir = std::env::temp_dir();
+    let id = Uuid::new_v4();
+    let input = dir.join(format!("garry-{}.heic", id));
+    let output = dir.join(format!("garry-{}.jpg", id));
+
+    let unsupported =
+        || AppError::UnsupportedMediaType("HEIC images cannot be processed".to_string());
+
+    tokio::fs::write(&input, data)
+        .await
+        .map_err(|e| AppError::Internal(format!("write {}: {}", input.display(), e)))?;
+
+    let status = tokio::process::Command::new(command)
+        .arg(&input)
+        .arg(&output)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .await;
+    let converted = match status {
+        Ok(status) if status.success() => tokio::fs::read(&output).await.ok(),
+        Ok(status) => {
+            tracing::warn!(%status, "HEIC conversion failed");
+            None
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, command, "HEIC converter unavailable");
+            None
+        }
+    };
+
+    let _ = tokio::fs::remove_file(&input).await;
+    let _ = tokio::fs::remove_file(&output).await;
+
+    converted.ok_or_else(unsupported)
+}
@@ -0,0 +1,160 @@
+use std::io::Cursor;
+
+use bytes::Bytes;
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, GenericImageView, ImageFormat, Rgb, RgbImage, Rgba, RgbaImage};
+
+use crate::error::AppError;
+use crate::imaging::{normalize, process_receipt, MAX_DIMENSION, THUMBNAIL_DIMENSION};
+
+fn jpeg(width: u32, height: u32) -> Vec<u8> {
+    let image = RgbImage::from_fn(width, height, |x, _| {
+        if x < width / 2 {
+            Rgb([255, 0, 0])
+        } else {
+            Rgb([0, 0, 255])
+        }
+    });
+    let mut out = Vec::new();
+    JpegEncoder::new_with_quality(&mut out, 90)
+        .encode_image(&image)
+        .unwrap();
+    out
+}
+
+/// Inserts an APP1 segment holding an EXIF orientation tag and a GPS IFD
+/// pointer right after the JPEG SOI marker.
+fn with_exif(jpeg: &[u8], orientation: u16) -> Vec<u8> {
+    let mut tiff = b"MM\0\x2a\0\0\0\x08".to_vec();
+    tiff.extend_from_slice(&2u16.to_be_bytes());
+    // Orientation (SHORT)
+    tiff.extend_from_slice(&[0x01, 0x12, 0x00, 0x03, 0, 0, 0, 1]);
+    tiff.extend_from_slice(&orientation.to_be_bytes());
+    tiff.extend_from_slice(&[0, 0]);
+    // GPSInfo IFD pointer (LONG), pointing at an empty IFD
+    tiff.extend_from_slice(&[0x88, 0x25, 0x00, 0x04, 0, 0, 0, 1]);
+    tiff.extend_from_slice(&38u32.to_be_bytes());
+    tiff.extend_from_slice(&0u32.to_be_bytes());
+    tiff.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+
+    let mut app1 = b"Exif\0\0".to_vec();
+    app1.extend_from_slice(&tiff);
+
+    let mut out = jpeg[..2].to_vec();
+    out.extend_from_slice(&[0xFF, 0xE1]);
+    out.extend_from_slice(&((app1.len() + 2) as u16).to_be_bytes());
+    out.extend_from_slice(&app1);
+    out.extend_from_slice(&jpeg[2..]);
+    out
+}
+
+fn decode(data: &[u8]) -> DynamicImage {
+    image::load_from_memory_with_format(data, ImageFormat::Jpeg).unwrap()
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+#[test]
+fn test_normalize_applies_exif_orientation_and_strips_metadata() {
+    let original = with_exif(&jpeg(40, 20), 6);
+    assert!(contains(&original, b"Exif\0\0"));
+
+    let normalized = normalize(&original).unwrap();
+    let image = decode(&normalized.jpeg);
+
+    // Orientation 6 means the camera was rotated 90° clockwise.
+    assert_eq!(image.dimensions(), (20, 40));
+    assert!(!contains(&normalized.jpeg, b"Exif\0\0"));
+    assert!(!contains(&normalized.thumbnail, b"Exif\0\0"));
+
+    // The red left half ends up on top once rotated.
+    let top = image.get_pixel(10, 2).0;
+    let bottom = image.get_pixel(10, 37).0;
+    assert!(top[0] > 200 && top[2] < 60, "top pixel was {:?}", top);
+    assert!(
+        bottom[2] > 200 && bottom[0] < 60,
+        "bottom pixel was {:?}",
+        bottom
+    );
+}
+
+#[test]
+fn test_normalize_caps_resolution_and_builds_thumbnail() {
+    let normalized = normalize(&jpeg(MAX_DIMENSION + 440, 1000)).unwrap();
+
+    let image = decode(&normalized.jpeg);
+    assert_eq!(image.width(), MAX_DIMENSION);
+    assert_eq!(image.height(), 1000 * MAX_DIMENSION / (MAX_DIMENSION + 440));
+
+    let thumbnail = decode(&normalized.thumbnail);
+    assert_eq!(thumbnail.width(), THUMBNAIL_DIMENSION);
+    assert!(thumbnail.height() < THUMBNAIL_DIMENSION);
+}
+
+#[test]
+fn test_normalize_keeps_small_images_at_their_size() {
+    let normalized = normalize(&jpeg(300, 200)).unwrap();
+    assert_eq!(decode(&normalized.jpeg).dimensions(), (300, 200));
+    assert_eq!(decode(&normalized.thumbnail).dimensions(), (300, 200));
+}
+
+#[test]
+fn test_normalize_flattens_transparency_onto_white() {
+    let image = RgbaImage::from_pixel(16, 16, Rgba([0, 0, 0, 0]));
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .unwrap();
+
+    let normalized = normalize(&png).unwrap();
+    let pixel = decode(&normalized.jpeg).get_pixel(8, 8).0;
+    assert!(pixel[..3].iter().all(|&c| c > 245), "pixel was {:?}", pixel);
+}
+
+#[test]
+fn test_normalize_rejects_corrupt_images() {
+    let mut truncated = jpeg(64, 64);
+    truncated.truncate(20);
+    assert!(matches!(
+        normalize(&truncated),
+        Err(AppError::UnsupportedMediaType(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_process_receipt_passes_pdfs_through() {
+    let pdf = Bytes::from_static(b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n");
+    let processed = process_receipt("application/pdf", pdf.clone(), "heif-convert")
+        .await
+        .unwrap();
+
+    assert_eq!(processed.content_type, "application/pdf");
+    assert_eq!(processed.data, pdf);
+    assert!(processed.thumbnail.is_none());
+}
+
+#[tokio::test]
+async fn test_process_receipt_reencodes_png_as_jpeg() {
+    let image = RgbImage::from_pixel(10, 10, Rgb([10, 200, 10]));
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .unwrap();
+
+    let processed = process_receipt("image/png", Bytes::from(png), "heif-convert")
+        .await
+        .unwrap();
+
+    assert_eq!(processed.content_type, "image/jpeg");
+    assert_eq!(decode(&processed.data).dimensions(), (10, 10));
+    assert!(processed.thumbnail.is_some());
+}
+
+#[tokio::test]
+async fn test_process_receipt_rejects_heic_without_converter() {
+    let heic = Bytes::from_static(b"\0\0\0\x18ftypheic\0\0\0\0");
+    let result = process_receipt("image/heic", heic, "garry-missing-heif-converter").await;
+    assert!(matches!(result, Err(AppError::UnsupportedMediaType(_))));
+}
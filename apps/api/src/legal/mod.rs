@@ -0,0 +1,155 @@
+//! The legal guarantee of conformity a purchase gets, from the rules tables
+//! of the country it was made in.
+
+mod rules;
+#[cfg(test)]
+mod tests;
+
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+pub use rules::{ConformityRule, CountryRules, RepairExtension, COUNTRIES, DEFAULT_COUNTRY};
+
+use crate::models::{ItemCondition, LegalGuarantee, PurchaseChannel, WarrantyCategory};
+use crate::warranty_period::warranty_end_date;
+
+pub struct Purchase<'a> {
+    pub category: &'a WarrantyCategory,
+    pub purchase_date: DateTime<Utc>,
+    pub condition: ItemCondition,
+    pub channel: PurchaseChannel,
+    /// When the item came back from each repair under the legal guarantee
+    pub repairs: &'a [DateTime<Utc>],
+}
+
+/// The rules of `country`, matched without regard to case.
+pub fn country_rules(country: &str) -> Option<&'static CountryRules> {
+    COUNTRIES
+        .iter()
+        .find(|rules| rules.country.eq_ignore_ascii_case(country.trim()))
+}
+
+pub fn supported_countries() -> Vec<&'static str> {
+    COUNTRIES.iter().map(|rules| rules.country).collect()
+}
+
+/// The first rule of `rules` matching `purchase`. Dates are compared on the
+/// buyer's calendar, in `timezone`.
+pub fn conformity_rule<'r>(
+    rules: &'r CountryRules,
+    purchase: &Purchase,
+    timezone: Tz,
+) -> Option<&'r ConformityRule> {
+    let purchased_on = local_date(purchase.purchase_date, timezone);
+    rules.conformity.iter().find(|rule| {
+        rule.category
+            .as_ref()
+            .is_none_or(|category| category == purchase.category)
+            && rule
+                .condition
+                .is_none_or(|condition| condition == purchase.condition)
+            && rule
+                .channel
+                .is_none_or(|channel| channel == purchase.channel)
+            && rule.since.is_none_or(|since| purchased_on >= since)
+    })
+}
+
+/// The legal guarantee of `purchase` and when it ends, extended by each
+/// repair made while it still ran. `None` when a date is out of range.
+pub fn legal_guarantee(
+    rules: &CountryRules,
+    purchase: &Purchase,
+    timezone: Tz,
+) -> Option<LegalGuarantee> {
+    let rule = conformity_rule(rules, purchase, timezone);
+    let (rule_id, months, legal_basis) = match rule {
+        Some(rule) => (rule.id, rule.months, rule.legal_basis),
+        None => ("none", 0, ""),
+    };
+    let covered = months > 0;
+
+    let mut end_date = None;
+    let mut extended_end_date = None;
+    let mut repair_extension_months = 0;
+    let mut repair_extension_basis = None;
+    let mut repairs_counted = 0;
+    if covered {
+        let end = warranty_end_date(purchase.purchase_date, months, timezone)?;
+        let mut extended = end;
+        if let Some(extension) = repair_extension(rules, purchase.purchase_date, timezone) {
+            repair_extension_months = extension.months;
+            repair_extension_basis = Some(extension.legal_basis.to_string());
+            let mut repairs = purchase.repairs.to_vec();
+            repairs.sort_unstable();
+            for repair in repairs {
+                if repair < purchase.purchase_date {
+                    continue;
+                }
+                if !extension.extends(repair, extended, repairs_counted as usize) {
+                    break;
+                }
+                repairs_counted += 1;
+                extended = warranty_end_date(
+                    purchase.purchase_date,
+                    months + extension.months * repairs_counted,
+                    timezone,
+                )?;
+            }
+        }
+        end_date = Some(end);
+        extended_end_date = Some(extended);
+    }
+
+    let (withdrawal_end_date, withdrawal_basis) = match purchase.channel {
+        PurchaseChannel::Online => (
+            Some(
+                purchase
+                    .purchase_date
+                    .with_timezone(&timezone)
+                    .checked_add_days(Days::new(rules.withdrawal.days))?
+                    .with_timezone(&Utc),
+            ),
+            Some(rules.withdrawal.legal_basis.to_string()),
+        ),
+        PurchaseChannel::Store | PurchaseChannel::Private => (None, None),
+    };
+
+    Some(LegalGuarantee {
+        country: rules.country.to_string(),
+        rule: rule_id.to_string(),
+        covered,
+        months,
+        legal_basis: legal_basis.to_string(),
+        end_date,
+        repair_extension_months,
+        repair_extension_basis,
+        repairs_counted,
+        extended_end_date,
+        withdrawal_end_date,
+        withdrawal_basis,
+    })
+}
+
+/// The extension a repair under the legal guarantee gives to an item bought
+/// on `purchase_date`, if any.
+pub fn repair_extension(
+    rules: &CountryRules,
+    purchase_date: DateTime<Utc>,
+    timezone: Tz,
+) -> Option<&RepairExtension> {
+    rules
+        .repair_extension
+        .as_ref()
+        .filter(|extension| local_date(purchase_date, timezone) >= extension.since)
+}
+
+/// Length of the legal guarantee of `purchase`, 0 when none applies. This is
+/// the warranty length used when the user gives none.
+pub fn legal_months(rules: &CountryRules, purchase: &Purchase, timezone: Tz) -> i32 {
+    conformity_rule(rules, purchase, timezone).map_or(0, |rule| rule.months)
+}
+
+fn local_date(date: DateTime<Utc>, timezone: Tz) -> NaiveDate {
+    date.with_timezone(&timezone).date_naive()
+}
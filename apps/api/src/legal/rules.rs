@@ -0,0 +1,263 @@
+//! The legal guarantee rules of each supported country. Within a table the
+//! first matching rule wins, so specific rules come before general ones and
+//! every table ends with a rule that matches any purchase.
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::models::{ItemCondition, PurchaseChannel, WarrantyCategory};
+
+pub struct CountryRules {
+    /// ISO 3166-1 alpha-2 code, or `EU`
+    pub country: &'static str,
+    pub conformity: &'static [ConformityRule],
+    pub repair_extension: Option<RepairExtension>,
+    pub withdrawal: Withdrawal,
+}
+
+/// How long the seller answers for defects of conformity.
+pub struct ConformityRule {
+    pub id: &'static str,
+    pub category: Option<WarrantyCategory>,
+    pub condition: Option<ItemCondition>,
+    pub channel: Option<PurchaseChannel>,
+    /// Only for purchases made on or after this day
+    pub since: Option<NaiveDate>,
+    /// 0 when no legal guarantee of conformity applies
+    pub months: i32,
+    pub legal_basis: &'static str,
+}
+
+/// Months added to the guarantee each time the item is repaired under it.
+pub struct RepairExtension {
+    pub months: i32,
+    /// Repairs past this many no longer extend the guarantee
+    pub max_repairs: Option<usize>,
+    /// Only for purchases made on or after this day
+    pub since: NaiveDate,
+    pub legal_basis: &'static str,
+}
+
+impl RepairExtension {
+    /// Whether a repair asked for at `repaired_at` extends a guarantee ending
+    /// at `ends_at` that `repairs_counted` repairs already extended. Repairs
+    /// after the end were not made under the guarantee.
+    pub fn extends(
+        &self,
+        repaired_at: DateTime<Utc>,
+        ends_at: DateTime<Utc>,
+        repairs_counted: usize,
+    ) -> bool {
+        repaired_at <= ends_at && self.max_repairs.is_none_or(|max| repairs_counted < max)
+    }
+}
+
+/// Cooling-off period of distance purchases.
+pub struct Withdrawal {
+    pub days: u64,
+    pub legal_basis: &'static str,
+}
+
+/// Used when no country is given or configured.
+pub const DEFAULT_COUNTRY: &str = "FR";
+
+pub static COUNTRIES: &[CountryRules] = &[FR, ES, PT, EU];
+
+const fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    match NaiveDate::from_ymd_opt(year, month, day) {
+        Some(date) => date,
+        None => panic!("invalid date"),
+    }
+}
+
+/// Both the French and Iberian reforms transposing directive 2019/771 apply
+/// to contracts made from this day.
+const DIRECTIVE_2019_771: NaiveDate = date(2022, 1, 1);
+
+const fn rule(id: &'static str, months: i32, legal_basis: &'static str) -> ConformityRule {
+    ConformityRule {
+        id,
+        category: None,
+        condition: None,
+        channel: None,
+        since: None,
+        months,
+        legal_basis,
+    }
+}
+
+const FR: CountryRules = CountryRules {
+    country: "FR",
+    conformity: &[
+        ConformityRule {
+            channel: Some(PurchaseChannel::Private),
+            ..rule(
+                "fr.private",
+                0,
+                "Code civil, art. 1641 : entre particuliers, seule la garantie des vices cachés s'applique",
+            )
+        },
+        ConformityRule {
+            condition: Some(ItemCondition::Used),
+            since: Some(DIRECTIVE_2019_771),
+            ..rule(
+                "fr.used",
+                12,
+                "Code de la consommation, art. L217-7 : biens d'occasion",
+            )
+        },
+        ConformityRule {
+            condition: Some(ItemCondition::Used),
+            ..rule(
+                "fr.used.before_2022",
+                24,
+                "Code de la consommation, ancien art. L217-12 : action prescrite par deux ans à compter de la délivrance, neuf ou d'occasion",
+            )
+        },
+        ConformityRule {
+            category: Some(WarrantyCategory::Clothing),
+            ..rule(
+                "fr.new.clothing",
+                6,
+                "Durée retenue par Garry pour les vêtements neufs",
+            )
+        },
+        ConformityRule {
+            category: Some(WarrantyCategory::Sports),
+            ..rule(
+                "fr.new.sports",
+                12,
+                "Durée retenue par Garry pour les articles de sport neufs",
+            )
+        },
+        ConformityRule {
+            category: Some(WarrantyCategory::Other),
+            ..rule(
+                "fr.new.other",
+                12,
+                "Durée retenue par Garry pour les autres biens neufs",
+            )
+        },
+        rule("fr.new", 24, "Code de la consommation, art. L217-3"),
+    ],
+    repair_extension: Some(RepairExtension {
+        months: 6,
+        max_repairs: None,
+        since: DIRECTIVE_2019_771,
+        legal_basis: "Code de la consommation, art. L217-13",
+    }),
+    withdrawal: Withdrawal {
+        days: 14,
+        legal_basis: "Code de la consommation, art. L221-18",
+    },
+};
+
+const ES: CountryRules = CountryRules {
+    country: "ES",
+    conformity: &[
+        ConformityRule {
+            channel: Some(PurchaseChannel::Private),
+            ..rule(
+                "es.private",
+                0,
+                "Real Decreto Legislativo 1/2007, art. 114 : ventes entre particuliers exclues",
+            )
+        },
+        ConformityRule {
+            condition: Some(ItemCondition::Used),
+            ..rule(
+                "es.used",
+                12,
+                "Real Decreto Legislativo 1/2007, art. 120 : biens d'occasion",
+            )
+        },
+        ConformityRule {
+            since: Some(DIRECTIVE_2019_771),
+            ..rule("es.new", 36, "Real Decreto Legislativo 1/2007, art. 120")
+        },
+        rule(
+            "es.new.before_2022",
+            24,
+            "Real Decreto Legislativo 1/2007, ancien art. 123",
+        ),
+    ],
+    repair_extension: None,
+    withdrawal: Withdrawal {
+        days: 14,
+        legal_basis: "Real Decreto Legislativo 1/2007, art. 102",
+    },
+};
+
+const PT: CountryRules = CountryRules {
+    country: "PT",
+    conformity: &[
+        ConformityRule {
+            channel: Some(PurchaseChannel::Private),
+            ..rule(
+                "pt.private",
+                0,
+                "Decreto-Lei n.º 84/2021 : ventes entre particuliers exclues",
+            )
+        },
+        ConformityRule {
+            condition: Some(ItemCondition::Used),
+            since: Some(DIRECTIVE_2019_771),
+            ..rule(
+                "pt.used",
+                18,
+                "Decreto-Lei n.º 84/2021, art. 12 : biens d'occasion",
+            )
+        },
+        ConformityRule {
+            condition: Some(ItemCondition::Used),
+            ..rule(
+                "pt.used.before_2022",
+                12,
+                "Decreto-Lei n.º 67/2003, art. 5 : biens d'occasion",
+            )
+        },
+        ConformityRule {
+            since: Some(DIRECTIVE_2019_771),
+            ..rule("pt.new", 36, "Decreto-Lei n.º 84/2021, art. 12")
+        },
+        rule("pt.new.before_2022", 24, "Decreto-Lei n.º 67/2003, art. 5"),
+    ],
+    repair_extension: Some(RepairExtension {
+        months: 6,
+        max_repairs: Some(4),
+        since: DIRECTIVE_2019_771,
+        legal_basis: "Decreto-Lei n.º 84/2021, art. 18",
+    }),
+    withdrawal: Withdrawal {
+        days: 14,
+        legal_basis: "Decreto-Lei n.º 24/2014, art. 10",
+    },
+};
+
+/// The minimum every member state guarantees.
+const EU: CountryRules = CountryRules {
+    country: "EU",
+    conformity: &[
+        ConformityRule {
+            channel: Some(PurchaseChannel::Private),
+            ..rule(
+                "eu.private",
+                0,
+                "Directive (UE) 2019/771, art. 1 : ventes entre particuliers exclues",
+            )
+        },
+        ConformityRule {
+            condition: Some(ItemCondition::Used),
+            ..rule(
+                "eu.used",
+                12,
+                "Directive (UE) 2019/771, art. 10 : biens d'occasion",
+            )
+        },
+        rule("eu.new", 24, "Directive (UE) 2019/771, art. 10"),
+    ],
+    repair_extension: None,
+    withdrawal: Withdrawal {
+        days: 14,
+        legal_basis: "Directive 2011/83/UE, art. 9",
+    },
+};
@@ -0,0 +1,423 @@
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use super::rules::{ConformityRule, CountryRules, Withdrawal};
+use crate::legal::{
+    conformity_rule, country_rules, legal_guarantee, legal_months, supported_countries, Purchase,
+    COUNTRIES,
+};
+use crate::models::{ItemCondition, PurchaseChannel, WarrantyCategory};
+
+fn at(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+    Tz::Europe__Paris
+        .with_ymd_and_hms(year, month, day, 10, 0, 0)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+fn purchase(
+    purchase_date: DateTime<Utc>,
+    condition: ItemCondition,
+    channel: PurchaseChannel,
+) -> Purchase<'static> {
+    Purchase {
+        category: &WarrantyCategory::Electronics,
+        purchase_date,
+        condition,
+        channel,
+        repairs: &[],
+    }
+}
+
+/// Id of the rule of `country` applying to `purchase`.
+fn rule_id(country: &str, purchase: &Purchase) -> &'static str {
+    conformity_rule(country_rules(country).unwrap(), purchase, Tz::Europe__Paris)
+        .unwrap()
+        .id
+}
+
+#[test]
+fn test_every_table_covers_every_purchase() {
+    for rules in COUNTRIES {
+        let last = rules.conformity.last().unwrap();
+        assert!(
+            last.category.is_none()
+                && last.condition.is_none()
+                && last.channel.is_none()
+                && last.since.is_none(),
+            "{} has no catch-all rule",
+            rules.country
+        );
+        let prefix = format!("{}.", rules.country.to_lowercase());
+        for rule in rules.conformity {
+            assert!(rule.id.starts_with(&prefix), "{}", rule.id);
+            assert!(!rule.legal_basis.is_empty(), "{}", rule.id);
+        }
+    }
+}
+
+#[test]
+fn test_country_rules_lookup() {
+    assert_eq!(country_rules("FR").unwrap().country, "FR");
+    assert_eq!(country_rules(" fr ").unwrap().country, "FR");
+    assert_eq!(country_rules("eu").unwrap().country, "EU");
+    assert!(country_rules("US").is_none());
+    assert!(country_rules("").is_none());
+    assert_eq!(supported_countries(), ["FR", "ES", "PT", "EU"]);
+}
+
+#[test]
+fn test_fr_new() {
+    let new = purchase(at(2024, 3, 15), ItemCondition::New, PurchaseChannel::Store);
+    assert_eq!(rule_id("FR", &new), "fr.new");
+    // Before the 2022 reform too.
+    let old = purchase(at(2020, 3, 15), ItemCondition::New, PurchaseChannel::Online);
+    assert_eq!(rule_id("FR", &old), "fr.new");
+
+    let guarantee = legal_guarantee(country_rules("FR").unwrap(), &new, Tz::Europe__Paris).unwrap();
+    assert!(guarantee.covered);
+    assert_eq!(guarantee.months, 24);
+    assert_eq!(
+        guarantee.legal_basis,
+        "Code de la consommation, art. L217-3"
+    );
+    assert_eq!(guarantee.end_date, Some(at(2026, 3, 15)));
+    assert_eq!(guarantee.extended_end_date, Some(at(2026, 3, 15)));
+    assert_eq!(guarantee.withdrawal_end_date, None);
+}
+
+#[test]
+fn test_fr_new_by_category() {
+    let rules = country_rules("FR").unwrap();
+    for (category, months) in [
+        (WarrantyCategory::Electronics, 24),
+        (WarrantyCategory::Appliances, 24),
+        (WarrantyCategory::Furniture, 24),
+        (WarrantyCategory::Clothing, 6),
+        (WarrantyCategory::Automotive, 24),
+        (WarrantyCategory::Sports, 12),
+        (WarrantyCategory::Other, 12),
+    ] {
+        let new = Purchase {
+            category: &category,
+            ..purchase(at(2024, 3, 15), ItemCondition::New, PurchaseChannel::Store)
+        };
+        assert_eq!(
+            legal_months(rules, &new, Tz::Europe__Paris),
+            months,
+            "{:?}",
+            category
+        );
+    }
+
+    // Used clothes follow the rules for used goods.
+    let used = Purchase {
+        category: &WarrantyCategory::Clothing,
+        ..purchase(at(2024, 3, 15), ItemCondition::Used, PurchaseChannel::Store)
+    };
+    assert_eq!(rule_id("FR", &used), "fr.used");
+}
+
+#[test]
+fn test_fr_used() {
+    let used = purchase(at(2024, 3, 15), ItemCondition::Used, PurchaseChannel::Store);
+    assert_eq!(rule_id("FR", &used), "fr.used");
+    let guarantee =
+        legal_guarantee(country_rules("FR").unwrap(), &used, Tz::Europe__Paris).unwrap();
+    assert_eq!(guarantee.months, 12);
+    assert_eq!(guarantee.end_date, Some(at(2025, 3, 15)));
+}
+
+#[test]
+fn test_fr_used_before_2022() {
+    let used = purchase(
+        at(2021, 12, 31),
+        ItemCondition::Used,
+        PurchaseChannel::Store,
+    );
+    assert_eq!(rule_id("FR", &used), "fr.used.before_2022");
+    // Only the presumption that a defect existed at delivery was shorter.
+    assert_eq!(
+        legal_months(country_rules("FR").unwrap(), &used, Tz::Europe__Paris),
+        24
+    );
+}
+
+#[test]
+fn test_reform_date_is_read_on_the_buyer_calendar() {
+    // 23:30 UTC on December 31 is already January 1 in Paris.
+    let purchase_date = Utc.with_ymd_and_hms(2021, 12, 31, 23, 30, 0).unwrap();
+    let used = purchase(purchase_date, ItemCondition::Used, PurchaseChannel::Store);
+    let rules = country_rules("FR").unwrap();
+    assert_eq!(legal_months(rules, &used, Tz::Europe__Paris), 12);
+    assert_eq!(legal_months(rules, &used, Tz::UTC), 24);
+}
+
+#[test]
+fn test_fr_private() {
+    for condition in [ItemCondition::New, ItemCondition::Used] {
+        let private = purchase(at(2024, 3, 15), condition, PurchaseChannel::Private);
+        assert_eq!(rule_id("FR", &private), "fr.private");
+
+        let guarantee =
+            legal_guarantee(country_rules("FR").unwrap(), &private, Tz::Europe__Paris).unwrap();
+        assert!(!guarantee.covered);
+        assert_eq!(guarantee.months, 0);
+        assert!(guarantee.legal_basis.contains("vices cachés"));
+        assert_eq!(guarantee.end_date, None);
+        assert_eq!(guarantee.extended_end_date, None);
+        assert_eq!(guarantee.withdrawal_end_date, None);
+    }
+}
+
+#[test]
+fn test_fr_repair_extends_by_six_months() {
+    let repairs = [at(2025, 6, 1)];
+    let repaired = Purchase {
+        repairs: &repairs,
+        ..purchase(at(2024, 3, 15), ItemCondition::New, PurchaseChannel::Store)
+    };
+    let guarantee =
+        legal_guarantee(country_rules("FR").unwrap(), &repaired, Tz::Europe__Paris).unwrap();
+    assert_eq!(guarantee.repair_extension_months, 6);
+    assert_eq!(
+        guarantee.repair_extension_basis.as_deref(),
+        Some("Code de la consommation, art. L217-13")
+    );
+    assert_eq!(guarantee.repairs_counted, 1);
+    assert_eq!(guarantee.end_date, Some(at(2026, 3, 15)));
+    assert_eq!(guarantee.extended_end_date, Some(at(2026, 9, 15)));
+}
+
+#[test]
+fn test_fr_repairs_chain_while_the_guarantee_runs() {
+    // The second repair comes after the original end but within the first
+    // extension; the third comes after everything has run out.
+    let repairs = [at(2027, 4, 10), at(2025, 6, 1), at(2026, 6, 1)];
+    let repaired = Purchase {
+        repairs: &repairs,
+        ..purchase(at(2024, 3, 15), ItemCondition::New, PurchaseChannel::Store)
+    };
+    let guarantee =
+        legal_guarantee(country_rules("FR").unwrap(), &repaired, Tz::Europe__Paris).unwrap();
+    assert_eq!(guarantee.repairs_counted, 2);
+    assert_eq!(guarantee.extended_end_date, Some(at(2027, 3, 15)));
+}
+
+#[test]
+fn test_fr_repair_after_the_end_does_not_extend() {
+    let repairs = [at(2025, 4, 1)];
+    let repaired = Purchase {
+        repairs: &repairs,
+        ..purchase(at(2024, 3, 15), ItemCondition::Used, PurchaseChannel::Store)
+    };
+    let guarantee =
+        legal_guarantee(country_rules("FR").unwrap(), &repaired, Tz::Europe__Paris).unwrap();
+    assert_eq!(guarantee.repairs_counted, 0);
+    assert_eq!(guarantee.extended_end_date, guarantee.end_date);
+}
+
+#[test]
+fn test_fr_repair_extension_only_since_2022() {
+    let repairs = [at(2021, 6, 1)];
+    let repaired = Purchase {
+        repairs: &repairs,
+        ..purchase(at(2020, 3, 15), ItemCondition::New, PurchaseChannel::Store)
+    };
+    let guarantee =
+        legal_guarantee(country_rules("FR").unwrap(), &repaired, Tz::Europe__Paris).unwrap();
+    assert_eq!(guarantee.repair_extension_months, 0);
+    assert_eq!(guarantee.repairs_counted, 0);
+    assert_eq!(guarantee.extended_end_date, Some(at(2022, 3, 15)));
+}
+
+#[test]
+fn test_fr_online_withdrawal_period() {
+    let online = purchase(at(2025, 3, 20), ItemCondition::New, PurchaseChannel::Online);
+    let guarantee =
+        legal_guarantee(country_rules("FR").unwrap(), &online, Tz::Europe__Paris).unwrap();
+    assert!(guarantee.covered);
+    // Fourteen days on the calendar, across the switch to summer time.
+    assert_eq!(guarantee.withdrawal_end_date, Some(at(2025, 4, 3)));
+    assert_eq!(
+        guarantee.withdrawal_basis.as_deref(),
+        Some("Code de la consommation, art. L221-18")
+    );
+}
+
+#[test]
+fn test_es_rules() {
+    let date = at(2024, 3, 15);
+    assert_eq!(
+        rule_id(
+            "ES",
+            &purchase(date, ItemCondition::New, PurchaseChannel::Store)
+        ),
+        "es.new"
+    );
+    assert_eq!(
+        rule_id(
+            "ES",
+            &purchase(at(2021, 6, 1), ItemCondition::New, PurchaseChannel::Store)
+        ),
+        "es.new.before_2022"
+    );
+    assert_eq!(
+        rule_id(
+            "ES",
+            &purchase(date, ItemCondition::Used, PurchaseChannel::Store)
+        ),
+        "es.used"
+    );
+    assert_eq!(
+        rule_id(
+            "ES",
+            &purchase(date, ItemCondition::New, PurchaseChannel::Private)
+        ),
+        "es.private"
+    );
+
+    let rules = country_rules("ES").unwrap();
+    let new = purchase(date, ItemCondition::New, PurchaseChannel::Store);
+    assert_eq!(legal_months(rules, &new, Tz::Europe__Madrid), 36);
+    // No extension after a repair.
+    let repairs = [at(2025, 1, 1)];
+    let repaired = Purchase {
+        repairs: &repairs,
+        ..new
+    };
+    let guarantee = legal_guarantee(rules, &repaired, Tz::Europe__Madrid).unwrap();
+    assert_eq!(guarantee.repairs_counted, 0);
+    assert_eq!(guarantee.extended_end_date, guarantee.end_date);
+}
+
+#[test]
+fn test_pt_rules() {
+    let date = at(2024, 3, 15);
+    for (condition, purchase_date, id, months) in [
+        (ItemCondition::New, date, "pt.new", 36),
+        (ItemCondition::New, at(2021, 6, 1), "pt.new.before_2022", 24),
+        (ItemCondition::Used, date, "pt.used", 18),
+        (
+            ItemCondition::Used,
+            at(2021, 6, 1),
+            "pt.used.before_2022",
+            12,
+        ),
+    ] {
+        let bought = purchase(purchase_date, condition, PurchaseChannel::Store);
+        assert_eq!(rule_id("PT", &bought), id);
+        assert_eq!(
+            legal_months(country_rules("PT").unwrap(), &bought, Tz::Europe__Lisbon),
+            months
+        );
+    }
+    assert_eq!(
+        rule_id(
+            "PT",
+            &purchase(date, ItemCondition::Used, PurchaseChannel::Private)
+        ),
+        "pt.private"
+    );
+}
+
+#[test]
+fn test_pt_repair_extension_stops_after_four_repairs() {
+    let repairs = [
+        at(2024, 6, 1),
+        at(2024, 9, 1),
+        at(2025, 1, 1),
+        at(2025, 4, 1),
+        at(2025, 7, 1),
+    ];
+    let repaired = Purchase {
+        repairs: &repairs,
+        ..purchase(at(2024, 3, 15), ItemCondition::New, PurchaseChannel::Store)
+    };
+    let guarantee =
+        legal_guarantee(country_rules("PT").unwrap(), &repaired, Tz::Europe__Lisbon).unwrap();
+    assert_eq!(guarantee.repairs_counted, 4);
+    assert_eq!(guarantee.end_date, Some(at(2027, 3, 15)));
+    assert_eq!(guarantee.extended_end_date, Some(at(2029, 3, 15)));
+}
+
+#[test]
+fn test_eu_rules() {
+    let date = at(2024, 3, 15);
+    for (condition, channel, id, months) in [
+        (ItemCondition::New, PurchaseChannel::Store, "eu.new", 24),
+        (ItemCondition::Used, PurchaseChannel::Online, "eu.used", 12),
+        (
+            ItemCondition::New,
+            PurchaseChannel::Private,
+            "eu.private",
+            0,
+        ),
+    ] {
+        let bought = purchase(date, condition, channel);
+        assert_eq!(rule_id("EU", &bought), id);
+        assert_eq!(
+            legal_months(country_rules("EU").unwrap(), &bought, Tz::UTC),
+            months
+        );
+    }
+}
+
+#[test]
+fn test_repairs_before_the_purchase_are_ignored() {
+    let repairs = [at(2024, 1, 1)];
+    let repaired = Purchase {
+        repairs: &repairs,
+        ..purchase(at(2024, 3, 15), ItemCondition::New, PurchaseChannel::Store)
+    };
+    let guarantee =
+        legal_guarantee(country_rules("FR").unwrap(), &repaired, Tz::Europe__Paris).unwrap();
+    assert_eq!(guarantee.repairs_counted, 0);
+}
+
+#[test]
+fn test_first_matching_rule_wins() {
+    static RULES: CountryRules = CountryRules {
+        country: "XX",
+        conformity: &[
+            ConformityRule {
+                id: "xx.clothing",
+                category: Some(WarrantyCategory::Clothing),
+                condition: None,
+                channel: None,
+                since: None,
+                months: 6,
+                legal_basis: "test",
+            },
+            ConformityRule {
+                id: "xx.any",
+                category: None,
+                condition: None,
+                channel: None,
+                since: None,
+                months: 24,
+                legal_basis: "test",
+            },
+        ],
+        repair_extension: None,
+        withdrawal: Withdrawal {
+            days: 7,
+            legal_basis: "test",
+        },
+    };
+
+    let clothing = Purchase {
+        category: &WarrantyCategory::Clothing,
+        ..purchase(at(2024, 3, 15), ItemCondition::New, PurchaseChannel::Online)
+    };
+    let guarantee = legal_guarantee(&RULES, &clothing, Tz::UTC).unwrap();
+    assert_eq!(guarantee.rule, "xx.clothing");
+    assert_eq!(guarantee.months, 6);
+    assert_eq!(
+        guarantee.withdrawal_end_date,
+        Some(clothing.purchase_date + chrono::Duration::days(7))
+    );
+
+    let other = purchase(at(2024, 3, 15), ItemCondition::New, PurchaseChannel::Store);
+    assert_eq!(legal_months(&RULES, &other, Tz::UTC), 24);
+}
@@ -0,0 +1,355 @@
+//! Filters and sort order of warranty listings, checked from the query
+//! string before `db::list_warranties` turns them into SQL, and the cursors
+//! that page through them.
+
+#[cfg(test)]
+mod tests;
+
+use std::str::FromStr;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::{Warranty, WarrantyCategory, WarrantyFilters};
+use crate::search;
+
+pub const DEFAULT_PER_PAGE: i64 = 20;
+pub const MAX_PER_PAGE: i64 = 100;
+
+/// Warranties ending within this many days are expiring soon.
+const EXPIRING_SOON_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    Relevance,
+    EndDate,
+    PurchaseDate,
+    Category,
+    ProductName,
+    Price,
+    CreatedAt,
+}
+
+impl FromStr for SortField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "relevance" => Ok(SortField::Relevance),
+            "end_date" => Ok(SortField::EndDate),
+            "purchase_date" => Ok(SortField::PurchaseDate),
+            "category" => Ok(SortField::Category),
+            "product_name" => Ok(SortField::ProductName),
+            "price" => Ok(SortField::Price),
+            "created_at" => Ok(SortField::CreatedAt),
+            _ => Err(format!("Unknown sort: {}", s)),
+        }
+    }
+}
+
+impl SortField {
+    /// What the listing orders by, in the `w` alias of `warranties`.
+    pub fn column(self) -> &'static str {
+        match self {
+            SortField::Relevance => "ts_rank_cd(w.search_vector, query)",
+            SortField::EndDate => "w.covered_until",
+            SortField::PurchaseDate => "w.purchase_date",
+            SortField::Category => "w.category::text",
+            SortField::ProductName => "lower(w.product_name)",
+            SortField::Price => "w.price_cents",
+            SortField::CreatedAt => "w.created_at",
+        }
+    }
+
+    fn default_order(self) -> SortOrder {
+        match self {
+            SortField::Relevance => SortOrder::Desc,
+            _ => SortOrder::Asc,
+        }
+    }
+
+    /// The value of `warranty` this field sorts on; `rank` is its relevance.
+    fn value_of(self, warranty: &Warranty, rank: Option<f32>) -> Option<SortValue> {
+        match self {
+            SortField::Relevance => rank.map(SortValue::Rank),
+            SortField::EndDate => Some(SortValue::Time(warranty.covered_until)),
+            SortField::PurchaseDate => Some(SortValue::Time(warranty.purchase_date)),
+            SortField::Category => Some(SortValue::Text(warranty.category.as_str().to_string())),
+            SortField::ProductName => Some(SortValue::Text(warranty.product_name.clone())),
+            SortField::Price => warranty.price_cents.map(SortValue::Cents),
+            SortField::CreatedAt => Some(SortValue::Time(warranty.created_at)),
+        }
+    }
+
+    /// Whether a cursor of this sort may hold `value`.
+    fn accepts(self, value: &Option<SortValue>) -> bool {
+        matches!(
+            (self, value),
+            (SortField::Relevance, Some(SortValue::Rank(_)))
+                | (
+                    SortField::EndDate | SortField::PurchaseDate | SortField::CreatedAt,
+                    Some(SortValue::Time(_))
+                )
+                | (
+                    SortField::Category | SortField::ProductName,
+                    Some(SortValue::Text(_))
+                )
+                | (SortField::Price, Some(SortValue::Cents(_)) | None)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "asc" => Ok(SortOrder::Asc),
+            "desc" => Ok(SortOrder::Desc),
+            _ => Err(format!("Unknown order: {}", s)),
+        }
+    }
+}
+
+impl SortOrder {
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// A value a listing sorts on, as SQL compares it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortValue {
+    Time(DateTime<Utc>),
+    Text(String),
+    Cents(i64),
+    Rank(f32),
+}
+
+/// Where the next page of a listing starts: after the warranty with this
+/// sort value and id, in this sort order. New warranties sorted before it
+/// do not shift the pages that follow.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub sort: SortField,
+    pub order: SortOrder,
+    /// `None` for warranties without a price
+    pub value: Option<SortValue>,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    /// The cursor of the page after `warranty`.
+    pub fn after(listing: &WarrantyListing, warranty: &Warranty, rank: Option<f32>) -> Self {
+        Cursor {
+            sort: listing.sort,
+            order: listing.order,
+            value: listing.sort.value_of(warranty, rank),
+            id: warranty.id,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        // Serializing plain data to JSON cannot fail.
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, String> {
+        let invalid = || "Invalid cursor".to_string();
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let cursor: Cursor = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+        if !cursor.sort.accepts(&cursor.value) {
+            return Err(invalid());
+        }
+        Ok(cursor)
+    }
+}
+
+/// Inclusive bounds, either of which may be open.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Range<T> {
+    pub from: Option<T>,
+    pub to: Option<T>,
+}
+
+impl<T: PartialOrd + Copy> Range<T> {
+    fn new(name: &str, from: Option<T>, to: Option<T>) -> Result<Self, String> {
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                return Err(format!("{} range is empty", name));
+            }
+        }
+        Ok(Range { from, to })
+    }
+
+    /// The part of `self` also within `other`.
+    fn intersect(self, other: Range<T>) -> Range<T> {
+        let max = |a: T, b: T| if a > b { a } else { b };
+        let min = |a: T, b: T| if a < b { a } else { b };
+        Range {
+            from: match (self.from, other.from) {
+                (Some(a), Some(b)) => Some(max(a, b)),
+                (a, b) => a.or(b),
+            },
+            to: match (self.to, other.to) {
+                (Some(a), Some(b)) => Some(min(a, b)),
+                (a, b) => a.or(b),
+            },
+        }
+    }
+}
+
+/// A warranty listing as asked for, checked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarrantyListing {
+    pub q: Option<String>,
+    /// Any of these; empty for all
+    pub categories: Vec<WarrantyCategory>,
+    pub brand: Option<String>,
+    pub store: Option<String>,
+    pub purchased: Range<DateTime<Utc>>,
+    /// Bounds on `covered_until`, from both `status` and `expires_*`
+    pub covered: Range<DateTime<Utc>>,
+    pub price_cents: Range<i64>,
+    pub has_receipt: Option<bool>,
+    /// One household of the user; all of them when unset
+    pub household_id: Option<Uuid>,
+    pub sort: SortField,
+    pub order: SortOrder,
+    /// Pages start after this instead of at `page`
+    pub cursor: Option<Cursor>,
+    pub page: i64,
+    pub per_page: i64,
+}
+
+impl WarrantyListing {
+    pub fn parse(filters: WarrantyFilters, now: DateTime<Utc>) -> Result<Self, String> {
+        let q = search::normalize_query(filters.q.as_deref())?;
+
+        let categories = filters
+            .category
+            .as_deref()
+            .map(|list| {
+                list.split(',')
+                    .map(str::trim)
+                    .filter(|category| !category.is_empty())
+                    .map(WarrantyCategory::from_str)
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        let status = match filters.status.as_deref() {
+            None => Range {
+                from: None,
+                to: None,
+            },
+            Some(status) => status_range(status, now)?,
+        };
+        let expires = Range::new("Expiry", filters.expires_from, filters.expires_to)?;
+
+        let price_cents = Range::new("Price", filters.min_price_cents, filters.max_price_cents)?;
+        if price_cents.from.is_some_and(|price| price < 0) {
+            return Err("Price cannot be negative".to_string());
+        }
+
+        let cursor = filters.cursor.as_deref().map(Cursor::decode).transpose()?;
+        if cursor.is_some() && filters.page.is_some() {
+            return Err("Use either page or cursor".to_string());
+        }
+
+        let sort = match (filters.sort.as_deref(), &cursor) {
+            (Some(sort), _) => sort.parse()?,
+            (None, Some(cursor)) => cursor.sort,
+            (None, None) if q.is_some() => SortField::Relevance,
+            (None, None) => SortField::EndDate,
+        };
+        if sort == SortField::Relevance && q.is_none() {
+            return Err("Sorting by relevance needs a search query".to_string());
+        }
+        let order = match (filters.order.as_deref(), &cursor) {
+            (Some(order), _) => order.parse()?,
+            (None, Some(cursor)) => cursor.order,
+            (None, None) => sort.default_order(),
+        };
+        if cursor
+            .as_ref()
+            .is_some_and(|cursor| (cursor.sort, cursor.order) != (sort, order))
+        {
+            return Err("Cursor belongs to another sort order".to_string());
+        }
+
+        Ok(WarrantyListing {
+            q,
+            categories,
+            brand: non_blank(filters.brand),
+            store: non_blank(filters.store),
+            purchased: Range::new(
+                "Purchase date",
+                filters.purchased_from,
+                filters.purchased_to,
+            )?,
+            covered: status.intersect(expires),
+            price_cents,
+            has_receipt: filters.has_receipt,
+            household_id: filters.household_id,
+            sort,
+            order,
+            cursor,
+            page: filters.page.unwrap_or(1).max(1),
+            per_page: filters
+                .per_page
+                .unwrap_or(DEFAULT_PER_PAGE)
+                .clamp(1, MAX_PER_PAGE),
+        })
+    }
+
+    /// Rows to skip: none when paging by cursor.
+    pub fn offset(&self) -> i64 {
+        match self.cursor {
+            Some(_) => 0,
+            None => (self.page - 1) * self.per_page,
+        }
+    }
+}
+
+/// Bounds on `covered_until` of `active`, `expiring_soon` and `expired`
+/// warranties.
+fn status_range(status: &str, now: DateTime<Utc>) -> Result<Range<DateTime<Utc>>, String> {
+    match status {
+        "active" => Ok(Range {
+            from: Some(now),
+            to: None,
+        }),
+        "expiring_soon" => Ok(Range {
+            from: Some(now),
+            to: Some(now + Duration::days(EXPIRING_SOON_DAYS)),
+        }),
+        "expired" => Ok(Range {
+            from: None,
+            to: Some(now),
+        }),
+        _ => Err(format!("Unknown status: {}", status)),
+    }
+}
+
+fn non_blank(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
@@ -0,0 +1,306 @@
+use chrono::{Duration, TimeZone, Utc};
+use uuid::Uuid;
+
+use crate::listing::{Cursor, Range, SortField, SortOrder, SortValue, WarrantyListing};
+use crate::models::{WarrantyCategory, WarrantyFilters};
+
+fn parse(filters: WarrantyFilters) -> Result<WarrantyListing, String> {
+    WarrantyListing::parse(filters, Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap())
+}
+
+#[test]
+fn test_defaults() {
+    let listing = parse(WarrantyFilters::default()).unwrap();
+
+    assert_eq!(listing.q, None);
+    assert!(listing.categories.is_empty());
+    assert_eq!(
+        listing.covered,
+        Range {
+            from: None,
+            to: None
+        }
+    );
+    assert_eq!(listing.sort, SortField::EndDate);
+    assert_eq!(listing.order, SortOrder::Asc);
+    assert_eq!((listing.page, listing.per_page), (1, 20));
+    assert_eq!(listing.offset(), 0);
+}
+
+#[test]
+fn test_pagination_is_clamped() {
+    let listing = parse(WarrantyFilters {
+        page: Some(3),
+        per_page: Some(500),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!((listing.page, listing.per_page), (3, 100));
+    assert_eq!(listing.offset(), 200);
+
+    let listing = parse(WarrantyFilters {
+        page: Some(-1),
+        per_page: Some(0),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!((listing.page, listing.per_page), (1, 1));
+}
+
+#[test]
+fn test_several_categories() {
+    let listing = parse(WarrantyFilters {
+        category: Some("appliances, electronics,".to_string()),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(
+        listing.categories,
+        vec![WarrantyCategory::Appliances, WarrantyCategory::Electronics]
+    );
+
+    assert_eq!(
+        parse(WarrantyFilters {
+            category: Some("appliances,toys".to_string()),
+            ..Default::default()
+        }),
+        Err("Unknown category: toys".to_string())
+    );
+}
+
+#[test]
+fn test_status_narrows_expiry_range() {
+    let now = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+
+    let listing = parse(WarrantyFilters {
+        status: Some("expiring_soon".to_string()),
+        expires_from: Some(now - Duration::days(10)),
+        expires_to: Some(now + Duration::days(10)),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(
+        listing.covered,
+        Range {
+            from: Some(now),
+            to: Some(now + Duration::days(10))
+        }
+    );
+
+    let listing = parse(WarrantyFilters {
+        status: Some("expired".to_string()),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(
+        listing.covered,
+        Range {
+            from: None,
+            to: Some(now)
+        }
+    );
+
+    assert!(parse(WarrantyFilters {
+        status: Some("all".to_string()),
+        ..Default::default()
+    })
+    .is_err());
+}
+
+#[test]
+fn test_ranges_must_not_be_empty() {
+    let now = Utc::now();
+
+    assert!(parse(WarrantyFilters {
+        purchased_from: Some(now),
+        purchased_to: Some(now - Duration::days(1)),
+        ..Default::default()
+    })
+    .is_err());
+    assert!(parse(WarrantyFilters {
+        min_price_cents: Some(50_000),
+        max_price_cents: Some(10_000),
+        ..Default::default()
+    })
+    .is_err());
+    assert!(parse(WarrantyFilters {
+        min_price_cents: Some(-1),
+        ..Default::default()
+    })
+    .is_err());
+
+    let listing = parse(WarrantyFilters {
+        min_price_cents: Some(10_000),
+        max_price_cents: Some(10_000),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(
+        listing.price_cents,
+        Range {
+            from: Some(10_000),
+            to: Some(10_000)
+        }
+    );
+}
+
+#[test]
+fn test_sort_and_order() {
+    let listing = parse(WarrantyFilters {
+        sort: Some("purchase_date".to_string()),
+        order: Some("desc".to_string()),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(listing.sort.column(), "w.purchase_date");
+    assert_eq!(listing.order.as_sql(), "DESC");
+
+    assert!(parse(WarrantyFilters {
+        sort: Some("purchase_date; DROP TABLE warranties".to_string()),
+        ..Default::default()
+    })
+    .is_err());
+    assert!(parse(WarrantyFilters {
+        order: Some("sideways".to_string()),
+        ..Default::default()
+    })
+    .is_err());
+}
+
+#[test]
+fn test_search_sorts_by_relevance() {
+    let listing = parse(WarrantyFilters {
+        q: Some(" lave-linge ".to_string()),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(listing.q.as_deref(), Some("lave-linge"));
+    assert_eq!(listing.sort, SortField::Relevance);
+    assert_eq!(listing.order, SortOrder::Desc);
+
+    let listing = parse(WarrantyFilters {
+        q: Some("lave-linge".to_string()),
+        sort: Some("price".to_string()),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(listing.sort, SortField::Price);
+    assert_eq!(listing.order, SortOrder::Asc);
+
+    assert_eq!(
+        parse(WarrantyFilters {
+            sort: Some("relevance".to_string()),
+            ..Default::default()
+        }),
+        Err("Sorting by relevance needs a search query".to_string())
+    );
+}
+
+#[test]
+fn test_blank_brand_and_store_are_ignored() {
+    let listing = parse(WarrantyFilters {
+        brand: Some(" Bosch ".to_string()),
+        store: Some("  ".to_string()),
+        has_receipt: Some(true),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(listing.brand.as_deref(), Some("Bosch"));
+    assert_eq!(listing.store, None);
+    assert_eq!(listing.has_receipt, Some(true));
+}
+
+fn cursor(sort: SortField, order: SortOrder, value: Option<SortValue>) -> Cursor {
+    Cursor {
+        sort,
+        order,
+        value,
+        id: Uuid::new_v4(),
+    }
+}
+
+#[test]
+fn test_cursor_round_trip() {
+    let now = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+    for cursor in [
+        cursor(
+            SortField::EndDate,
+            SortOrder::Asc,
+            Some(SortValue::Time(now)),
+        ),
+        cursor(
+            SortField::ProductName,
+            SortOrder::Desc,
+            Some(SortValue::Text("Lave-linge Bosch".to_string())),
+        ),
+        cursor(SortField::Price, SortOrder::Asc, None),
+        cursor(
+            SortField::Relevance,
+            SortOrder::Desc,
+            Some(SortValue::Rank(0.1)),
+        ),
+    ] {
+        let encoded = cursor.encode();
+        assert!(encoded
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(Cursor::decode(&encoded), Ok(cursor));
+    }
+}
+
+#[test]
+fn test_invalid_cursor() {
+    assert_eq!(
+        Cursor::decode("not a cursor"),
+        Err("Invalid cursor".to_string())
+    );
+    // A value of the wrong kind for its sort
+    let mismatched = cursor(
+        SortField::EndDate,
+        SortOrder::Asc,
+        Some(SortValue::Cents(100)),
+    );
+    assert!(Cursor::decode(&mismatched.encode()).is_err());
+    let missing = cursor(SortField::CreatedAt, SortOrder::Asc, None);
+    assert!(Cursor::decode(&missing.encode()).is_err());
+}
+
+#[test]
+fn test_cursor_keeps_its_sort() {
+    let now = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+    let next = cursor(
+        SortField::PurchaseDate,
+        SortOrder::Desc,
+        Some(SortValue::Time(now)),
+    );
+
+    let listing = parse(WarrantyFilters {
+        cursor: Some(next.encode()),
+        per_page: Some(10),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(
+        (listing.sort, listing.order),
+        (SortField::PurchaseDate, SortOrder::Desc)
+    );
+    assert_eq!(listing.cursor, Some(next.clone()));
+    assert_eq!(listing.offset(), 0);
+
+    assert_eq!(
+        parse(WarrantyFilters {
+            cursor: Some(next.encode()),
+            order: Some("asc".to_string()),
+            ..Default::default()
+        }),
+        Err("Cursor belongs to another sort order".to_string())
+    );
+    assert_eq!(
+        parse(WarrantyFilters {
+            cursor: Some(next.encode()),
+            page: Some(2),
+            ..Default::default()
+        }),
+        Err("Use either page or cursor".to_string())
+    );
+}
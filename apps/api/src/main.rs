@@ -1,20 +1,40 @@
+mod audit;
+mod calendar;
 mod config;
 mod db;
 mod error;
+mod extraction;
+mod handlers;
+mod households;
+mod imaging;
+mod legal;
+mod listing;
+mod maintenance;
 mod models;
+mod notifications;
+mod outbound;
+mod reminders;
+mod search;
+mod signing;
+mod storage;
+mod sync;
+mod trash;
+mod warranty_period;
+mod webhooks;
 
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 
 use axum::{
-    extract::{Path, Query, State},
+    extract::{DefaultBodyLimit, FromRequest, Multipart, Path, Query, State},
     http::{header, Method, Request, StatusCode},
     middleware::{self, Next},
     response::Response,
-    routing::{get, post},
+    routing::{delete, get, post, put},
     Json, Router,
 };
+use chrono::{DateTime, Utc};
 use jsonwebtoken::{decode, DecodingKey, Validation};
 use serde::{Deserialize, Serialize};
 use tower_governor::{
@@ -28,18 +48,37 @@ use utoipa::{OpenApi, ToSchema};
 use utoipa_swagger_ui::SwaggerUi;
 use uuid::Uuid;
 
+use audit::{Actor, RequestId};
 use config::Config;
 use db::{PaginatedWarranties, WarrantyStats};
 use error::{AppError, ErrorResponse, Result};
+use listing::WarrantyListing;
 use models::{
-    CreateWarrantyRequest, UpdateWarrantyRequest, Warranty, WarrantyCategory, WarrantyFilters,
-    WarrantyListResponse,
+    AcceptInvitationRequest, Attachment, AttachmentKind, CalendarFeedResponse, Claim, ClaimDetail,
+    ClaimEvent, ClaimStatus, ClaimTransitionRequest, Coverage, CoverageKind, CreateClaimRequest,
+    CreateCoverageRequest, CreateHouseholdRequest, CreateInvitationRequest,
+    CreateMaintenanceCompletionRequest, CreateMaintenanceTaskRequest,
+    CreatePushSubscriptionRequest, CreateWarrantyRequest, CreateWebhookRequest, CreatedInvitation,
+    CreatedWebhook, DeletePushSubscriptionRequest, DeliveryStatus, DigestFrequency,
+    ExtractionStatus, FieldChange, Household, HouseholdDetail, HouseholdInvitation,
+    HouseholdMember, HouseholdRole, ItemCondition, LegalGuarantee, LegalGuaranteeRequest,
+    MaintenanceCompletion, MaintenanceTask, NotificationChannel, PurchaseChannel, PushSubscription,
+    PushSubscriptionKeys, QuietHours, ReceiptExtraction, ReceiptVariant, ReminderMode,
+    ReminderPreferences, SearchMatch, SyncChange, SyncPushRequest, SyncPushResponse, SyncResponse,
+    SyncResult, SyncStatus, Tombstone, TrashListResponse, TrashedWarranty, UpdateAttachmentRequest,
+    UpdateClaimRequest, UpdateCoverageRequest, UpdateHouseholdRequest,
+    UpdateMaintenanceTaskRequest, UpdateMemberRequest, UpdateReminderPreferencesRequest,
+    UpdateWarrantyRequest, UpdateWebhookRequest, VapidPublicKeyResponse, Warranty, WarrantyAction,
+    WarrantyCategory, WarrantyEvent, WarrantyFilters, WarrantyListResponse, WarrantySuggestion,
+    Webhook, WebhookDelivery, WebhookEvent,
 };
+use storage::Storage;
 
 #[derive(Clone)]
 pub struct AppState {
     pub pool: sqlx::PgPool,
     pub config: Config,
+    pub storage: Arc<dyn Storage>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -71,6 +110,73 @@ struct AuthUser {
         delete_warranty_handler,
         list_expiring,
         get_stats,
+        upload_receipt,
+        download_receipt,
+        create_receipt_signed_url,
+        download_signed_receipt,
+        handlers::attachments::list_attachments,
+        handlers::attachments::create_attachment,
+        handlers::attachments::get_attachment,
+        handlers::attachments::download_attachment,
+        handlers::attachments::update_attachment,
+        handlers::attachments::delete_attachment,
+        handlers::coverages::list_coverages,
+        handlers::coverages::create_coverage,
+        handlers::coverages::get_coverage,
+        handlers::coverages::update_coverage,
+        handlers::coverages::delete_coverage,
+        handlers::claims::list_claims,
+        handlers::claims::create_claim,
+        handlers::claims::get_claim,
+        handlers::claims::update_claim,
+        handlers::claims::delete_claim,
+        handlers::claims::transition_claim,
+        handlers::maintenance::list_maintenance_tasks,
+        handlers::maintenance::create_maintenance_task,
+        handlers::maintenance::get_maintenance_task,
+        handlers::maintenance::update_maintenance_task,
+        handlers::maintenance::delete_maintenance_task,
+        handlers::maintenance::list_maintenance_completions,
+        handlers::maintenance::create_maintenance_completion,
+        handlers::maintenance::delete_maintenance_completion,
+        handlers::sync::get_changes,
+        handlers::sync::push_changes,
+        handlers::trash::list_trash,
+        handlers::trash::restore_warranty,
+        handlers::history::list_warranty_history,
+        handlers::households::list_households,
+        handlers::households::create_household,
+        handlers::households::get_household,
+        handlers::households::update_household,
+        handlers::households::delete_household,
+        handlers::households::update_member,
+        handlers::households::remove_member,
+        handlers::households::list_invitations,
+        handlers::households::create_invitation,
+        handlers::households::delete_invitation,
+        handlers::households::accept_invitation,
+        handlers::extractions::create_extraction,
+        handlers::extractions::get_extraction,
+        handlers::extractions::get_receipt_extraction,
+        handlers::push_subscriptions::get_vapid_public_key,
+        handlers::push_subscriptions::create_push_subscription,
+        handlers::push_subscriptions::delete_push_subscription,
+        handlers::reminder_preferences::get_reminder_preferences,
+        handlers::reminder_preferences::update_reminder_preferences,
+        handlers::digests::confirm_digest_unsubscribe,
+        handlers::digests::unsubscribe_digest,
+        handlers::webhooks::list_webhooks,
+        handlers::webhooks::create_webhook,
+        handlers::webhooks::get_webhook,
+        handlers::webhooks::update_webhook,
+        handlers::webhooks::delete_webhook,
+        handlers::webhooks::list_webhook_deliveries,
+        handlers::webhooks::redeliver_webhook_delivery,
+        handlers::calendar::get_calendar_feed,
+        handlers::calendar::regenerate_calendar_feed,
+        handlers::calendar::delete_calendar_feed,
+        handlers::calendar::warranty_calendar,
+        handlers::legal_guarantee::evaluate_legal_guarantee,
     ),
     components(
         schemas(
@@ -83,15 +189,103 @@ struct AuthUser {
             UpdateWarrantyRequest,
             WarrantyListResponse,
             WarrantyFilters,
+            SearchMatch,
             WarrantyStats,
             ErrorResponse,
             ExpiringQuery,
+            ReceiptUploadForm,
+            SignedUrlResponse,
+            ReceiptVariant,
+            Attachment,
+            AttachmentKind,
+            UpdateAttachmentRequest,
+            handlers::attachments::AttachmentUploadForm,
+            Coverage,
+            CoverageKind,
+            CreateCoverageRequest,
+            UpdateCoverageRequest,
+            Claim,
+            ClaimStatus,
+            ClaimEvent,
+            ClaimDetail,
+            CreateClaimRequest,
+            UpdateClaimRequest,
+            ClaimTransitionRequest,
+            MaintenanceTask,
+            MaintenanceCompletion,
+            CreateMaintenanceTaskRequest,
+            UpdateMaintenanceTaskRequest,
+            CreateMaintenanceCompletionRequest,
+            SyncResponse,
+            Tombstone,
+            SyncPushRequest,
+            SyncChange,
+            SyncPushResponse,
+            SyncResult,
+            SyncStatus,
+            TrashedWarranty,
+            TrashListResponse,
+            WarrantyEvent,
+            WarrantyAction,
+            FieldChange,
+            Household,
+            HouseholdRole,
+            HouseholdMember,
+            HouseholdDetail,
+            CreateHouseholdRequest,
+            UpdateHouseholdRequest,
+            UpdateMemberRequest,
+            HouseholdInvitation,
+            CreateInvitationRequest,
+            CreatedInvitation,
+            AcceptInvitationRequest,
+            ExtractionStatus,
+            ReceiptExtraction,
+            WarrantySuggestion,
+            handlers::extractions::ExtractionUploadForm,
+            PushSubscription,
+            CreatePushSubscriptionRequest,
+            PushSubscriptionKeys,
+            DeletePushSubscriptionRequest,
+            VapidPublicKeyResponse,
+            ReminderPreferences,
+            ReminderMode,
+            DigestFrequency,
+            NotificationChannel,
+            UpdateReminderPreferencesRequest,
+            QuietHours,
+            Webhook,
+            WebhookEvent,
+            CreatedWebhook,
+            CreateWebhookRequest,
+            UpdateWebhookRequest,
+            WebhookDelivery,
+            DeliveryStatus,
+            CalendarFeedResponse,
+            ItemCondition,
+            PurchaseChannel,
+            LegalGuaranteeRequest,
+            LegalGuarantee,
         )
     ),
     tags(
         (name = "health", description = "Health check endpoints"),
         (name = "categories", description = "Warranty category endpoints"),
         (name = "warranties", description = "Warranty management endpoints"),
+        (name = "attachments", description = "Warranty attachment endpoints"),
+        (name = "coverages", description = "Manufacturer warranties and extensions on top of a warranty"),
+        (name = "claims", description = "After-sales claims and repairs of a warranty"),
+        (name = "maintenance", description = "Recurring maintenance tasks of an item and their completion log"),
+        (name = "sync", description = "Delta sync of offline web and mobile clients"),
+        (name = "trash", description = "Deleted warranties kept for restore until purged"),
+        (name = "history", description = "Change history of a warranty"),
+        (name = "households", description = "Households sharing warranties, their members and invitations"),
+        (name = "extractions", description = "Receipt OCR endpoints that pre-fill warranties"),
+        (name = "push", description = "Web Push subscription endpoints"),
+        (name = "reminders", description = "Expiry reminder and digest settings"),
+        (name = "webhooks", description = "Outgoing webhooks for warranty events"),
+        (name = "calendar", description = "iCalendar feed of warranty end dates"),
+        (name = "legal", description = "Legal guarantee of conformity rules"),
         (name = "stats", description = "Statistics endpoints")
     )
 )]
@@ -130,8 +324,34 @@ async fn main() {
     let state = AppState {
         pool,
         config: config.clone(),
+        storage: storage::from_config(&config),
     };
 
+    extraction::spawn_worker(
+        state.pool.clone(),
+        state.storage.clone(),
+        Arc::new(extraction::TesseractRecognizer {
+            command: config.ocr_command.clone(),
+            languages: config.ocr_languages.clone(),
+            pdf_rasterize_command: config.pdf_rasterize_command.clone(),
+        }),
+    );
+
+    let senders = notifications::senders_from_config(&config, &state.pool);
+    reminders::spawn_scheduler(
+        state.pool.clone(),
+        config.reminder_thresholds_days.clone(),
+        senders.iter().map(|sender| sender.channel()).collect(),
+        Duration::from_secs(config.reminder_interval_secs),
+    );
+    notifications::spawn_dispatcher(state.pool.clone(), senders);
+    webhooks::spawn_dispatcher(state.pool.clone(), !config.is_production());
+    trash::spawn_purger(
+        state.pool.clone(),
+        state.storage.clone(),
+        config.trash_retention_days,
+    );
+
     let governor_conf = Arc::new(
         GovernorConfigBuilder::default()
             .per_second(10)
@@ -145,7 +365,17 @@ async fn main() {
 
     let public_routes = Router::new()
         .route("/health", get(health_check))
-        .route("/api/v1/categories", get(list_categories));
+        .route("/api/v1/categories", get(list_categories))
+        .route("/api/v1/files/receipts/:id", get(download_signed_receipt))
+        .route(
+            "/api/v1/digest/unsubscribe/:token",
+            get(handlers::digests::confirm_digest_unsubscribe)
+                .post(handlers::digests::unsubscribe_digest),
+        )
+        .route(
+            "/api/v1/calendar/:token/warranties.ics",
+            get(handlers::calendar::warranty_calendar),
+        );
 
     let protected_routes = Router::new()
         .route(
@@ -158,7 +388,172 @@ async fn main() {
                 .put(update_warranty)
                 .delete(delete_warranty_handler),
         )
-        .route("/api/v1/warranties/:id/receipt", post(upload_receipt))
+        .route(
+            "/api/v1/warranties/:id/restore",
+            post(handlers::trash::restore_warranty),
+        )
+        .route(
+            "/api/v1/warranties/:id/history",
+            get(handlers::history::list_warranty_history),
+        )
+        .route(
+            "/api/v1/warranties/:id/receipt",
+            // Leave room for the multipart envelope around the file itself.
+            post(upload_receipt)
+                .layer(DefaultBodyLimit::max(config.max_upload_bytes + 64 * 1024))
+                .get(download_receipt),
+        )
+        .route(
+            "/api/v1/warranties/:id/receipt/signed-url",
+            post(create_receipt_signed_url),
+        )
+        .route(
+            "/api/v1/warranties/:id/receipt/extraction",
+            get(handlers::extractions::get_receipt_extraction),
+        )
+        .route(
+            "/api/v1/warranties/:id/attachments",
+            post(handlers::attachments::create_attachment)
+                .layer(DefaultBodyLimit::max(config.max_upload_bytes + 64 * 1024))
+                .get(handlers::attachments::list_attachments),
+        )
+        .route(
+            "/api/v1/warranties/:id/attachments/:attachment_id",
+            get(handlers::attachments::get_attachment)
+                .put(handlers::attachments::update_attachment)
+                .delete(handlers::attachments::delete_attachment),
+        )
+        .route(
+            "/api/v1/warranties/:id/attachments/:attachment_id/content",
+            get(handlers::attachments::download_attachment),
+        )
+        .route(
+            "/api/v1/warranties/:id/coverages",
+            get(handlers::coverages::list_coverages).post(handlers::coverages::create_coverage),
+        )
+        .route(
+            "/api/v1/warranties/:id/coverages/:coverage_id",
+            get(handlers::coverages::get_coverage)
+                .put(handlers::coverages::update_coverage)
+                .delete(handlers::coverages::delete_coverage),
+        )
+        .route(
+            "/api/v1/warranties/:id/claims",
+            get(handlers::claims::list_claims).post(handlers::claims::create_claim),
+        )
+        .route(
+            "/api/v1/warranties/:id/claims/:claim_id",
+            get(handlers::claims::get_claim)
+                .put(handlers::claims::update_claim)
+                .delete(handlers::claims::delete_claim),
+        )
+        .route(
+            "/api/v1/warranties/:id/claims/:claim_id/transitions",
+            post(handlers::claims::transition_claim),
+        )
+        .route(
+            "/api/v1/warranties/:id/maintenance-tasks",
+            get(handlers::maintenance::list_maintenance_tasks)
+                .post(handlers::maintenance::create_maintenance_task),
+        )
+        .route(
+            "/api/v1/warranties/:id/maintenance-tasks/:task_id",
+            get(handlers::maintenance::get_maintenance_task)
+                .put(handlers::maintenance::update_maintenance_task)
+                .delete(handlers::maintenance::delete_maintenance_task),
+        )
+        .route(
+            "/api/v1/warranties/:id/maintenance-tasks/:task_id/completions",
+            get(handlers::maintenance::list_maintenance_completions)
+                .post(handlers::maintenance::create_maintenance_completion),
+        )
+        .route(
+            "/api/v1/warranties/:id/maintenance-tasks/:task_id/completions/:completion_id",
+            delete(handlers::maintenance::delete_maintenance_completion),
+        )
+        .route("/api/v1/trash", get(handlers::trash::list_trash))
+        .route(
+            "/api/v1/households",
+            get(handlers::households::list_households).post(handlers::households::create_household),
+        )
+        .route(
+            "/api/v1/households/:id",
+            get(handlers::households::get_household)
+                .put(handlers::households::update_household)
+                .delete(handlers::households::delete_household),
+        )
+        .route(
+            "/api/v1/households/:id/members/:user_id",
+            put(handlers::households::update_member).delete(handlers::households::remove_member),
+        )
+        .route(
+            "/api/v1/households/:id/invitations",
+            get(handlers::households::list_invitations)
+                .post(handlers::households::create_invitation),
+        )
+        .route(
+            "/api/v1/households/:id/invitations/:invitation_id",
+            delete(handlers::households::delete_invitation),
+        )
+        .route(
+            "/api/v1/household-invitations/accept",
+            post(handlers::households::accept_invitation),
+        )
+        .route(
+            "/api/v1/sync",
+            get(handlers::sync::get_changes).post(handlers::sync::push_changes),
+        )
+        .route(
+            "/api/v1/extractions",
+            post(handlers::extractions::create_extraction)
+                .layer(DefaultBodyLimit::max(config.max_upload_bytes + 64 * 1024)),
+        )
+        .route(
+            "/api/v1/extractions/:id",
+            get(handlers::extractions::get_extraction),
+        )
+        .route(
+            "/api/v1/push-subscriptions",
+            post(handlers::push_subscriptions::create_push_subscription)
+                .delete(handlers::push_subscriptions::delete_push_subscription),
+        )
+        .route(
+            "/api/v1/push-subscriptions/vapid-public-key",
+            get(handlers::push_subscriptions::get_vapid_public_key),
+        )
+        .route(
+            "/api/v1/reminder-preferences",
+            get(handlers::reminder_preferences::get_reminder_preferences)
+                .put(handlers::reminder_preferences::update_reminder_preferences),
+        )
+        .route(
+            "/api/v1/calendar-feed",
+            get(handlers::calendar::get_calendar_feed)
+                .post(handlers::calendar::regenerate_calendar_feed)
+                .delete(handlers::calendar::delete_calendar_feed),
+        )
+        .route(
+            "/api/v1/webhooks",
+            get(handlers::webhooks::list_webhooks).post(handlers::webhooks::create_webhook),
+        )
+        .route(
+            "/api/v1/webhooks/:id",
+            get(handlers::webhooks::get_webhook)
+                .put(handlers::webhooks::update_webhook)
+                .delete(handlers::webhooks::delete_webhook),
+        )
+        .route(
+            "/api/v1/webhooks/:id/deliveries",
+            get(handlers::webhooks::list_webhook_deliveries),
+        )
+        .route(
+            "/api/v1/webhooks/:id/deliveries/:delivery_id/redeliver",
+            post(handlers::webhooks::redeliver_webhook_delivery),
+        )
+        .route(
+            "/api/v1/legal-guarantee",
+            post(handlers::legal_guarantee::evaluate_legal_guarantee),
+        )
         .route("/api/v1/warranties/expiring", get(list_expiring))
         .route("/api/v1/stats", get(get_stats))
         .layer(middleware::from_fn_with_state(
@@ -175,7 +570,11 @@ async fn main() {
         .layer(
             TraceLayer::new_for_http()
                 .make_span_with(|request: &Request<_>| {
-                    let request_id = uuid::Uuid::new_v4().to_string();
+                    let request_id = request
+                        .extensions()
+                        .get::<RequestId>()
+                        .map(|request_id| request_id.0.to_string())
+                        .unwrap_or_default();
                     tracing::info_span!(
                         "request",
                         method = %request.method(),
@@ -191,6 +590,7 @@ async fn main() {
                     );
                 }),
         )
+        .layer(middleware::from_fn(audit::assign_request_id))
         .with_state(state)
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()));
 
@@ -226,6 +626,7 @@ fn build_cors_layer(config: &Config) -> CorsLayer {
                 Method::OPTIONS,
             ])
             .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE, header::ACCEPT])
+            .expose_headers([audit::REQUEST_ID_HEADER])
             .allow_credentials(true)
     }
 }
@@ -310,6 +711,8 @@ struct CategoryInfo {
     id: String,
     name: String,
     name_fr: String,
+    /// Legal guarantee of a new item bought in a shop today, in the server's
+    /// country
     default_warranty_months: i32,
 }
 
@@ -321,7 +724,10 @@ struct CategoryInfo {
         (status = 200, description = "List of warranty categories", body = Vec<CategoryInfo>)
     )
 )]
-async fn list_categories() -> Json<Vec<CategoryInfo>> {
+async fn list_categories(State(state): State<AppState>) -> Result<Json<Vec<CategoryInfo>>> {
+    let rules = handlers::legal_guarantee::rules_for(&state.config.legal_country)?;
+    let now = Utc::now();
+
     let categories = vec![
         WarrantyCategory::Electronics,
         WarrantyCategory::Appliances,
@@ -338,11 +744,21 @@ async fn list_categories() -> Json<Vec<CategoryInfo>> {
             id: format!("{:?}", c).to_lowercase(),
             name: format!("{:?}", c),
             name_fr: c.display_name_fr().to_string(),
-            default_warranty_months: c.default_warranty_months(),
+            default_warranty_months: legal::legal_months(
+                rules,
+                &legal::Purchase {
+                    category: &c,
+                    purchase_date: now,
+                    condition: ItemCondition::New,
+                    channel: PurchaseChannel::Store,
+                    repairs: &[],
+                },
+                chrono_tz::Tz::UTC,
+            ),
         })
         .collect();
 
-    Json(info)
+    Ok(Json(info))
 }
 
 #[utoipa::path(
@@ -350,13 +766,27 @@ async fn list_categories() -> Json<Vec<CategoryInfo>> {
     path = "/api/v1/warranties",
     tag = "warranties",
     params(
-        ("category" = Option<String>, Query, description = "Filter by category"),
-        ("status" = Option<String>, Query, description = "Filter by status (active, expiring_soon, expired)"),
+        ("q" = Option<String>, Query, description = "Full-text search over product name, brand, store, notes and receipt text, accents ignored; results come ranked with highlighted snippets"),
+        ("category" = Option<String>, Query, description = "Filter by category, or several separated by commas"),
+        ("status" = Option<String>, Query, description = "Filter by status of `covered_until` (active, expiring_soon, expired)"),
+        ("brand" = Option<String>, Query, description = "Filter by brand, ignoring case"),
+        ("store" = Option<String>, Query, description = "Filter by store, ignoring case"),
+        ("purchased_from" = Option<DateTime<Utc>>, Query, description = "Purchased on or after"),
+        ("purchased_to" = Option<DateTime<Utc>>, Query, description = "Purchased on or before"),
+        ("expires_from" = Option<DateTime<Utc>>, Query, description = "`covered_until` on or after"),
+        ("expires_to" = Option<DateTime<Utc>>, Query, description = "`covered_until` on or before"),
+        ("min_price_cents" = Option<i64>, Query, description = "Price paid at least, in euro cents"),
+        ("max_price_cents" = Option<i64>, Query, description = "Price paid at most, in euro cents"),
+        ("has_receipt" = Option<bool>, Query, description = "Only warranties with (true) or without (false) a receipt"),
+        ("sort" = Option<String>, Query, description = "end_date (`covered_until`, default), purchase_date, category, product_name, price, created_at, or relevance (default with `q`)"),
+        ("order" = Option<String>, Query, description = "asc or desc (default: desc for relevance, asc otherwise)"),
         ("page" = Option<i64>, Query, description = "Page number (default: 1)"),
-        ("per_page" = Option<i64>, Query, description = "Items per page (default: 20, max: 100)")
+        ("per_page" = Option<i64>, Query, description = "Items per page (default: 20, max: 100)"),
+        ("cursor" = Option<String>, Query, description = "`next_cursor` of the previous page, instead of `page`; keeps its sort, but the filters must be sent again")
     ),
     responses(
         (status = 200, description = "Paginated list of warranties", body = WarrantyListResponse),
+        (status = 400, description = "Invalid filters or sort", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 429, description = "Too many requests", body = ErrorResponse)
     ),
@@ -374,21 +804,30 @@ async fn list_warranties(
         .get::<AuthUser>()
         .ok_or(AppError::Unauthorized)?;
 
-    let page = filters.page.unwrap_or(1).max(1);
-    let per_page = filters.per_page.unwrap_or(20).clamp(1, 100);
+    let listing = WarrantyListing::parse(filters, Utc::now()).map_err(AppError::BadRequest)?;
 
-    let PaginatedWarranties { warranties, total } =
-        db::list_warranties(&state.pool, &user.user_id, filters).await?;
+    let page = db::list_warranties(&state.pool, &user.user_id, &listing).await?;
 
-    let total_pages = (total as f64 / per_page as f64).ceil() as i64;
+    Ok(Json(list_response(&listing, page)))
+}
 
-    Ok(Json(WarrantyListResponse {
+fn list_response(listing: &WarrantyListing, page: PaginatedWarranties) -> WarrantyListResponse {
+    let PaginatedWarranties {
         warranties,
         total,
-        page,
-        per_page,
-        total_pages,
-    }))
+        matches,
+        next_cursor,
+    } = page;
+
+    WarrantyListResponse {
+        warranties,
+        total,
+        page: listing.cursor.is_none().then_some(listing.page),
+        per_page: listing.per_page,
+        total_pages: total.map(|total| (total as f64 / listing.per_page as f64).ceil() as i64),
+        next_cursor: next_cursor.map(|cursor| cursor.encode()),
+        matches,
+    }
 }
 
 #[utoipa::path(
@@ -400,6 +839,8 @@ async fn list_warranties(
         (status = 201, description = "Warranty created successfully", body = Warranty),
         (status = 400, description = "Invalid request", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Household not found", body = ErrorResponse),
         (status = 429, description = "Too many requests", body = ErrorResponse)
     ),
     security(
@@ -415,17 +856,31 @@ async fn create_warranty(
         .get::<AuthUser>()
         .ok_or(AppError::Unauthorized)?
         .clone();
+    let actor = Actor::new(&user.user_id, &request);
 
     let body = axum::body::to_bytes(request.into_body(), 1024 * 1024)
         .await
         .map_err(|_| AppError::BadRequest("Invalid body".to_string()))?;
 
-    let payload: CreateWarrantyRequest = serde_json::from_slice(&body)
+    let mut payload: CreateWarrantyRequest = serde_json::from_slice(&body)
         .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
 
     validate_create_warranty(&payload)?;
-
-    let warranty = db::create_warranty(&state.pool, &user.user_id, payload).await?;
+    let household_id =
+        handlers::household_for_new_warranty(&state.pool, &user.user_id, payload.household_id)
+            .await?;
+    fill_warranty_months(&state, &user.user_id, None, &mut payload).await?;
+
+    let warranty = db::create_warranty(
+        &state.pool,
+        &user.user_id,
+        household_id,
+        None,
+        payload,
+        &actor,
+    )
+    .await?;
+    webhooks::emit(&state.pool, WebhookEvent::Created, &warranty).await;
 
     info!(warranty_id = %warranty.id, user_id = %user.user_id, "warranty created");
 
@@ -455,6 +910,18 @@ fn validate_create_warranty(req: &CreateWarrantyRequest) -> Result<()> {
             ));
         }
     }
+    if let Some(price_cents) = req.price_cents {
+        if price_cents < 0 {
+            return Err(AppError::BadRequest("Price cannot be negative".to_string()));
+        }
+    }
+    if let Some(ref invoice_number) = req.invoice_number {
+        if invoice_number.len() > 100 {
+            return Err(AppError::BadRequest(
+                "Invoice number must be less than 100 characters".to_string(),
+            ));
+        }
+    }
     if let Some(ref notes) = req.notes {
         if notes.len() > 2000 {
             return Err(AppError::BadRequest(
@@ -469,9 +936,48 @@ fn validate_create_warranty(req: &CreateWarrantyRequest) -> Result<()> {
             ));
         }
     }
+    if let Some(ref lead_days) = req.reminder_lead_days {
+        reminders::validate_lead_days(lead_days).map_err(AppError::BadRequest)?;
+    }
     Ok(())
 }
 
+/// Defaults `warranty_months` to the legal guarantee of the purchase, on
+/// the calendar of the user creating the warranty or, when it replaces an
+/// `existing` one, of its creator.
+async fn fill_warranty_months(
+    state: &AppState,
+    user_id: &str,
+    existing: Option<Uuid>,
+    payload: &mut CreateWarrantyRequest,
+) -> Result<()> {
+    if payload.warranty_months.is_some() {
+        return Ok(());
+    }
+
+    let rules = handlers::legal_guarantee::rules_for(&state.config.legal_country)?;
+    let timezone = match existing {
+        Some(id) => db::get_warranty_timezone(&state.pool, id).await?,
+        None => db::get_user_timezone(&state.pool, user_id).await?,
+    };
+    let purchase = legal::Purchase {
+        category: &payload.category,
+        purchase_date: payload.purchase_date,
+        condition: payload.condition,
+        channel: payload.purchase_channel,
+        repairs: &[],
+    };
+    match legal::legal_months(rules, &purchase, timezone) {
+        0 => Err(AppError::BadRequest(
+            "Warranty months is required: no legal guarantee applies to this purchase".to_string(),
+        )),
+        months => {
+            payload.warranty_months = Some(months);
+            Ok(())
+        }
+    }
+}
+
 #[utoipa::path(
     get,
     path = "/api/v1/warranties/{id}",
@@ -515,6 +1021,7 @@ async fn get_warranty(
         (status = 200, description = "Warranty updated successfully", body = Warranty),
         (status = 400, description = "Invalid request", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
         (status = 404, description = "Warranty not found", body = ErrorResponse),
         (status = 429, description = "Too many requests", body = ErrorResponse)
     ),
@@ -532,6 +1039,7 @@ async fn update_warranty(
         .get::<AuthUser>()
         .ok_or(AppError::Unauthorized)?
         .clone();
+    let actor = Actor::new(&user.user_id, &request);
 
     let body = axum::body::to_bytes(request.into_body(), 1024 * 1024)
         .await
@@ -541,8 +1049,10 @@ async fn update_warranty(
         .map_err(|e| AppError::BadRequest(format!("Invalid JSON: {}", e)))?;
 
     validate_update_warranty(&payload)?;
+    handlers::authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
 
-    let warranty = db::update_warranty(&state.pool, id, &user.user_id, payload).await?;
+    let warranty = db::update_warranty(&state.pool, id, &user.user_id, payload, &actor).await?;
+    webhooks::emit(&state.pool, WebhookEvent::Updated, &warranty).await;
 
     info!(warranty_id = %warranty.id, user_id = %user.user_id, "warranty updated");
 
@@ -576,6 +1086,18 @@ fn validate_update_warranty(req: &UpdateWarrantyRequest) -> Result<()> {
             ));
         }
     }
+    if let Some(price_cents) = req.price_cents {
+        if price_cents < 0 {
+            return Err(AppError::BadRequest("Price cannot be negative".to_string()));
+        }
+    }
+    if let Some(ref invoice_number) = req.invoice_number {
+        if invoice_number.len() > 100 {
+            return Err(AppError::BadRequest(
+                "Invoice number must be less than 100 characters".to_string(),
+            ));
+        }
+    }
     if let Some(ref notes) = req.notes {
         if notes.len() > 2000 {
             return Err(AppError::BadRequest(
@@ -590,6 +1112,9 @@ fn validate_update_warranty(req: &UpdateWarrantyRequest) -> Result<()> {
             ));
         }
     }
+    if let Some(Some(ref lead_days)) = req.reminder_lead_days {
+        reminders::validate_lead_days(lead_days).map_err(AppError::BadRequest)?;
+    }
     Ok(())
 }
 
@@ -601,8 +1126,9 @@ fn validate_update_warranty(req: &UpdateWarrantyRequest) -> Result<()> {
         ("id" = Uuid, Path, description = "Warranty ID")
     ),
     responses(
-        (status = 204, description = "Warranty deleted successfully"),
+        (status = 204, description = "Warranty moved to the trash"),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
         (status = 404, description = "Warranty not found", body = ErrorResponse),
         (status = 429, description = "Too many requests", body = ErrorResponse)
     ),
@@ -620,9 +1146,12 @@ async fn delete_warranty_handler(
         .get::<AuthUser>()
         .ok_or(AppError::Unauthorized)?;
 
-    db::delete_warranty(&state.pool, id, &user.user_id).await?;
+    handlers::authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+    let actor = Actor::new(&user.user_id, &request);
+    let warranty = db::trash_warranty(&state.pool, id, &user.user_id, None, &actor).await?;
+    webhooks::emit(&state.pool, WebhookEvent::Deleted, &warranty).await;
 
-    info!(warranty_id = %id, user_id = %user.user_id, "warranty deleted");
+    info!(warranty_id = %id, user_id = %user.user_id, "warranty moved to trash");
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -630,6 +1159,8 @@ async fn delete_warranty_handler(
 #[derive(Debug, Deserialize, ToSchema)]
 struct ExpiringQuery {
     days: Option<i64>,
+    per_page: Option<i64>,
+    cursor: Option<String>,
 }
 
 #[utoipa::path(
@@ -637,10 +1168,13 @@ struct ExpiringQuery {
     path = "/api/v1/warranties/expiring",
     tag = "warranties",
     params(
-        ("days" = Option<i64>, Query, description = "Number of days to look ahead (default: 30, max: 365)")
+        ("days" = Option<i64>, Query, description = "Number of days to look ahead (default: 30, max: 365)"),
+        ("per_page" = Option<i64>, Query, description = "Pages of this many items, by `covered_until` (default: all at once, max: 100)"),
+        ("cursor" = Option<String>, Query, description = "`next_cursor` of the previous page")
     ),
     responses(
         (status = 200, description = "List of expiring warranties", body = WarrantyListResponse),
+        (status = 400, description = "Invalid cursor", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
         (status = 429, description = "Too many requests", body = ErrorResponse)
     ),
@@ -659,16 +1193,36 @@ async fn list_expiring(
         .ok_or(AppError::Unauthorized)?;
     let days = query.days.unwrap_or(30).clamp(1, 365);
 
-    let warranties = db::get_expiring_warranties(&state.pool, &user.user_id, days).await?;
-    let total = warranties.len() as i64;
+    if query.per_page.is_none() && query.cursor.is_none() {
+        let warranties = db::get_expiring_warranties(&state.pool, &user.user_id, days).await?;
+        let total = warranties.len() as i64;
+
+        return Ok(Json(WarrantyListResponse {
+            warranties,
+            total: Some(total),
+            page: Some(1),
+            per_page: total,
+            total_pages: Some(1),
+            next_cursor: None,
+            matches: Vec::new(),
+        }));
+    }
 
-    Ok(Json(WarrantyListResponse {
-        warranties,
-        total,
-        page: 1,
-        per_page: total,
-        total_pages: 1,
-    }))
+    let now = Utc::now();
+    let listing = WarrantyListing::parse(
+        WarrantyFilters {
+            expires_from: Some(now),
+            expires_to: Some(now + chrono::Duration::days(days)),
+            per_page: query.per_page,
+            cursor: query.cursor,
+            ..Default::default()
+        },
+        now,
+    )
+    .map_err(AppError::BadRequest)?;
+    let page = db::list_warranties(&state.pool, &user.user_id, &listing).await?;
+
+    Ok(Json(list_response(&listing, page)))
 }
 
 #[utoipa::path(
@@ -697,6 +1251,36 @@ async fn get_stats(
     Ok(Json(stats))
 }
 
+#[derive(ToSchema)]
+#[allow(dead_code)]
+struct ReceiptUploadForm {
+    /// JPEG, PNG, WebP, HEIC or PDF file
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/warranties/{id}/receipt",
+    tag = "warranties",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID")
+    ),
+    request_body(content = ReceiptUploadForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Receipt uploaded successfully", body = Warranty),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Role too low in the household", body = ErrorResponse),
+        (status = 404, description = "Warranty not found", body = ErrorResponse),
+        (status = 413, description = "File too large", body = ErrorResponse),
+        (status = 415, description = "Unsupported file type", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
 async fn upload_receipt(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
@@ -707,12 +1291,265 @@ async fn upload_receipt(
         .get::<AuthUser>()
         .ok_or(AppError::Unauthorized)?
         .clone();
+    let actor = Actor::new(&user.user_id, &request);
 
-    let receipt_url = format!("/uploads/{}/{}.jpg", user.user_id, id);
+    handlers::authorize_warranty(&state.pool, id, &user.user_id, HouseholdRole::Editor).await?;
+    let existing = db::get_warranty_by_id(&state.pool, id, &user.user_id).await?;
 
-    let warranty = db::update_receipt_url(&state.pool, id, &user.user_id, &receipt_url).await?;
+    let mut multipart = Multipart::from_request(request, &state)
+        .await
+        .map_err(|e| AppError::BadRequest(e.body_text()))?;
+    let upload = handlers::read_upload(&mut multipart, state.config.max_upload_bytes)
+        .await?
+        .file;
+
+    let original_size = upload.data.len();
+    let receipt = imaging::process_receipt(
+        upload.content_type,
+        upload.data,
+        &state.config.heic_convert_command,
+    )
+    .await?;
+
+    let receipt_key = storage::receipt_key(&existing.user_id, id, receipt.content_type);
+    let receipt_url = format!("/api/v1/warranties/{}/receipt", id);
+    let size = receipt.data.len();
+
+    state
+        .storage
+        .put(&receipt_key, receipt.content_type, receipt.data)
+        .await?;
+
+    let thumbnail_key = match receipt.thumbnail {
+        Some(thumbnail) => {
+            let key = storage::receipt_thumbnail_key(&existing.user_id, id);
+            state.storage.put(&key, "image/jpeg", thumbnail).await?;
+            Some(key)
+        }
+        None => None,
+    };
+    let thumbnail_url = thumbnail_key
+        .as_ref()
+        .map(|_| format!("{}?variant=thumbnail", receipt_url));
+
+    let warranty = db::update_receipt(
+        &state.pool,
+        id,
+        &user.user_id,
+        db::StoredReceipt {
+            url: &receipt_url,
+            key: &receipt_key,
+            content_type: receipt.content_type,
+            thumbnail_url: thumbnail_url.as_deref(),
+            thumbnail_key: thumbnail_key.as_deref(),
+        },
+        &actor,
+    )
+    .await?;
 
-    info!(warranty_id = %id, user_id = %user.user_id, "receipt uploaded");
+    // Keys are stable per warranty, so only a change of format or a PDF
+    // replacing an image leaves stale blobs behind.
+    let stale_keys = [existing.receipt_key, existing.receipt_thumbnail_key]
+        .into_iter()
+        .flatten()
+        .filter(|key| key != &receipt_key && Some(key) != thumbnail_key.as_ref());
+    storage::delete_blobs(state.storage.as_ref(), stale_keys).await;
+
+    let queued = db::create_extraction(
+        &state.pool,
+        db::NewExtraction {
+            id: Uuid::new_v4(),
+            user_id: &user.user_id,
+            warranty_id: Some(id),
+            storage_key: &receipt_key,
+            content_type: receipt.content_type,
+        },
+    )
+    .await;
+    if let Err(e) = queued {
+        tracing::warn!(error = %e, warranty_id = %id, "failed to queue receipt extraction");
+    }
+
+    info!(
+        warranty_id = %id,
+        user_id = %user.user_id,
+        uploaded_content_type = upload.content_type,
+        content_type = receipt.content_type,
+        original_size,
+        size,
+        "receipt uploaded"
+    );
 
     Ok(Json(warranty))
 }
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/warranties/{id}/receipt",
+    tag = "warranties",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("variant" = Option<ReceiptVariant>, Query, description = "Rendition to serve (default: original)")
+    ),
+    responses(
+        (status = 200, description = "Receipt file", content_type = "application/octet-stream"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Warranty or receipt not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn download_receipt(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ReceiptVariantQuery>,
+    request: Request<axum::body::Body>,
+) -> Result<Response> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let warranty = db::get_warranty_by_id(&state.pool, id, &user.user_id).await?;
+    receipt_response(&state, &warranty, query.variant).await
+}
+
+#[derive(Debug, Deserialize)]
+struct ReceiptVariantQuery {
+    #[serde(default)]
+    variant: ReceiptVariant,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct SignedUrlResponse {
+    url: String,
+    expires_at: DateTime<Utc>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/warranties/{id}/receipt/signed-url",
+    tag = "warranties",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("variant" = Option<ReceiptVariant>, Query, description = "Rendition the URL serves (default: original)")
+    ),
+    responses(
+        (status = 200, description = "Short-lived URL serving the receipt without a bearer token", body = SignedUrlResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 404, description = "Warranty or receipt not found", body = ErrorResponse),
+        (status = 429, description = "Too many requests", body = ErrorResponse)
+    ),
+    security(
+        ("bearer_auth" = [])
+    )
+)]
+async fn create_receipt_signed_url(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ReceiptVariantQuery>,
+    request: Request<axum::body::Body>,
+) -> Result<Json<SignedUrlResponse>> {
+    let user = request
+        .extensions()
+        .get::<AuthUser>()
+        .ok_or(AppError::Unauthorized)?;
+
+    let warranty = db::get_warranty_by_id(&state.pool, id, &user.user_id).await?;
+    receipt_variant_key(&warranty, query.variant)?;
+
+    let signed = signing::signed_receipt_url(
+        &state.config.url_signing_key,
+        id,
+        &user.user_id,
+        query.variant.as_str(),
+        Utc::now(),
+        chrono::Duration::seconds(state.config.signed_url_ttl_secs),
+    );
+
+    Ok(Json(SignedUrlResponse {
+        url: signed.url,
+        expires_at: signed.expires_at,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct SignedReceiptQuery {
+    uid: String,
+    #[serde(default)]
+    variant: ReceiptVariant,
+    expires: i64,
+    signature: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/files/receipts/{id}",
+    tag = "warranties",
+    params(
+        ("id" = Uuid, Path, description = "Warranty ID"),
+        ("uid" = String, Query, description = "Owner of the warranty"),
+        ("variant" = Option<ReceiptVariant>, Query, description = "Rendition to serve (default: original)"),
+        ("expires" = i64, Query, description = "Expiry as a Unix timestamp"),
+        ("signature" = String, Query, description = "HMAC-SHA256 signature")
+    ),
+    responses(
+        (status = 200, description = "Receipt file", content_type = "application/octet-stream"),
+        (status = 401, description = "Invalid or expired signature", body = ErrorResponse),
+        (status = 404, description = "Warranty or receipt not found", body = ErrorResponse)
+    )
+)]
+async fn download_signed_receipt(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<SignedReceiptQuery>,
+) -> Result<Response> {
+    let valid = signing::verify_receipt_url(
+        &state.config.url_signing_key,
+        id,
+        &query.uid,
+        query.variant.as_str(),
+        query.expires,
+        &query.signature,
+        Utc::now(),
+    );
+    if !valid {
+        return Err(AppError::Unauthorized);
+    }
+
+    let warranty = db::get_warranty_by_id(&state.pool, id, &query.uid).await?;
+    receipt_response(&state, &warranty, query.variant).await
+}
+
+fn receipt_variant_key(warranty: &Warranty, variant: ReceiptVariant) -> Result<&str> {
+    let key = match variant {
+        ReceiptVariant::Original => warranty.receipt_key.as_deref(),
+        ReceiptVariant::Thumbnail => warranty.receipt_thumbnail_key.as_deref(),
+    };
+    key.ok_or_else(|| AppError::NotFound("Receipt not found".to_string()))
+}
+
+async fn receipt_response(
+    state: &AppState,
+    warranty: &Warranty,
+    variant: ReceiptVariant,
+) -> Result<Response> {
+    let key = receipt_variant_key(warranty, variant)?;
+    let (content_type, file_name) = match variant {
+        ReceiptVariant::Original => {
+            let content_type = warranty
+                .receipt_content_type
+                .as_deref()
+                .unwrap_or("application/octet-stream");
+            (
+                content_type,
+                format!("receipt.{}", storage::extension_for(content_type)),
+            )
+        }
+        ReceiptVariant::Thumbnail => ("image/jpeg", "receipt-thumbnail.jpg".to_string()),
+    };
+
+    handlers::file_response(state.storage.as_ref(), key, content_type, &file_name).await
+}
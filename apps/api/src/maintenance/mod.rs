@@ -0,0 +1,71 @@
+//! Recurring maintenance of an item: when each task next comes due, in
+//! calendar months, kilometres, or whichever comes first.
+
+#[cfg(test)]
+mod tests;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+
+use crate::warranty_period::warranty_end_date;
+
+pub const MAX_INTERVAL_MONTHS: i32 = 120;
+pub const MAX_INTERVAL_KM: i32 = 1_000_000;
+pub const MAX_LEAD_DAYS: i32 = 365;
+pub const DEFAULT_LEAD_DAYS: i32 = 7;
+
+/// The last time a task was done, or when its schedule started if it never
+/// was, with the odometer reading taken then.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LastDone {
+    pub at: DateTime<Utc>,
+    pub km: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NextDue {
+    pub at: Option<DateTime<Utc>>,
+    pub km: Option<i32>,
+}
+
+/// Checks the intervals of a task: at least one of them, each in range.
+pub fn validate_schedule(
+    interval_months: Option<i32>,
+    interval_km: Option<i32>,
+) -> Result<(), String> {
+    if interval_months.is_none() && interval_km.is_none() {
+        return Err("Either interval_months or interval_km is required".to_string());
+    }
+    if interval_months.is_some_and(|months| !(1..=MAX_INTERVAL_MONTHS).contains(&months)) {
+        return Err(format!(
+            "Interval months must be between 1 and {}",
+            MAX_INTERVAL_MONTHS
+        ));
+    }
+    if interval_km.is_some_and(|km| !(1..=MAX_INTERVAL_KM).contains(&km)) {
+        return Err(format!(
+            "Interval km must be between 1 and {}",
+            MAX_INTERVAL_KM
+        ));
+    }
+    Ok(())
+}
+
+/// When a task next comes due: `interval_months` calendar months after it
+/// was last done, counted on the owner's calendar in `timezone`, and
+/// `interval_km` past the odometer reading taken then. Either is `None` when
+/// the task has no such interval, or no reading to count from.
+pub fn next_due(
+    interval_months: Option<i32>,
+    interval_km: Option<i32>,
+    last_done: LastDone,
+    timezone: Tz,
+) -> NextDue {
+    NextDue {
+        at: interval_months.and_then(|months| warranty_end_date(last_done.at, months, timezone)),
+        km: last_done
+            .km
+            .zip(interval_km)
+            .and_then(|(km, interval)| km.checked_add(interval)),
+    }
+}
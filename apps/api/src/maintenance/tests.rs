@@ -0,0 +1,53 @@
+use chrono::{TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::maintenance::{next_due, validate_schedule, LastDone, NextDue};
+
+#[test]
+fn test_validate_schedule_needs_an_interval() {
+    assert!(validate_schedule(Some(6), None).is_ok());
+    assert!(validate_schedule(None, Some(15_000)).is_ok());
+    assert!(validate_schedule(Some(12), Some(15_000)).is_ok());
+
+    assert!(validate_schedule(None, None).is_err());
+    assert!(validate_schedule(Some(0), None).is_err());
+    assert!(validate_schedule(Some(121), Some(15_000)).is_err());
+    assert!(validate_schedule(Some(12), Some(-1)).is_err());
+}
+
+#[test]
+fn test_next_due_counts_calendar_months_and_km() {
+    let last_done = LastDone {
+        at: Utc.with_ymd_and_hms(2025, 8, 31, 8, 0, 0).unwrap(),
+        km: Some(42_300),
+    };
+
+    assert_eq!(
+        next_due(Some(6), Some(15_000), last_done, Tz::Europe__Paris),
+        NextDue {
+            // Summer time on August 31, winter time on February 28.
+            at: Some(Utc.with_ymd_and_hms(2026, 2, 28, 9, 0, 0).unwrap()),
+            km: Some(57_300),
+        }
+    );
+}
+
+#[test]
+fn test_next_due_without_reading_or_interval() {
+    let last_done = LastDone {
+        at: Utc.with_ymd_and_hms(2025, 1, 10, 9, 0, 0).unwrap(),
+        km: None,
+    };
+
+    assert_eq!(
+        next_due(None, Some(15_000), last_done, Tz::UTC),
+        NextDue { at: None, km: None }
+    );
+    assert_eq!(
+        next_due(Some(12), None, last_done, Tz::UTC),
+        NextDue {
+            at: Some(Utc.with_ymd_and_hms(2026, 1, 10, 9, 0, 0).unwrap()),
+            km: None,
+        }
+    );
+}
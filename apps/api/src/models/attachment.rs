@@ -0,0 +1,57 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "attachment_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentKind {
+    Invoice,
+    ProductPhoto,
+    WarrantyCard,
+    Manual,
+    SerialPlate,
+    Other,
+}
+
+impl FromStr for AttachmentKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "invoice" => Ok(AttachmentKind::Invoice),
+            "product_photo" => Ok(AttachmentKind::ProductPhoto),
+            "warranty_card" => Ok(AttachmentKind::WarrantyCard),
+            "manual" => Ok(AttachmentKind::Manual),
+            "serial_plate" => Ok(AttachmentKind::SerialPlate),
+            "other" => Ok(AttachmentKind::Other),
+            _ => Err(format!("Unknown attachment kind: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Attachment {
+    pub id: Uuid,
+    pub warranty_id: Uuid,
+    /// The claim the attachment was filed under, if any
+    pub claim_id: Option<Uuid>,
+    pub kind: AttachmentKind,
+    pub file_name: Option<String>,
+    #[serde(skip)]
+    pub storage_key: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub checksum_sha256: String,
+    pub uploaded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UpdateAttachmentRequest {
+    pub kind: Option<AttachmentKind>,
+    pub file_name: Option<String>,
+}
@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct CalendarFeed {
+    pub token: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The secret address of a user's warranty calendar.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CalendarFeedResponse {
+    /// Anyone with this URL can read the feed
+    pub url: String,
+    /// The same feed, for calendar apps that subscribe on `webcal://` links
+    pub webcal_url: String,
+    pub created_at: DateTime<Utc>,
+}
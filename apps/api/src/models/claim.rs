@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::Attachment;
+
+/// Where an after-sales claim stands. A claim only moves forward:
+/// opened, then shipped, then in repair, then one of the closed statuses,
+/// possibly skipping steps.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "claim_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimStatus {
+    Opened,
+    /// Sent or dropped off for repair
+    Shipped,
+    InRepair,
+    /// Repaired
+    Resolved,
+    Refused,
+    Replaced,
+}
+
+impl ClaimStatus {
+    fn step(self) -> u8 {
+        match self {
+            ClaimStatus::Opened => 0,
+            ClaimStatus::Shipped => 1,
+            ClaimStatus::InRepair => 2,
+            ClaimStatus::Resolved | ClaimStatus::Refused | ClaimStatus::Replaced => 3,
+        }
+    }
+
+    pub fn is_closed(self) -> bool {
+        self.step() == 3
+    }
+
+    pub fn can_move_to(self, next: ClaimStatus) -> bool {
+        next.step() > self.step()
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ClaimStatus::Opened => "opened",
+            ClaimStatus::Shipped => "shipped",
+            ClaimStatus::InRepair => "in_repair",
+            ClaimStatus::Resolved => "resolved",
+            ClaimStatus::Refused => "refused",
+            ClaimStatus::Replaced => "replaced",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Claim {
+    pub id: Uuid,
+    pub warranty_id: Uuid,
+    pub status: ClaimStatus,
+    /// What went wrong
+    pub description: String,
+    /// Return authorization number given by the store or brand
+    pub rma_number: Option<String>,
+    /// When the store or brand was first contacted
+    pub contacted_at: Option<DateTime<Utc>>,
+    pub dropped_off_at: Option<DateTime<Utc>>,
+    pub returned_at: Option<DateTime<Utc>>,
+    /// Months the repair added to the warranty under the legal guarantee
+    pub extension_months: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Claim {
+    /// Fills the drop-off or return date the move to `status` at `at` implies,
+    /// keeping any date the user already entered.
+    pub fn stamp(&mut self, status: ClaimStatus, at: DateTime<Utc>) {
+        match status {
+            ClaimStatus::Opened => {}
+            ClaimStatus::Shipped | ClaimStatus::InRepair => {
+                self.dropped_off_at.get_or_insert(at);
+            }
+            ClaimStatus::Resolved | ClaimStatus::Refused | ClaimStatus::Replaced => {
+                self.returned_at.get_or_insert(at);
+            }
+        }
+    }
+
+    /// When the repair was asked for, which decides whether it was made
+    /// under the guarantee.
+    pub fn requested_at(&self) -> DateTime<Utc> {
+        self.contacted_at.unwrap_or(self.created_at)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ClaimEvent {
+    pub id: Uuid,
+    pub claim_id: Uuid,
+    pub status: ClaimStatus,
+    pub note: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A claim with its history and the attachments filed under it.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ClaimDetail {
+    pub claim: Claim,
+    pub events: Vec<ClaimEvent>,
+    pub attachments: Vec<Attachment>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateClaimRequest {
+    pub description: String,
+    pub rma_number: Option<String>,
+    /// Defaults to now
+    pub contacted_at: Option<DateTime<Utc>>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateClaimRequest {
+    pub description: Option<String>,
+    pub rma_number: Option<String>,
+    pub contacted_at: Option<DateTime<Utc>>,
+    pub dropped_off_at: Option<DateTime<Utc>>,
+    pub returned_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ClaimTransitionRequest {
+    pub status: ClaimStatus,
+    pub note: Option<String>,
+    /// Defaults to now
+    pub occurred_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "coverage_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum CoverageKind {
+    /// Legal guarantee of conformity owed by the seller
+    Legal,
+    /// Commercial warranty of the brand
+    Manufacturer,
+    /// Paid or bundled extension, e.g. from the retailer or a bank card
+    Extended,
+}
+
+/// One of the overlapping coverages of an item, besides its own warranty.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Coverage {
+    pub id: Uuid,
+    pub warranty_id: Uuid,
+    pub kind: CoverageKind,
+    /// Brand, retailer, insurer or card issuer
+    pub provider: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub contract_number: Option<String>,
+    /// Price paid for the coverage, in euro cents
+    pub cost_cents: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateCoverageRequest {
+    pub kind: CoverageKind,
+    pub provider: Option<String>,
+    /// Defaults to the purchase date of the warranty
+    pub starts_at: Option<DateTime<Utc>>,
+    /// Either `ends_at` or `months` is required
+    pub ends_at: Option<DateTime<Utc>>,
+    /// Length in calendar months from `starts_at`
+    pub months: Option<i32>,
+    pub contract_number: Option<String>,
+    /// Price paid for the coverage, in euro cents
+    pub cost_cents: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateCoverageRequest {
+    pub kind: Option<CoverageKind>,
+    pub provider: Option<String>,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    /// Sets `ends_at` this many calendar months after `starts_at`
+    pub months: Option<i32>,
+    pub contract_number: Option<String>,
+    /// Price paid for the coverage, in euro cents
+    pub cost_cents: Option<i64>,
+}
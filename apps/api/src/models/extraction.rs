@@ -0,0 +1,57 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::WarrantyCategory;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "extraction_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractionStatus {
+    Pending,
+    Processing,
+    Completed,
+    Failed,
+}
+
+/// Fields of a `CreateWarrantyRequest` read from a receipt. Anything the
+/// extractor could not find is left empty, and every field that is filled in
+/// has an entry in `confidence` between 0 and 1.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct WarrantySuggestion {
+    pub product_name: Option<String>,
+    pub brand: Option<String>,
+    pub category: Option<WarrantyCategory>,
+    pub purchase_date: Option<DateTime<Utc>>,
+    pub warranty_months: Option<i32>,
+    pub store: Option<String>,
+    /// Price paid, in euro cents
+    pub price_cents: Option<i64>,
+    pub invoice_number: Option<String>,
+    pub confidence: BTreeMap<String, f32>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct ReceiptExtraction {
+    pub id: Uuid,
+    /// Set when the extraction runs on a warranty's stored receipt
+    pub warranty_id: Option<Uuid>,
+    pub status: ExtractionStatus,
+    #[serde(skip)]
+    pub storage_key: String,
+    #[serde(skip)]
+    pub content_type: String,
+    /// Empty until the extraction has completed
+    #[schema(value_type = Vec<WarrantySuggestion>)]
+    pub suggestions: Json<Vec<WarrantySuggestion>>,
+    pub error: Option<String>,
+    #[serde(skip)]
+    pub attempts: i32,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// What a member may do in a household, in increasing order of rights.
+#[derive(
+    Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq, PartialOrd, Ord, ToSchema,
+)]
+#[sqlx(type_name = "household_role", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum HouseholdRole {
+    /// Reads the household's warranties
+    Viewer,
+    /// Also adds, changes and deletes them
+    Editor,
+    /// Also manages the household, its members and invitations
+    Owner,
+}
+
+impl HouseholdRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HouseholdRole::Viewer => "viewer",
+            HouseholdRole::Editor => "editor",
+            HouseholdRole::Owner => "owner",
+        }
+    }
+}
+
+/// A household as one of its members sees it.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct Household {
+    pub id: Uuid,
+    pub name: String,
+    /// Role of the caller in the household
+    pub role: HouseholdRole,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct HouseholdMember {
+    pub user_id: String,
+    pub role: HouseholdRole,
+    pub joined_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HouseholdDetail {
+    #[serde(flatten)]
+    pub household: Household,
+    pub members: Vec<HouseholdMember>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateHouseholdRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateHouseholdRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateMemberRequest {
+    pub role: HouseholdRole,
+}
+
+/// An invitation to join a household, sent by email.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct HouseholdInvitation {
+    pub id: Uuid,
+    pub household_id: Uuid,
+    pub email: String,
+    /// Role given on joining
+    pub role: HouseholdRole,
+    #[serde(skip)]
+    pub token: Uuid,
+    pub invited_by: String,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_by: Option<String>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A newly created invitation. The token is only ever shown here, for
+/// sharing the invitation some other way than its email.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedInvitation {
+    #[serde(flatten)]
+    pub invitation: HouseholdInvitation,
+    pub token: Uuid,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateInvitationRequest {
+    pub email: String,
+    /// Defaults to `editor`; an invitation cannot make an owner
+    #[serde(default = "default_invitation_role")]
+    pub role: HouseholdRole,
+}
+
+fn default_invitation_role() -> HouseholdRole {
+    HouseholdRole::Editor
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AcceptInvitationRequest {
+    /// Token from the invitation email
+    pub token: Uuid,
+}
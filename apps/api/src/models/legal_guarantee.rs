@@ -0,0 +1,71 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::WarrantyCategory;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemCondition {
+    #[default]
+    New,
+    /// Second-hand, including refurbished
+    Used,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PurchaseChannel {
+    /// In a shop, from a professional seller
+    #[default]
+    Store,
+    /// At a distance from a professional seller: website, phone, catalogue
+    Online,
+    /// From another consumer, e.g. through a classifieds site
+    Private,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct LegalGuaranteeRequest {
+    /// ISO 3166-1 alpha-2 code, or `EU` for the directive's minimum;
+    /// defaults to the server's country
+    #[schema(example = "FR")]
+    pub country: Option<String>,
+    pub category: WarrantyCategory,
+    pub purchase_date: DateTime<Utc>,
+    #[serde(default)]
+    pub condition: ItemCondition,
+    #[serde(default)]
+    pub purchase_channel: PurchaseChannel,
+    /// When the item came back from each repair made under the legal guarantee
+    #[serde(default)]
+    pub repairs: Vec<DateTime<Utc>>,
+}
+
+/// The legal guarantee of conformity owed by the seller, as opposed to a
+/// manufacturer's commercial warranty.
+#[derive(Debug, Clone, Serialize, PartialEq, ToSchema)]
+pub struct LegalGuarantee {
+    pub country: String,
+    /// Rule of the country's table that applied
+    #[schema(example = "fr.new")]
+    pub rule: String,
+    /// False when no legal guarantee of conformity applies, e.g. between
+    /// private individuals
+    pub covered: bool,
+    pub months: i32,
+    pub legal_basis: String,
+    /// End of the legal guarantee, before any extension
+    pub end_date: Option<DateTime<Utc>>,
+    /// Months added by each repair under the guarantee
+    pub repair_extension_months: i32,
+    pub repair_extension_basis: Option<String>,
+    /// Repairs that extended the guarantee
+    pub repairs_counted: i32,
+    /// End of the legal guarantee once extended by repairs
+    pub extended_end_date: Option<DateTime<Utc>>,
+    /// Last day to withdraw from a distance purchase, counted from the
+    /// purchase date as if the item was delivered the same day
+    pub withdrawal_end_date: Option<DateTime<Utc>>,
+    pub withdrawal_basis: Option<String>,
+}
@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::double_option;
+
+/// Recurring upkeep of an item, due every `interval_months`, every
+/// `interval_km`, or whichever comes first when both are set.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct MaintenanceTask {
+    pub id: Uuid,
+    pub warranty_id: Uuid,
+    pub title: String,
+    pub notes: Option<String>,
+    pub interval_months: Option<i32>,
+    pub interval_km: Option<i32>,
+    /// The schedule counts from here until the task is first done
+    pub starts_at: DateTime<Utc>,
+    /// Odometer reading at `starts_at`
+    pub start_km: Option<i32>,
+    /// Days before each due date the reminder goes out
+    pub lead_days: i32,
+    /// Missing for tasks counted in kilometres only
+    pub next_due_at: Option<DateTime<Utc>>,
+    /// Missing until an odometer reading is known
+    pub next_due_km: Option<i32>,
+    pub last_done_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One entry of the completion log of a task.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct MaintenanceCompletion {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub done_at: DateTime<Utc>,
+    pub odometer_km: Option<i32>,
+    pub notes: Option<String>,
+    /// In euro cents
+    pub cost_cents: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateMaintenanceTaskRequest {
+    pub title: String,
+    pub notes: Option<String>,
+    /// At least one of `interval_months` and `interval_km` is required
+    pub interval_months: Option<i32>,
+    pub interval_km: Option<i32>,
+    /// Defaults to the purchase date of the warranty
+    pub starts_at: Option<DateTime<Utc>>,
+    pub start_km: Option<i32>,
+    /// Defaults to 7
+    pub lead_days: Option<i32>,
+}
+
+/// Fields left out keep their current value.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct UpdateMaintenanceTaskRequest {
+    pub title: Option<String>,
+    pub notes: Option<String>,
+    /// `null` drops the interval in months
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<i32>)]
+    pub interval_months: Option<Option<i32>>,
+    /// `null` drops the interval in kilometres
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<i32>)]
+    pub interval_km: Option<Option<i32>>,
+    pub starts_at: Option<DateTime<Utc>>,
+    /// `null` forgets the starting odometer reading
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<i32>)]
+    pub start_km: Option<Option<i32>>,
+    pub lead_days: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateMaintenanceCompletionRequest {
+    /// Defaults to now
+    pub done_at: Option<DateTime<Utc>>,
+    pub odometer_km: Option<i32>,
+    pub notes: Option<String>,
+    /// In euro cents
+    pub cost_cents: Option<i64>,
+}
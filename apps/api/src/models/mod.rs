@@ -1,5 +1,35 @@
+mod attachment;
+mod calendar_feed;
+mod claim;
+mod coverage;
+mod extraction;
+mod household;
+mod legal_guarantee;
+mod maintenance;
+mod notification;
+mod push_subscription;
+mod reminder_preferences;
+mod sync;
 #[cfg(test)]
 mod tests;
+mod trash;
 mod warranty;
+mod warranty_event;
+mod webhook;
 
+pub use attachment::*;
+pub use calendar_feed::*;
+pub use claim::*;
+pub use coverage::*;
+pub use extraction::*;
+pub use household::*;
+pub use legal_guarantee::*;
+pub use maintenance::*;
+pub use notification::*;
+pub use push_subscription::*;
+pub use reminder_preferences::*;
+pub use sync::*;
+pub use trash::*;
 pub use warranty::*;
+pub use warranty_event::*;
+pub use webhook::*;
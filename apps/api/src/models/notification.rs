@@ -0,0 +1,132 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::DigestFrequency;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "notification_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    ExpiryReminder,
+    Digest,
+    /// A warranty reached its end date
+    Expired,
+    /// A maintenance task of a warranty is coming due
+    MaintenanceDue,
+    /// Someone was invited to a household; sent to the invited address
+    HouseholdInvitation,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct Notification {
+    pub id: Uuid,
+    pub user_id: String,
+    /// Missing for digests
+    pub warranty_id: Option<Uuid>,
+    pub kind: NotificationKind,
+    /// Days before the end of the warranty that triggered the reminder
+    pub threshold_days: Option<i32>,
+    /// End of the coverage, extensions included, the notification is about
+    pub warranty_end_date: Option<DateTime<Utc>>,
+    /// The numbers a digest reported
+    #[schema(value_type = Option<DigestSummary>)]
+    pub digest: Option<Json<DigestSummary>>,
+    pub created_at: DateTime<Utc>,
+    pub read_at: Option<DateTime<Utc>>,
+    /// The task a maintenance reminder is about
+    pub maintenance_task_id: Option<Uuid>,
+    /// The due date a maintenance reminder announced
+    pub maintenance_due_at: Option<DateTime<Utc>>,
+    /// The invitation an invitation email is about
+    pub household_invitation_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "notification_channel", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationChannel {
+    Email,
+    Push,
+    Webhook,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "delivery_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Pending,
+    Sending,
+    Sent,
+    Failed,
+}
+
+/// A user's warranties over one digest period, counted as in the stats.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DigestSummary {
+    pub frequency: DigestFrequency,
+    /// Start of the period: the previous digest, or the opt-in
+    pub since: DateTime<Utc>,
+    pub expiring_within_30_days: i64,
+    pub expiring_within_90_days: i64,
+    /// Warranties that ended during the period
+    pub expired_since_last_digest: i64,
+    /// Active warranties without a receipt
+    pub missing_receipts: i64,
+    /// The first warranties to end in the next 30 days
+    pub expiring: Vec<DigestItem>,
+}
+
+impl DigestSummary {
+    /// Nothing worth a message.
+    pub fn is_empty(&self) -> bool {
+        self.expiring_within_90_days == 0
+            && self.expired_since_last_digest == 0
+            && self.missing_receipts == 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DigestItem {
+    pub warranty_id: Uuid,
+    pub product_name: String,
+    pub brand: Option<String>,
+    pub warranty_end_date: DateTime<Utc>,
+}
+
+/// A claimed delivery with everything needed to write the message. The
+/// warranty fields are set for expiry and maintenance reminders, the
+/// maintenance fields for the latter, `digest` for digests and the household
+/// fields for invitations.
+#[derive(Debug, Clone, FromRow)]
+pub struct PendingDelivery {
+    pub id: Uuid,
+    pub channel: NotificationChannel,
+    pub attempts: i32,
+    pub user_id: String,
+    pub kind: NotificationKind,
+    pub warranty_id: Option<Uuid>,
+    pub product_name: Option<String>,
+    pub brand: Option<String>,
+    pub warranty_end_date: Option<DateTime<Utc>>,
+    pub digest: Option<Json<DigestSummary>>,
+    pub maintenance_task_id: Option<Uuid>,
+    pub maintenance_title: Option<String>,
+    pub maintenance_due_at: Option<DateTime<Utc>>,
+    pub maintenance_due_km: Option<i32>,
+    /// Lets the recipient turn digests off without signing in
+    pub digest_token: Option<Uuid>,
+    pub household_name: Option<String>,
+    /// Accepts the invitation an invitation email is about
+    pub invitation_token: Option<Uuid>,
+    /// From the auth service's `users` table; missing if the account is gone
+    pub recipient_email: Option<String>,
+    pub recipient_name: Option<String>,
+    /// From the recipient's reminder preferences; missing for invitations,
+    /// whose recipient may have no account yet
+    pub recipient_timezone: Option<String>,
+    pub recipient_locale: Option<String>,
+}
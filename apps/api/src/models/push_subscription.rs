@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A browser registered for Web Push, as handed out by `PushManager.subscribe`.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct PushSubscription {
+    pub id: Uuid,
+    pub endpoint: String,
+    #[serde(skip)]
+    pub p256dh: String,
+    #[serde(skip)]
+    pub auth: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The JSON form of a browser `PushSubscription`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreatePushSubscriptionRequest {
+    /// Push service URL, unique to the browser
+    pub endpoint: String,
+    pub keys: PushSubscriptionKeys,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PushSubscriptionKeys {
+    /// Browser public key, P-256 uncompressed point in base64url
+    pub p256dh: String,
+    /// 16-byte authentication secret in base64url
+    pub auth: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DeletePushSubscriptionRequest {
+    pub endpoint: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VapidPublicKeyResponse {
+    /// The `applicationServerKey` to pass to `PushManager.subscribe`
+    pub public_key: String,
+}
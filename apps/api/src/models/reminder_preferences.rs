@@ -0,0 +1,133 @@
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+use crate::models::NotificationChannel;
+
+/// Used until the user picks a timezone.
+pub const DEFAULT_TIMEZONE: &str = "Europe/Paris";
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "reminder_mode", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ReminderMode {
+    /// One message per reminder
+    #[default]
+    Individual,
+    /// Reminders are only listed in the periodic digest
+    Digest,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "digest_frequency", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DigestFrequency {
+    Weekly,
+    Monthly,
+}
+
+/// How and when a user hears about expiring warranties.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct ReminderPreferences {
+    /// Days before the end of a warranty to send a reminder
+    pub lead_days: Vec<i32>,
+    pub channels: Vec<NotificationChannel>,
+    /// Local time, as `HH:MM`, from which nothing is sent
+    #[serde(serialize_with = "serialize_time")]
+    #[schema(value_type = Option<String>, example = "22:00")]
+    pub quiet_hours_start: Option<NaiveTime>,
+    /// Local time, as `HH:MM`, from which messages go out again
+    #[serde(serialize_with = "serialize_time")]
+    #[schema(value_type = Option<String>, example = "07:00")]
+    pub quiet_hours_end: Option<NaiveTime>,
+    /// IANA name, such as `Europe/Paris`
+    pub timezone: String,
+    /// Language of notifications and of the calendar feed, `fr` or `en`;
+    /// `null` follows the server's default
+    #[schema(example = "fr")]
+    pub locale: Option<String>,
+    pub mode: ReminderMode,
+    /// How often to send the digest; `null` when the user gets none. In
+    /// digest mode without a digest, nothing is sent at all.
+    pub digest_frequency: Option<DigestFrequency>,
+    /// Missing until the user saves preferences
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl ReminderPreferences {
+    /// What a user who never saved preferences gets.
+    pub fn defaults(lead_days: &[i32]) -> Self {
+        Self {
+            lead_days: lead_days.to_vec(),
+            channels: vec![
+                NotificationChannel::Email,
+                NotificationChannel::Push,
+                NotificationChannel::Webhook,
+            ],
+            quiet_hours_start: None,
+            quiet_hours_end: None,
+            timezone: DEFAULT_TIMEZONE.to_string(),
+            locale: None,
+            mode: ReminderMode::Individual,
+            digest_frequency: None,
+            updated_at: None,
+        }
+    }
+}
+
+/// Fields left out keep their current value.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct UpdateReminderPreferencesRequest {
+    pub lead_days: Option<Vec<i32>>,
+    pub channels: Option<Vec<NotificationChannel>>,
+    /// `null` turns quiet hours off
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<QuietHours>)]
+    pub quiet_hours: Option<Option<QuietHours>>,
+    pub timezone: Option<String>,
+    /// `null` goes back to the server's default
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<String>)]
+    pub locale: Option<Option<String>>,
+    pub mode: Option<ReminderMode>,
+    /// `null` turns the digest off
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<DigestFrequency>)]
+    pub digest_frequency: Option<Option<DigestFrequency>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, ToSchema)]
+pub struct QuietHours {
+    #[serde(deserialize_with = "deserialize_time")]
+    #[schema(value_type = String, example = "22:00")]
+    pub start: NaiveTime,
+    #[serde(deserialize_with = "deserialize_time")]
+    #[schema(value_type = String, example = "07:00")]
+    pub end: NaiveTime,
+}
+
+/// Tells a field set to `null` (`Some(None)`) from a missing one (`None`).
+pub fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+fn serialize_time<S: Serializer>(
+    time: &Option<NaiveTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match time {
+        Some(time) => serializer.serialize_str(&time.format("%H:%M").to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn deserialize_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    NaiveTime::parse_from_str(&value, "%H:%M")
+        .map_err(|_| serde::de::Error::custom(format!("expected HH:MM, got {:?}", value)))
+}
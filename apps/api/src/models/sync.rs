@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::{CreateWarrantyRequest, Warranty};
+
+/// A deleted warranty, for clients to drop their copy.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Tombstone {
+    pub id: Uuid,
+    pub deleted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SyncQuery {
+    /// `token` of the previous sync; without it, every warranty is sent
+    pub since: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SyncResponse {
+    /// Created or updated since `since`; some may have been sent before
+    pub warranties: Vec<Warranty>,
+    /// Deleted since `since`
+    pub deleted: Vec<Tombstone>,
+    /// Pass as `since` on the next sync
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SyncPushRequest {
+    /// Applied in order
+    pub changes: Vec<SyncChange>,
+}
+
+/// A change made offline. `base_updated_at` is the `updated_at` of the
+/// server copy the change was made on; the change conflicts when the server
+/// copy was updated since.
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum SyncChange {
+    /// Creates the warranty under `id` without `base_updated_at`, replaces
+    /// it with one
+    Upsert {
+        id: Uuid,
+        base_updated_at: Option<DateTime<Utc>>,
+        warranty: CreateWarrantyRequest,
+    },
+    Delete {
+        id: Uuid,
+        base_updated_at: DateTime<Utc>,
+    },
+}
+
+impl SyncChange {
+    pub fn id(&self) -> Uuid {
+        match self {
+            SyncChange::Upsert { id, .. } | SyncChange::Delete { id, .. } => *id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncStatus {
+    Applied,
+    /// The server copy changed or was deleted since; it is kept
+    Conflict,
+    /// Invalid, or about a warranty the client cannot see
+    Rejected,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SyncResult {
+    pub id: Uuid,
+    pub status: SyncStatus,
+    /// The server copy once the change is applied, or the one kept on
+    /// conflict; missing when the warranty is deleted
+    pub warranty: Option<Warranty>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SyncPushResponse {
+    /// One per change, in the same order
+    pub results: Vec<SyncResult>,
+}
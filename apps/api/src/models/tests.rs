@@ -1,141 +1,350 @@
-#[cfg(test)]
-mod tests {
-    use crate::models::{
-        CreateWarrantyRequest, UpdateWarrantyRequest, WarrantyCategory, WarrantyFilters,
+use crate::models::{
+    AttachmentKind, Claim, ClaimStatus, CreateWarrantyRequest, DigestFrequency, ItemCondition,
+    NotificationChannel, PurchaseChannel, QuietHours, ReminderMode, ReminderPreferences,
+    SyncChange, UpdateReminderPreferencesRequest, UpdateWarrantyRequest, WarrantyCategory,
+    WarrantyFilters,
+};
+use chrono::{Duration, NaiveTime, Utc};
+use uuid::Uuid;
+
+#[test]
+fn test_warranty_category_display_name_fr() {
+    assert_eq!(
+        WarrantyCategory::Electronics.display_name_fr(),
+        "Électronique"
+    );
+    assert_eq!(
+        WarrantyCategory::Appliances.display_name_fr(),
+        "Électroménager"
+    );
+    assert_eq!(WarrantyCategory::Furniture.display_name_fr(), "Mobilier");
+    assert_eq!(WarrantyCategory::Clothing.display_name_fr(), "Vêtements");
+    assert_eq!(WarrantyCategory::Automotive.display_name_fr(), "Automobile");
+    assert_eq!(WarrantyCategory::Sports.display_name_fr(), "Sport");
+    assert_eq!(WarrantyCategory::Other.display_name_fr(), "Autre");
+}
+
+#[test]
+fn test_warranty_category_serialization() {
+    let category = WarrantyCategory::Electronics;
+    let json = serde_json::to_string(&category).unwrap();
+    assert_eq!(json, "\"electronics\"");
+
+    let deserialized: WarrantyCategory = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, WarrantyCategory::Electronics);
+}
+
+#[test]
+fn test_create_warranty_request_serialization() {
+    let request = CreateWarrantyRequest {
+        household_id: None,
+        product_name: "iPhone 15".to_string(),
+        brand: Some("Apple".to_string()),
+        category: WarrantyCategory::Electronics,
+        purchase_date: Utc::now(),
+        warranty_months: Some(24),
+        condition: ItemCondition::New,
+        purchase_channel: PurchaseChannel::Store,
+        store: Some("Apple Store".to_string()),
+        price_cents: Some(96_900),
+        invoice_number: None,
+        notes: None,
+        reminder_lead_days: None,
     };
-    use chrono::{Duration, Utc};
-
-    #[test]
-    fn test_warranty_category_default_months() {
-        assert_eq!(WarrantyCategory::Electronics.default_warranty_months(), 24);
-        assert_eq!(WarrantyCategory::Appliances.default_warranty_months(), 24);
-        assert_eq!(WarrantyCategory::Furniture.default_warranty_months(), 24);
-        assert_eq!(WarrantyCategory::Clothing.default_warranty_months(), 6);
-        assert_eq!(WarrantyCategory::Automotive.default_warranty_months(), 24);
-        assert_eq!(WarrantyCategory::Sports.default_warranty_months(), 12);
-        assert_eq!(WarrantyCategory::Other.default_warranty_months(), 24);
-    }
 
-    #[test]
-    fn test_warranty_category_display_name_fr() {
-        assert_eq!(
-            WarrantyCategory::Electronics.display_name_fr(),
-            "Électronique"
-        );
-        assert_eq!(
-            WarrantyCategory::Appliances.display_name_fr(),
-            "Électroménager"
-        );
-        assert_eq!(WarrantyCategory::Furniture.display_name_fr(), "Mobilier");
-        assert_eq!(WarrantyCategory::Clothing.display_name_fr(), "Vêtements");
-        assert_eq!(WarrantyCategory::Automotive.display_name_fr(), "Automobile");
-        assert_eq!(WarrantyCategory::Sports.display_name_fr(), "Sport");
-        assert_eq!(WarrantyCategory::Other.display_name_fr(), "Autre");
-    }
+    let json = serde_json::to_string(&request).unwrap();
+    assert!(json.contains("iPhone 15"));
+    assert!(json.contains("Apple"));
+    assert!(json.contains("electronics"));
+    assert!(json.contains("\"price_cents\":96900"));
+}
 
-    #[test]
-    fn test_warranty_category_serialization() {
-        let category = WarrantyCategory::Electronics;
-        let json = serde_json::to_string(&category).unwrap();
-        assert_eq!(json, "\"electronics\"");
+#[test]
+fn test_create_warranty_request_deserialization() {
+    let json = r#"{
+        "product_name": "MacBook Pro",
+        "brand": "Apple",
+        "category": "electronics",
+        "purchase_date": "2024-01-15T10:00:00Z",
+        "warranty_months": 36,
+        "store": "Apple Store"
+    }"#;
 
-        let deserialized: WarrantyCategory = serde_json::from_str(&json).unwrap();
-        assert_eq!(deserialized, WarrantyCategory::Electronics);
-    }
+    let request: CreateWarrantyRequest = serde_json::from_str(json).unwrap();
+    assert_eq!(request.product_name, "MacBook Pro");
+    assert_eq!(request.brand, Some("Apple".to_string()));
+    assert_eq!(request.category, WarrantyCategory::Electronics);
+    assert_eq!(request.warranty_months, Some(36));
+}
 
-    #[test]
-    fn test_create_warranty_request_serialization() {
-        let request = CreateWarrantyRequest {
-            product_name: "iPhone 15".to_string(),
-            brand: Some("Apple".to_string()),
-            category: WarrantyCategory::Electronics,
-            purchase_date: Utc::now(),
-            warranty_months: Some(24),
-            store: Some("Apple Store".to_string()),
-            notes: None,
-        };
-
-        let json = serde_json::to_string(&request).unwrap();
-        assert!(json.contains("iPhone 15"));
-        assert!(json.contains("Apple"));
-        assert!(json.contains("electronics"));
-    }
+#[test]
+fn test_create_warranty_request_minimal() {
+    let json = r#"{
+        "product_name": "Test Product",
+        "category": "other",
+        "purchase_date": "2024-01-15T10:00:00Z"
+    }"#;
 
-    #[test]
-    fn test_create_warranty_request_deserialization() {
-        let json = r#"{
-            "product_name": "MacBook Pro",
-            "brand": "Apple",
-            "category": "electronics",
-            "purchase_date": "2024-01-15T10:00:00Z",
-            "warranty_months": 36,
-            "store": "Apple Store"
-        }"#;
-
-        let request: CreateWarrantyRequest = serde_json::from_str(json).unwrap();
-        assert_eq!(request.product_name, "MacBook Pro");
-        assert_eq!(request.brand, Some("Apple".to_string()));
-        assert_eq!(request.category, WarrantyCategory::Electronics);
-        assert_eq!(request.warranty_months, Some(36));
-    }
+    let request: CreateWarrantyRequest = serde_json::from_str(json).unwrap();
+    assert_eq!(request.product_name, "Test Product");
+    assert_eq!(request.brand, None);
+    assert_eq!(request.warranty_months, None);
+    assert_eq!(request.store, None);
+    assert_eq!(request.notes, None);
+    assert_eq!(request.condition, ItemCondition::New);
+    assert_eq!(request.purchase_channel, PurchaseChannel::Store);
+}
 
-    #[test]
-    fn test_create_warranty_request_minimal() {
-        let json = r#"{
-            "product_name": "Test Product",
-            "category": "other",
-            "purchase_date": "2024-01-15T10:00:00Z"
-        }"#;
-
-        let request: CreateWarrantyRequest = serde_json::from_str(json).unwrap();
-        assert_eq!(request.product_name, "Test Product");
-        assert_eq!(request.brand, None);
-        assert_eq!(request.warranty_months, None);
-        assert_eq!(request.store, None);
-        assert_eq!(request.notes, None);
-    }
+#[test]
+fn test_update_warranty_request_partial() {
+    let json = r#"{"product_name": "Updated Name"}"#;
 
-    #[test]
-    fn test_update_warranty_request_partial() {
-        let json = r#"{"product_name": "Updated Name"}"#;
+    let request: UpdateWarrantyRequest = serde_json::from_str(json).unwrap();
+    assert_eq!(request.product_name, Some("Updated Name".to_string()));
+    assert_eq!(request.brand, None);
+    assert_eq!(request.category, None);
+    assert_eq!(request.reminder_lead_days, None);
+}
 
-        let request: UpdateWarrantyRequest = serde_json::from_str(json).unwrap();
-        assert_eq!(request.product_name, Some("Updated Name".to_string()));
-        assert_eq!(request.brand, None);
-        assert_eq!(request.category, None);
-    }
+#[test]
+fn test_update_warranty_request_reminder_override() {
+    let request: UpdateWarrantyRequest =
+        serde_json::from_str(r#"{"reminder_lead_days": [14, 3]}"#).unwrap();
+    assert_eq!(request.reminder_lead_days, Some(Some(vec![14, 3])));
 
-    #[test]
-    fn test_warranty_filters_default() {
-        let filters = WarrantyFilters::default();
-        assert_eq!(filters.category, None);
-        assert_eq!(filters.status, None);
-        assert_eq!(filters.page, None);
-        assert_eq!(filters.per_page, None);
-    }
+    // `null` clears the override, which a missing field leaves alone.
+    let request: UpdateWarrantyRequest =
+        serde_json::from_str(r#"{"reminder_lead_days": null}"#).unwrap();
+    assert_eq!(request.reminder_lead_days, Some(None));
+}
+
+#[test]
+fn test_warranty_filters_default() {
+    let filters = WarrantyFilters::default();
+    assert_eq!(filters.category, None);
+    assert_eq!(filters.status, None);
+    assert_eq!(filters.page, None);
+    assert_eq!(filters.per_page, None);
+}
+
+#[test]
+fn test_warranty_end_date_calculation() {
+    let purchase_date = Utc::now();
+    let warranty_months = 24;
+    let expected_end = purchase_date + Duration::days(warranty_months as i64 * 30);
+
+    let days_diff = (expected_end - purchase_date).num_days();
+    assert_eq!(days_diff, 720);
+}
+
+#[test]
+fn test_all_categories_exist() {
+    let categories = [
+        WarrantyCategory::Electronics,
+        WarrantyCategory::Appliances,
+        WarrantyCategory::Furniture,
+        WarrantyCategory::Clothing,
+        WarrantyCategory::Automotive,
+        WarrantyCategory::Sports,
+        WarrantyCategory::Other,
+    ];
+
+    assert_eq!(categories.len(), 7);
+}
+
+#[test]
+fn test_attachment_kind_parsing() {
+    assert_eq!(
+        "product_photo".parse::<AttachmentKind>(),
+        Ok(AttachmentKind::ProductPhoto)
+    );
+    assert_eq!(
+        "serial_plate".parse::<AttachmentKind>(),
+        Ok(AttachmentKind::SerialPlate)
+    );
+    assert!("photo".parse::<AttachmentKind>().is_err());
+}
 
-    #[test]
-    fn test_warranty_end_date_calculation() {
-        let purchase_date = Utc::now();
-        let warranty_months = 24;
-        let expected_end = purchase_date + Duration::days(warranty_months as i64 * 30);
+#[test]
+fn test_attachment_kind_serialization() {
+    let json = serde_json::to_string(&AttachmentKind::WarrantyCard).unwrap();
+    assert_eq!(json, "\"warranty_card\"");
 
-        let days_diff = (expected_end - purchase_date).num_days();
-        assert_eq!(days_diff, 720);
+    let kind: AttachmentKind = serde_json::from_str("\"manual\"").unwrap();
+    assert_eq!(kind, AttachmentKind::Manual);
+}
+
+#[test]
+fn test_update_reminder_preferences_request() {
+    let request: UpdateReminderPreferencesRequest = serde_json::from_str(
+        r#"{
+            "channels": ["push"],
+            "quiet_hours": {"start": "22:00", "end": "07:30"},
+            "mode": "digest",
+            "digest_frequency": "weekly"
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(request.lead_days, None);
+    assert_eq!(request.channels, Some(vec![NotificationChannel::Push]));
+    assert_eq!(
+        request.quiet_hours,
+        Some(Some(QuietHours {
+            start: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(7, 30, 0).unwrap(),
+        }))
+    );
+    assert_eq!(request.mode, Some(ReminderMode::Digest));
+    assert_eq!(
+        request.digest_frequency,
+        Some(Some(DigestFrequency::Weekly))
+    );
+
+    let request: UpdateReminderPreferencesRequest =
+        serde_json::from_str(r#"{"quiet_hours": null, "digest_frequency": null}"#).unwrap();
+    assert_eq!(request.quiet_hours, Some(None));
+    assert_eq!(request.digest_frequency, Some(None));
+
+    assert!(serde_json::from_str::<UpdateReminderPreferencesRequest>(
+        r#"{"quiet_hours": {"start": "10pm", "end": "07:00"}}"#
+    )
+    .is_err());
+}
+
+#[test]
+fn test_reminder_preferences_serialization() {
+    let mut preferences = ReminderPreferences::defaults(&[30, 7]);
+    preferences.quiet_hours_start = NaiveTime::from_hms_opt(22, 0, 0);
+    preferences.quiet_hours_end = NaiveTime::from_hms_opt(7, 0, 0);
+
+    let json = serde_json::to_value(&preferences).unwrap();
+    assert_eq!(json["lead_days"], serde_json::json!([30, 7]));
+    assert_eq!(
+        json["channels"],
+        serde_json::json!(["email", "push", "webhook"])
+    );
+    assert_eq!(json["quiet_hours_start"], "22:00");
+    assert_eq!(json["quiet_hours_end"], "07:00");
+    assert_eq!(json["timezone"], "Europe/Paris");
+    assert_eq!(json["mode"], "individual");
+    assert_eq!(json["digest_frequency"], serde_json::Value::Null);
+}
+
+fn claim(status: ClaimStatus) -> Claim {
+    let created_at = Utc::now() - Duration::days(10);
+    Claim {
+        id: Uuid::new_v4(),
+        warranty_id: Uuid::new_v4(),
+        status,
+        description: "Screen flickers".to_string(),
+        rma_number: None,
+        contacted_at: None,
+        dropped_off_at: None,
+        returned_at: None,
+        extension_months: 0,
+        created_at,
+        updated_at: created_at,
     }
+}
+
+#[test]
+fn test_claim_status_moves_forward_only() {
+    use ClaimStatus::*;
+
+    assert!(Opened.can_move_to(Shipped));
+    assert!(Opened.can_move_to(Resolved));
+    assert!(Shipped.can_move_to(InRepair));
+    assert!(InRepair.can_move_to(Replaced));
+
+    assert!(!Opened.can_move_to(Opened));
+    assert!(!InRepair.can_move_to(Shipped));
+    assert!(!Resolved.can_move_to(Refused));
+    assert!(Refused.is_closed());
+    assert!(!InRepair.is_closed());
+}
 
-    #[test]
-    fn test_all_categories_exist() {
-        let categories = vec![
-            WarrantyCategory::Electronics,
-            WarrantyCategory::Appliances,
-            WarrantyCategory::Furniture,
-            WarrantyCategory::Clothing,
-            WarrantyCategory::Automotive,
-            WarrantyCategory::Sports,
-            WarrantyCategory::Other,
-        ];
-
-        assert_eq!(categories.len(), 7);
+#[test]
+fn test_claim_status_serialization() {
+    let json = serde_json::to_string(&ClaimStatus::InRepair).unwrap();
+    assert_eq!(json, "\"in_repair\"");
+    assert_eq!(ClaimStatus::InRepair.as_str(), "in_repair");
+}
+
+#[test]
+fn test_claim_stamp_fills_missing_dates() {
+    let now = Utc::now();
+    let mut claim = claim(ClaimStatus::Opened);
+
+    claim.stamp(ClaimStatus::Shipped, now - Duration::days(5));
+    claim.stamp(ClaimStatus::InRepair, now - Duration::days(3));
+    assert_eq!(claim.dropped_off_at, Some(now - Duration::days(5)));
+    assert_eq!(claim.returned_at, None);
+
+    claim.stamp(ClaimStatus::Resolved, now);
+    assert_eq!(claim.returned_at, Some(now));
+}
+
+#[test]
+fn test_claim_stamp_keeps_entered_dates() {
+    let now = Utc::now();
+    let mut claim = claim(ClaimStatus::InRepair);
+    claim.returned_at = Some(now - Duration::days(1));
+
+    claim.stamp(ClaimStatus::Replaced, now);
+    assert_eq!(claim.returned_at, Some(now - Duration::days(1)));
+    assert_eq!(claim.dropped_off_at, None);
+}
+
+#[test]
+fn test_claim_requested_at_defaults_to_creation() {
+    let mut claim = claim(ClaimStatus::Opened);
+    assert_eq!(claim.requested_at(), claim.created_at);
+
+    let contacted_at = claim.created_at - Duration::days(2);
+    claim.contacted_at = Some(contacted_at);
+    assert_eq!(claim.requested_at(), contacted_at);
+}
+
+#[test]
+fn test_sync_change_deserialization() {
+    let change: SyncChange = serde_json::from_str(
+        r#"{
+            "op": "upsert",
+            "id": "6f1c1a0e-8d4b-4c1e-9a57-3f5b2c7d9e01",
+            "base_updated_at": null,
+            "warranty": {
+                "product_name": "Lave-linge",
+                "category": "appliances",
+                "purchase_date": "2026-03-01T12:00:00Z"
+            }
+        }"#,
+    )
+    .unwrap();
+    match change {
+        SyncChange::Upsert {
+            base_updated_at,
+            warranty,
+            ..
+        } => {
+            assert_eq!(base_updated_at, None);
+            assert_eq!(warranty.product_name, "Lave-linge");
+            assert_eq!(warranty.warranty_months, None);
+        }
+        SyncChange::Delete { .. } => panic!("expected an upsert"),
     }
+
+    let change: SyncChange = serde_json::from_str(
+        r#"{"op": "delete", "id": "6f1c1a0e-8d4b-4c1e-9a57-3f5b2c7d9e01", "base_updated_at": "2026-03-01T12:00:00Z"}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        change.id(),
+        Uuid::parse_str("6f1c1a0e-8d4b-4c1e-9a57-3f5b2c7d9e01").unwrap()
+    );
+
+    // A delete must say which version it deletes.
+    assert!(serde_json::from_str::<SyncChange>(
+        r#"{"op": "delete", "id": "6f1c1a0e-8d4b-4c1e-9a57-3f5b2c7d9e01"}"#
+    )
+    .is_err());
 }
@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::models::Warranty;
+
+/// A warranty in the trash.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TrashedWarranty {
+    #[serde(flatten)]
+    pub warranty: Warranty,
+    /// When it is deleted for good, with its receipt and attachments
+    pub purge_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TrashListResponse {
+    /// Most recently deleted first
+    pub warranties: Vec<TrashedWarranty>,
+    /// Days a warranty stays in the trash
+    pub retention_days: i64,
+}
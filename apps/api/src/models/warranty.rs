@@ -1,9 +1,13 @@
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::models::{double_option, ItemCondition, PurchaseChannel};
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq, ToSchema)]
 #[sqlx(type_name = "warranty_category", rename_all = "snake_case")]
 #[serde(rename_all = "snake_case")]
@@ -18,18 +22,6 @@ pub enum WarrantyCategory {
 }
 
 impl WarrantyCategory {
-    pub fn default_warranty_months(&self) -> i32 {
-        match self {
-            WarrantyCategory::Electronics => 24,
-            WarrantyCategory::Appliances => 24,
-            WarrantyCategory::Furniture => 24,
-            WarrantyCategory::Clothing => 6,
-            WarrantyCategory::Automotive => 24,
-            WarrantyCategory::Sports => 12,
-            WarrantyCategory::Other => 24,
-        }
-    }
-
     pub fn display_name_fr(&self) -> &'static str {
         match self {
             WarrantyCategory::Electronics => "Électronique",
@@ -41,34 +33,117 @@ impl WarrantyCategory {
             WarrantyCategory::Other => "Autre",
         }
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WarrantyCategory::Electronics => "electronics",
+            WarrantyCategory::Appliances => "appliances",
+            WarrantyCategory::Furniture => "furniture",
+            WarrantyCategory::Clothing => "clothing",
+            WarrantyCategory::Automotive => "automotive",
+            WarrantyCategory::Sports => "sports",
+            WarrantyCategory::Other => "other",
+        }
+    }
+}
+
+impl FromStr for WarrantyCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "electronics" => Ok(WarrantyCategory::Electronics),
+            "appliances" => Ok(WarrantyCategory::Appliances),
+            "furniture" => Ok(WarrantyCategory::Furniture),
+            "clothing" => Ok(WarrantyCategory::Clothing),
+            "automotive" => Ok(WarrantyCategory::Automotive),
+            "sports" => Ok(WarrantyCategory::Sports),
+            "other" => Ok(WarrantyCategory::Other),
+            _ => Err(format!("Unknown category: {}", s)),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Warranty {
     pub id: Uuid,
+    /// User who created it, and gets its reminders and webhook events
     pub user_id: String,
+    /// Household it belongs to; its members can see it
+    pub household_id: Uuid,
     pub product_name: String,
     pub brand: Option<String>,
     pub category: WarrantyCategory,
     pub purchase_date: DateTime<Utc>,
     pub warranty_end_date: DateTime<Utc>,
     pub warranty_months: i32,
+    /// Months added by repairs under the legal guarantee, on top of
+    /// `warranty_months`
+    pub repair_extension_months: i32,
+    /// Latest end date across this warranty and its coverages
+    pub covered_until: DateTime<Utc>,
     pub store: Option<String>,
+    /// Price paid, in euro cents
+    pub price_cents: Option<i64>,
+    pub invoice_number: Option<String>,
     pub receipt_url: Option<String>,
+    #[serde(skip)]
+    pub receipt_key: Option<String>,
+    pub receipt_content_type: Option<String>,
+    pub receipt_thumbnail_url: Option<String>,
+    #[serde(skip)]
+    pub receipt_thumbnail_key: Option<String>,
     pub notes: Option<String>,
+    /// Replaces the user's reminder lead days for this warranty; empty mutes it
+    pub reminder_lead_days: Option<Vec<i32>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// In the trash since, until restored or purged
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+/// Stored renditions of a receipt: the normalized upload and, for images, a
+/// small JPEG thumbnail.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptVariant {
+    #[default]
+    Original,
+    Thumbnail,
+}
+
+impl ReceiptVariant {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReceiptVariant::Original => "original",
+            ReceiptVariant::Thumbnail => "thumbnail",
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateWarrantyRequest {
+    /// Defaults to the user's own household
+    pub household_id: Option<Uuid>,
     pub product_name: String,
     pub brand: Option<String>,
     pub category: WarrantyCategory,
     pub purchase_date: DateTime<Utc>,
+    /// Defaults to the legal guarantee of the purchase
     pub warranty_months: Option<i32>,
+    /// With `purchase_channel`, picks the legal guarantee when
+    /// `warranty_months` is omitted; not stored
+    #[serde(default)]
+    pub condition: ItemCondition,
+    #[serde(default)]
+    pub purchase_channel: PurchaseChannel,
     pub store: Option<String>,
+    /// Price paid, in euro cents
+    pub price_cents: Option<i64>,
+    pub invoice_number: Option<String>,
     pub notes: Option<String>,
+    /// Days before the end to send reminders, instead of the user's own
+    pub reminder_lead_days: Option<Vec<i32>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -79,22 +154,77 @@ pub struct UpdateWarrantyRequest {
     pub purchase_date: Option<DateTime<Utc>>,
     pub warranty_months: Option<i32>,
     pub store: Option<String>,
+    /// Price paid, in euro cents
+    pub price_cents: Option<i64>,
+    pub invoice_number: Option<String>,
     pub notes: Option<String>,
+    /// `null` goes back to the user's reminder preferences
+    #[serde(
+        default,
+        deserialize_with = "double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[schema(value_type = Option<Vec<i32>>)]
+    pub reminder_lead_days: Option<Option<Vec<i32>>>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
 pub struct WarrantyListResponse {
     pub warranties: Vec<Warranty>,
-    pub total: i64,
-    pub page: i64,
+    /// Left out when paging by cursor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+    /// Left out when paging by cursor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<i64>,
     pub per_page: i64,
-    pub total_pages: i64,
+    /// Left out when paging by cursor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_pages: Option<i64>,
+    /// Pass as `cursor` to get the next page; missing on the last one
+    pub next_cursor: Option<String>,
+    /// With `q`, how each warranty of the page matched, in the same order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub matches: Vec<SearchMatch>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SearchMatch {
+    pub warranty_id: Uuid,
+    /// Higher ranks first
+    pub rank: f32,
+    /// HTML excerpt with the matched words in `<mark>`
+    pub snippet: String,
 }
 
 #[derive(Debug, Deserialize, Default, ToSchema)]
 pub struct WarrantyFilters {
-    pub category: Option<WarrantyCategory>,
+    /// Full-text search over product name, brand, store, notes and receipt
+    /// text; results come ranked
+    pub q: Option<String>,
+    /// One category, or several separated by commas
+    pub category: Option<String>,
     pub status: Option<String>,
+    pub brand: Option<String>,
+    pub store: Option<String>,
+    pub purchased_from: Option<DateTime<Utc>>,
+    pub purchased_to: Option<DateTime<Utc>>,
+    /// Bounds on `covered_until`
+    pub expires_from: Option<DateTime<Utc>>,
+    pub expires_to: Option<DateTime<Utc>>,
+    pub min_price_cents: Option<i64>,
+    pub max_price_cents: Option<i64>,
+    pub has_receipt: Option<bool>,
+    /// Only the warranties of this household
+    pub household_id: Option<Uuid>,
+    /// `next_cursor` of the previous page, instead of `page`; the filters
+    /// must be the same as for that page
+    pub cursor: Option<String>,
+    /// `end_date` (default), `purchase_date`, `category`, `product_name`,
+    /// `price`, `created_at`, or `relevance` (default with `q`)
+    pub sort: Option<String>,
+    /// `asc` or `desc`; defaults to `desc` for relevance, `asc` otherwise
+    pub order: Option<String>,
     pub page: Option<i64>,
     pub per_page: Option<i64>,
 }
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, ToSchema)]
+#[sqlx(type_name = "warranty_action", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum WarrantyAction {
+    Created,
+    Updated,
+    ReceiptUploaded,
+    /// Moved to the trash
+    Trashed,
+    /// Restored from the trash
+    Restored,
+}
+
+/// One field of a warranty as it was before and after a change.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct FieldChange {
+    pub field: String,
+    #[schema(value_type = Object)]
+    pub old: serde_json::Value,
+    #[schema(value_type = Object)]
+    pub new: serde_json::Value,
+}
+
+/// An entry of a warranty's change history.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct WarrantyEvent {
+    pub id: Uuid,
+    pub warranty_id: Uuid,
+    pub action: WarrantyAction,
+    /// Fields the action changed; all fields set, for a creation
+    #[schema(value_type = Vec<FieldChange>)]
+    pub changes: Json<Vec<FieldChange>>,
+    /// User who made the change
+    pub actor_user_id: String,
+    /// `X-Request-Id` of the request that made the change
+    pub request_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
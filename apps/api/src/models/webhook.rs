@@ -0,0 +1,142 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::{double_option, DeliveryStatus};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq, ToSchema)]
+#[sqlx(type_name = "webhook_event")]
+pub enum WebhookEvent {
+    #[sqlx(rename = "warranty.created")]
+    #[serde(rename = "warranty.created")]
+    Created,
+    #[sqlx(rename = "warranty.updated")]
+    #[serde(rename = "warranty.updated")]
+    Updated,
+    #[sqlx(rename = "warranty.deleted")]
+    #[serde(rename = "warranty.deleted")]
+    Deleted,
+    /// Taken back out of the trash
+    #[sqlx(rename = "warranty.restored")]
+    #[serde(rename = "warranty.restored")]
+    Restored,
+    /// A reminder threshold was crossed
+    #[sqlx(rename = "warranty.expiring")]
+    #[serde(rename = "warranty.expiring")]
+    Expiring,
+    #[sqlx(rename = "warranty.expired")]
+    #[serde(rename = "warranty.expired")]
+    Expired,
+    /// A maintenance task is coming due
+    #[sqlx(rename = "maintenance.due")]
+    #[serde(rename = "maintenance.due")]
+    MaintenanceDue,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 7] = [
+        WebhookEvent::Created,
+        WebhookEvent::Updated,
+        WebhookEvent::Deleted,
+        WebhookEvent::Restored,
+        WebhookEvent::Expiring,
+        WebhookEvent::Expired,
+        WebhookEvent::MaintenanceDue,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::Created => "warranty.created",
+            WebhookEvent::Updated => "warranty.updated",
+            WebhookEvent::Deleted => "warranty.deleted",
+            WebhookEvent::Restored => "warranty.restored",
+            WebhookEvent::Expiring => "warranty.expiring",
+            WebhookEvent::Expired => "warranty.expired",
+            WebhookEvent::MaintenanceDue => "maintenance.due",
+        }
+    }
+}
+
+/// An endpoint a user registered to receive warranty events.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub url: String,
+    pub description: Option<String>,
+    pub events: Vec<WebhookEvent>,
+    /// Inactive webhooks get no new events, and their queued deliveries wait
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A newly created webhook. The secret is only ever shown here.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    /// Key of the HMAC-SHA256 in the `Garry-Signature` header
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub description: Option<String>,
+    /// Events to send; every event when left out
+    pub events: Option<Vec<WebhookEvent>>,
+}
+
+/// Fields left out keep their current value.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    /// `null` removes the description
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<String>)]
+    pub description: Option<Option<String>>,
+    pub events: Option<Vec<WebhookEvent>>,
+    pub active: Option<bool>,
+}
+
+/// One event sent, or to be sent, to a webhook, with the outcome of its
+/// last attempt.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    /// Shared by the deliveries of one event, redeliveries included
+    pub event_id: Uuid,
+    pub event: WebhookEvent,
+    /// The JSON body posted to the webhook
+    #[schema(value_type = Object)]
+    pub payload: Json<serde_json::Value>,
+    /// The delivery this one repeats
+    pub redelivery_of: Option<Uuid>,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    /// HTTP status of the last response
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub duration_ms: Option<i32>,
+    /// When the next attempt is due, while pending
+    pub run_after: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+/// A claimed delivery with its webhook's address and secret.
+#[derive(Debug, Clone, FromRow)]
+pub struct PendingWebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub url: String,
+    pub secret: String,
+    pub event_id: Uuid,
+    pub event: WebhookEvent,
+    pub payload: Json<serde_json::Value>,
+    pub attempts: i32,
+}
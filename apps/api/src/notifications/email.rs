@@ -0,0 +1,146 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use lettre::message::header::{ContentType, HeaderName, HeaderValue};
+use lettre::message::{Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Address, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use crate::config::{SmtpConfig, SmtpTls};
+use crate::models::{NotificationChannel, NotificationKind, PendingDelivery};
+use crate::notifications::templates::{
+    digest_email, expiry_reminder_email, household_invitation_email, maintenance_reminder_email,
+    unsubscribe_url, Digest, ExpiryReminder, HouseholdInvitation, Locale, MaintenanceReminder,
+};
+use crate::notifications::{DeliveryError, Sender};
+
+/// Sends reminders and digests by email through an SMTP relay.
+pub struct SmtpSender {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    /// For recipients who never picked a language
+    default_locale: Locale,
+    web_app_url: String,
+    public_api_url: String,
+}
+
+impl SmtpSender {
+    pub fn new(
+        config: &SmtpConfig,
+        default_locale: Locale,
+        web_app_url: &str,
+        public_api_url: &str,
+    ) -> Result<Self, String> {
+        let mut builder = match config.tls {
+            SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+            SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+                .map_err(|e| e.to_string())?,
+            SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)
+                .map_err(|e| e.to_string())?,
+        }
+        .port(config.port);
+        if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: config
+                .from
+                .parse()
+                .map_err(|e| format!("invalid SMTP_FROM: {}", e))?,
+            default_locale,
+            web_app_url: web_app_url.to_string(),
+            public_api_url: public_api_url.to_string(),
+        })
+    }
+
+    fn message(&self, delivery: &PendingDelivery) -> Result<Message, DeliveryError> {
+        let address: Address = delivery
+            .recipient_email
+            .as_deref()
+            .ok_or_else(|| DeliveryError::Permanent("recipient has no email address".to_string()))?
+            .parse()
+            .map_err(|e| DeliveryError::Permanent(format!("invalid recipient address: {}", e)))?;
+        let to = Mailbox::new(delivery.recipient_name.clone(), address);
+
+        let locale = Locale::for_recipient(delivery, self.default_locale);
+        let email = match delivery.kind {
+            NotificationKind::ExpiryReminder => {
+                ExpiryReminder::new(delivery, Utc::now(), &self.web_app_url)
+                    .map(|reminder| expiry_reminder_email(locale, &reminder))
+            }
+            NotificationKind::Digest => {
+                Digest::new(delivery, &self.web_app_url).map(|mut digest| {
+                    digest.unsubscribe_url = delivery
+                        .digest_token
+                        .map(|token| unsubscribe_url(&self.public_api_url, token));
+                    digest_email(locale, &digest)
+                })
+            }
+            NotificationKind::MaintenanceDue => {
+                MaintenanceReminder::new(delivery, Utc::now(), &self.web_app_url)
+                    .map(|reminder| maintenance_reminder_email(locale, &reminder))
+            }
+            NotificationKind::HouseholdInvitation => {
+                HouseholdInvitation::new(delivery, &self.web_app_url)
+                    .map(|invitation| household_invitation_email(locale, &invitation))
+            }
+            // Expiries only go out as webhook events.
+            NotificationKind::Expired => None,
+        }
+        .ok_or_else(|| DeliveryError::Permanent("notification has nothing to say".to_string()))?;
+
+        let mut builder = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(email.subject);
+        if let Some(url) = email.unsubscribe_url {
+            builder = builder
+                .raw_header(HeaderValue::new(
+                    HeaderName::new_from_ascii_str("List-Unsubscribe"),
+                    format!("<{}>", url),
+                ))
+                .raw_header(HeaderValue::new(
+                    HeaderName::new_from_ascii_str("List-Unsubscribe-Post"),
+                    "List-Unsubscribe=One-Click".to_string(),
+                ));
+        }
+
+        builder
+            .multipart(
+                MultiPart::alternative()
+                    .singlepart(
+                        SinglePart::builder()
+                            .header(ContentType::TEXT_PLAIN)
+                            .body(email.text),
+                    )
+                    .singlepart(
+                        SinglePart::builder()
+                            .header(ContentType::TEXT_HTML)
+                            .body(email.html),
+                    ),
+            )
+            .map_err(|e| DeliveryError::Permanent(format!("could not build email: {}", e)))
+    }
+}
+
+#[async_trait]
+impl Sender for SmtpSender {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Email
+    }
+
+    async fn send(&self, delivery: &PendingDelivery) -> Result<(), DeliveryError> {
+        let message = self.message(delivery)?;
+        self.transport.send(message).await.map_err(|e| {
+            // 5xx replies will not change on a retry; connection problems
+            // and 4xx replies may.
+            if e.is_permanent() {
+                DeliveryError::Permanent(e.to_string())
+            } else {
+                DeliveryError::Retry(e.to_string())
+            }
+        })?;
+        Ok(())
+    }
+}
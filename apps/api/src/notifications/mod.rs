@@ -0,0 +1,126 @@
+mod email;
+mod push;
+pub mod templates;
+#[cfg(test)]
+mod tests;
+
+pub use email::SmtpSender;
+pub use push::{validate_endpoint, validate_subscription_keys, VapidKey, WebPushSender};
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::config::Config;
+use crate::db;
+use crate::models::{NotificationChannel, PendingDelivery};
+use templates::Locale;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const MAX_ATTEMPTS: i32 = 5;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeliveryError {
+    /// Worth trying again later: the server was unreachable or busy.
+    #[error("{0}")]
+    Retry(String),
+    /// Will fail the same way every time, e.g. a rejected address.
+    #[error("{0}")]
+    Permanent(String),
+}
+
+/// Delivers notifications on one channel.
+#[async_trait]
+pub trait Sender: Send + Sync {
+    fn channel(&self) -> NotificationChannel;
+    async fn send(&self, delivery: &PendingDelivery) -> Result<(), DeliveryError>;
+}
+
+/// The senders for every channel configured in the environment.
+pub fn senders_from_config(config: &Config, pool: &PgPool) -> Vec<Arc<dyn Sender>> {
+    let locale = Locale::parse(&config.default_locale);
+    let mut senders: Vec<Arc<dyn Sender>> = Vec::new();
+
+    if let Some(smtp) = &config.smtp {
+        let sender = SmtpSender::new(smtp, locale, &config.web_app_url, &config.public_api_url)
+            .unwrap_or_else(|e| panic!("invalid SMTP configuration: {}", e));
+        senders.push(Arc::new(sender));
+    }
+    if let Some(vapid) = &config.vapid {
+        let sender = WebPushSender::new(
+            pool.clone(),
+            vapid,
+            locale,
+            &config.web_app_url,
+            !config.is_production(),
+        )
+        .unwrap_or_else(|e| panic!("invalid Web Push configuration: {}", e));
+        senders.push(Arc::new(sender));
+    }
+
+    senders
+}
+
+/// Starts the task that drains the `notification_deliveries` queue. Every
+/// replica can run one: deliveries are claimed with `SKIP LOCKED`.
+pub fn spawn_dispatcher(
+    pool: PgPool,
+    senders: Vec<Arc<dyn Sender>>,
+) -> tokio::task::JoinHandle<()> {
+    let channels: Vec<NotificationChannel> = senders.iter().map(|s| s.channel()).collect();
+
+    tokio::spawn(async move {
+        if channels.is_empty() {
+            return;
+        }
+        loop {
+            match db::claim_delivery(&pool, &channels).await {
+                Ok(Some(delivery)) => {
+                    let sender = senders
+                        .iter()
+                        .find(|s| s.channel() == delivery.channel)
+                        .expect("claimed deliveries use a configured channel");
+                    deliver(&pool, sender.as_ref(), delivery).await;
+                }
+                Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+                Err(e) => {
+                    tracing::warn!(error = %e, "failed to claim notification delivery");
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
+        }
+    })
+}
+
+async fn deliver(pool: &PgPool, sender: &dyn Sender, delivery: PendingDelivery) {
+    let recorded = match sender.send(&delivery).await {
+        Ok(()) => {
+            tracing::info!(delivery_id = %delivery.id, channel = ?delivery.channel, "notification sent");
+            db::complete_delivery(pool, delivery.id).await
+        }
+        Err(DeliveryError::Retry(e)) if delivery.attempts < MAX_ATTEMPTS => {
+            tracing::warn!(
+                delivery_id = %delivery.id,
+                attempt = delivery.attempts,
+                error = %e,
+                "notification delivery failed, will retry"
+            );
+            db::retry_delivery(pool, delivery.id, &e, retry_delay(delivery.attempts)).await
+        }
+        Err(e) => {
+            tracing::warn!(delivery_id = %delivery.id, error = %e, "notification delivery failed");
+            db::fail_delivery(pool, delivery.id, &e.to_string()).await
+        }
+    };
+
+    if let Err(e) = recorded {
+        tracing::error!(delivery_id = %delivery.id, error = %e, "failed to record delivery result");
+    }
+}
+
+/// One minute after the first failure, then doubling: 1, 2, 4 and 8 minutes.
+pub fn retry_delay(attempts: i32) -> chrono::Duration {
+    chrono::Duration::minutes(1 << (attempts - 1).clamp(0, 10))
+}
@@ -0,0 +1,362 @@
+//! Web Push (RFC 8030) to the PWA, signed with VAPID (RFC 8292) and
+//! encrypted with `aes128gcm` (RFC 8291).
+
+use std::time::Duration;
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes128Gcm, Nonce};
+use async_trait::async_trait;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::Utc;
+use hkdf::Hkdf;
+use p256::ecdh::EphemeralSecret;
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use p256::PublicKey;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use reqwest::{header, StatusCode, Url};
+use sha2::Sha256;
+use sqlx::PgPool;
+
+use crate::config::VapidConfig;
+use crate::db;
+use crate::models::{NotificationChannel, NotificationKind, PendingDelivery, PushSubscription};
+use crate::notifications::templates::{
+    digest_push, expiry_reminder_push, maintenance_reminder_push, Digest, ExpiryReminder, Locale,
+    MaintenanceReminder,
+};
+use crate::notifications::{DeliveryError, Sender};
+use crate::outbound;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long a push service holds a reminder for a device that is offline.
+const TTL_SECS: u32 = 24 * 60 * 60;
+
+/// Push services refuse VAPID tokens valid for more than 24 hours.
+const VAPID_TOKEN_HOURS: i64 = 12;
+
+/// Every payload fits in one record of this size.
+const RECORD_SIZE: u32 = 4096;
+
+/// Tag length of AES-GCM, added to every record.
+const TAG_SIZE: usize = 16;
+
+/// The application server key pair that signs every push request.
+pub struct VapidKey {
+    signing_key: SigningKey,
+    public_key: String,
+    subject: String,
+}
+
+impl VapidKey {
+    pub fn new(config: &VapidConfig) -> Result<Self, String> {
+        let private_key = decode_base64url(&config.private_key)
+            .ok_or_else(|| "VAPID_PRIVATE_KEY is not base64url".to_string())?;
+        let signing_key = SigningKey::from_slice(&private_key)
+            .map_err(|_| "VAPID_PRIVATE_KEY is not a P-256 private key".to_string())?;
+        let public_key = URL_SAFE_NO_PAD.encode(
+            signing_key
+                .verifying_key()
+                .to_encoded_point(false)
+                .as_bytes(),
+        );
+
+        Ok(Self {
+            signing_key,
+            public_key,
+            subject: config.subject.clone(),
+        })
+    }
+
+    /// The uncompressed public key in base64url, which browsers take as
+    /// `applicationServerKey`.
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    /// The `Authorization` header for a request to `endpoint`: a JWT for the
+    /// push service's origin, signed with ES256.
+    pub fn authorization(&self, endpoint: &Url) -> String {
+        let header = URL_SAFE_NO_PAD.encode(r#"{"typ":"JWT","alg":"ES256"}"#);
+        let claims = serde_json::json!({
+            "aud": endpoint.origin().ascii_serialization(),
+            "exp": (Utc::now() + chrono::Duration::hours(VAPID_TOKEN_HOURS)).timestamp(),
+            "sub": self.subject,
+        });
+        let signing_input = format!("{}.{}", header, URL_SAFE_NO_PAD.encode(claims.to_string()));
+        let signature: Signature = self.signing_key.sign(signing_input.as_bytes());
+
+        format!(
+            "vapid t={}.{}, k={}",
+            signing_input,
+            URL_SAFE_NO_PAD.encode(signature.to_bytes()),
+            self.public_key
+        )
+    }
+}
+
+pub const MAX_ENDPOINT_LENGTH: usize = 2048;
+
+/// Checks the endpoint of a browser subscription before it is stored. Push
+/// services are only reached over HTTPS and, unless `allow_private`, as in
+/// development, at a public address.
+pub fn validate_endpoint(endpoint: &str, allow_private: bool) -> Result<Url, String> {
+    if endpoint.len() > MAX_ENDPOINT_LENGTH {
+        return Err(format!(
+            "Endpoint must be less than {} characters",
+            MAX_ENDPOINT_LENGTH
+        ));
+    }
+    let url = Url::parse(endpoint)
+        .ok()
+        .filter(|url| url.scheme() == "https" && url.host().is_some())
+        .ok_or_else(|| "Endpoint must be an https URL".to_string())?;
+    if !allow_private && url.host_str().is_some_and(outbound::is_private_host) {
+        return Err("Endpoint must point to a public address".to_string());
+    }
+    Ok(url)
+}
+
+/// Checks the keys of a browser subscription before it is stored.
+pub fn validate_subscription_keys(p256dh: &str, auth: &str) -> Result<(), String> {
+    subscription_keys(p256dh, auth).map(|_| ())
+}
+
+fn subscription_keys(p256dh: &str, auth: &str) -> Result<(PublicKey, Vec<u8>), String> {
+    let public_key = decode_base64url(p256dh)
+        .and_then(|bytes| PublicKey::from_sec1_bytes(&bytes).ok())
+        .ok_or_else(|| "p256dh must be a base64url P-256 public key".to_string())?;
+    let auth = decode_base64url(auth)
+        .filter(|bytes| bytes.len() == 16)
+        .ok_or_else(|| "auth must be a base64url 16-byte secret".to_string())?;
+
+    Ok((public_key, auth))
+}
+
+/// Browsers send unpadded base64url; some libraries pad it.
+fn decode_base64url(value: &str) -> Option<Vec<u8>> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim().trim_end_matches('='))
+        .ok()
+}
+
+/// Encrypts `payload` for one browser as a single `aes128gcm` record, keyed
+/// from a fresh ECDH exchange with its `p256dh` key and its `auth` secret.
+pub fn encrypt(payload: &[u8], p256dh: &str, auth: &str) -> Result<Vec<u8>, String> {
+    // The payload, the delimiter byte and the tag must fit in the record.
+    if payload.len() + 1 + TAG_SIZE > RECORD_SIZE as usize {
+        return Err(format!(
+            "push payload of {} bytes is too large",
+            payload.len()
+        ));
+    }
+    let (browser_key, auth) = subscription_keys(p256dh, auth)?;
+
+    let secret = EphemeralSecret::random(&mut OsRng);
+    let server_key = secret.public_key().to_encoded_point(false);
+    let shared = secret.diffie_hellman(&browser_key);
+
+    let mut key_info = b"WebPush: info\0".to_vec();
+    key_info.extend_from_slice(browser_key.to_encoded_point(false).as_bytes());
+    key_info.extend_from_slice(server_key.as_bytes());
+    let mut ikm = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(&auth), shared.raw_secret_bytes())
+        .expand(&key_info, &mut ikm)
+        .expect("32 bytes is a valid HKDF-SHA256 output");
+
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let prk = Hkdf::<Sha256>::new(Some(&salt), &ikm);
+    let mut key = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    prk.expand(b"Content-Encoding: aes128gcm\0", &mut key)
+        .expect("16 bytes is a valid HKDF-SHA256 output");
+    prk.expand(b"Content-Encoding: nonce\0", &mut nonce)
+        .expect("12 bytes is a valid HKDF-SHA256 output");
+
+    // 0x02 marks the last, and here only, record.
+    let mut plaintext = payload.to_vec();
+    plaintext.push(2);
+    let ciphertext = Aes128Gcm::new(&key.into())
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+        .map_err(|_| "push payload encryption failed".to_string())?;
+
+    let server_key = server_key.as_bytes();
+    let mut body = Vec::with_capacity(21 + server_key.len() + ciphertext.len());
+    body.extend_from_slice(&salt);
+    body.extend_from_slice(&RECORD_SIZE.to_be_bytes());
+    body.push(server_key.len() as u8);
+    body.extend_from_slice(server_key);
+    body.extend_from_slice(&ciphertext);
+    Ok(body)
+}
+
+#[derive(Debug)]
+pub enum PushOutcome {
+    Delivered,
+    /// The browser unsubscribed or the subscription expired (404 or 410).
+    Gone,
+    Failed(DeliveryError),
+}
+
+/// Sends reminders and digests to every browser a user subscribed, and forgets the
+/// browsers the push services no longer know.
+pub struct WebPushSender {
+    pool: PgPool,
+    client: reqwest::Client,
+    vapid: VapidKey,
+    /// For recipients who never picked a language
+    default_locale: Locale,
+    web_app_url: String,
+    allow_private: bool,
+}
+
+impl WebPushSender {
+    /// Unless `allow_private`, as in development, endpoints are only reached
+    /// at public addresses.
+    pub fn new(
+        pool: PgPool,
+        config: &VapidConfig,
+        default_locale: Locale,
+        web_app_url: &str,
+        allow_private: bool,
+    ) -> Result<Self, String> {
+        let client = outbound::restrict(reqwest::Client::builder(), allow_private)
+            .timeout(REQUEST_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            pool,
+            client,
+            vapid: VapidKey::new(config)?,
+            default_locale,
+            web_app_url: web_app_url.to_string(),
+            allow_private,
+        })
+    }
+
+    /// Sends one encrypted message to one browser.
+    pub async fn push(&self, subscription: &PushSubscription, payload: &[u8]) -> PushOutcome {
+        let endpoint = match Url::parse(&subscription.endpoint) {
+            Ok(endpoint) => endpoint,
+            Err(e) => {
+                return PushOutcome::Failed(DeliveryError::Permanent(format!(
+                    "invalid push endpoint: {}",
+                    e
+                )))
+            }
+        };
+        // Checked when saved too; names pointing inside are refused by the
+        // client's resolver.
+        if !self.allow_private && endpoint.host_str().is_some_and(outbound::is_private_host) {
+            return PushOutcome::Failed(DeliveryError::Permanent(
+                "push endpoint is not a public address".to_string(),
+            ));
+        }
+        let body = match encrypt(payload, &subscription.p256dh, &subscription.auth) {
+            Ok(body) => body,
+            Err(e) => return PushOutcome::Failed(DeliveryError::Permanent(e)),
+        };
+
+        let response = self
+            .client
+            .post(endpoint.clone())
+            .header(header::AUTHORIZATION, self.vapid.authorization(&endpoint))
+            .header(header::CONTENT_ENCODING, "aes128gcm")
+            .header(header::CONTENT_TYPE, "application/octet-stream")
+            .header("TTL", TTL_SECS)
+            .header("Urgency", "normal")
+            .body(body)
+            .send()
+            .await;
+
+        match response {
+            Ok(response) if response.status().is_success() => PushOutcome::Delivered,
+            Ok(response)
+                if matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::GONE) =>
+            {
+                PushOutcome::Gone
+            }
+            Ok(response) => {
+                let status = response.status();
+                let error = format!("push service answered {}", status);
+                if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+                    PushOutcome::Failed(DeliveryError::Retry(error))
+                } else {
+                    PushOutcome::Failed(DeliveryError::Permanent(error))
+                }
+            }
+            Err(e) => PushOutcome::Failed(DeliveryError::Retry(e.to_string())),
+        }
+    }
+}
+
+#[async_trait]
+impl Sender for WebPushSender {
+    fn channel(&self) -> NotificationChannel {
+        NotificationChannel::Push
+    }
+
+    async fn send(&self, delivery: &PendingDelivery) -> Result<(), DeliveryError> {
+        let subscriptions = db::list_push_subscriptions(&self.pool, &delivery.user_id)
+            .await
+            .map_err(|e| DeliveryError::Retry(e.to_string()))?;
+
+        // Push has no unsubscribe link: the digest is turned off in the app.
+        let locale = Locale::for_recipient(delivery, self.default_locale);
+        let message = match delivery.kind {
+            NotificationKind::ExpiryReminder => {
+                ExpiryReminder::new(delivery, Utc::now(), &self.web_app_url)
+                    .map(|reminder| expiry_reminder_push(locale, &reminder))
+            }
+            NotificationKind::Digest => {
+                Digest::new(delivery, &self.web_app_url).map(|digest| digest_push(locale, &digest))
+            }
+            NotificationKind::MaintenanceDue => {
+                MaintenanceReminder::new(delivery, Utc::now(), &self.web_app_url)
+                    .map(|reminder| maintenance_reminder_push(locale, &reminder))
+            }
+            // Expiries only go out as webhook events, invitations by email.
+            NotificationKind::Expired | NotificationKind::HouseholdInvitation => None,
+        }
+        .ok_or_else(|| DeliveryError::Permanent("notification has nothing to say".to_string()))?;
+        let payload =
+            serde_json::to_vec(&message).map_err(|e| DeliveryError::Permanent(e.to_string()))?;
+
+        let mut delivered = false;
+        let mut error = None;
+        for subscription in &subscriptions {
+            match self.push(subscription, &payload).await {
+                PushOutcome::Delivered => delivered = true,
+                PushOutcome::Gone => {
+                    tracing::info!(subscription_id = %subscription.id, "pruning expired push subscription");
+                    if let Err(e) = db::prune_push_subscription(&self.pool, subscription.id).await {
+                        tracing::warn!(subscription_id = %subscription.id, error = %e, "failed to prune push subscription");
+                    }
+                }
+                PushOutcome::Failed(e) => {
+                    tracing::warn!(subscription_id = %subscription.id, error = %e, "push failed");
+                    // One browser worth retrying is enough to retry.
+                    if !matches!(error, Some(DeliveryError::Retry(_))) {
+                        error = Some(e);
+                    }
+                }
+            }
+        }
+
+        // Once a browser has shown the reminder a retry would show it again,
+        // so browsers that failed alongside it go without.
+        if delivered {
+            return Ok(());
+        }
+        Err(error.unwrap_or_else(|| {
+            DeliveryError::Permanent("user has no push subscription left".to_string())
+        }))
+    }
+}
@@ -0,0 +1,878 @@
+//! The text of notification messages, in French and English.
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+use crate::models::{DigestFrequency, DigestSummary, PendingDelivery, DEFAULT_TIMEZONE};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    Fr,
+    En,
+}
+
+impl Locale {
+    /// Reads a language tag such as `fr`, `en-GB` or `fr_FR`; anything else
+    /// falls back to French.
+    pub fn parse(tag: &str) -> Self {
+        match tag
+            .get(..2)
+            .map(|lang| lang.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("en") => Locale::En,
+            _ => Locale::Fr,
+        }
+    }
+
+    /// The recipient's language, or `default` if they never picked one.
+    pub fn for_recipient(delivery: &PendingDelivery, default: Locale) -> Self {
+        delivery
+            .recipient_locale
+            .as_deref()
+            .map_or(default, Locale::parse)
+    }
+}
+
+/// The timezone the recipient's dates are shown in.
+fn recipient_timezone(delivery: &PendingDelivery) -> Tz {
+    delivery
+        .recipient_timezone
+        .as_deref()
+        .unwrap_or(DEFAULT_TIMEZONE)
+        .parse()
+        .unwrap_or(Tz::UTC)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmailMessage {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+    /// One-click unsubscribe URL (RFC 8058) for the `List-Unsubscribe` header
+    pub unsubscribe_url: Option<String>,
+}
+
+/// The JSON payload the PWA service worker turns into a notification.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PushMessage {
+    pub title: String,
+    pub body: String,
+    /// Page opened when the notification is clicked
+    pub url: String,
+    /// Replaces an earlier notification with the same tag on the device, so
+    /// a warranty shows one reminder at a time
+    pub tag: String,
+}
+
+/// What an expiry reminder says, whatever the channel.
+#[derive(Debug, Clone)]
+pub struct ExpiryReminder {
+    pub recipient_name: Option<String>,
+    /// Product name, followed by the brand when the name does not mention it
+    pub product: String,
+    pub warranty_end_date: DateTime<Utc>,
+    /// The recipient's, which the end date is shown in
+    pub timezone: Tz,
+    pub days_left: i64,
+    pub link: String,
+}
+
+impl ExpiryReminder {
+    /// `None` unless `delivery` is for a warranty.
+    pub fn new(delivery: &PendingDelivery, now: DateTime<Utc>, web_app_url: &str) -> Option<Self> {
+        let warranty_id = delivery.warranty_id?;
+        let warranty_end_date = delivery.warranty_end_date?;
+        let product = product_label(delivery.product_name.as_deref()?, delivery.brand.as_deref());
+
+        // A warranty ending in 29 days and 2 hours still has 30 days to go.
+        let hours_left = (warranty_end_date - now).num_hours().max(0);
+
+        Some(Self {
+            recipient_name: delivery.recipient_name.clone(),
+            product,
+            warranty_end_date,
+            timezone: recipient_timezone(delivery),
+            days_left: (hours_left + 23) / 24,
+            link: format!(
+                "{}/warranty/{}",
+                web_app_url.trim_end_matches('/'),
+                warranty_id
+            ),
+        })
+    }
+
+    /// "expire dans 30 jours", "expires tomorrow"...
+    pub fn when(&self, locale: Locale) -> String {
+        match (locale, self.days_left) {
+            (Locale::Fr, 0) => "expire aujourd'hui".to_string(),
+            (Locale::Fr, 1) => "expire demain".to_string(),
+            (Locale::Fr, days) => format!("expire dans {} jours", days),
+            (Locale::En, 0) => "expires today".to_string(),
+            (Locale::En, 1) => "expires tomorrow".to_string(),
+            (Locale::En, days) => format!("expires in {} days", days),
+        }
+    }
+
+    pub fn end_date(&self, locale: Locale) -> String {
+        format_date(locale, self.warranty_end_date, self.timezone)
+    }
+}
+
+/// The product name, followed by the brand when the name does not mention it.
+pub fn product_label(name: &str, brand: Option<&str>) -> String {
+    let mut product = name.trim().to_string();
+    if let Some(brand) = brand.map(str::trim) {
+        if !brand.is_empty() && !product.to_lowercase().contains(&brand.to_lowercase()) {
+            product = format!("{} {}", product, brand);
+        }
+    }
+    product
+}
+
+/// `date` as the calendar day it is in `timezone`.
+pub fn format_date(locale: Locale, date: DateTime<Utc>, timezone: Tz) -> String {
+    let date = date.with_timezone(&timezone);
+    match locale {
+        Locale::Fr => date.format("%d/%m/%Y").to_string(),
+        Locale::En => date.format("%B %-d, %Y").to_string(),
+    }
+}
+
+fn lang(locale: Locale) -> &'static str {
+    match locale {
+        Locale::Fr => "fr",
+        Locale::En => "en",
+    }
+}
+
+pub fn expiry_reminder_email(locale: Locale, reminder: &ExpiryReminder) -> EmailMessage {
+    let (subject, greeting, body, action, footer) = match locale {
+        Locale::Fr => (
+            format!(
+                "Votre garantie {} {}",
+                reminder.product,
+                reminder.when(locale)
+            ),
+            match &reminder.recipient_name {
+                Some(name) => format!("Bonjour {},", name),
+                None => "Bonjour,".to_string(),
+            },
+            format!(
+                "La garantie de votre {} {}, le {}. Pensez à vérifier que tout fonctionne \
+                 et à faire jouer la garantie si besoin avant cette date.",
+                reminder.product,
+                reminder.when(locale),
+                reminder.end_date(locale)
+            ),
+            "Voir la garantie",
+            "Vous recevez cet e-mail car les rappels d'expiration sont activés sur Garry.",
+        ),
+        Locale::En => (
+            format!(
+                "Your {} warranty {}",
+                reminder.product,
+                reminder.when(locale)
+            ),
+            match &reminder.recipient_name {
+                Some(name) => format!("Hello {},", name),
+                None => "Hello,".to_string(),
+            },
+            format!(
+                "The warranty on your {} {}, on {}. Check that everything still works \
+                 and make any claim before that date.",
+                reminder.product,
+                reminder.when(locale),
+                reminder.end_date(locale)
+            ),
+            "View warranty",
+            "You are receiving this email because expiry reminders are turned on in Garry.",
+        ),
+    };
+
+    let text = format!(
+        "{}\n\n{}\n\n{}: {}\n\n--\n{}\n",
+        greeting, body, action, reminder.link, footer
+    );
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html lang="{lang}">
+<head><meta charset="utf-8"><title>{subject}</title></head>
+<body style="font-family: sans-serif; color: #1f2937; line-height: 1.5;">
+<p>{greeting}</p>
+<p>{body}</p>
+<p><a href="{link}" style="display: inline-block; padding: 10px 16px; background: #0284c7; color: #ffffff; border-radius: 6px; text-decoration: none;">{action}</a></p>
+<p style="font-size: 12px; color: #6b7280;">{footer}</p>
+</body>
+</html>
+"#,
+        lang = lang(locale),
+        subject = escape_html(&subject),
+        greeting = escape_html(&greeting),
+        body = escape_html(&body),
+        link = escape_html(&reminder.link),
+        action = action,
+        footer = escape_html(footer),
+    );
+
+    EmailMessage {
+        subject,
+        text,
+        html,
+        unsubscribe_url: None,
+    }
+}
+
+pub fn expiry_reminder_push(locale: Locale, reminder: &ExpiryReminder) -> PushMessage {
+    let (title, body) = match locale {
+        Locale::Fr => (
+            "Rappel de garantie",
+            format!(
+                "La garantie de votre {} {}, le {}.",
+                reminder.product,
+                reminder.when(locale),
+                reminder.end_date(locale)
+            ),
+        ),
+        Locale::En => (
+            "Warranty reminder",
+            format!(
+                "The warranty on your {} {}, on {}.",
+                reminder.product,
+                reminder.when(locale),
+                reminder.end_date(locale)
+            ),
+        ),
+    };
+
+    PushMessage {
+        title: title.to_string(),
+        body,
+        url: reminder.link.clone(),
+        tag: reminder.link.clone(),
+    }
+}
+
+/// What a maintenance reminder says, whatever the channel.
+#[derive(Debug, Clone)]
+pub struct MaintenanceReminder {
+    pub recipient_name: Option<String>,
+    /// Product name, followed by the brand when the name does not mention it
+    pub product: String,
+    pub task: String,
+    pub due_at: DateTime<Utc>,
+    /// The recipient's, which the due date is shown in
+    pub timezone: Tz,
+    /// Odometer reading the task is also due at, whichever comes first
+    pub due_km: Option<i32>,
+    /// Negative once the due date has passed
+    pub days_left: i64,
+    pub link: String,
+    /// Keeps one notification per task on a device
+    pub tag: String,
+}
+
+impl MaintenanceReminder {
+    /// `None` unless `delivery` is for a maintenance task.
+    pub fn new(delivery: &PendingDelivery, now: DateTime<Utc>, web_app_url: &str) -> Option<Self> {
+        let warranty_id = delivery.warranty_id?;
+        let task_id = delivery.maintenance_task_id?;
+        let due_at = delivery.maintenance_due_at?;
+        let product = product_label(delivery.product_name.as_deref()?, delivery.brand.as_deref());
+
+        // Due in 6 days and 2 hours is still 7 days to go; due 2 hours ago
+        // is today.
+        let hours_left = (due_at - now).num_hours();
+        let days_left = if hours_left >= 0 {
+            (hours_left + 23) / 24
+        } else {
+            hours_left / 24
+        };
+
+        Some(Self {
+            recipient_name: delivery.recipient_name.clone(),
+            product,
+            task: delivery.maintenance_title.clone()?,
+            due_at,
+            timezone: recipient_timezone(delivery),
+            due_km: delivery.maintenance_due_km,
+            days_left,
+            link: format!(
+                "{}/warranty/{}",
+                web_app_url.trim_end_matches('/'),
+                warranty_id
+            ),
+            tag: format!("maintenance-{}", task_id),
+        })
+    }
+
+    /// "dans 7 jours", "tomorrow", "overdue"...
+    pub fn when(&self, locale: Locale) -> String {
+        match (locale, self.days_left) {
+            (Locale::Fr, days) if days < 0 => "en retard".to_string(),
+            (Locale::Fr, 0) => "aujourd'hui".to_string(),
+            (Locale::Fr, 1) => "demain".to_string(),
+            (Locale::Fr, days) => format!("dans {} jours", days),
+            (Locale::En, days) if days < 0 => "overdue".to_string(),
+            (Locale::En, 0) => "due today".to_string(),
+            (Locale::En, 1) => "due tomorrow".to_string(),
+            (Locale::En, days) => format!("due in {} days", days),
+        }
+    }
+
+    /// "le 28/02/2026 ou à 57 300 km, au premier des deux termes"...
+    pub fn due(&self, locale: Locale) -> String {
+        let date = format_date(locale, self.due_at, self.timezone);
+        match (locale, self.due_km) {
+            (Locale::Fr, Some(km)) => format!(
+                "le {} ou à {} km, au premier des deux termes",
+                date,
+                format_km(locale, km)
+            ),
+            (Locale::Fr, None) => format!("le {}", date),
+            (Locale::En, Some(km)) => format!(
+                "on {} or at {} km, whichever comes first",
+                date,
+                format_km(locale, km)
+            ),
+            (Locale::En, None) => format!("on {}", date),
+        }
+    }
+}
+
+/// "57 300" or "57,300".
+pub fn format_km(locale: Locale, km: i32) -> String {
+    let separator = match locale {
+        Locale::Fr => ' ',
+        Locale::En => ',',
+    };
+    let digits = km.unsigned_abs().to_string();
+    let mut formatted = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(separator);
+        }
+        formatted.push(digit);
+    }
+    if km < 0 {
+        formatted.insert(0, '-');
+    }
+    formatted
+}
+
+pub fn maintenance_reminder_email(locale: Locale, reminder: &MaintenanceReminder) -> EmailMessage {
+    let (subject, greeting, body, action, footer) = match locale {
+        Locale::Fr => (
+            format!(
+                "Entretien de votre {} : {} ({})",
+                reminder.product,
+                reminder.task,
+                reminder.when(locale)
+            ),
+            match &reminder.recipient_name {
+                Some(name) => format!("Bonjour {},", name),
+                None => "Bonjour,".to_string(),
+            },
+            format!(
+                "« {} » est à faire sur votre {} {}. Une fois fait, notez-le dans Garry \
+                 pour planifier le suivant.",
+                reminder.task,
+                reminder.product,
+                reminder.due(locale)
+            ),
+            "Voir l'entretien",
+            "Vous recevez cet e-mail car vous avez planifié cet entretien sur Garry.",
+        ),
+        Locale::En => (
+            format!(
+                "{} on your {} ({})",
+                reminder.task,
+                reminder.product,
+                reminder.when(locale)
+            ),
+            match &reminder.recipient_name {
+                Some(name) => format!("Hello {},", name),
+                None => "Hello,".to_string(),
+            },
+            format!(
+                "\"{}\" is due on your {} {}. Once it is done, log it in Garry to \
+                 schedule the next one.",
+                reminder.task,
+                reminder.product,
+                reminder.due(locale)
+            ),
+            "View maintenance",
+            "You are receiving this email because you scheduled this maintenance in Garry.",
+        ),
+    };
+
+    let text = format!(
+        "{}\n\n{}\n\n{}: {}\n\n--\n{}\n",
+        greeting, body, action, reminder.link, footer
+    );
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html lang="{lang}">
+<head><meta charset="utf-8"><title>{subject}</title></head>
+<body style="font-family: sans-serif; color: #1f2937; line-height: 1.5;">
+<p>{greeting}</p>
+<p>{body}</p>
+<p><a href="{link}" style="display: inline-block; padding: 10px 16px; background: #0284c7; color: #ffffff; border-radius: 6px; text-decoration: none;">{action}</a></p>
+<p style="font-size: 12px; color: #6b7280;">{footer}</p>
+</body>
+</html>
+"#,
+        lang = lang(locale),
+        subject = escape_html(&subject),
+        greeting = escape_html(&greeting),
+        body = escape_html(&body),
+        link = escape_html(&reminder.link),
+        action = action,
+        footer = escape_html(footer),
+    );
+
+    EmailMessage {
+        subject,
+        text,
+        html,
+        unsubscribe_url: None,
+    }
+}
+
+pub fn maintenance_reminder_push(locale: Locale, reminder: &MaintenanceReminder) -> PushMessage {
+    let (title, body) = match locale {
+        Locale::Fr => (
+            "Rappel d'entretien",
+            format!(
+                "{} ({}) : votre {}, {}.",
+                reminder.task,
+                reminder.when(locale),
+                reminder.product,
+                reminder.due(locale)
+            ),
+        ),
+        Locale::En => (
+            "Maintenance reminder",
+            format!(
+                "{} ({}): your {}, {}.",
+                reminder.task,
+                reminder.when(locale),
+                reminder.product,
+                reminder.due(locale)
+            ),
+        ),
+    };
+
+    PushMessage {
+        title: title.to_string(),
+        body,
+        url: reminder.link.clone(),
+        tag: reminder.tag.clone(),
+    }
+}
+
+/// What an invitation to a household says. It only goes out by email: the
+/// invitee may not have an account yet.
+#[derive(Debug, Clone)]
+pub struct HouseholdInvitation {
+    pub household: String,
+    /// Page of the web app that accepts the invitation
+    pub link: String,
+}
+
+impl HouseholdInvitation {
+    /// `None` unless `delivery` is for an invitation.
+    pub fn new(delivery: &PendingDelivery, web_app_url: &str) -> Option<Self> {
+        Some(Self {
+            household: delivery.household_name.clone()?,
+            link: format!(
+                "{}/invitations/{}",
+                web_app_url.trim_end_matches('/'),
+                delivery.invitation_token?
+            ),
+        })
+    }
+}
+
+pub fn household_invitation_email(
+    locale: Locale,
+    invitation: &HouseholdInvitation,
+) -> EmailMessage {
+    let (subject, body, action, footer) = match locale {
+        Locale::Fr => (
+            format!(
+                "Invitation à rejoindre « {} » sur Garry",
+                invitation.household
+            ),
+            format!(
+                "Vous êtes invité à rejoindre le foyer « {} » pour partager ses garanties \
+                 sur Garry. L'invitation est valable 7 jours.",
+                invitation.household
+            ),
+            "Rejoindre le foyer",
+            "Si vous n'attendiez pas cette invitation, ignorez cet e-mail.",
+        ),
+        Locale::En => (
+            format!("Invitation to join \"{}\" on Garry", invitation.household),
+            format!(
+                "You are invited to join the household \"{}\" to share its warranties on \
+                 Garry. The invitation is valid for 7 days.",
+                invitation.household
+            ),
+            "Join the household",
+            "If you were not expecting this invitation, you can ignore this email.",
+        ),
+    };
+    let greeting = match locale {
+        Locale::Fr => "Bonjour,",
+        Locale::En => "Hello,",
+    };
+
+    let text = format!(
+        "{}\n\n{}\n\n{}: {}\n\n--\n{}\n",
+        greeting, body, action, invitation.link, footer
+    );
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html lang="{lang}">
+<head><meta charset="utf-8"><title>{subject}</title></head>
+<body style="font-family: sans-serif; color: #1f2937; line-height: 1.5;">
+<p>{greeting}</p>
+<p>{body}</p>
+<p><a href="{link}" style="display: inline-block; padding: 10px 16px; background: #0284c7; color: #ffffff; border-radius: 6px; text-decoration: none;">{action}</a></p>
+<p style="font-size: 12px; color: #6b7280;">{footer}</p>
+</body>
+</html>
+"#,
+        lang = lang(locale),
+        subject = escape_html(&subject),
+        greeting = greeting,
+        body = escape_html(&body),
+        link = escape_html(&invitation.link),
+        action = action,
+        footer = escape_html(footer),
+    );
+
+    EmailMessage {
+        subject,
+        text,
+        html,
+        unsubscribe_url: None,
+    }
+}
+
+/// What a digest says, whatever the channel.
+#[derive(Debug, Clone)]
+pub struct Digest {
+    pub recipient_name: Option<String>,
+    pub summary: DigestSummary,
+    /// The recipient's, which dates are shown in
+    pub timezone: Tz,
+    /// The web app, where the warranties are listed
+    pub link: String,
+    pub unsubscribe_url: Option<String>,
+}
+
+impl Digest {
+    /// `None` unless `delivery` is for a digest. The unsubscribe link is
+    /// left for channels that can carry one.
+    pub fn new(delivery: &PendingDelivery, web_app_url: &str) -> Option<Self> {
+        let summary = delivery.digest.as_ref()?.0.clone();
+        Some(Self {
+            recipient_name: delivery.recipient_name.clone(),
+            summary,
+            timezone: recipient_timezone(delivery),
+            link: web_app_url.trim_end_matches('/').to_string(),
+            unsubscribe_url: None,
+        })
+    }
+
+    /// One line per number the digest reports.
+    fn lines(&self, locale: Locale) -> Vec<String> {
+        let summary = &self.summary;
+        let since = format_date(locale, summary.since, self.timezone);
+        match locale {
+            Locale::Fr => vec![
+                format!(
+                    "Garanties qui expirent dans les 30 prochains jours : {}",
+                    summary.expiring_within_30_days
+                ),
+                format!(
+                    "Garanties qui expirent dans les 90 prochains jours : {}",
+                    summary.expiring_within_90_days
+                ),
+                format!(
+                    "Garanties expirées depuis le {} : {}",
+                    since, summary.expired_since_last_digest
+                ),
+                format!(
+                    "Garanties actives sans facture : {}",
+                    summary.missing_receipts
+                ),
+            ],
+            Locale::En => vec![
+                format!(
+                    "Warranties expiring in the next 30 days: {}",
+                    summary.expiring_within_30_days
+                ),
+                format!(
+                    "Warranties expiring in the next 90 days: {}",
+                    summary.expiring_within_90_days
+                ),
+                format!(
+                    "Warranties expired since {}: {}",
+                    since, summary.expired_since_last_digest
+                ),
+                format!(
+                    "Active warranties without a receipt: {}",
+                    summary.missing_receipts
+                ),
+            ],
+        }
+    }
+
+    /// "TV Samsung : 22/10/2025" for each warranty listed by name.
+    fn expiring(&self, locale: Locale) -> Vec<String> {
+        let separator = match locale {
+            Locale::Fr => " : ",
+            Locale::En => ": ",
+        };
+        self.summary
+            .expiring
+            .iter()
+            .map(|item| {
+                format!(
+                    "{}{}{}",
+                    product_label(&item.product_name, item.brand.as_deref()),
+                    separator,
+                    format_date(locale, item.warranty_end_date, self.timezone)
+                )
+            })
+            .collect()
+    }
+}
+
+/// Where a digest recipient unsubscribes, by `GET` from the email or by
+/// one-click `POST` from their mail client.
+pub fn unsubscribe_url(public_api_url: &str, token: uuid::Uuid) -> String {
+    format!(
+        "{}/api/v1/digest/unsubscribe/{}",
+        public_api_url.trim_end_matches('/'),
+        token
+    )
+}
+
+pub fn digest_email(locale: Locale, digest: &Digest) -> EmailMessage {
+    let (subject, greeting, intro, expiring_title, action, footer, unsubscribe) = match locale {
+        Locale::Fr => (
+            match digest.summary.frequency {
+                DigestFrequency::Weekly => "Votre récapitulatif hebdomadaire Garry",
+                DigestFrequency::Monthly => "Votre récapitulatif mensuel Garry",
+            },
+            match &digest.recipient_name {
+                Some(name) => format!("Bonjour {},", name),
+                None => "Bonjour,".to_string(),
+            },
+            "Voici où en sont vos garanties.",
+            "Prochaines échéances :",
+            "Voir mes garanties",
+            "Vous recevez ce récapitulatif car vous l'avez activé sur Garry.",
+            "Se désabonner",
+        ),
+        Locale::En => (
+            match digest.summary.frequency {
+                DigestFrequency::Weekly => "Your weekly Garry digest",
+                DigestFrequency::Monthly => "Your monthly Garry digest",
+            },
+            match &digest.recipient_name {
+                Some(name) => format!("Hello {},", name),
+                None => "Hello,".to_string(),
+            },
+            "Here is where your warranties stand.",
+            "Ending soon:",
+            "View my warranties",
+            "You are receiving this digest because you turned it on in Garry.",
+            "Unsubscribe",
+        ),
+    };
+    let lines = digest.lines(locale);
+    let expiring = digest.expiring(locale);
+
+    let mut text = format!("{}\n\n{}\n\n", greeting, intro);
+    for line in &lines {
+        text.push_str(&format!("- {}\n", line));
+    }
+    if !expiring.is_empty() {
+        text.push_str(&format!("\n{}\n", expiring_title));
+        for item in &expiring {
+            text.push_str(&format!("- {}\n", item));
+        }
+    }
+    text.push_str(&format!(
+        "\n{}: {}\n\n--\n{}\n",
+        action, digest.link, footer
+    ));
+    if let Some(url) = &digest.unsubscribe_url {
+        text.push_str(&format!("{}: {}\n", unsubscribe, url));
+    }
+
+    let list = |items: &[String]| {
+        items
+            .iter()
+            .map(|item| format!("<li>{}</li>", escape_html(item)))
+            .collect::<String>()
+    };
+    let expiring_html = if expiring.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "<p>{}</p>\n<ul>{}</ul>\n",
+            escape_html(expiring_title),
+            list(&expiring)
+        )
+    };
+    let unsubscribe_html = match &digest.unsubscribe_url {
+        Some(url) => format!(
+            r#" <a href="{}" style="color: #6b7280;">{}</a>"#,
+            escape_html(url),
+            unsubscribe
+        ),
+        None => String::new(),
+    };
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html lang="{lang}">
+<head><meta charset="utf-8"><title>{subject}</title></head>
+<body style="font-family: sans-serif; color: #1f2937; line-height: 1.5;">
+<p>{greeting}</p>
+<p>{intro}</p>
+<ul>{lines}</ul>
+{expiring}<p><a href="{link}" style="display: inline-block; padding: 10px 16px; background: #0284c7; color: #ffffff; border-radius: 6px; text-decoration: none;">{action}</a></p>
+<p style="font-size: 12px; color: #6b7280;">{footer}{unsubscribe}</p>
+</body>
+</html>
+"#,
+        lang = lang(locale),
+        subject = escape_html(subject),
+        greeting = escape_html(&greeting),
+        intro = escape_html(intro),
+        lines = list(&lines),
+        expiring = expiring_html,
+        link = escape_html(&digest.link),
+        action = action,
+        footer = escape_html(footer),
+        unsubscribe = unsubscribe_html,
+    );
+
+    EmailMessage {
+        subject: subject.to_string(),
+        text,
+        html,
+        unsubscribe_url: digest.unsubscribe_url.clone(),
+    }
+}
+
+pub fn digest_push(locale: Locale, digest: &Digest) -> PushMessage {
+    let summary = &digest.summary;
+    let (title, body) = match locale {
+        Locale::Fr => (
+            "Récapitulatif Garry",
+            format!(
+                "Dans les 30 jours : {} garantie(s) expirent. Expirées depuis le dernier \
+                 récapitulatif : {}. Factures manquantes : {}.",
+                summary.expiring_within_30_days,
+                summary.expired_since_last_digest,
+                summary.missing_receipts
+            ),
+        ),
+        Locale::En => (
+            "Garry digest",
+            format!(
+                "Expiring within 30 days: {}. Expired since the last digest: {}. \
+                 Missing receipts: {}.",
+                summary.expiring_within_30_days,
+                summary.expired_since_last_digest,
+                summary.missing_receipts
+            ),
+        ),
+    };
+
+    PushMessage {
+        title: title.to_string(),
+        body,
+        url: digest.link.clone(),
+        tag: "digest".to_string(),
+    }
+}
+
+/// The page behind the unsubscribe link: a confirmation form posting to
+/// `action_url`, so that link scanners opening it change nothing, or the
+/// confirmation once done.
+pub fn unsubscribe_page(locale: Locale, action_url: Option<&str>) -> String {
+    let (title, body) = match (locale, action_url) {
+        (Locale::Fr, Some(_)) => (
+            "Se désabonner du récapitulatif",
+            "Vous ne recevrez plus le récapitulatif de vos garanties. Les rappels \
+             d'expiration ne changent pas.",
+        ),
+        (Locale::Fr, None) => (
+            "Désabonnement confirmé",
+            "Vous ne recevrez plus le récapitulatif. Vous pouvez le réactiver à tout \
+             moment dans les réglages de Garry.",
+        ),
+        (Locale::En, Some(_)) => (
+            "Unsubscribe from the digest",
+            "You will no longer receive the digest of your warranties. Expiry \
+             reminders are not affected.",
+        ),
+        (Locale::En, None) => (
+            "You are unsubscribed",
+            "You will no longer receive the digest. You can turn it back on at any \
+             time in Garry's settings.",
+        ),
+    };
+    let form = match action_url {
+        Some(url) => format!(
+            r#"<form method="post" action="{}"><button type="submit" style="padding: 10px 16px; background: #0284c7; color: #ffffff; border: 0; border-radius: 6px;">{}</button></form>
+"#,
+            escape_html(url),
+            match locale {
+                Locale::Fr => "Confirmer",
+                Locale::En => "Confirm",
+            }
+        ),
+        None => String::new(),
+    };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="{lang}">
+<head><meta charset="utf-8"><meta name="viewport" content="width=device-width, initial-scale=1"><title>{title}</title></head>
+<body style="font-family: sans-serif; color: #1f2937; line-height: 1.5; max-width: 32rem; margin: 3rem auto; padding: 0 1rem;">
+<h1 style="font-size: 1.25rem;">{title}</h1>
+<p>{body}</p>
+{form}</body>
+</html>
+"#,
+        lang = lang(locale),
+        title = title,
+        body = body,
+        form = form,
+    )
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
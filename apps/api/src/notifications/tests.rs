@@ -0,0 +1,906 @@
+use std::sync::{Arc, Mutex};
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes128Gcm, Nonce};
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{Duration, TimeZone, Utc};
+use chrono_tz::Tz;
+use hkdf::Hkdf;
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use p256::{PublicKey, SecretKey};
+use rand::rngs::OsRng;
+use sha2::Sha256;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use uuid::Uuid;
+
+use crate::config::{SmtpConfig, SmtpTls, VapidConfig};
+use crate::models::{
+    DigestFrequency, DigestItem, DigestSummary, NotificationChannel, NotificationKind,
+    PendingDelivery, PushSubscription,
+};
+use crate::notifications::push::{encrypt, PushOutcome};
+use crate::notifications::templates::{
+    digest_email, digest_push, expiry_reminder_email, expiry_reminder_push, format_km,
+    household_invitation_email, maintenance_reminder_email, maintenance_reminder_push,
+    unsubscribe_page, Digest, ExpiryReminder, HouseholdInvitation, Locale, MaintenanceReminder,
+};
+use crate::notifications::{
+    retry_delay, validate_endpoint, validate_subscription_keys, DeliveryError, Sender, SmtpSender,
+    VapidKey, WebPushSender,
+};
+
+fn delivery(end_in: Duration) -> PendingDelivery {
+    PendingDelivery {
+        id: Uuid::new_v4(),
+        channel: NotificationChannel::Email,
+        attempts: 1,
+        user_id: "user-1".to_string(),
+        kind: NotificationKind::ExpiryReminder,
+        warranty_id: Some(Uuid::parse_str("6f1c1c8e-3c1b-4d7e-9a59-2f1d3b0c4a11").unwrap()),
+        product_name: Some("TV".to_string()),
+        brand: Some("Samsung".to_string()),
+        warranty_end_date: Some(Utc::now() + end_in),
+        digest: None,
+        maintenance_task_id: None,
+        maintenance_title: None,
+        maintenance_due_at: None,
+        maintenance_due_km: None,
+        digest_token: None,
+        household_name: None,
+        invitation_token: None,
+        recipient_email: Some("marie@example.com".to_string()),
+        recipient_name: Some("Marie".to_string()),
+        recipient_timezone: Some("Europe/Paris".to_string()),
+        recipient_locale: None,
+    }
+}
+
+#[test]
+fn test_locale_parse() {
+    assert_eq!(Locale::parse("fr"), Locale::Fr);
+    assert_eq!(Locale::parse("en-GB"), Locale::En);
+    assert_eq!(Locale::parse("EN_us"), Locale::En);
+    assert_eq!(Locale::parse("de"), Locale::Fr);
+    assert_eq!(Locale::parse(""), Locale::Fr);
+}
+
+#[test]
+fn test_expiry_reminder_context() {
+    let now = Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap();
+    let mut delivery = delivery(Duration::zero());
+    delivery.warranty_end_date = Some(now + Duration::days(29) + Duration::hours(2));
+
+    let reminder = ExpiryReminder::new(&delivery, now, "https://garry.app/").unwrap();
+    assert_eq!(reminder.product, "TV Samsung");
+    assert_eq!(reminder.days_left, 30);
+    assert_eq!(
+        reminder.link,
+        "https://garry.app/warranty/6f1c1c8e-3c1b-4d7e-9a59-2f1d3b0c4a11"
+    );
+
+    // The brand is not repeated when the name already has it.
+    delivery.product_name = Some("Samsung Galaxy S24".to_string());
+    let reminder = ExpiryReminder::new(&delivery, now, "https://garry.app").unwrap();
+    assert_eq!(reminder.product, "Samsung Galaxy S24");
+
+    delivery.kind = NotificationKind::Digest;
+    delivery.warranty_id = None;
+    assert!(ExpiryReminder::new(&delivery, now, "https://garry.app").is_none());
+}
+
+#[test]
+fn test_expiry_reminder_dates_are_the_recipients_days() {
+    let now = Utc.with_ymd_and_hms(2025, 2, 1, 9, 0, 0).unwrap();
+    let mut delivery = delivery(Duration::zero());
+    // Midnight on 28 February in Paris.
+    delivery.warranty_end_date = Some(Utc.with_ymd_and_hms(2025, 2, 27, 23, 0, 0).unwrap());
+
+    let reminder = ExpiryReminder::new(&delivery, now, "https://garry.app").unwrap();
+    assert_eq!(reminder.end_date(Locale::Fr), "28/02/2025");
+    assert_eq!(reminder.end_date(Locale::En), "February 28, 2025");
+
+    delivery.recipient_timezone = Some("America/New_York".to_string());
+    let reminder = ExpiryReminder::new(&delivery, now, "https://garry.app").unwrap();
+    assert_eq!(reminder.end_date(Locale::Fr), "27/02/2025");
+}
+
+#[test]
+fn test_locale_for_recipient() {
+    let mut delivery = delivery(Duration::days(7));
+    assert_eq!(Locale::for_recipient(&delivery, Locale::Fr), Locale::Fr);
+    assert_eq!(Locale::for_recipient(&delivery, Locale::En), Locale::En);
+
+    delivery.recipient_locale = Some("en".to_string());
+    assert_eq!(Locale::for_recipient(&delivery, Locale::Fr), Locale::En);
+}
+
+#[test]
+fn test_expiry_reminder_email_in_french_and_english() {
+    let reminder = ExpiryReminder {
+        recipient_name: Some("Marie".to_string()),
+        product: "TV Samsung".to_string(),
+        warranty_end_date: Utc.with_ymd_and_hms(2025, 10, 22, 0, 0, 0).unwrap(),
+        timezone: Tz::UTC,
+        days_left: 30,
+        link: "https://garry.app/warranty/1".to_string(),
+    };
+
+    let fr = expiry_reminder_email(Locale::Fr, &reminder);
+    assert_eq!(fr.subject, "Votre garantie TV Samsung expire dans 30 jours");
+    assert!(fr.text.starts_with("Bonjour Marie,"));
+    assert!(fr.text.contains("le 22/10/2025"));
+    assert!(fr.text.contains("https://garry.app/warranty/1"));
+    assert!(fr.html.contains(r#"<html lang="fr">"#));
+
+    let en = expiry_reminder_email(Locale::En, &reminder);
+    assert_eq!(en.subject, "Your TV Samsung warranty expires in 30 days");
+    assert!(en.text.contains("on October 22, 2025"));
+
+    let tomorrow = ExpiryReminder {
+        days_left: 1,
+        recipient_name: None,
+        ..reminder
+    };
+    let fr = expiry_reminder_email(Locale::Fr, &tomorrow);
+    assert_eq!(fr.subject, "Votre garantie TV Samsung expire demain");
+    assert!(fr.text.starts_with("Bonjour,"));
+}
+
+#[test]
+fn test_expiry_reminder_email_escapes_html() {
+    let reminder = ExpiryReminder {
+        recipient_name: Some("<b>Marie</b>".to_string()),
+        product: "Ampli \"Hi-Fi\" & co".to_string(),
+        warranty_end_date: Utc::now(),
+        timezone: Tz::UTC,
+        days_left: 7,
+        link: "https://garry.app/warranty/1".to_string(),
+    };
+
+    let email = expiry_reminder_email(Locale::Fr, &reminder);
+    assert!(email.html.contains("&lt;b&gt;Marie&lt;/b&gt;"));
+    assert!(email.html.contains("Ampli &quot;Hi-Fi&quot; &amp; co"));
+    assert!(!email.html.contains("<b>"));
+    // Plain text stays as written.
+    assert!(email.text.contains("Ampli \"Hi-Fi\" & co"));
+}
+
+#[test]
+fn test_maintenance_reminder_context() {
+    let now = Utc.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap();
+    let task_id = Uuid::new_v4();
+    let mut delivery = PendingDelivery {
+        kind: NotificationKind::MaintenanceDue,
+        warranty_end_date: None,
+        maintenance_task_id: Some(task_id),
+        maintenance_title: Some("Vidange".to_string()),
+        maintenance_due_at: Some(now + Duration::days(6) + Duration::hours(2)),
+        maintenance_due_km: Some(57_300),
+        ..delivery(Duration::zero())
+    };
+
+    let reminder = MaintenanceReminder::new(&delivery, now, "https://garry.app/").unwrap();
+    assert_eq!(reminder.task, "Vidange");
+    assert_eq!(reminder.days_left, 7);
+    assert_eq!(reminder.due_km, Some(57_300));
+    assert_eq!(reminder.tag, format!("maintenance-{}", task_id));
+    assert_eq!(
+        reminder.link,
+        "https://garry.app/warranty/6f1c1c8e-3c1b-4d7e-9a59-2f1d3b0c4a11"
+    );
+
+    delivery.maintenance_due_at = Some(now - Duration::hours(2));
+    let reminder = MaintenanceReminder::new(&delivery, now, "https://garry.app").unwrap();
+    assert_eq!(reminder.days_left, 0);
+    delivery.maintenance_due_at = Some(now - Duration::days(3));
+    let reminder = MaintenanceReminder::new(&delivery, now, "https://garry.app").unwrap();
+    assert_eq!(reminder.when(Locale::En), "overdue");
+
+    // Expiry reminders have no task.
+    assert!(
+        MaintenanceReminder::new(&self::delivery(Duration::days(7)), now, "https://garry.app")
+            .is_none()
+    );
+}
+
+#[test]
+fn test_format_km() {
+    assert_eq!(format_km(Locale::Fr, 57_300), "57 300");
+    assert_eq!(format_km(Locale::En, 1_000_000), "1,000,000");
+    assert_eq!(format_km(Locale::En, 900), "900");
+}
+
+#[test]
+fn test_maintenance_reminder_email_and_push() {
+    let reminder = MaintenanceReminder {
+        recipient_name: Some("Marie".to_string()),
+        product: "Clio Renault".to_string(),
+        task: "Vidange".to_string(),
+        due_at: Utc.with_ymd_and_hms(2026, 2, 28, 9, 0, 0).unwrap(),
+        timezone: Tz::UTC,
+        due_km: Some(57_300),
+        days_left: 7,
+        link: "https://garry.app/warranty/1".to_string(),
+        tag: "maintenance-1".to_string(),
+    };
+
+    let fr = maintenance_reminder_email(Locale::Fr, &reminder);
+    assert_eq!(
+        fr.subject,
+        "Entretien de votre Clio Renault : Vidange (dans 7 jours)"
+    );
+    assert!(fr.text.starts_with("Bonjour Marie,"));
+    assert!(fr
+        .text
+        .contains("le 28/02/2026 ou à 57 300 km, au premier des deux termes"));
+    assert!(fr.html.contains(r#"<html lang="fr">"#));
+
+    let en = maintenance_reminder_email(Locale::En, &reminder);
+    assert_eq!(en.subject, "Vidange on your Clio Renault (due in 7 days)");
+    assert!(en
+        .text
+        .contains("on February 28, 2026 or at 57,300 km, whichever comes first"));
+
+    let push = maintenance_reminder_push(
+        Locale::Fr,
+        &MaintenanceReminder {
+            due_km: None,
+            ..reminder
+        },
+    );
+    assert_eq!(push.title, "Rappel d'entretien");
+    assert_eq!(
+        push.body,
+        "Vidange (dans 7 jours) : votre Clio Renault, le 28/02/2026."
+    );
+    assert_eq!(push.tag, "maintenance-1");
+}
+
+#[test]
+fn test_household_invitation_email() {
+    let token = Uuid::parse_str("0b7a3c52-9d4e-4f61-8a2b-5c3d7e9f1a24").unwrap();
+    let delivery = PendingDelivery {
+        kind: NotificationKind::HouseholdInvitation,
+        warranty_id: None,
+        product_name: None,
+        brand: None,
+        warranty_end_date: None,
+        household_name: Some("Les Martin".to_string()),
+        invitation_token: Some(token),
+        ..delivery(Duration::zero())
+    };
+
+    let invitation = HouseholdInvitation::new(&delivery, "https://garry.app/").unwrap();
+    assert_eq!(
+        invitation.link,
+        "https://garry.app/invitations/0b7a3c52-9d4e-4f61-8a2b-5c3d7e9f1a24"
+    );
+
+    let fr = household_invitation_email(Locale::Fr, &invitation);
+    assert_eq!(
+        fr.subject,
+        "Invitation à rejoindre « Les Martin » sur Garry"
+    );
+    assert!(fr.text.contains(&invitation.link));
+    assert!(fr.html.contains(r#"<html lang="fr">"#));
+
+    let en = household_invitation_email(Locale::En, &invitation);
+    assert_eq!(en.subject, "Invitation to join \"Les Martin\" on Garry");
+    assert_eq!(en.unsubscribe_url, None);
+
+    // Reminders are about no household.
+    assert!(
+        HouseholdInvitation::new(&self::delivery(Duration::days(7)), "https://garry.app").is_none()
+    );
+}
+
+#[test]
+fn test_retry_delay_doubles() {
+    assert_eq!(retry_delay(1), Duration::minutes(1));
+    assert_eq!(retry_delay(2), Duration::minutes(2));
+    assert_eq!(retry_delay(4), Duration::minutes(8));
+}
+
+/// A one-shot SMTP server that accepts a single message, or rejects the
+/// recipient with `rcpt_reply`. Returns its port and the received data.
+async fn smtp_sink(rcpt_reply: &'static str) -> (u16, tokio::task::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let handle = tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (read, mut write) = socket.into_split();
+        let mut lines = BufReader::new(read).lines();
+        let mut data = String::new();
+        let mut in_data = false;
+
+        write.write_all(b"220 sink ESMTP\r\n").await.unwrap();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if in_data {
+                if line == "." {
+                    in_data = false;
+                    write.write_all(b"250 queued\r\n").await.unwrap();
+                } else {
+                    data.push_str(&line);
+                    data.push('\n');
+                }
+                continue;
+            }
+            let reply: &[u8] = match line.get(..4).map(str::to_ascii_uppercase).as_deref() {
+                Some("EHLO") | Some("HELO") => b"250 sink\r\n",
+                Some("RCPT") => rcpt_reply.as_bytes(),
+                Some("DATA") => {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                }
+                Some("QUIT") => {
+                    write.write_all(b"221 bye\r\n").await.unwrap();
+                    break;
+                }
+                _ => b"250 ok\r\n",
+            };
+            write.write_all(reply).await.unwrap();
+        }
+        data
+    });
+
+    (port, handle)
+}
+
+fn smtp_sender(port: u16) -> SmtpSender {
+    let config = SmtpConfig {
+        host: "127.0.0.1".to_string(),
+        port,
+        username: None,
+        password: None,
+        tls: SmtpTls::None,
+        from: "Garry <noreply@garry.app>".to_string(),
+    };
+    SmtpSender::new(
+        &config,
+        Locale::Fr,
+        "https://garry.app",
+        "https://api.garry.app",
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_smtp_sender_delivers_text_and_html() {
+    let (port, sink) = smtp_sink("250 ok\r\n").await;
+
+    smtp_sender(port)
+        .send(&delivery(Duration::days(30)))
+        .await
+        .unwrap();
+
+    let data = sink.await.unwrap();
+    assert!(data.contains("To: Marie <marie@example.com>"));
+    assert!(data.contains("Subject: Votre garantie TV Samsung expire dans 30 jours"));
+    assert!(data.contains("Content-Type: text/plain"));
+    assert!(data.contains("Content-Type: text/html"));
+}
+
+#[tokio::test]
+async fn test_smtp_sender_writes_in_the_recipients_language() {
+    let (port, sink) = smtp_sink("250 ok\r\n").await;
+    let mut delivery = delivery(Duration::days(30));
+    delivery.recipient_locale = Some("en".to_string());
+
+    smtp_sender(port).send(&delivery).await.unwrap();
+
+    let data = sink.await.unwrap();
+    assert!(data.contains("Subject: Your TV Samsung warranty expires in 30 days"));
+}
+
+#[tokio::test]
+async fn test_smtp_sender_delivers_digest_with_unsubscribe_headers() {
+    let (port, sink) = smtp_sink("250 ok\r\n").await;
+    let token = Uuid::parse_str("0b7a3c52-9d4e-4f61-8a2b-5c3d7e9f1a24").unwrap();
+
+    smtp_sender(port)
+        .send(&digest_delivery(token))
+        .await
+        .unwrap();
+
+    let data = sink.await.unwrap();
+    assert!(data.contains("To: Marie <marie@example.com>"));
+    assert!(data.contains(
+        "List-Unsubscribe: <https://api.garry.app/api/v1/digest/unsubscribe/0b7a3c52-9d4e-4f61-8a2b-5c3d7e9f1a24>"
+    ));
+    assert!(data.contains("List-Unsubscribe-Post: List-Unsubscribe=One-Click"));
+}
+
+#[tokio::test]
+async fn test_smtp_sender_classifies_failures() {
+    let (port, _sink) = smtp_sink("550 no such user\r\n").await;
+    assert!(matches!(
+        smtp_sender(port).send(&delivery(Duration::days(7))).await,
+        Err(DeliveryError::Permanent(_))
+    ));
+
+    // Nothing listens on a freshly released port.
+    let port = TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    assert!(matches!(
+        smtp_sender(port).send(&delivery(Duration::days(7))).await,
+        Err(DeliveryError::Retry(_))
+    ));
+
+    let mut without_email = delivery(Duration::days(7));
+    without_email.recipient_email = None;
+    assert!(matches!(
+        smtp_sender(port).send(&without_email).await,
+        Err(DeliveryError::Permanent(_))
+    ));
+}
+
+#[test]
+fn test_expiry_reminder_push() {
+    let reminder = ExpiryReminder {
+        recipient_name: Some("Marie".to_string()),
+        product: "TV Samsung".to_string(),
+        warranty_end_date: Utc.with_ymd_and_hms(2025, 10, 22, 0, 0, 0).unwrap(),
+        timezone: Tz::UTC,
+        days_left: 7,
+        link: "https://garry.app/warranty/1".to_string(),
+    };
+
+    let fr = expiry_reminder_push(Locale::Fr, &reminder);
+    assert_eq!(fr.title, "Rappel de garantie");
+    assert_eq!(
+        fr.body,
+        "La garantie de votre TV Samsung expire dans 7 jours, le 22/10/2025."
+    );
+    assert_eq!(fr.url, "https://garry.app/warranty/1");
+
+    let en = expiry_reminder_push(Locale::En, &reminder);
+    assert_eq!(
+        en.body,
+        "The warranty on your TV Samsung expires in 7 days, on October 22, 2025."
+    );
+}
+
+/// Reverses `encrypt` on the browser side, following RFC 8291.
+fn decrypt(body: &[u8], browser_secret: &SecretKey, auth: &[u8]) -> Vec<u8> {
+    let (salt, rest) = body.split_at(16);
+    let record_size = u32::from_be_bytes(rest[..4].try_into().unwrap());
+    assert!(record_size >= 18);
+    let key_length = rest[4] as usize;
+    let (server_key, ciphertext) = rest[5..].split_at(key_length);
+
+    let server_key = PublicKey::from_sec1_bytes(server_key).unwrap();
+    let shared =
+        p256::ecdh::diffie_hellman(browser_secret.to_nonzero_scalar(), server_key.as_affine());
+
+    let mut key_info = b"WebPush: info\0".to_vec();
+    key_info.extend_from_slice(
+        browser_secret
+            .public_key()
+            .to_encoded_point(false)
+            .as_bytes(),
+    );
+    key_info.extend_from_slice(server_key.to_encoded_point(false).as_bytes());
+    let mut ikm = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(auth), shared.raw_secret_bytes())
+        .expand(&key_info, &mut ikm)
+        .unwrap();
+
+    let prk = Hkdf::<Sha256>::new(Some(salt), &ikm);
+    let mut key = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    prk.expand(b"Content-Encoding: aes128gcm\0", &mut key)
+        .unwrap();
+    prk.expand(b"Content-Encoding: nonce\0", &mut nonce)
+        .unwrap();
+
+    let mut plaintext = Aes128Gcm::new(&key.into())
+        .decrypt(Nonce::from_slice(&nonce), ciphertext)
+        .unwrap();
+    assert_eq!(plaintext.pop(), Some(2), "last record delimiter");
+    plaintext
+}
+
+fn browser_keys() -> (SecretKey, String, String) {
+    let secret = SecretKey::random(&mut OsRng);
+    let p256dh = URL_SAFE_NO_PAD.encode(secret.public_key().to_encoded_point(false).as_bytes());
+    let auth = URL_SAFE_NO_PAD.encode(Uuid::new_v4().as_bytes());
+    (secret, p256dh, auth)
+}
+
+#[test]
+fn test_decrypt_matches_rfc8291_example() {
+    // Appendix A of RFC 8291.
+    let browser_secret = SecretKey::from_slice(
+        &URL_SAFE_NO_PAD
+            .decode("q1dXpw3UpT5VOmu_cf_v6ih07Aems3njxI-JWgLcM94")
+            .unwrap(),
+    )
+    .unwrap();
+    let auth = URL_SAFE_NO_PAD.decode("BTBZMqHH6r4Tts7J_aSIgg").unwrap();
+    let body = URL_SAFE_NO_PAD
+        .decode(
+            "DGv6ra1nlYgDCS1FRnbzlwAAEABBBP4z9KsN6nGRTbVYI_c7VJSPQTBtkgcy27mlmlMoZIIgDll6e3vCYLoc\
+             InmYWAmS6TlzAC8wEqKK6PBru3jl7A_yl95bQpu6cVPTpK4Mqgkf1CXztLVBSt2Ks3oZwbuwXPXLWyouBW\
+             LVWGNWQexSgSxsj_Qulcy4a-fN",
+        )
+        .unwrap();
+
+    assert_eq!(
+        decrypt(&body, &browser_secret, &auth),
+        b"When I grow up, I want to be a watermelon"
+    );
+}
+
+#[test]
+fn test_encrypt_round_trip() {
+    let (secret, p256dh, auth) = browser_keys();
+    let payload = br#"{"title":"Rappel de garantie"}"#;
+
+    let body = encrypt(payload, &p256dh, &auth).unwrap();
+    let auth = URL_SAFE_NO_PAD.decode(auth).unwrap();
+    assert_eq!(decrypt(&body, &secret, &auth), payload);
+
+    // Each message uses a fresh key and salt.
+    assert_ne!(
+        encrypt(payload, &p256dh, &URL_SAFE_NO_PAD.encode(&auth)).unwrap(),
+        body
+    );
+    assert!(encrypt(&[b'a'; 4096], &p256dh, &URL_SAFE_NO_PAD.encode(&auth)).is_err());
+}
+
+#[test]
+fn test_validate_subscription_keys() {
+    let (_, p256dh, auth) = browser_keys();
+    assert!(validate_subscription_keys(&p256dh, &auth).is_ok());
+    assert!(validate_subscription_keys(&format!("{}==", p256dh), &auth).is_ok());
+
+    assert!(validate_subscription_keys("not a key", &auth).is_err());
+    // A compressed point's length, but not a point on the curve.
+    assert!(validate_subscription_keys(&URL_SAFE_NO_PAD.encode([7u8; 33]), &auth).is_err());
+    assert!(validate_subscription_keys(&p256dh, &URL_SAFE_NO_PAD.encode([1u8; 8])).is_err());
+}
+
+#[test]
+fn test_validate_endpoint() {
+    let fcm = "https://fcm.googleapis.com/fcm/send/abc";
+    assert_eq!(validate_endpoint(fcm, false).unwrap().as_str(), fcm);
+    assert!(validate_endpoint(
+        "https://updates.push.services.mozilla.com/wpush/v2/x",
+        false
+    )
+    .is_ok());
+
+    for endpoint in [
+        "http://fcm.googleapis.com/fcm/send/abc",
+        "not a url",
+        "https://127.0.0.1/push",
+        "https://169.254.169.254/latest/meta-data",
+        "https://[fd00::1]/push",
+        "https://localhost/push",
+        "https://metadata.google.internal/push",
+    ] {
+        assert!(validate_endpoint(endpoint, false).is_err(), "{}", endpoint);
+    }
+    assert!(validate_endpoint("https://localhost:8443/push", true).is_ok());
+    assert!(validate_endpoint("http://localhost:8443/push", true).is_err());
+    let long = format!("https://push.example.net/{}", "a".repeat(2048));
+    assert!(validate_endpoint(&long, true).is_err());
+}
+
+fn vapid_config() -> VapidConfig {
+    VapidConfig {
+        private_key: URL_SAFE_NO_PAD.encode(SecretKey::random(&mut OsRng).to_bytes()),
+        subject: "mailto:admin@garry.app".to_string(),
+    }
+}
+
+#[test]
+fn test_vapid_authorization_is_a_signed_jwt() {
+    let key = VapidKey::new(&vapid_config()).unwrap();
+    let endpoint = reqwest::Url::parse("https://push.example.net:443/wpush/v2/abc").unwrap();
+
+    let authorization = key.authorization(&endpoint);
+    let (token, public_key) = authorization
+        .strip_prefix("vapid t=")
+        .and_then(|rest| rest.split_once(", k="))
+        .unwrap();
+    assert_eq!(public_key, key.public_key());
+
+    let (signing_input, signature) = token.rsplit_once('.').unwrap();
+    let verifying_key =
+        VerifyingKey::from_sec1_bytes(&URL_SAFE_NO_PAD.decode(public_key).unwrap()).unwrap();
+    let signature = Signature::from_slice(&URL_SAFE_NO_PAD.decode(signature).unwrap()).unwrap();
+    verifying_key
+        .verify(signing_input.as_bytes(), &signature)
+        .unwrap();
+
+    let claims: serde_json::Value = serde_json::from_slice(
+        &URL_SAFE_NO_PAD
+            .decode(signing_input.split_once('.').unwrap().1)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(claims["aud"], "https://push.example.net");
+    assert_eq!(claims["sub"], "mailto:admin@garry.app");
+    let exp = claims["exp"].as_i64().unwrap();
+    assert!(exp > Utc::now().timestamp() && exp <= (Utc::now() + Duration::hours(24)).timestamp());
+
+    assert!(VapidKey::new(&VapidConfig {
+        private_key: "short".to_string(),
+        subject: String::new(),
+    })
+    .is_err());
+}
+
+#[derive(Clone, Default)]
+struct PushServiceLog(Arc<Mutex<Vec<(HeaderMap, Bytes)>>>);
+
+/// A push service answering every request to `/push/{status}` with that
+/// status, and logging what it received.
+async fn mock_push_service() -> (String, PushServiceLog) {
+    async fn receive(
+        State(log): State<PushServiceLog>,
+        Path(status): Path<u16>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> StatusCode {
+        log.0.lock().unwrap().push((headers, body));
+        StatusCode::from_u16(status).unwrap()
+    }
+
+    let log = PushServiceLog::default();
+    let app = Router::new()
+        .route("/push/:status", post(receive))
+        .with_state(log.clone());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    (url, log)
+}
+
+fn push_sender(allow_private: bool) -> WebPushSender {
+    // Only `send` reads the database.
+    let pool = sqlx::PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+    WebPushSender::new(
+        pool,
+        &vapid_config(),
+        Locale::Fr,
+        "https://garry.app",
+        allow_private,
+    )
+    .unwrap()
+}
+
+fn subscription(endpoint: String, p256dh: &str, auth: &str) -> PushSubscription {
+    PushSubscription {
+        id: Uuid::new_v4(),
+        endpoint,
+        p256dh: p256dh.to_string(),
+        auth: auth.to_string(),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn test_push_sends_encrypted_payload() {
+    let (url, log) = mock_push_service().await;
+    let (secret, p256dh, auth) = browser_keys();
+    let sender = push_sender(true);
+
+    let outcome = sender
+        .push(
+            &subscription(format!("{}/push/201", url), &p256dh, &auth),
+            b"{\"title\":\"Rappel\"}",
+        )
+        .await;
+    assert!(matches!(outcome, PushOutcome::Delivered));
+
+    let (headers, body) = log.0.lock().unwrap().pop().unwrap();
+    assert_eq!(headers["content-encoding"], "aes128gcm");
+    assert_eq!(headers["ttl"], "86400");
+    assert!(headers["authorization"]
+        .to_str()
+        .unwrap()
+        .starts_with("vapid t="));
+    let auth = URL_SAFE_NO_PAD.decode(auth).unwrap();
+    assert_eq!(decrypt(&body, &secret, &auth), b"{\"title\":\"Rappel\"}");
+}
+
+#[tokio::test]
+async fn test_push_classifies_responses() {
+    let (url, _log) = mock_push_service().await;
+    let (_, p256dh, auth) = browser_keys();
+    let sender = push_sender(true);
+    let push = |status: u16| {
+        let subscription = subscription(format!("{}/push/{}", url, status), &p256dh, &auth);
+        let sender = &sender;
+        async move { sender.push(&subscription, b"{}").await }
+    };
+
+    assert!(matches!(push(404).await, PushOutcome::Gone));
+    assert!(matches!(push(410).await, PushOutcome::Gone));
+    assert!(matches!(
+        push(429).await,
+        PushOutcome::Failed(DeliveryError::Retry(_))
+    ));
+    assert!(matches!(
+        push(503).await,
+        PushOutcome::Failed(DeliveryError::Retry(_))
+    ));
+    assert!(matches!(
+        push(400).await,
+        PushOutcome::Failed(DeliveryError::Permanent(_))
+    ));
+    assert!(matches!(
+        push(403).await,
+        PushOutcome::Failed(DeliveryError::Permanent(_))
+    ));
+
+    let unreachable = subscription("http://127.0.0.1:1/push".to_string(), &p256dh, &auth);
+    assert!(matches!(
+        sender.push(&unreachable, b"{}").await,
+        PushOutcome::Failed(DeliveryError::Retry(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_push_refuses_private_endpoints_in_production() {
+    let (url, log) = mock_push_service().await;
+    let (_, p256dh, auth) = browser_keys();
+    let subscription = subscription(format!("{}/push/201", url), &p256dh, &auth);
+
+    assert!(matches!(
+        push_sender(false).push(&subscription, b"{}").await,
+        PushOutcome::Failed(DeliveryError::Permanent(_))
+    ));
+    assert!(log.0.lock().unwrap().is_empty());
+}
+
+fn digest_summary() -> DigestSummary {
+    DigestSummary {
+        frequency: DigestFrequency::Weekly,
+        since: Utc.with_ymd_and_hms(2025, 10, 1, 9, 0, 0).unwrap(),
+        expiring_within_30_days: 1,
+        expiring_within_90_days: 4,
+        expired_since_last_digest: 2,
+        missing_receipts: 3,
+        expiring: vec![DigestItem {
+            warranty_id: Uuid::parse_str("6f1c1c8e-3c1b-4d7e-9a59-2f1d3b0c4a11").unwrap(),
+            product_name: "TV".to_string(),
+            brand: Some("Samsung".to_string()),
+            warranty_end_date: Utc.with_ymd_and_hms(2025, 10, 22, 0, 0, 0).unwrap(),
+        }],
+    }
+}
+
+fn digest_delivery(token: Uuid) -> PendingDelivery {
+    PendingDelivery {
+        kind: NotificationKind::Digest,
+        warranty_id: None,
+        product_name: None,
+        brand: None,
+        warranty_end_date: None,
+        digest: Some(sqlx::types::Json(digest_summary())),
+        digest_token: Some(token),
+        ..delivery(Duration::zero())
+    }
+}
+
+#[test]
+fn test_digest_context() {
+    let token = Uuid::new_v4();
+    let digest = Digest::new(&digest_delivery(token), "https://garry.app/").unwrap();
+    assert_eq!(digest.link, "https://garry.app");
+    assert_eq!(digest.summary, digest_summary());
+    // Only the email sender adds the link, push has nowhere to put it.
+    assert_eq!(digest.unsubscribe_url, None);
+
+    assert!(Digest::new(&delivery(Duration::days(7)), "https://garry.app").is_none());
+}
+
+#[test]
+fn test_digest_email_in_french_and_english() {
+    let digest = Digest {
+        recipient_name: Some("Marie".to_string()),
+        summary: digest_summary(),
+        timezone: Tz::UTC,
+        link: "https://garry.app".to_string(),
+        unsubscribe_url: Some("https://api.garry.app/api/v1/digest/unsubscribe/1".to_string()),
+    };
+
+    let fr = digest_email(Locale::Fr, &digest);
+    assert_eq!(fr.subject, "Votre récapitulatif hebdomadaire Garry");
+    assert!(fr.text.starts_with("Bonjour Marie,"));
+    assert!(fr
+        .text
+        .contains("Garanties qui expirent dans les 30 prochains jours : 1"));
+    assert!(fr
+        .text
+        .contains("Garanties qui expirent dans les 90 prochains jours : 4"));
+    assert!(fr
+        .text
+        .contains("Garanties expirées depuis le 01/10/2025 : 2"));
+    assert!(fr.text.contains("Garanties actives sans facture : 3"));
+    assert!(fr.text.contains("- TV Samsung : 22/10/2025"));
+    assert!(fr
+        .text
+        .contains("Se désabonner: https://api.garry.app/api/v1/digest/unsubscribe/1"));
+    assert!(fr.html.contains("<li>TV Samsung : 22/10/2025</li>"));
+    assert_eq!(
+        fr.unsubscribe_url.as_deref(),
+        Some("https://api.garry.app/api/v1/digest/unsubscribe/1")
+    );
+
+    let monthly = Digest {
+        summary: DigestSummary {
+            frequency: DigestFrequency::Monthly,
+            expiring: Vec::new(),
+            ..digest_summary()
+        },
+        unsubscribe_url: None,
+        ..digest
+    };
+    let en = digest_email(Locale::En, &monthly);
+    assert_eq!(en.subject, "Your monthly Garry digest");
+    assert!(en
+        .text
+        .contains("Warranties expired since October 1, 2025: 2"));
+    assert!(!en.text.contains("Ending soon"));
+    assert!(!en.text.contains("Unsubscribe"));
+}
+
+#[test]
+fn test_digest_push() {
+    let digest = Digest {
+        recipient_name: None,
+        summary: digest_summary(),
+        timezone: Tz::UTC,
+        link: "https://garry.app".to_string(),
+        unsubscribe_url: None,
+    };
+
+    let fr = digest_push(Locale::Fr, &digest);
+    assert_eq!(fr.title, "Récapitulatif Garry");
+    assert_eq!(
+        fr.body,
+        "Dans les 30 jours : 1 garantie(s) expirent. Expirées depuis le dernier \
+         récapitulatif : 2. Factures manquantes : 3."
+    );
+    assert_eq!(fr.url, "https://garry.app");
+    assert_eq!(fr.tag, "digest");
+
+    let en = digest_push(Locale::En, &digest);
+    assert_eq!(
+        en.body,
+        "Expiring within 30 days: 1. Expired since the last digest: 2. Missing receipts: 3."
+    );
+}
+
+#[test]
+fn test_unsubscribe_page_posts_back() {
+    let url = "https://api.garry.app/api/v1/digest/unsubscribe/1?a=1&b=2";
+    let page = unsubscribe_page(Locale::Fr, Some(url));
+    assert!(page.contains(
+        r#"<form method="post" action="https://api.garry.app/api/v1/digest/unsubscribe/1?a=1&amp;b=2">"#
+    ));
+
+    let done = unsubscribe_page(Locale::En, None);
+    assert!(done.contains("You are unsubscribed"));
+    assert!(!done.contains("<form"));
+}
@@ -0,0 +1,100 @@
+//! Guards for requests the server makes to addresses its users choose, such
+//! as webhooks and push endpoints, so they cannot reach inside the network.
+
+#[cfg(test)]
+mod tests;
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+
+/// Whether `host`, an IP address or a name, is obviously not public. Names
+/// are not resolved here; [`PublicResolver`] checks what they resolve to.
+pub fn is_private_host(host: &str) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return is_private_ip(ip);
+    }
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    host == "localhost"
+        || [".localhost", ".local", ".internal", ".home.arpa"]
+            .iter()
+            .any(|suffix| host.ends_with(suffix))
+}
+
+/// Whether `ip` is not a public unicast address: loopback, private,
+/// link-local (which holds the cloud metadata services), shared, reserved
+/// or multicast.
+pub fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                // "This network", 0.0.0.0/8
+                || a == 0
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (a == 100 && (64..128).contains(&b))
+                // IETF protocol assignments, 192.0.0.0/24
+                || (a == 192 && b == 0 && c == 0)
+                // Benchmarking, 198.18.0.0/15
+                || (a == 198 && (18..20).contains(&b))
+                // Reserved, 240.0.0.0/4
+                || a >= 240
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_private_ip(IpAddr::V4(ip));
+            }
+            let segments = ip.segments();
+            // NAT64, 64:ff9b::/96, reaches the IPv4 address it embeds
+            if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                let [.., a, b, c, d] = ip.octets();
+                return is_private_ip(IpAddr::V4(Ipv4Addr::new(a, b, c, d)));
+            }
+            let first = segments[0];
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // Unique local, fc00::/7, and link-local, fe80::/10
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+/// Resolves names like the system does, but only to public addresses, so a
+/// public name pointing inside fails to connect. IP addresses in URLs are
+/// not resolved: check them with [`is_private_host`].
+pub struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let resolved = tokio::net::lookup_host((host.as_str(), 0)).await?;
+            let public: Vec<SocketAddr> =
+                resolved.filter(|addr| !is_private_ip(addr.ip())).collect();
+            if public.is_empty() {
+                return Err(format!("{} does not resolve to a public address", host).into());
+            }
+            let addrs: Addrs = Box::new(public.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// Sets up `builder` to only reach public addresses, unless `allow_private`,
+/// as in development. Proxies are bypassed, as they would resolve the names.
+pub fn restrict(builder: reqwest::ClientBuilder, allow_private: bool) -> reqwest::ClientBuilder {
+    if allow_private {
+        builder
+    } else {
+        builder.no_proxy().dns_resolver(Arc::new(PublicResolver))
+    }
+}
@@ -0,0 +1,88 @@
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use reqwest::dns::{Name, Resolve};
+
+use crate::outbound::{is_private_host, is_private_ip, restrict, PublicResolver};
+
+fn ip(ip: &str) -> IpAddr {
+    ip.parse().unwrap()
+}
+
+#[test]
+fn test_is_private_ip() {
+    for private in [
+        "127.0.0.1",
+        "10.0.0.5",
+        "172.16.3.4",
+        "192.168.1.10",
+        "169.254.169.254",
+        "100.64.0.1",
+        "0.0.0.0",
+        "0.1.2.3",
+        "192.0.0.192",
+        "198.18.0.1",
+        "224.0.0.1",
+        "255.255.255.255",
+        "::1",
+        "::",
+        "fd00:ec2::254",
+        "fe80::1",
+        "ff02::1",
+        "::ffff:10.0.0.1",
+        "64:ff9b::a9fe:a9fe",
+    ] {
+        assert!(is_private_ip(ip(private)), "{} is private", private);
+    }
+    for public in [
+        "93.184.216.34",
+        "1.1.1.1",
+        "100.128.0.1",
+        "2606:4700:4700::1111",
+        "64:ff9b::5db8:d822",
+    ] {
+        assert!(!is_private_ip(ip(public)), "{} is public", public);
+    }
+}
+
+#[test]
+fn test_is_private_host() {
+    assert!(is_private_host("localhost"));
+    assert!(is_private_host("LOCALHOST."));
+    assert!(is_private_host("metadata.google.internal"));
+    assert!(is_private_host("printer.local"));
+    assert!(is_private_host("[::1]"));
+    assert!(is_private_host("169.254.169.254"));
+    assert!(!is_private_host("hooks.example.com"));
+    assert!(!is_private_host("[2606:4700:4700::1111]"));
+}
+
+#[tokio::test]
+async fn test_resolver_refuses_names_pointing_inside() {
+    let error = match PublicResolver
+        .resolve(Name::from_str("localhost").unwrap())
+        .await
+    {
+        Ok(_) => panic!("localhost resolved"),
+        Err(e) => e.to_string(),
+    };
+    assert_eq!(error, "localhost does not resolve to a public address");
+}
+
+#[tokio::test]
+async fn test_restricted_client_does_not_connect_inside() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let url = format!("http://localhost:{}/", port);
+
+    let client = restrict(reqwest::Client::builder(), false).build().unwrap();
+    let error = client.get(&url).send().await.unwrap_err();
+    assert!(error.is_connect());
+
+    let client = restrict(reqwest::Client::builder(), true).build().unwrap();
+    tokio::spawn(async move {
+        let _ = listener.accept().await;
+    });
+    let result = client.get(&url).send().await;
+    assert!(!matches!(result, Err(e) if e.is_connect()));
+}
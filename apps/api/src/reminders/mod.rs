@@ -0,0 +1,472 @@
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::db::{
+    self, DigestCandidate, MaintenanceCandidate, NewDelivery, NewReminder, NewWebhookEvent,
+    ReminderCandidate,
+};
+use crate::error::Result;
+use crate::models::{
+    MaintenanceTask, Notification, NotificationChannel, NotificationKind, ReminderMode, Warranty,
+    WebhookEvent, DEFAULT_TIMEZONE,
+};
+use crate::webhooks;
+
+pub const MAX_LEAD_DAYS: i32 = 365;
+const MAX_LEAD_TIMES: usize = 10;
+
+/// How far back a run looks for warranties that ended, so that expiries are
+/// still reported after the scheduler was down for a while.
+const EXPIRED_LOOKBACK_DAYS: i32 = 7;
+
+/// Starts the task that records expiry and maintenance reminders and due
+/// digests every `interval` and queues them on `channels`. Every replica can
+/// run one: a tick only does work while holding the advisory lock, and the
+/// unique index on notifications guards the rest.
+pub fn spawn_scheduler(
+    pool: PgPool,
+    default_lead_days: Vec<i32>,
+    channels: Vec<NotificationChannel>,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            match run_once(&pool, &default_lead_days, &channels, Utc::now()).await {
+                Ok(Some(created)) if !created.is_empty() => {
+                    tracing::info!(count = created.len(), "reminders created");
+                }
+                Ok(Some(_)) => {}
+                Ok(None) => tracing::debug!("reminder scheduler locked by another replica"),
+                Err(e) => tracing::warn!(error = %e, "reminder scheduler run failed"),
+            }
+            match run_digests(&pool, &channels, Utc::now()).await {
+                Ok(Some(created)) if !created.is_empty() => {
+                    tracing::info!(count = created.len(), "digests created");
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "digest run failed"),
+            }
+        }
+    })
+}
+
+/// Records the expiry and maintenance reminders due at `now` and queues
+/// their deliveries on the configured `channels` each user enabled, then
+/// records the warranties that expired and queues webhook events for all of
+/// them. Returns `None` when another replica is already running.
+pub async fn run_once(
+    pool: &PgPool,
+    default_lead_days: &[i32],
+    channels: &[NotificationChannel],
+    now: DateTime<Utc>,
+) -> Result<Option<Vec<Notification>>> {
+    let mut tx = pool.begin().await?;
+    if !db::try_lock_reminders(&mut tx).await? {
+        return Ok(None);
+    }
+
+    let candidates = db::get_reminder_candidates(&mut tx, now, default_lead_days).await?;
+    let reminders: Vec<NewReminder> = candidates
+        .iter()
+        .filter_map(|candidate| {
+            let threshold_days =
+                crossed_threshold(&candidate.lead_days, now, candidate.warranty_end_date)?;
+            Some(NewReminder {
+                user_id: candidate.user_id.clone(),
+                warranty_id: candidate.warranty_id,
+                threshold_days,
+                warranty_end_date: candidate.warranty_end_date,
+            })
+        })
+        .collect();
+
+    let mut created = db::create_expiry_notifications(&mut tx, &reminders).await?;
+    let by_recipient: HashMap<(Uuid, &str), &ReminderCandidate> = candidates
+        .iter()
+        .map(|candidate| {
+            (
+                (candidate.warranty_id, candidate.user_id.as_str()),
+                candidate,
+            )
+        })
+        .collect();
+    let deliveries: Vec<NewDelivery> = created
+        .iter()
+        .filter_map(|notification| {
+            let key = (notification.warranty_id?, notification.user_id.as_str());
+            let candidate = by_recipient.get(&key)?;
+            Some(deliveries_for(
+                notification.id,
+                notification.kind,
+                (*candidate).into(),
+                channels,
+                now,
+            ))
+        })
+        .flatten()
+        .collect();
+    db::enqueue_deliveries(&mut tx, &deliveries).await?;
+
+    let maintenance_candidates = db::get_maintenance_candidates(&mut tx, now).await?;
+    let maintenance =
+        db::create_maintenance_notifications(&mut tx, &maintenance_candidates).await?;
+    let by_recipient: HashMap<(Uuid, &str), &MaintenanceCandidate> = maintenance_candidates
+        .iter()
+        .map(|candidate| ((candidate.task_id, candidate.user_id.as_str()), candidate))
+        .collect();
+    let deliveries: Vec<NewDelivery> = maintenance
+        .iter()
+        .filter_map(|notification| {
+            let key = (
+                notification.maintenance_task_id?,
+                notification.user_id.as_str(),
+            );
+            let candidate = by_recipient.get(&key)?;
+            Some(deliveries_for(
+                notification.id,
+                notification.kind,
+                (*candidate).into(),
+                channels,
+                now,
+            ))
+        })
+        .flatten()
+        .collect();
+    db::enqueue_deliveries(&mut tx, &deliveries).await?;
+
+    let expired = db::create_expired_notifications(&mut tx, now, EXPIRED_LOOKBACK_DAYS).await?;
+    let mut events = webhook_events(&mut tx, &created, &expired, now).await?;
+    events.extend(maintenance_webhook_events(&mut tx, &maintenance, now).await?);
+    db::enqueue_webhook_events(&mut tx, &events).await?;
+    tx.commit().await?;
+
+    created.extend(maintenance);
+    Ok(Some(created))
+}
+
+/// `warranty.expiring` for each new reminder and `warranty.expired` for each
+/// new expiry, with the warranty as it is at `now`, to the webhooks of the
+/// member notified.
+async fn webhook_events(
+    conn: &mut PgConnection,
+    reminders: &[Notification],
+    expired: &[Notification],
+    now: DateTime<Utc>,
+) -> Result<Vec<NewWebhookEvent>> {
+    let ids: Vec<Uuid> = reminders
+        .iter()
+        .chain(expired)
+        .filter_map(|notification| notification.warranty_id)
+        .collect();
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let warranties: HashMap<Uuid, Warranty> = db::get_warranties_by_ids(conn, &ids)
+        .await?
+        .into_iter()
+        .map(|warranty| (warranty.id, warranty))
+        .collect();
+
+    let expiring = reminders.iter().filter_map(|notification| {
+        let warranty = warranties.get(&notification.warranty_id?)?;
+        Some(webhooks::expiring_event(
+            warranty,
+            &notification.user_id,
+            notification.threshold_days?,
+            now,
+        ))
+    });
+    let expired = expired.iter().filter_map(|notification| {
+        let warranty = warranties.get(&notification.warranty_id?)?;
+        Some(webhooks::warranty_event(
+            WebhookEvent::Expired,
+            warranty,
+            &notification.user_id,
+            now,
+        ))
+    });
+    Ok(expiring.chain(expired).collect())
+}
+
+/// `maintenance.due` for each new maintenance reminder, with the warranty
+/// and the task as they are at `now`, to the webhooks of the member
+/// notified.
+async fn maintenance_webhook_events(
+    conn: &mut PgConnection,
+    reminders: &[Notification],
+    now: DateTime<Utc>,
+) -> Result<Vec<NewWebhookEvent>> {
+    if reminders.is_empty() {
+        return Ok(Vec::new());
+    }
+    let warranty_ids: Vec<Uuid> = reminders
+        .iter()
+        .filter_map(|notification| notification.warranty_id)
+        .collect();
+    let task_ids: Vec<Uuid> = reminders
+        .iter()
+        .filter_map(|notification| notification.maintenance_task_id)
+        .collect();
+    let warranties: HashMap<Uuid, Warranty> = db::get_warranties_by_ids(conn, &warranty_ids)
+        .await?
+        .into_iter()
+        .map(|warranty| (warranty.id, warranty))
+        .collect();
+    let tasks: HashMap<Uuid, MaintenanceTask> = db::get_maintenance_tasks_by_ids(conn, &task_ids)
+        .await?
+        .into_iter()
+        .map(|task| (task.id, task))
+        .collect();
+
+    Ok(reminders
+        .iter()
+        .filter_map(|notification| {
+            let warranty = warranties.get(&notification.warranty_id?)?;
+            let task = tasks.get(&notification.maintenance_task_id?)?;
+            Some(webhooks::maintenance_due_event(
+                warranty,
+                task,
+                &notification.user_id,
+                now,
+            ))
+        })
+        .collect())
+}
+
+/// Records the digests due at `now`, skipping those with nothing to report,
+/// and starts the next period of every user it looked at. Returns `None`
+/// when another replica is already running.
+pub async fn run_digests(
+    pool: &PgPool,
+    channels: &[NotificationChannel],
+    now: DateTime<Utc>,
+) -> Result<Option<Vec<Notification>>> {
+    let mut tx = pool.begin().await?;
+    if !db::try_lock_reminders(&mut tx).await? {
+        return Ok(None);
+    }
+
+    let candidates = db::get_digest_candidates(&mut tx, now).await?;
+    let digests: Vec<(String, _)> = candidates
+        .iter()
+        .map(|candidate| (candidate.user_id.clone(), candidate.summary()))
+        .filter(|(_, summary)| !summary.is_empty())
+        .collect();
+
+    let created = db::create_digest_notifications(&mut tx, &digests).await?;
+    let by_user: HashMap<&str, &DigestCandidate> = candidates
+        .iter()
+        .map(|candidate| (candidate.user_id.as_str(), candidate))
+        .collect();
+    let deliveries: Vec<NewDelivery> = created
+        .iter()
+        .filter_map(|notification| {
+            let candidate = by_user.get(notification.user_id.as_str())?;
+            Some(deliveries_for(
+                notification.id,
+                notification.kind,
+                (*candidate).into(),
+                channels,
+                now,
+            ))
+        })
+        .flatten()
+        .collect();
+    db::enqueue_deliveries(&mut tx, &deliveries).await?;
+
+    let user_ids: Vec<String> = candidates.into_iter().map(|c| c.user_id).collect();
+    db::start_digest_periods(&mut tx, &user_ids, now).await?;
+    tx.commit().await?;
+    Ok(Some(created))
+}
+
+/// How a recipient wants to hear from Garry; `None` where they saved no
+/// preferences, which means every channel, no quiet hours, the default
+/// timezone and individual reminders.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeliveryPreferences<'a> {
+    pub channels: Option<&'a [NotificationChannel]>,
+    pub quiet_hours_start: Option<NaiveTime>,
+    pub quiet_hours_end: Option<NaiveTime>,
+    pub timezone: Option<&'a str>,
+    pub mode: Option<ReminderMode>,
+}
+
+impl<'a> From<&'a ReminderCandidate> for DeliveryPreferences<'a> {
+    fn from(candidate: &'a ReminderCandidate) -> Self {
+        DeliveryPreferences {
+            channels: candidate.channels.as_deref(),
+            quiet_hours_start: candidate.quiet_hours_start,
+            quiet_hours_end: candidate.quiet_hours_end,
+            timezone: candidate.timezone.as_deref(),
+            mode: candidate.mode,
+        }
+    }
+}
+
+impl<'a> From<&'a MaintenanceCandidate> for DeliveryPreferences<'a> {
+    fn from(candidate: &'a MaintenanceCandidate) -> Self {
+        DeliveryPreferences {
+            channels: candidate.channels.as_deref(),
+            quiet_hours_start: candidate.quiet_hours_start,
+            quiet_hours_end: candidate.quiet_hours_end,
+            timezone: candidate.timezone.as_deref(),
+            mode: None,
+        }
+    }
+}
+
+impl<'a> From<&'a DigestCandidate> for DeliveryPreferences<'a> {
+    fn from(candidate: &'a DigestCandidate) -> Self {
+        DeliveryPreferences {
+            channels: Some(&candidate.channels),
+            quiet_hours_start: candidate.quiet_hours_start,
+            quiet_hours_end: candidate.quiet_hours_end,
+            timezone: Some(&candidate.timezone),
+            mode: None,
+        }
+    }
+}
+
+/// The deliveries of one notification of `kind` under its recipient's
+/// `preferences`: one per configured channel they enabled, held back until
+/// their quiet hours end. Expiry reminders get none in digest mode, where the
+/// digest lists them instead; the digest does not cover maintenance, so
+/// maintenance reminders still go out one by one.
+pub fn deliveries_for(
+    notification_id: Uuid,
+    kind: NotificationKind,
+    preferences: DeliveryPreferences<'_>,
+    channels: &[NotificationChannel],
+    now: DateTime<Utc>,
+) -> Vec<NewDelivery> {
+    if kind == NotificationKind::ExpiryReminder && preferences.mode == Some(ReminderMode::Digest) {
+        return Vec::new();
+    }
+
+    let run_after = send_after(
+        preferences.quiet_hours_start,
+        preferences.quiet_hours_end,
+        preferences.timezone,
+        now,
+    );
+    channels
+        .iter()
+        .filter(|channel| {
+            preferences
+                .channels
+                .is_none_or(|enabled| enabled.contains(channel))
+        })
+        .map(|&channel| NewDelivery {
+            notification_id,
+            channel,
+            run_after,
+        })
+        .collect()
+}
+
+/// `now`, or the end of the quiet hours it falls in.
+fn send_after(
+    quiet_hours_start: Option<NaiveTime>,
+    quiet_hours_end: Option<NaiveTime>,
+    timezone: Option<&str>,
+    now: DateTime<Utc>,
+) -> DateTime<Utc> {
+    let timezone = timezone
+        .unwrap_or(DEFAULT_TIMEZONE)
+        .parse()
+        .unwrap_or(Tz::UTC);
+    match (quiet_hours_start, quiet_hours_end) {
+        (Some(start), Some(end)) => quiet_until(now, start, end, timezone).unwrap_or(now),
+        _ => now,
+    }
+}
+
+/// If `now` falls in the quiet hours from `start` to `end`, local times in
+/// `timezone` that may span midnight, the moment they end.
+pub fn quiet_until(
+    now: DateTime<Utc>,
+    start: NaiveTime,
+    end: NaiveTime,
+    timezone: Tz,
+) -> Option<DateTime<Utc>> {
+    let local = now.with_timezone(&timezone);
+    let time = local.time();
+    let quiet = if start <= end {
+        start <= time && time < end
+    } else {
+        time >= start || time < end
+    };
+    if !quiet {
+        return None;
+    }
+
+    let date = if time < end {
+        local.date_naive()
+    } else {
+        local.date_naive().succ_opt()?
+    };
+    let end = date.and_time(end);
+    // An end skipped by a daylight saving jump falls an hour later.
+    timezone
+        .from_local_datetime(&end)
+        .earliest()
+        .or_else(|| {
+            timezone
+                .from_local_datetime(&(end + chrono::Duration::hours(1)))
+                .earliest()
+        })
+        .map(|end| end.with_timezone(&Utc))
+}
+
+/// Checks lead times given by a user. An empty list turns reminders off.
+pub fn validate_lead_days(lead_days: &[i32]) -> std::result::Result<(), String> {
+    if lead_days.len() > MAX_LEAD_TIMES {
+        return Err(format!(
+            "At most {} reminder lead times are allowed",
+            MAX_LEAD_TIMES
+        ));
+    }
+    if lead_days
+        .iter()
+        .any(|days| !(1..=MAX_LEAD_DAYS).contains(days))
+    {
+        return Err(format!(
+            "Reminder lead days must be between 1 and {}",
+            MAX_LEAD_DAYS
+        ));
+    }
+    Ok(())
+}
+
+/// The tightest threshold a warranty ending at `end` has crossed by `now`.
+///
+/// Only that one fires: a warranty added 5 days before its end gets the
+/// 7-day reminder, not the 60- and 30-day ones as well. Wider thresholds it
+/// skipped never fire later, since they stay wider than the tightest.
+pub fn crossed_threshold(
+    thresholds: &[i32],
+    now: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Option<i32> {
+    if end <= now {
+        return None;
+    }
+    thresholds
+        .iter()
+        .copied()
+        .filter(|days| *days > 0 && end <= now + chrono::Duration::days((*days).into()))
+        .min()
+}
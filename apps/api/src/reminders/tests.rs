@@ -0,0 +1,260 @@
+use chrono::{Duration, NaiveTime, TimeZone, Utc};
+use chrono_tz::Europe::Paris;
+use uuid::Uuid;
+
+use crate::db::{DigestCandidate, MaintenanceCandidate, ReminderCandidate};
+use crate::models::{DigestFrequency, NotificationChannel, NotificationKind, ReminderMode};
+use crate::reminders::{
+    crossed_threshold, deliveries_for, quiet_until, validate_lead_days, DeliveryPreferences,
+};
+
+const THRESHOLDS: &[i32] = &[60, 30, 7, 1];
+
+#[test]
+fn test_crossed_threshold_picks_tightest() {
+    let now = Utc.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap();
+
+    assert_eq!(
+        crossed_threshold(THRESHOLDS, now, now + Duration::days(90)),
+        None
+    );
+    assert_eq!(
+        crossed_threshold(THRESHOLDS, now, now + Duration::days(60)),
+        Some(60)
+    );
+    assert_eq!(
+        crossed_threshold(THRESHOLDS, now, now + Duration::days(45)),
+        Some(60)
+    );
+    // Added late: straight to the 7-day reminder.
+    assert_eq!(
+        crossed_threshold(THRESHOLDS, now, now + Duration::days(5)),
+        Some(7)
+    );
+    assert_eq!(
+        crossed_threshold(THRESHOLDS, now, now + Duration::hours(3)),
+        Some(1)
+    );
+}
+
+#[test]
+fn test_crossed_threshold_ignores_expired_warranties() {
+    let now = Utc.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap();
+
+    assert_eq!(crossed_threshold(THRESHOLDS, now, now), None);
+    assert_eq!(
+        crossed_threshold(THRESHOLDS, now, now - Duration::days(2)),
+        None
+    );
+}
+
+#[test]
+fn test_crossed_threshold_without_thresholds() {
+    let now = Utc::now();
+
+    assert_eq!(crossed_threshold(&[], now, now + Duration::days(1)), None);
+    assert_eq!(
+        crossed_threshold(&[0, -3], now, now + Duration::days(1)),
+        None
+    );
+}
+
+fn time(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+}
+
+#[test]
+fn test_quiet_until_across_midnight() {
+    // 23:30 in Paris, winter time.
+    let now = Utc.with_ymd_and_hms(2025, 1, 10, 22, 30, 0).unwrap();
+    assert_eq!(
+        quiet_until(now, time(22, 0), time(7, 0), Paris),
+        Some(Utc.with_ymd_and_hms(2025, 1, 11, 6, 0, 0).unwrap())
+    );
+
+    // 05:00 the next morning, still quiet.
+    let now = Utc.with_ymd_and_hms(2025, 1, 11, 4, 0, 0).unwrap();
+    assert_eq!(
+        quiet_until(now, time(22, 0), time(7, 0), Paris),
+        Some(Utc.with_ymd_and_hms(2025, 1, 11, 6, 0, 0).unwrap())
+    );
+
+    // 12:00, outside.
+    let now = Utc.with_ymd_and_hms(2025, 1, 11, 11, 0, 0).unwrap();
+    assert_eq!(quiet_until(now, time(22, 0), time(7, 0), Paris), None);
+}
+
+#[test]
+fn test_quiet_until_within_a_day_and_daylight_saving() {
+    // 13:00 in Paris, summer time.
+    let now = Utc.with_ymd_and_hms(2025, 7, 1, 11, 0, 0).unwrap();
+    assert_eq!(
+        quiet_until(now, time(12, 0), time(14, 0), Paris),
+        Some(Utc.with_ymd_and_hms(2025, 7, 1, 12, 0, 0).unwrap())
+    );
+    assert_eq!(quiet_until(now, time(14, 0), time(16, 0), Paris), None);
+
+    // 02:30 does not exist on 30 March 2025 in Paris: quiet hours ending then
+    // end at 03:30 summer time instead.
+    let now = Utc.with_ymd_and_hms(2025, 3, 29, 23, 0, 0).unwrap();
+    assert_eq!(
+        quiet_until(now, time(23, 0), time(2, 30), Paris),
+        Some(Utc.with_ymd_and_hms(2025, 3, 30, 1, 30, 0).unwrap())
+    );
+}
+
+fn candidate() -> ReminderCandidate {
+    ReminderCandidate {
+        warranty_id: Uuid::new_v4(),
+        user_id: "user-1".to_string(),
+        warranty_end_date: Utc::now() + Duration::days(7),
+        lead_days: vec![7],
+        channels: None,
+        quiet_hours_start: None,
+        quiet_hours_end: None,
+        timezone: None,
+        mode: None,
+    }
+}
+
+#[test]
+fn test_deliveries_follow_preferences() {
+    let now = Utc.with_ymd_and_hms(2025, 1, 10, 22, 30, 0).unwrap();
+    let configured = [NotificationChannel::Email, NotificationChannel::Push];
+    let id = Uuid::new_v4();
+
+    // Without preferences, every configured channel right away.
+    let expiry = NotificationKind::ExpiryReminder;
+    let deliveries = deliveries_for(id, expiry, (&candidate()).into(), &configured, now);
+    assert_eq!(deliveries.len(), 2);
+    assert!(deliveries.iter().all(|d| d.run_after == now));
+
+    let mut preferences = candidate();
+    preferences.channels = Some(vec![
+        NotificationChannel::Push,
+        NotificationChannel::Webhook,
+    ]);
+    preferences.quiet_hours_start = Some(time(22, 0));
+    preferences.quiet_hours_end = Some(time(7, 0));
+    preferences.timezone = Some("Europe/Paris".to_string());
+    let deliveries = deliveries_for(id, expiry, (&preferences).into(), &configured, now);
+    assert_eq!(deliveries.len(), 1);
+    assert_eq!(deliveries[0].channel, NotificationChannel::Push);
+    assert_eq!(
+        deliveries[0].run_after,
+        Utc.with_ymd_and_hms(2025, 1, 11, 6, 0, 0).unwrap()
+    );
+
+    preferences.mode = Some(ReminderMode::Digest);
+    assert!(deliveries_for(id, expiry, (&preferences).into(), &configured, now).is_empty());
+}
+
+#[test]
+fn test_maintenance_deliveries_follow_preferences() {
+    let now = Utc.with_ymd_and_hms(2025, 1, 10, 22, 30, 0).unwrap();
+    let configured = [NotificationChannel::Email, NotificationChannel::Push];
+    let mut candidate = MaintenanceCandidate {
+        task_id: Uuid::new_v4(),
+        warranty_id: Uuid::new_v4(),
+        user_id: "user-1".to_string(),
+        next_due_at: now + Duration::days(7),
+        channels: Some(vec![NotificationChannel::Email]),
+        quiet_hours_start: None,
+        quiet_hours_end: None,
+        timezone: None,
+    };
+
+    let due = NotificationKind::MaintenanceDue;
+    let deliveries = deliveries_for(Uuid::new_v4(), due, (&candidate).into(), &configured, now);
+    assert_eq!(deliveries.len(), 1);
+    assert_eq!(deliveries[0].channel, NotificationChannel::Email);
+    assert_eq!(deliveries[0].run_after, now);
+
+    candidate.quiet_hours_start = Some(time(22, 0));
+    candidate.quiet_hours_end = Some(time(7, 0));
+    candidate.timezone = Some("Europe/Paris".to_string());
+    let deliveries = deliveries_for(Uuid::new_v4(), due, (&candidate).into(), &configured, now);
+    assert_eq!(
+        deliveries[0].run_after,
+        Utc.with_ymd_and_hms(2025, 1, 11, 6, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn test_digest_mode_only_holds_back_expiry_reminders() {
+    let now = Utc.with_ymd_and_hms(2025, 1, 10, 12, 0, 0).unwrap();
+    let configured = [NotificationChannel::Email];
+    let preferences = DeliveryPreferences {
+        mode: Some(ReminderMode::Digest),
+        ..DeliveryPreferences::default()
+    };
+
+    for (kind, count) in [
+        (NotificationKind::ExpiryReminder, 0),
+        (NotificationKind::MaintenanceDue, 1),
+        (NotificationKind::Digest, 1),
+    ] {
+        let deliveries = deliveries_for(Uuid::new_v4(), kind, preferences, &configured, now);
+        assert_eq!(deliveries.len(), count, "{:?}", kind);
+    }
+}
+
+fn digest_candidate() -> DigestCandidate {
+    DigestCandidate {
+        user_id: "user-1".to_string(),
+        channels: vec![NotificationChannel::Email],
+        quiet_hours_start: Some(time(22, 0)),
+        quiet_hours_end: Some(time(7, 0)),
+        timezone: "Europe/Paris".to_string(),
+        frequency: DigestFrequency::Weekly,
+        since: Utc.with_ymd_and_hms(2025, 1, 3, 21, 30, 0).unwrap(),
+        expiring_within_30_days: 0,
+        expiring_within_90_days: 0,
+        expired_since_last_digest: 0,
+        missing_receipts: 0,
+        expiring: sqlx::types::Json(Vec::new()),
+    }
+}
+
+#[test]
+fn test_digest_deliveries_follow_preferences() {
+    let now = Utc.with_ymd_and_hms(2025, 1, 10, 22, 30, 0).unwrap();
+    let configured = [NotificationChannel::Email, NotificationChannel::Push];
+
+    let deliveries = deliveries_for(
+        Uuid::new_v4(),
+        NotificationKind::Digest,
+        (&digest_candidate()).into(),
+        &configured,
+        now,
+    );
+    assert_eq!(deliveries.len(), 1);
+    assert_eq!(deliveries[0].channel, NotificationChannel::Email);
+    assert_eq!(
+        deliveries[0].run_after,
+        Utc.with_ymd_and_hms(2025, 1, 11, 6, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn test_empty_digest_is_skipped() {
+    let mut candidate = digest_candidate();
+    assert!(candidate.summary().is_empty());
+
+    // Warranties ending in 31 to 90 days are still worth a digest.
+    candidate.expiring_within_90_days = 2;
+    assert!(!candidate.summary().is_empty());
+
+    let mut candidate = digest_candidate();
+    candidate.missing_receipts = 1;
+    assert!(!candidate.summary().is_empty());
+}
+
+#[test]
+fn test_validate_lead_days() {
+    assert!(validate_lead_days(&[60, 30, 7, 1]).is_ok());
+    assert!(validate_lead_days(&[]).is_ok());
+    assert!(validate_lead_days(&[0]).is_err());
+    assert!(validate_lead_days(&[400]).is_err());
+    assert!(validate_lead_days(&[1; 11]).is_err());
+}
@@ -0,0 +1,47 @@
+//! Full-text search over warranties: the `q` of a listing, and the snippets
+//! PostgreSQL highlights in its results.
+
+#[cfg(test)]
+mod tests;
+
+use crate::notifications::templates::escape_html;
+
+pub const MAX_QUERY_LENGTH: usize = 200;
+
+/// Text search configuration of the index: French stemming on unaccented
+/// words.
+pub const TEXT_SEARCH_CONFIG: &str = "french_unaccent";
+
+/// Marks PostgreSQL puts around matched words, turned into `<mark>` once the
+/// rest of the snippet is escaped.
+const START_SEL: char = '\u{2}';
+const STOP_SEL: char = '\u{3}';
+
+/// Options of `ts_headline`: up to two fragments of a few words each.
+pub fn headline_options() -> String {
+    format!(
+        "StartSel={}, StopSel={}, MaxFragments=2, MinWords=5, MaxWords=20, FragmentDelimiter=\" … \"",
+        START_SEL, STOP_SEL
+    )
+}
+
+/// The search terms of a listing, or `None` when it has none.
+pub fn normalize_query(q: Option<&str>) -> Result<Option<String>, String> {
+    let Some(q) = q.map(str::trim).filter(|q| !q.is_empty()) else {
+        return Ok(None);
+    };
+    if q.chars().count() > MAX_QUERY_LENGTH {
+        return Err(format!(
+            "Search query must be at most {} characters",
+            MAX_QUERY_LENGTH
+        ));
+    }
+    Ok(Some(q.to_string()))
+}
+
+/// HTML snippet of a `ts_headline`, with matched words in `<mark>`.
+pub fn highlight(headline: &str) -> String {
+    escape_html(headline.trim())
+        .replace(START_SEL, "<mark>")
+        .replace(STOP_SEL, "</mark>")
+}
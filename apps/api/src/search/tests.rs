@@ -0,0 +1,31 @@
+use crate::search::{headline_options, highlight, normalize_query, MAX_QUERY_LENGTH};
+
+#[test]
+fn test_normalize_query() {
+    assert_eq!(normalize_query(None), Ok(None));
+    assert_eq!(normalize_query(Some("   ")), Ok(None));
+    assert_eq!(
+        normalize_query(Some("  lave-linge bosch ")),
+        Ok(Some("lave-linge bosch".to_string()))
+    );
+
+    let accented = "é".repeat(MAX_QUERY_LENGTH);
+    assert!(normalize_query(Some(&accented)).is_ok());
+    assert!(normalize_query(Some(&format!("{}e", accented))).is_err());
+}
+
+#[test]
+fn test_highlight_escapes_and_marks() {
+    assert_eq!(
+        highlight("\u{2}Réfrigérateur\u{3} <Samsung> & \u{2}électroménager\u{3} "),
+        "<mark>Réfrigérateur</mark> &lt;Samsung&gt; &amp; <mark>électroménager</mark>"
+    );
+    assert_eq!(highlight("Garantie"), "Garantie");
+}
+
+#[test]
+fn test_headline_options_use_the_marks() {
+    let options = headline_options();
+    assert!(options.starts_with("StartSel=\u{2}, StopSel=\u{3},"));
+    assert!(options.contains("MaxFragments=2"));
+}
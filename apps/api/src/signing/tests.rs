@@ -0,0 +1,98 @@
+use chrono::{Duration, TimeZone, Utc};
+use uuid::Uuid;
+
+use crate::signing::{percent_encode, sign, signed_receipt_url, verify, verify_receipt_url};
+
+const KEY: &str = "test-signing-key";
+
+#[test]
+fn test_sign_is_hmac_sha256() {
+    // RFC 4231 test case 2.
+    assert_eq!(
+        sign("Jefe", "what do ya want for nothing?"),
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+}
+
+#[test]
+fn test_verify_rejects_tampering() {
+    let signature = sign(KEY, "message");
+    assert!(verify(KEY, "message", &signature));
+    assert!(!verify(KEY, "other message", &signature));
+    assert!(!verify("other-key", "message", &signature));
+    assert!(!verify(KEY, "message", "not-hex"));
+}
+
+#[test]
+fn test_percent_encode_keeps_only_unreserved_characters() {
+    assert_eq!(percent_encode("AZaz09-_.~"), "AZaz09-_.~");
+    assert_eq!(percent_encode("a b/c+d=é&"), "a%20b%2Fc%2Bd%3D%C3%A9%26");
+}
+
+#[test]
+fn test_signed_receipt_url_round_trip() {
+    let id = Uuid::new_v4();
+    let now = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+    let signed = signed_receipt_url(KEY, id, "user-1", "original", now, Duration::minutes(15));
+
+    assert_eq!(signed.expires_at, now + Duration::minutes(15));
+    assert!(signed.url.starts_with(&format!(
+        "/api/v1/files/receipts/{}?uid=user-1&variant=original&expires=",
+        id
+    )));
+
+    let expires = signed.expires_at.timestamp();
+    let signature = signed.url.rsplit("signature=").next().unwrap();
+    assert!(verify_receipt_url(
+        KEY, id, "user-1", "original", expires, signature, now
+    ));
+    assert!(!verify_receipt_url(
+        KEY, id, "user-2", "original", expires, signature, now
+    ));
+    assert!(!verify_receipt_url(
+        KEY,
+        id,
+        "user-1",
+        "thumbnail",
+        expires,
+        signature,
+        now
+    ));
+    assert!(!verify_receipt_url(
+        KEY,
+        Uuid::new_v4(),
+        "user-1",
+        "original",
+        expires,
+        signature,
+        now
+    ));
+    assert!(!verify_receipt_url(
+        KEY,
+        id,
+        "user-1",
+        "original",
+        expires + 60,
+        signature,
+        now
+    ));
+}
+
+#[test]
+fn test_signed_receipt_url_expires() {
+    let id = Uuid::new_v4();
+    let now = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+    let signed = signed_receipt_url(KEY, id, "user-1", "original", now, Duration::minutes(15));
+    let expires = signed.expires_at.timestamp();
+    let signature = signed.url.rsplit("signature=").next().unwrap();
+
+    assert!(!verify_receipt_url(
+        KEY,
+        id,
+        "user-1",
+        "original",
+        expires,
+        signature,
+        now + Duration::minutes(15)
+    ));
+}
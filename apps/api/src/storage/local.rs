@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::StreamExt;
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+
+use super::{validate_key, Storage, StoredObject};
+use crate::error::{AppError, Result};
+
+/// Stores blobs as plain files under `UPLOAD_DIR`.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path_for(&self, key: &str) -> Result<PathBuf> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, _content_type: &str, data: Bytes) -> Result<()> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| AppError::Storage(format!("create {}: {}", parent.display(), e)))?;
+        }
+
+        // Write next to the target and rename so readers never see a partial file.
+        let tmp_path = path.with_extension(format!("{}.tmp", Uuid::new_v4()));
+        tokio::fs::write(&tmp_path, &data)
+            .await
+            .map_err(|e| AppError::Storage(format!("write {}: {}", tmp_path.display(), e)))?;
+        tokio::fs::rename(&tmp_path, &path)
+            .await
+            .map_err(|e| AppError::Storage(format!("rename {}: {}", path.display(), e)))?;
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<StoredObject> {
+        let path = self.path_for(key)?;
+        let file = match tokio::fs::File::open(&path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(AppError::NotFound("File not found".to_string()))
+            }
+            Err(e) => return Err(AppError::Storage(format!("open {}: {}", path.display(), e))),
+        };
+        let content_length = file.metadata().await.ok().map(|m| m.len());
+
+        Ok(StoredObject {
+            body: ReaderStream::new(file).boxed(),
+            content_length,
+        })
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let path = self.path_for(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::Storage(format!(
+                "delete {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+}
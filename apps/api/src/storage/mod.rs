@@ -0,0 +1,184 @@
+mod local;
+mod s3;
+#[cfg(test)]
+mod tests;
+
+pub use local::LocalStorage;
+pub use s3::S3Storage;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use futures::stream::BoxStream;
+use futures::TryStreamExt;
+use uuid::Uuid;
+
+use crate::config::{Config, StorageBackend};
+use crate::error::{AppError, Result};
+
+pub const ALLOWED_CONTENT_TYPES: &[&str] = &[
+    "image/jpeg",
+    "image/png",
+    "image/webp",
+    "image/heic",
+    "application/pdf",
+];
+
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn put(&self, key: &str, content_type: &str, data: Bytes) -> Result<()>;
+    /// Opens a blob for streaming. Missing blobs are reported as `NotFound`.
+    async fn get(&self, key: &str) -> Result<StoredObject>;
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+pub struct StoredObject {
+    pub body: BoxStream<'static, std::io::Result<Bytes>>,
+    pub content_length: Option<u64>,
+}
+
+impl StoredObject {
+    /// Buffers the whole blob, for consumers that need it in memory.
+    pub async fn into_bytes(self) -> Result<Bytes> {
+        let data = self
+            .body
+            .try_fold(BytesMut::new(), |mut acc, chunk| async move {
+                acc.extend_from_slice(&chunk);
+                Ok(acc)
+            })
+            .await
+            .map_err(|e| AppError::Storage(format!("read blob: {}", e)))?;
+        Ok(data.freeze())
+    }
+}
+
+pub fn from_config(config: &Config) -> Arc<dyn Storage> {
+    match &config.storage_backend {
+        StorageBackend::Local => Arc::new(LocalStorage::new(&config.upload_dir)),
+        StorageBackend::S3(s3) => Arc::new(S3Storage::new(s3.clone())),
+    }
+}
+
+/// Best-effort removal of blobs whose database rows are already gone; failures
+/// are logged rather than surfaced to the caller.
+pub async fn delete_blobs(storage: &dyn Storage, keys: impl IntoIterator<Item = String>) {
+    for key in keys {
+        if let Err(e) = storage.delete(&key).await {
+            tracing::warn!(error = %e, key = %key, "failed to delete blob");
+        }
+    }
+}
+
+pub fn receipt_key(user_id: &str, warranty_id: Uuid, content_type: &str) -> String {
+    format!(
+        "{}/{}/receipt.{}",
+        user_id,
+        warranty_id,
+        extension_for(content_type)
+    )
+}
+
+pub fn receipt_thumbnail_key(user_id: &str, warranty_id: Uuid) -> String {
+    format!("{}/{}/receipt-thumbnail.jpg", user_id, warranty_id)
+}
+
+pub fn extraction_key(user_id: &str, extraction_id: Uuid, content_type: &str) -> String {
+    format!(
+        "{}/extractions/{}.{}",
+        user_id,
+        extraction_id,
+        extension_for(content_type)
+    )
+}
+
+pub fn attachment_key(
+    user_id: &str,
+    warranty_id: Uuid,
+    attachment_id: Uuid,
+    content_type: &str,
+) -> String {
+    format!(
+        "{}/{}/attachments/{}.{}",
+        user_id,
+        warranty_id,
+        attachment_id,
+        extension_for(content_type)
+    )
+}
+
+pub fn extension_for(content_type: &str) -> &'static str {
+    match content_type {
+        "image/jpeg" => "jpg",
+        "image/png" => "png",
+        "image/webp" => "webp",
+        "image/heic" => "heic",
+        "application/pdf" => "pdf",
+        _ => "bin",
+    }
+}
+
+/// Checks the declared content type of an upload against its magic bytes and
+/// returns the canonical content type to store it under.
+pub fn validate_content_type(declared: Option<&str>, data: &[u8]) -> Result<&'static str> {
+    let declared = match declared.map(|ct| ct.trim().to_ascii_lowercase()) {
+        Some(ct) if ct == "image/jpg" => "image/jpeg".to_string(),
+        Some(ct) if ct == "image/heif" => "image/heic".to_string(),
+        Some(ct) => ct,
+        None => {
+            return Err(AppError::UnsupportedMediaType(
+                "File content type is required".to_string(),
+            ))
+        }
+    };
+
+    if !ALLOWED_CONTENT_TYPES.contains(&declared.as_str()) {
+        return Err(AppError::UnsupportedMediaType(format!(
+            "Content type {} is not allowed",
+            declared
+        )));
+    }
+
+    match sniff_content_type(data) {
+        Some(sniffed) if sniffed == declared => Ok(sniffed),
+        _ => Err(AppError::UnsupportedMediaType(format!(
+            "File content does not match {}",
+            declared
+        ))),
+    }
+}
+
+pub fn sniff_content_type(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if data.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some("image/png")
+    } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if data.len() >= 12
+        && &data[4..8] == b"ftyp"
+        && matches!(
+            &data[8..12],
+            b"heic" | b"heix" | b"heim" | b"heis" | b"hevc" | b"mif1" | b"msf1"
+        )
+    {
+        Some("image/heic")
+    } else if data.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else {
+        None
+    }
+}
+
+fn validate_key(key: &str) -> Result<()> {
+    let is_safe = !key.contains('\\')
+        && key
+            .split('/')
+            .all(|segment| !segment.is_empty() && segment != "." && segment != "..");
+
+    if is_safe {
+        Ok(())
+    } else {
+        Err(AppError::Storage(format!("Invalid storage key: {}", key)))
+    }
+}
@@ -0,0 +1,212 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::Utc;
+use futures::{StreamExt, TryStreamExt};
+use hmac::{Hmac, Mac};
+use reqwest::{header, Method, Url};
+use sha2::{Digest, Sha256};
+
+use super::{validate_key, Storage, StoredObject};
+use crate::config::S3Config;
+use crate::error::{AppError, Result};
+use crate::signing::percent_encode;
+
+const EMPTY_PAYLOAD_SHA256: &str =
+    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+/// Talks to any S3-compatible object store (AWS S3, MinIO, Garage, ...) using
+/// path-style addressing and AWS Signature Version 4.
+pub struct S3Storage {
+    client: reqwest::Client,
+    config: S3Config,
+}
+
+impl S3Storage {
+    pub fn new(config: S3Config) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    fn request(
+        &self,
+        method: Method,
+        key: &str,
+        payload_hash: &str,
+    ) -> Result<reqwest::RequestBuilder> {
+        validate_key(key)?;
+
+        let endpoint = Url::parse(&self.config.endpoint)
+            .map_err(|e| AppError::Storage(format!("invalid S3 endpoint: {}", e)))?;
+        let host = match (endpoint.host_str(), endpoint.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(AppError::Storage("S3 endpoint has no host".to_string())),
+        };
+
+        let canonical_uri = format!(
+            "{}/{}/{}",
+            endpoint.path().trim_end_matches('/'),
+            percent_encode(&self.config.bucket),
+            key.split('/')
+                .map(percent_encode)
+                .collect::<Vec<_>>()
+                .join("/")
+        );
+        let url = format!("{}://{}{}", endpoint.scheme(), host, canonical_uri);
+
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let authorization = authorization_header(
+            &SigningParams {
+                access_key_id: &self.config.access_key_id,
+                secret_access_key: &self.config.secret_access_key,
+                region: &self.config.region,
+                amz_date: &amz_date,
+            },
+            method.as_str(),
+            &canonical_uri,
+            &[
+                ("host", &host),
+                ("x-amz-content-sha256", payload_hash),
+                ("x-amz-date", &amz_date),
+            ],
+            payload_hash,
+        );
+
+        Ok(self
+            .client
+            .request(method, url)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .header(header::AUTHORIZATION, authorization))
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, content_type: &str, data: Bytes) -> Result<()> {
+        let payload_hash = hex::encode(Sha256::digest(&data));
+        let response = self
+            .request(Method::PUT, key, &payload_hash)?
+            .header(header::CONTENT_TYPE, content_type)
+            .body(data)
+            .send()
+            .await
+            .map_err(|e| AppError::Storage(format!("S3 PUT {}: {}", key, e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Storage(format!(
+                "S3 PUT {} returned {}",
+                key,
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<StoredObject> {
+        let response = self
+            .request(Method::GET, key, EMPTY_PAYLOAD_SHA256)?
+            .send()
+            .await
+            .map_err(|e| AppError::Storage(format!("S3 GET {}: {}", key, e)))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(AppError::NotFound("File not found".to_string()));
+        }
+        if !status.is_success() {
+            return Err(AppError::Storage(format!(
+                "S3 GET {} returned {}",
+                key, status
+            )));
+        }
+
+        Ok(StoredObject {
+            content_length: response.content_length(),
+            body: response
+                .bytes_stream()
+                .map_err(std::io::Error::other)
+                .boxed(),
+        })
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let response = self
+            .request(Method::DELETE, key, EMPTY_PAYLOAD_SHA256)?
+            .send()
+            .await
+            .map_err(|e| AppError::Storage(format!("S3 DELETE {}: {}", key, e)))?;
+
+        let status = response.status();
+        if !status.is_success() && status != reqwest::StatusCode::NOT_FOUND {
+            return Err(AppError::Storage(format!(
+                "S3 DELETE {} returned {}",
+                key, status
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+pub(crate) struct SigningParams<'a> {
+    pub access_key_id: &'a str,
+    pub secret_access_key: &'a str,
+    pub region: &'a str,
+    pub amz_date: &'a str,
+}
+
+/// Builds the `Authorization` header value for an S3 request.
+///
+/// `headers` must be lowercase and sorted by name; all of them are signed.
+pub(crate) fn authorization_header(
+    params: &SigningParams,
+    method: &str,
+    canonical_uri: &str,
+    headers: &[(&str, &str)],
+    payload_hash: &str,
+) -> String {
+    let date = &params.amz_date[..8];
+    let scope = format!("{}/{}/s3/aws4_request", date, params.region);
+
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let canonical_request = format!(
+        "{}\n{}\n\n{}\n{}\n{}",
+        method, canonical_uri, canonical_headers, signed_headers, payload_hash
+    );
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        params.amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let signing_key = [date, params.region, "s3", "aws4_request"].iter().fold(
+        format!("AWS4{}", params.secret_access_key).into_bytes(),
+        |key, part| hmac_sha256(&key, part.as_bytes()),
+    );
+    let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        params.access_key_id, scope, signed_headers, signature
+    )
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
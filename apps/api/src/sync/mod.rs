@@ -0,0 +1,38 @@
+//! Delta sync of offline clients: the token that tells how far the copy of a
+//! client goes, and the limits of what it may push back.
+
+#[cfg(test)]
+mod tests;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+/// Changes a client may push at once.
+pub const MAX_PUSH_CHANGES: usize = 100;
+
+/// Where the last sync of a client stopped: the oldest transaction still
+/// running then. Changes made by it and later ones are sent on the next
+/// sync, so a change committed late is never skipped; one already sent may
+/// come again.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SyncToken {
+    pub xmin: i64,
+}
+
+impl SyncToken {
+    pub fn encode(&self) -> String {
+        // Serializing plain data to JSON cannot fail.
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(token: &str) -> Result<Self, String> {
+        let invalid = || "Invalid sync token".to_string();
+        let bytes = URL_SAFE_NO_PAD.decode(token).map_err(|_| invalid())?;
+        let token: SyncToken = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+        if token.xmin < 0 {
+            return Err(invalid());
+        }
+        Ok(token)
+    }
+}
@@ -0,0 +1,27 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+
+use crate::sync::SyncToken;
+
+#[test]
+fn test_sync_token_round_trip() {
+    let token = SyncToken {
+        xmin: 4_294_967_301,
+    };
+    let encoded = token.encode();
+
+    assert!(encoded
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    assert_eq!(SyncToken::decode(&encoded), Ok(token));
+}
+
+#[test]
+fn test_invalid_sync_token() {
+    assert_eq!(
+        SyncToken::decode("not a token"),
+        Err("Invalid sync token".to_string())
+    );
+    assert!(SyncToken::decode(&URL_SAFE_NO_PAD.encode(br#"{"xmin":"12"}"#)).is_err());
+    assert!(SyncToken::decode(&URL_SAFE_NO_PAD.encode(br#"{"xmin":-1}"#)).is_err());
+}
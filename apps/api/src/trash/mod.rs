@@ -0,0 +1,71 @@
+//! The trash: deleted warranties are kept for a retention period so they can
+//! be restored, then purged with their stored files.
+
+#[cfg(test)]
+mod tests;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::db;
+use crate::error::Result;
+use crate::storage::Storage;
+
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Warranties purged per transaction.
+const PURGE_BATCH: i64 = 100;
+
+/// When a warranty trashed at `deleted_at` is purged.
+pub fn purge_at(deleted_at: DateTime<Utc>, retention_days: i64) -> DateTime<Utc> {
+    deleted_at + chrono::Duration::days(retention_days)
+}
+
+/// Warranties trashed before this are due for purging at `now`.
+pub fn purge_cutoff(now: DateTime<Utc>, retention_days: i64) -> DateTime<Utc> {
+    now - chrono::Duration::days(retention_days)
+}
+
+/// Starts the background task that purges the trash every hour. Every replica
+/// can run one: warranties are claimed with `SKIP LOCKED`.
+pub fn spawn_purger(
+    pool: PgPool,
+    storage: Arc<dyn Storage>,
+    retention_days: i64,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(PURGE_INTERVAL);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            ticker.tick().await;
+            match purge_once(&pool, storage.as_ref(), retention_days, Utc::now()).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!(count, "trashed warranties purged"),
+                Err(e) => tracing::warn!(error = %e, "trash purge failed"),
+            }
+        }
+    })
+}
+
+/// Purges every warranty whose retention period is over at `now`. Returns
+/// how many were purged.
+pub async fn purge_once(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    retention_days: i64,
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    let cutoff = purge_cutoff(now, retention_days);
+    let mut purged = 0;
+    loop {
+        let count = db::purge_trashed_warranties(pool, storage, cutoff, PURGE_BATCH).await?;
+        purged += count;
+        if (count as i64) < PURGE_BATCH {
+            return Ok(purged);
+        }
+    }
+}
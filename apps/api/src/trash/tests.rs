@@ -0,0 +1,23 @@
+use chrono::{Duration, TimeZone, Utc};
+
+use crate::trash::{purge_at, purge_cutoff};
+
+#[test]
+fn test_purge_at_after_retention() {
+    let deleted_at = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+
+    assert_eq!(
+        purge_at(deleted_at, 30),
+        Utc.with_ymd_and_hms(2026, 3, 31, 12, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn test_purge_cutoff_matches_purge_at() {
+    let deleted_at = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+    let due = purge_at(deleted_at, 30);
+
+    // Purged on the first run after its purge date, not before.
+    assert!(deleted_at >= purge_cutoff(due, 30));
+    assert!(deleted_at < purge_cutoff(due + Duration::seconds(1), 30));
+}
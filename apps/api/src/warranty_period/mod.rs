@@ -0,0 +1,39 @@
+//! When a warranty ends: the purchase date plus the warranty length in
+//! calendar months, counted on the owner's local calendar.
+
+#[cfg(test)]
+mod tests;
+
+use chrono::{DateTime, Duration, Months, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// `date` plus `months` calendar months. A day missing from the target month
+/// is clamped to its last day: January 31 plus one month is February 28, or
+/// 29 in a leap year.
+pub fn add_months(date: NaiveDate, months: u32) -> Option<NaiveDate> {
+    date.checked_add_months(Months::new(months))
+}
+
+/// The end of a warranty of `months` bought at `purchase_date`. The months
+/// are added to the purchase date as seen in `timezone`, so a purchase made
+/// on January 31 in Paris, 23:00 UTC on January 30, ends on the last day of
+/// the month in Paris too. The time of day is kept.
+pub fn warranty_end_date(
+    purchase_date: DateTime<Utc>,
+    months: i32,
+    timezone: Tz,
+) -> Option<DateTime<Utc>> {
+    let local = purchase_date.with_timezone(&timezone).naive_local();
+    let end_date = add_months(local.date(), u32::try_from(months).ok()?)?;
+    let end = end_date.and_time(local.time());
+    // A time skipped by a daylight saving jump falls an hour later.
+    timezone
+        .from_local_datetime(&end)
+        .earliest()
+        .or_else(|| {
+            timezone
+                .from_local_datetime(&(end + Duration::hours(1)))
+                .earliest()
+        })
+        .map(|end| end.with_timezone(&Utc))
+}
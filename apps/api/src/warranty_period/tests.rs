@@ -0,0 +1,139 @@
+use chrono::{Datelike, NaiveDate, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use proptest::prelude::*;
+
+use crate::warranty_period::{add_months, warranty_end_date};
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    date(next_year, next_month, 1).pred_opt().unwrap().day()
+}
+
+#[test]
+fn test_add_months_clamps_to_month_end() {
+    assert_eq!(add_months(date(2025, 1, 31), 1), Some(date(2025, 2, 28)));
+    assert_eq!(add_months(date(2024, 1, 31), 1), Some(date(2024, 2, 29)));
+    assert_eq!(add_months(date(2024, 2, 29), 12), Some(date(2025, 2, 28)));
+    assert_eq!(add_months(date(2024, 2, 29), 48), Some(date(2028, 2, 29)));
+    assert_eq!(add_months(date(2025, 3, 31), 1), Some(date(2025, 4, 30)));
+    assert_eq!(add_months(date(2025, 8, 31), 6), Some(date(2026, 2, 28)));
+    assert_eq!(add_months(date(2025, 11, 15), 2), Some(date(2026, 1, 15)));
+}
+
+#[test]
+fn test_two_years_is_not_720_days() {
+    let purchase = Utc.with_ymd_and_hms(2024, 3, 15, 10, 0, 0).unwrap();
+    assert_eq!(
+        warranty_end_date(purchase, 24, Tz::Europe__Paris),
+        Some(Utc.with_ymd_and_hms(2026, 3, 15, 10, 0, 0).unwrap())
+    );
+}
+
+#[test]
+fn test_end_date_follows_the_local_calendar() {
+    // January 31 at midnight in Paris is still January 30 in UTC.
+    let purchase = Tz::Europe__Paris
+        .with_ymd_and_hms(2025, 1, 31, 0, 0, 0)
+        .unwrap()
+        .with_timezone(&Utc);
+    assert_eq!(purchase.day(), 30);
+
+    let end = warranty_end_date(purchase, 1, Tz::Europe__Paris).unwrap();
+    let local = end.with_timezone(&Tz::Europe__Paris);
+    assert_eq!(local.date_naive(), date(2025, 2, 28));
+    assert_eq!(local.hour(), 0);
+    // Counted in UTC it would have ended on February 28 at 23:00 UTC, i.e.
+    // March 1 in Paris.
+    assert_eq!(end, Utc.with_ymd_and_hms(2025, 2, 27, 23, 0, 0).unwrap());
+}
+
+#[test]
+fn test_end_date_keeps_local_time_across_daylight_saving() {
+    // Bought in winter (UTC+1), ends in summer (UTC+2).
+    let purchase = Tz::Europe__Paris
+        .with_ymd_and_hms(2025, 1, 10, 9, 30, 0)
+        .unwrap()
+        .with_timezone(&Utc);
+    let end = warranty_end_date(purchase, 6, Tz::Europe__Paris).unwrap();
+    assert_eq!(end, Utc.with_ymd_and_hms(2025, 7, 10, 7, 30, 0).unwrap());
+}
+
+#[test]
+fn test_end_date_in_a_skipped_hour_moves_forward() {
+    // 02:30 on March 30, 2025 does not exist in Paris.
+    let purchase = Tz::Europe__Paris
+        .with_ymd_and_hms(2025, 1, 30, 2, 30, 0)
+        .unwrap()
+        .with_timezone(&Utc);
+    let end = warranty_end_date(purchase, 2, Tz::Europe__Paris).unwrap();
+    assert_eq!(
+        end.with_timezone(&Tz::Europe__Paris).naive_local(),
+        date(2025, 3, 30).and_hms_opt(3, 30, 0).unwrap()
+    );
+}
+
+#[test]
+fn test_invalid_months() {
+    let purchase = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    assert_eq!(warranty_end_date(purchase, -1, Tz::UTC), None);
+}
+
+proptest! {
+    #[test]
+    fn prop_add_months_lands_in_the_target_month(
+        year in 1990i32..2100,
+        month in 1u32..=12,
+        day in 1u32..=31,
+        months in 0u32..=120,
+    ) {
+        let day = day.min(days_in_month(year, month));
+        let start = date(year, month, day);
+        let end = add_months(start, months).unwrap();
+
+        let total = (year * 12 + month as i32 - 1) + months as i32;
+        prop_assert_eq!(end.year(), total.div_euclid(12));
+        prop_assert_eq!(end.month(), total.rem_euclid(12) as u32 + 1);
+        // Same day of the month, or the last day when the month is shorter.
+        prop_assert_eq!(end.day(), day.min(days_in_month(end.year(), end.month())));
+    }
+
+    #[test]
+    fn prop_add_months_is_monotonic(
+        days in 0i64..40_000,
+        months in 1u32..=120,
+    ) {
+        let start = date(1990, 1, 1) + chrono::Duration::days(days);
+        let next = start.succ_opt().unwrap();
+        prop_assert!(add_months(start, months).unwrap() <= add_months(next, months).unwrap());
+        prop_assert!(add_months(start, months).unwrap() < add_months(start, months + 1).unwrap());
+    }
+
+    #[test]
+    fn prop_end_date_is_months_later_on_the_local_calendar(
+        seconds in 631_152_000i64..4_102_444_800,
+        months in 1i32..=120,
+        timezone in prop::sample::select(vec![
+            Tz::UTC,
+            Tz::Europe__Paris,
+            Tz::America__New_York,
+            Tz::Pacific__Auckland,
+            Tz::Asia__Kolkata,
+        ]),
+    ) {
+        let purchase = Utc.timestamp_opt(seconds, 0).unwrap();
+        let end = warranty_end_date(purchase, months, timezone).unwrap();
+
+        let local_purchase = purchase.with_timezone(&timezone).date_naive();
+        let local_end = end.with_timezone(&timezone).date_naive();
+        prop_assert_eq!(local_end, add_months(local_purchase, months as u32).unwrap());
+        prop_assert!(end > purchase);
+    }
+}
@@ -0,0 +1,303 @@
+//! Outgoing webhooks: warranty events posted as signed JSON to the endpoints
+//! users register, retried with exponential backoff.
+
+#[cfg(test)]
+mod tests;
+
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use reqwest::{header, Url};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{self, NewWebhookEvent, WebhookAttempt};
+use crate::error::Result;
+use crate::models::{
+    DeliveryStatus, MaintenanceTask, PendingWebhookDelivery, Warranty, WebhookEvent,
+};
+use crate::notifications::retry_delay;
+use crate::outbound;
+use crate::signing;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// With delays doubling from one minute, the last attempt comes a little over
+/// four hours after the first.
+const MAX_ATTEMPTS: i32 = 9;
+
+pub const MAX_URL_LENGTH: usize = 2048;
+
+/// The event sent to the webhooks of `user_id` when something happened to
+/// `warranty`.
+pub fn warranty_event(
+    event: WebhookEvent,
+    warranty: &Warranty,
+    user_id: &str,
+    now: DateTime<Utc>,
+) -> NewWebhookEvent {
+    let event_id = Uuid::new_v4();
+    NewWebhookEvent {
+        user_id: user_id.to_string(),
+        event_id,
+        event,
+        payload: json!({
+            "id": event_id,
+            "type": event,
+            "created_at": now,
+            "data": { "warranty": warranty },
+        }),
+    }
+}
+
+/// `warranty.expiring`, for the reminder of `user_id` `threshold_days`
+/// before the end.
+pub fn expiring_event(
+    warranty: &Warranty,
+    user_id: &str,
+    threshold_days: i32,
+    now: DateTime<Utc>,
+) -> NewWebhookEvent {
+    let mut event = warranty_event(WebhookEvent::Expiring, warranty, user_id, now);
+    event.payload["data"]["threshold_days"] = json!(threshold_days);
+    event
+}
+
+/// `maintenance.due`, when the reminder of `user_id` for the next occurrence
+/// of `task` goes out.
+pub fn maintenance_due_event(
+    warranty: &Warranty,
+    task: &MaintenanceTask,
+    user_id: &str,
+    now: DateTime<Utc>,
+) -> NewWebhookEvent {
+    let mut event = warranty_event(WebhookEvent::MaintenanceDue, warranty, user_id, now);
+    event.payload["data"]["maintenance_task"] = json!(task);
+    event
+}
+
+/// Queues `event` for the webhooks of every current member of the household
+/// of `warranty`, all with the same event id. A failure is logged rather than
+/// failing the request that caused the event.
+pub async fn emit(pool: &PgPool, event: WebhookEvent, warranty: &Warranty) {
+    let result: Result<()> = async {
+        let mut conn = pool.acquire().await?;
+        let members = db::household_member_ids(&mut conn, warranty.household_id).await?;
+        let Some(first) = members.first() else {
+            return Ok(());
+        };
+        let sent = warranty_event(event, warranty, first, Utc::now());
+        let events: Vec<NewWebhookEvent> = members
+            .iter()
+            .map(|user_id| NewWebhookEvent {
+                user_id: user_id.clone(),
+                ..sent.clone()
+            })
+            .collect();
+        db::enqueue_webhook_events(&mut conn, &events).await
+    }
+    .await;
+
+    if let Err(e) = result {
+        tracing::warn!(
+            error = %e,
+            warranty_id = %warranty.id,
+            event = event.as_str(),
+            "failed to queue webhook event"
+        );
+    }
+}
+
+/// A fresh signing secret, shown to the user once.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+/// The `Garry-Signature` header: `t=<unix time>,v1=<hex HMAC-SHA256>` of
+/// `<unix time>.<body>`, keyed with the webhook secret. Receivers should
+/// reject timestamps too far in the past to stop replays.
+pub fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+    format!(
+        "t={},v1={}",
+        timestamp,
+        signing::sign(secret, &format!("{}.{}", timestamp, body))
+    )
+}
+
+/// Checks a webhook URL. Unless `allow_private`, as in development, it must
+/// use https and must not name a loopback, private or link-local address.
+/// Names pointing inside are refused when sending, by the dispatcher's
+/// resolver.
+pub fn validate_url(url: &str, allow_private: bool) -> std::result::Result<Url, String> {
+    if url.len() > MAX_URL_LENGTH {
+        return Err(format!(
+            "Webhook URL must be at most {} characters",
+            MAX_URL_LENGTH
+        ));
+    }
+    let parsed = Url::parse(url).map_err(|_| "Webhook URL is not a valid URL".to_string())?;
+    match parsed.scheme() {
+        "https" => {}
+        "http" if allow_private => {}
+        _ => return Err("Webhook URL must use https".to_string()),
+    }
+    if !parsed.username().is_empty() || parsed.password().is_some() {
+        return Err("Webhook URL must not contain credentials".to_string());
+    }
+    let host = parsed
+        .host_str()
+        .filter(|host| !host.is_empty())
+        .ok_or_else(|| "Webhook URL must have a host".to_string())?
+        .to_ascii_lowercase();
+
+    if !allow_private && outbound::is_private_host(&host) {
+        return Err("Webhook URL must point to a public address".to_string());
+    }
+    Ok(parsed)
+}
+
+/// Whether a delivery is done, to be retried or given up after an attempt
+/// that got `response_status` back, or no response at all.
+pub fn next_step(
+    response_status: Option<u16>,
+    attempts: i32,
+) -> (DeliveryStatus, Option<chrono::Duration>) {
+    let retryable = match response_status {
+        Some(status) if (200..300).contains(&status) => return (DeliveryStatus::Sent, None),
+        Some(408) | Some(429) => true,
+        Some(status) => status >= 500,
+        None => true,
+    };
+    if retryable && attempts < MAX_ATTEMPTS {
+        (DeliveryStatus::Pending, Some(retry_delay(attempts)))
+    } else {
+        (DeliveryStatus::Failed, None)
+    }
+}
+
+/// Starts the task that drains the `webhook_deliveries` queue. Every replica
+/// can run one: deliveries are claimed with `SKIP LOCKED`.
+pub fn spawn_dispatcher(pool: PgPool, allow_private: bool) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = outbound::restrict(reqwest::Client::builder(), allow_private)
+            .timeout(REQUEST_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent("Garry-Webhooks/1.0")
+            .build()
+            .expect("webhook HTTP client");
+
+        loop {
+            match db::claim_webhook_delivery(&pool).await {
+                Ok(Some(delivery)) => {
+                    let attempt = send(&client, &delivery, allow_private, Utc::now()).await;
+                    if attempt.status == DeliveryStatus::Sent {
+                        tracing::info!(delivery_id = %delivery.id, webhook_id = %delivery.webhook_id, "webhook delivered");
+                    } else {
+                        tracing::warn!(
+                            delivery_id = %delivery.id,
+                            webhook_id = %delivery.webhook_id,
+                            attempt = delivery.attempts,
+                            status = ?attempt.response_status,
+                            error = ?attempt.error,
+                            "webhook delivery failed"
+                        );
+                    }
+                    if let Err(e) = db::record_webhook_attempt(&pool, delivery.id, &attempt).await {
+                        tracing::error!(delivery_id = %delivery.id, error = %e, "failed to record webhook attempt");
+                    }
+                }
+                Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+                Err(e) => {
+                    tracing::warn!(error = %e, "failed to claim webhook delivery");
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
+        }
+    })
+}
+
+/// Posts one delivery, signed at `now`, and says what came of it.
+pub async fn send(
+    client: &reqwest::Client,
+    delivery: &PendingWebhookDelivery,
+    allow_private: bool,
+    now: DateTime<Utc>,
+) -> WebhookAttempt {
+    let started = Instant::now();
+    let elapsed = |started: Instant| started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+    // The URL was checked when saved; check again in case the rules changed.
+    let url = match validate_url(&delivery.url, allow_private) {
+        Ok(url) => url,
+        Err(e) => {
+            return WebhookAttempt {
+                status: DeliveryStatus::Failed,
+                response_status: None,
+                error: Some(e),
+                duration_ms: 0,
+                retry_in: None,
+            }
+        }
+    };
+    let body = delivery.payload.0.to_string();
+
+    let response = client
+        .post(url)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header("Garry-Event", delivery.event.as_str())
+        .header("Garry-Event-Id", delivery.event_id.to_string())
+        .header("Garry-Delivery", delivery.id.to_string())
+        .header(
+            "Garry-Signature",
+            signature(&delivery.secret, now.timestamp(), &body),
+        )
+        .body(body)
+        .send()
+        .await;
+
+    match response {
+        Ok(response) => {
+            // Only the status is kept: a body could leak whatever the
+            // webhook URL reached.
+            let status = response.status().as_u16();
+            let (delivery_status, retry_in) = next_step(Some(status), delivery.attempts);
+            WebhookAttempt {
+                status: delivery_status,
+                response_status: Some(status.into()),
+                error: (delivery_status != DeliveryStatus::Sent)
+                    .then(|| format!("webhook answered {}", status)),
+                duration_ms: elapsed(started),
+                retry_in,
+            }
+        }
+        Err(e) => {
+            let (status, retry_in) = next_step(None, delivery.attempts);
+            WebhookAttempt {
+                status,
+                response_status: None,
+                error: Some(describe(&e)),
+                duration_ms: elapsed(started),
+                retry_in,
+            }
+        }
+    }
+}
+
+/// reqwest keeps the useful part, such as "connection refused", in the
+/// error's sources.
+fn describe(error: &reqwest::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
@@ -7,7 +7,7 @@ pub async fn create_test_app() -> Router {
         http::{header, Request, StatusCode},
         middleware::{self, Next},
         response::Response,
-        routing::{get, post},
+        routing::{delete, get, post, put},
         Json,
     };
     use jsonwebtoken::{decode, DecodingKey, Validation};
@@ -30,6 +30,7 @@ pub async fn create_test_app() -> Router {
     }
 
     #[derive(Clone)]
+    #[allow(dead_code)]
     struct AuthUser {
         user_id: String,
     }
@@ -180,6 +181,238 @@ pub async fn create_test_app() -> Router {
         StatusCode::OK
     }
 
+    async fn download_receipt() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn create_receipt_signed_url() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn list_attachments() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn create_attachment() -> StatusCode {
+        StatusCode::CREATED
+    }
+
+    async fn get_attachment() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn delete_attachment() -> StatusCode {
+        StatusCode::NO_CONTENT
+    }
+
+    async fn list_coverages() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn create_coverage() -> StatusCode {
+        StatusCode::CREATED
+    }
+
+    async fn get_coverage() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn update_coverage() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn delete_coverage() -> StatusCode {
+        StatusCode::NO_CONTENT
+    }
+
+    async fn list_claims() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn create_claim() -> StatusCode {
+        StatusCode::CREATED
+    }
+
+    async fn get_claim() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn update_claim() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn delete_claim() -> StatusCode {
+        StatusCode::NO_CONTENT
+    }
+
+    async fn transition_claim() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn list_maintenance_tasks() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn create_maintenance_task() -> StatusCode {
+        StatusCode::CREATED
+    }
+
+    async fn get_maintenance_task() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn update_maintenance_task() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn delete_maintenance_task() -> StatusCode {
+        StatusCode::NO_CONTENT
+    }
+
+    async fn list_maintenance_completions() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn create_maintenance_completion() -> StatusCode {
+        StatusCode::CREATED
+    }
+
+    async fn delete_maintenance_completion() -> StatusCode {
+        StatusCode::NO_CONTENT
+    }
+
+    async fn get_sync_changes() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn push_sync_changes() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn list_trash() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn restore_warranty() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn list_warranty_history() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn list_households() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn create_household() -> StatusCode {
+        StatusCode::CREATED
+    }
+
+    async fn get_household() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn update_household() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn delete_household() -> StatusCode {
+        StatusCode::NO_CONTENT
+    }
+
+    async fn update_household_member() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn remove_household_member() -> StatusCode {
+        StatusCode::NO_CONTENT
+    }
+
+    async fn list_household_invitations() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn create_household_invitation() -> StatusCode {
+        StatusCode::CREATED
+    }
+
+    async fn delete_household_invitation() -> StatusCode {
+        StatusCode::NO_CONTENT
+    }
+
+    async fn accept_household_invitation() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn create_extraction() -> StatusCode {
+        StatusCode::ACCEPTED
+    }
+
+    async fn get_extraction() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn create_push_subscription() -> StatusCode {
+        StatusCode::CREATED
+    }
+
+    async fn delete_push_subscription() -> StatusCode {
+        StatusCode::NO_CONTENT
+    }
+
+    async fn get_reminder_preferences() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn update_reminder_preferences() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn get_calendar_feed() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn regenerate_calendar_feed() -> StatusCode {
+        StatusCode::CREATED
+    }
+
+    async fn delete_calendar_feed() -> StatusCode {
+        StatusCode::NO_CONTENT
+    }
+
+    async fn list_webhooks() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn create_webhook() -> StatusCode {
+        StatusCode::CREATED
+    }
+
+    async fn get_webhook() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn update_webhook() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn delete_webhook() -> StatusCode {
+        StatusCode::NO_CONTENT
+    }
+
+    async fn list_webhook_deliveries() -> StatusCode {
+        StatusCode::OK
+    }
+
+    async fn redeliver_webhook_delivery() -> StatusCode {
+        StatusCode::ACCEPTED
+    }
+
+    async fn evaluate_legal_guarantee() -> StatusCode {
+        StatusCode::OK
+    }
+
     let state = AppState {
         config: TestConfig {
             jwt_secret: "test-secret-key-for-testing-only".to_string(),
@@ -206,7 +439,129 @@ pub async fn create_test_app() -> Router {
                 .put(update_warranty)
                 .delete(delete_warranty),
         )
-        .route("/api/v1/warranties/:id/receipt", post(upload_receipt))
+        .route(
+            "/api/v1/warranties/:id/receipt",
+            post(upload_receipt).get(download_receipt),
+        )
+        .route(
+            "/api/v1/warranties/:id/receipt/signed-url",
+            post(create_receipt_signed_url),
+        )
+        .route(
+            "/api/v1/warranties/:id/receipt/extraction",
+            get(get_extraction),
+        )
+        .route(
+            "/api/v1/warranties/:id/attachments",
+            get(list_attachments).post(create_attachment),
+        )
+        .route(
+            "/api/v1/warranties/:id/attachments/:attachment_id",
+            get(get_attachment).delete(delete_attachment),
+        )
+        .route(
+            "/api/v1/warranties/:id/coverages",
+            get(list_coverages).post(create_coverage),
+        )
+        .route(
+            "/api/v1/warranties/:id/coverages/:coverage_id",
+            get(get_coverage)
+                .put(update_coverage)
+                .delete(delete_coverage),
+        )
+        .route(
+            "/api/v1/warranties/:id/claims",
+            get(list_claims).post(create_claim),
+        )
+        .route(
+            "/api/v1/warranties/:id/claims/:claim_id",
+            get(get_claim).put(update_claim).delete(delete_claim),
+        )
+        .route(
+            "/api/v1/warranties/:id/claims/:claim_id/transitions",
+            post(transition_claim),
+        )
+        .route(
+            "/api/v1/warranties/:id/maintenance-tasks",
+            get(list_maintenance_tasks).post(create_maintenance_task),
+        )
+        .route(
+            "/api/v1/warranties/:id/maintenance-tasks/:task_id",
+            get(get_maintenance_task)
+                .put(update_maintenance_task)
+                .delete(delete_maintenance_task),
+        )
+        .route(
+            "/api/v1/warranties/:id/maintenance-tasks/:task_id/completions",
+            get(list_maintenance_completions).post(create_maintenance_completion),
+        )
+        .route(
+            "/api/v1/warranties/:id/maintenance-tasks/:task_id/completions/:completion_id",
+            delete(delete_maintenance_completion),
+        )
+        .route(
+            "/api/v1/sync",
+            get(get_sync_changes).post(push_sync_changes),
+        )
+        .route("/api/v1/trash", get(list_trash))
+        .route("/api/v1/warranties/:id/restore", post(restore_warranty))
+        .route("/api/v1/warranties/:id/history", get(list_warranty_history))
+        .route(
+            "/api/v1/households",
+            get(list_households).post(create_household),
+        )
+        .route(
+            "/api/v1/households/:id",
+            get(get_household)
+                .put(update_household)
+                .delete(delete_household),
+        )
+        .route(
+            "/api/v1/households/:id/members/:user_id",
+            put(update_household_member).delete(remove_household_member),
+        )
+        .route(
+            "/api/v1/households/:id/invitations",
+            get(list_household_invitations).post(create_household_invitation),
+        )
+        .route(
+            "/api/v1/households/:id/invitations/:invitation_id",
+            delete(delete_household_invitation),
+        )
+        .route(
+            "/api/v1/household-invitations/accept",
+            post(accept_household_invitation),
+        )
+        .route("/api/v1/extractions", post(create_extraction))
+        .route("/api/v1/extractions/:id", get(get_extraction))
+        .route(
+            "/api/v1/push-subscriptions",
+            post(create_push_subscription).delete(delete_push_subscription),
+        )
+        .route(
+            "/api/v1/reminder-preferences",
+            get(get_reminder_preferences).put(update_reminder_preferences),
+        )
+        .route(
+            "/api/v1/calendar-feed",
+            get(get_calendar_feed)
+                .post(regenerate_calendar_feed)
+                .delete(delete_calendar_feed),
+        )
+        .route("/api/v1/webhooks", get(list_webhooks).post(create_webhook))
+        .route(
+            "/api/v1/webhooks/:id",
+            get(get_webhook).put(update_webhook).delete(delete_webhook),
+        )
+        .route(
+            "/api/v1/webhooks/:id/deliveries",
+            get(list_webhook_deliveries),
+        )
+        .route(
+            "/api/v1/webhooks/:id/deliveries/:delivery_id/redeliver",
+            post(redeliver_webhook_delivery),
+        )
+        .route("/api/v1/legal-guarantee", post(evaluate_legal_guarantee))
         .route("/api/v1/warranties/expiring", get(list_expiring))
         .route("/api/v1/stats", get(get_stats))
         .layer(middleware::from_fn_with_state(
@@ -86,6 +86,44 @@ async fn list_warranties_without_auth_returns_unauthorized() {
     assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
 }
 
+#[tokio::test]
+async fn search_warranties_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/warranties?q=electromenager")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn filtered_warranty_list_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(
+                    "/api/v1/warranties?category=appliances,electronics&brand=bosch\
+                     &purchased_from=2024-01-01T00:00:00Z&min_price_cents=10000\
+                     &has_receipt=true&sort=purchase_date&order=desc",
+                )
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
 #[tokio::test]
 async fn create_warranty_without_auth_returns_unauthorized() {
     let app = common::create_test_app().await;
@@ -176,13 +214,36 @@ async fn delete_warranty_without_auth_returns_unauthorized() {
 }
 
 #[tokio::test]
-async fn get_stats_without_auth_returns_unauthorized() {
+async fn download_receipt_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+    let fake_uuid = "00000000-0000-0000-0000-000000000001";
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v1/warranties/{}/receipt", fake_uuid))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn create_receipt_signed_url_without_auth_returns_unauthorized() {
     let app = common::create_test_app().await;
+    let fake_uuid = "00000000-0000-0000-0000-000000000001";
 
     let response = app
         .oneshot(
             Request::builder()
-                .uri("/api/v1/stats")
+                .method("POST")
+                .uri(format!(
+                    "/api/v1/warranties/{}/receipt/signed-url",
+                    fake_uuid
+                ))
                 .body(Body::empty())
                 .unwrap(),
         )
@@ -193,13 +254,588 @@ async fn get_stats_without_auth_returns_unauthorized() {
 }
 
 #[tokio::test]
-async fn list_expiring_without_auth_returns_unauthorized() {
+async fn list_attachments_without_auth_returns_unauthorized() {
     let app = common::create_test_app().await;
+    let fake_uuid = "00000000-0000-0000-0000-000000000001";
 
     let response = app
         .oneshot(
             Request::builder()
-                .uri("/api/v1/warranties/expiring")
+                .uri(format!("/api/v1/warranties/{}/attachments", fake_uuid))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn delete_attachment_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+    let fake_uuid = "00000000-0000-0000-0000-000000000001";
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!(
+                    "/api/v1/warranties/{}/attachments/{}",
+                    fake_uuid, fake_uuid
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn create_extraction_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/extractions")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn get_receipt_extraction_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+    let fake_uuid = "00000000-0000-0000-0000-000000000001";
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!(
+                    "/api/v1/warranties/{}/receipt/extraction",
+                    fake_uuid
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn create_push_subscription_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/push-subscriptions")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    r#"{"endpoint":"https://push.example.net/abc","keys":{"p256dh":"x","auth":"y"}}"#,
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn delete_push_subscription_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri("/api/v1/push-subscriptions")
+                .header("Content-Type", "application/json")
+                .body(Body::from(r#"{"endpoint":"https://push.example.net/abc"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn get_reminder_preferences_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/reminder-preferences")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn update_reminder_preferences_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("PUT")
+                .uri("/api/v1/reminder-preferences")
+                .header("Content-Type", "application/json")
+                .body(Body::from(r#"{"lead_days":[14]}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn regenerate_calendar_feed_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/calendar-feed")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn create_webhook_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/webhooks")
+                .header("Content-Type", "application/json")
+                .body(Body::from(r#"{"url":"https://hooks.example.com/garry"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn list_webhook_deliveries_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+    let fake_uuid = "00000000-0000-0000-0000-000000000001";
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v1/webhooks/{}/deliveries", fake_uuid))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn redeliver_webhook_delivery_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+    let fake_uuid = "00000000-0000-0000-0000-000000000001";
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!(
+                    "/api/v1/webhooks/{}/deliveries/{}/redeliver",
+                    fake_uuid, fake_uuid
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn evaluate_legal_guarantee_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/legal-guarantee")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    r#"{"category":"electronics","purchase_date":"2024-03-15T10:00:00Z"}"#,
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn create_coverage_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+    let fake_uuid = "00000000-0000-0000-0000-000000000001";
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/v1/warranties/{}/coverages", fake_uuid))
+                .header("Content-Type", "application/json")
+                .body(Body::from(r#"{"kind":"manufacturer","months":60}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn update_coverage_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+    let fake_uuid = "00000000-0000-0000-0000-000000000001";
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("PUT")
+                .uri(format!(
+                    "/api/v1/warranties/{}/coverages/{}",
+                    fake_uuid, fake_uuid
+                ))
+                .header("Content-Type", "application/json")
+                .body(Body::from(r#"{"provider":"Visa Premier"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn create_claim_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+    let fake_uuid = "00000000-0000-0000-0000-000000000001";
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/v1/warranties/{}/claims", fake_uuid))
+                .header("Content-Type", "application/json")
+                .body(Body::from(r#"{"description":"Screen flickers"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn transition_claim_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+    let fake_uuid = "00000000-0000-0000-0000-000000000001";
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!(
+                    "/api/v1/warranties/{}/claims/{}/transitions",
+                    fake_uuid, fake_uuid
+                ))
+                .header("Content-Type", "application/json")
+                .body(Body::from(r#"{"status":"resolved"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn create_maintenance_task_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+    let fake_uuid = "00000000-0000-0000-0000-000000000001";
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!(
+                    "/api/v1/warranties/{}/maintenance-tasks",
+                    fake_uuid
+                ))
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    r#"{"title":"Vidange","interval_months":12,"interval_km":15000}"#,
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn create_maintenance_completion_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+    let fake_uuid = "00000000-0000-0000-0000-000000000001";
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!(
+                    "/api/v1/warranties/{}/maintenance-tasks/{}/completions",
+                    fake_uuid, fake_uuid
+                ))
+                .header("Content-Type", "application/json")
+                .body(Body::from(r#"{"odometer_km":57300}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn sync_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/sync?since=eyJ4bWluIjo3NDB9")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn push_sync_changes_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+    let fake_uuid = "00000000-0000-0000-0000-000000000001";
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/sync")
+                .header("Content-Type", "application/json")
+                .body(Body::from(format!(
+                    r#"{{"changes":[{{"op":"delete","id":"{}","base_updated_at":"2026-03-01T12:00:00Z"}}]}}"#,
+                    fake_uuid
+                )))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn list_trash_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/trash")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn restore_warranty_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+    let fake_uuid = "00000000-0000-0000-0000-000000000001";
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/v1/warranties/{}/restore", fake_uuid))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn warranty_history_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+    let fake_uuid = "00000000-0000-0000-0000-000000000001";
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v1/warranties/{}/history", fake_uuid))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn list_households_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/households")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn create_household_invitation_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+    let fake_uuid = "00000000-0000-0000-0000-000000000001";
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/v1/households/{}/invitations", fake_uuid))
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    json!({"email": "marie@example.com", "role": "viewer"}).to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn accept_household_invitation_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/household-invitations/accept")
+                .header("Content-Type", "application/json")
+                .body(Body::from(
+                    json!({"token": "00000000-0000-0000-0000-000000000001"}).to_string(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn get_stats_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/stats")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn list_expiring_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/warranties/expiring")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn paged_expiring_list_without_auth_returns_unauthorized() {
+    let app = common::create_test_app().await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v1/warranties/expiring?days=90&per_page=10&cursor=eyJzb3J0IjoiZW5kX2RhdGUifQ")
                 .body(Body::empty())
                 .unwrap(),
         )